@@ -0,0 +1,145 @@
+use crate::{Error, Interceptor};
+use axum_core::body::Body;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use hyper::{
+    Request,
+    header::{AUTHORIZATION, HeaderValue},
+};
+use std::future::Future;
+
+/// [`Interceptor`] sending an `Authorization: Basic` header on every request which doesn't already
+/// set one, see [`ClientUnixBuilder::basic_auth`](crate::ClientUnixBuilder::basic_auth).
+#[derive(Clone)]
+pub struct BasicAuth {
+    value: HeaderValue,
+}
+
+impl BasicAuth {
+    pub fn new(user: &str, password: Option<&str>) -> Self {
+        let credentials = format!("{}:{}", user, password.unwrap_or_default());
+        let mut value = HeaderValue::try_from(format!("Basic {}", STANDARD.encode(credentials)))
+            .expect("base64 is a valid header value");
+        value.set_sensitive(true);
+        BasicAuth { value }
+    }
+}
+
+impl Interceptor for BasicAuth {
+    async fn before(&self, request: &mut Request<Body>) -> Result<(), Error> {
+        request
+            .headers_mut()
+            .entry(AUTHORIZATION)
+            .or_insert_with(|| self.value.clone());
+        Ok(())
+    }
+}
+
+/// Source of the token sent by [`BearerAuth`], asked before every request so short-lived tokens
+/// are refreshed without reconnecting.
+///
+/// Caching the token until it expires is up to the implementation.
+pub trait TokenProvider: Send + Sync {
+    /// Returning an error aborts the request with that error.
+    fn token(&self) -> impl Future<Output = Result<String, Error>> + Send;
+}
+
+/// A static token.
+impl TokenProvider for String {
+    async fn token(&self) -> Result<String, Error> {
+        Ok(self.clone())
+    }
+}
+
+/// [`Interceptor`] sending an `Authorization: Bearer` header from a [`TokenProvider`] on every
+/// request which doesn't already set one, see
+/// [`ClientUnixBuilder::bearer_auth`](crate::ClientUnixBuilder::bearer_auth).
+pub struct BearerAuth<P> {
+    provider: P,
+}
+
+impl<P: TokenProvider> BearerAuth<P> {
+    pub fn new(provider: P) -> Self {
+        BearerAuth { provider }
+    }
+}
+
+impl<P: TokenProvider> Interceptor for BearerAuth<P> {
+    async fn before(&self, request: &mut Request<Body>) -> Result<(), Error> {
+        if request.headers().contains_key(AUTHORIZATION) {
+            return Ok(());
+        }
+        let token = self.provider.token().await?;
+        let mut value = HeaderValue::try_from(format!("Bearer {}", token))
+            .map_err(|e| Error::Interceptor(Box::new(e)))?;
+        value.set_sensitive(true);
+        request.headers_mut().insert(AUTHORIZATION, value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, test_helpers::server::Server, test_helpers::util::*};
+    use hyper::Method;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn basic_auth_header() {
+        let socket_path = make_socket_path_test("auth", "basic_auth_header");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .basic_auth("nolanv", Some("open sesame"))
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let (_, response) = client
+            .send_request("/header/authorization", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(response, "Basic bm9sYW52Om9wZW4gc2VzYW1l".as_bytes());
+
+        let (_, response) = client
+            .send_request(
+                "/header/authorization",
+                Method::GET,
+                &[("Authorization", "Bearer nolanv")],
+                None,
+            )
+            .await
+            .expect("client.send_request");
+        assert_eq!(response, "Bearer nolanv".as_bytes());
+    }
+
+    struct Refreshing(AtomicUsize);
+
+    impl TokenProvider for Refreshing {
+        async fn token(&self) -> Result<String, Error> {
+            Ok(format!("token-{}", self.0.fetch_add(1, Ordering::SeqCst)))
+        }
+    }
+
+    #[tokio::test]
+    async fn bearer_token_refreshed() {
+        let socket_path = make_socket_path_test("auth", "bearer_token_refreshed");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .bearer_auth(Refreshing(AtomicUsize::new(0)))
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        for expected in ["Bearer token-0", "Bearer token-1"] {
+            let (_, response) = client
+                .send_request("/header/authorization", Method::GET, &[], None)
+                .await
+                .expect("client.send_request");
+            assert_eq!(response, expected.as_bytes());
+        }
+    }
+}
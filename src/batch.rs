@@ -0,0 +1,372 @@
+use crate::{ClientUnix, Error, ErrorAndResponse, client::Options, interceptor::BoxFuture};
+use futures_core::Stream;
+use hyper::{Method, Response, body::Bytes};
+use std::{
+    collections::VecDeque,
+    future::Future,
+    iter::Peekable,
+    path::PathBuf,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, ready},
+};
+use tokio::task::JoinHandle;
+
+/// Connections used by [`ClientUnix::send_all`], this one included, unless set with
+/// [`ClientUnixBuilder::batch_connections`](crate::ClientUnixBuilder::batch_connections).
+pub(crate) const DEFAULT_BATCH_CONNECTIONS: usize = 8;
+
+type BatchResult = Result<Response<Vec<u8>>, ErrorAndResponse>;
+type BatchQueue = Mutex<VecDeque<(usize, BatchRequest)>>;
+
+/// Request of [`ClientUnix::send_all`] and [`ClientUnix::fan_out`], owning its parts to be sent on any connection.
+#[derive(Debug, Clone)]
+pub struct BatchRequest {
+    pub method: Method,
+    pub endpoint: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Bytes>,
+}
+
+impl BatchRequest {
+    pub fn new(method: Method, endpoint: &str) -> Self {
+        BatchRequest {
+            method,
+            endpoint: endpoint.to_string(),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+}
+
+impl ClientUnix {
+    /// Send the requests concurrently, on this connection and new ones with the same settings, up
+    /// to 8 connections in all unless set with
+    /// [`batch_connections`](crate::ClientUnixBuilder::batch_connections), and return their
+    /// results, as of [`send_request_full`](Self::send_request_full), in the order of the
+    /// requests.
+    ///
+    /// ```rust,no_run
+    /// use http_client_unix_domain_socket::{BatchRequest, ClientUnix, Method};
+    ///
+    /// # async fn run(mut client: ClientUnix) {
+    /// let requests = ["nginx", "redis", "postgres"]
+    ///     .map(|name| BatchRequest::new(Method::GET, &format!("/containers/{}/json", name)));
+    /// for result in client.send_all(requests).await {
+    ///     let response = result.expect("client.send_all");
+    /// }
+    /// # }
+    /// ```
+    pub async fn send_all(
+        &mut self,
+        requests: impl IntoIterator<Item = BatchRequest>,
+    ) -> Vec<BatchResult> {
+        let queue: VecDeque<_> = requests.into_iter().enumerate().collect();
+        let len = queue.len();
+        let queue = Arc::new(Mutex::new(queue));
+
+        let connections = self
+            .options()
+            .batch_connections
+            .unwrap_or(DEFAULT_BATCH_CONNECTIONS);
+        let mut workers = Vec::new();
+        for _ in 1..len.min(connections) {
+            let (socket_path, options) = self.connection_settings();
+            // Without another connection, the remaining ones share this one
+            let Ok(mut client) = ClientUnix::try_connect(socket_path, options).await else {
+                break;
+            };
+            let queue = queue.clone();
+            workers.push(tokio::spawn(async move {
+                send_queued(&mut client, &queue).await
+            }));
+        }
+
+        let mut results: Vec<Option<BatchResult>> = (0..len).map(|_| None).collect();
+        for (index, result) in send_queued(self, &queue).await {
+            results[index] = Some(result);
+        }
+        for worker in workers {
+            let sent = match worker.await {
+                Ok(sent) => sent,
+                Err(e) => std::panic::resume_unwind(e.into_panic()),
+            };
+            for (index, result) in sent {
+                results[index] = Some(result);
+            }
+        }
+        results
+            .into_iter()
+            .map(|result| result.expect("every batch request is sent"))
+            .collect()
+    }
+}
+
+/// [`Stream`] of the results of [`ClientUnix::fan_out`], in the order of the requests, ending
+/// once every request is answered.
+///
+/// At most `parallelism` requests are in flight, each on its own connection reused by the next
+/// requests. A request is only sent once the previous results can be yielded, so a slow request
+/// holds back the ones after it rather than piling up their results.
+pub struct FanOut<I: Iterator<Item = BatchRequest>> {
+    requests: Peekable<I>,
+    socket_path: PathBuf,
+    options: Options,
+    parallelism: usize,
+    idle: Vec<ClientUnix>,
+    connecting: Option<BoxFuture<'static, Result<ClientUnix, Error>>>,
+    in_flight: VecDeque<JoinHandle<(ClientUnix, BatchResult)>>,
+}
+
+impl<I: Iterator<Item = BatchRequest>> FanOut<I> {
+    /// An idle connection or a new one, `Pending` while it connects.
+    fn poll_client(&mut self, cx: &mut Context<'_>) -> Poll<Result<ClientUnix, Error>> {
+        if let Some(client) = self.idle.pop() {
+            return Poll::Ready(Ok(client));
+        }
+        let connecting = self.connecting.get_or_insert_with(|| {
+            Box::pin(ClientUnix::try_connect(
+                self.socket_path.clone(),
+                self.options.clone(),
+            ))
+        });
+        let connected = ready!(connecting.as_mut().poll(cx));
+        self.connecting = None;
+        Poll::Ready(connected)
+    }
+}
+
+impl<I: Iterator<Item = BatchRequest> + Unpin> Stream for FanOut<I> {
+    type Item = BatchResult;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<BatchResult>> {
+        let this = self.get_mut();
+        while this.in_flight.len() < this.parallelism && this.requests.peek().is_some() {
+            let client = match this.poll_client(cx) {
+                Poll::Ready(Ok(client)) => client,
+                // Wait for the connections in use
+                Poll::Ready(Err(_)) if !this.in_flight.is_empty() => break,
+                Poll::Ready(Err(e)) => {
+                    this.requests.next();
+                    return Poll::Ready(Some(Err(ErrorAndResponse::InternalError(e))));
+                }
+                Poll::Pending if !this.in_flight.is_empty() => break,
+                Poll::Pending => return Poll::Pending,
+            };
+            let Some(request) = this.requests.next() else {
+                this.idle.push(client);
+                break;
+            };
+            this.in_flight.push_back(tokio::spawn(async move {
+                let mut client = client;
+                let result = send_batch_request(&mut client, &request).await;
+                (client, result)
+            }));
+        }
+
+        let Some(sending) = this.in_flight.front_mut() else {
+            return Poll::Ready(None);
+        };
+        let (client, result) = match ready!(Pin::new(sending).poll(cx)) {
+            Ok(sent) => sent,
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        };
+        this.in_flight.pop_front();
+        this.idle.push(client);
+        Poll::Ready(Some(result))
+    }
+}
+
+impl<I: Iterator<Item = BatchRequest>> Drop for FanOut<I> {
+    fn drop(&mut self) {
+        for request in &self.in_flight {
+            request.abort();
+        }
+    }
+}
+
+impl ClientUnix {
+    /// Send the requests with at most `parallelism` in flight, on new connections with the
+    /// settings of this client, and yield their results in the order of the requests. The
+    /// requests are only pulled from the iterator as they are sent.
+    ///
+    /// ```rust,no_run
+    /// use futures_util::StreamExt;
+    /// use http_client_unix_domain_socket::{BatchRequest, ClientUnix, Method};
+    ///
+    /// # async fn run(client: ClientUnix, ids: Vec<String>) {
+    /// let requests = ids
+    ///     .iter()
+    ///     .map(|id| BatchRequest::new(Method::POST, &format!("/containers/{}/start", id)));
+    /// let mut results = client.fan_out(requests, 16);
+    /// while let Some(result) = results.next().await {
+    ///     result.expect("client.fan_out");
+    /// }
+    /// # }
+    /// ```
+    pub fn fan_out<I: IntoIterator<Item = BatchRequest>>(
+        &self,
+        requests: I,
+        parallelism: usize,
+    ) -> FanOut<I::IntoIter> {
+        let (socket_path, options) = self.connection_settings();
+        FanOut {
+            requests: requests.into_iter().peekable(),
+            socket_path,
+            options,
+            parallelism: parallelism.max(1),
+            idle: Vec::new(),
+            connecting: None,
+            in_flight: VecDeque::new(),
+        }
+    }
+}
+
+/// Send the requests of `queue` one at a time until it is empty.
+async fn send_queued(client: &mut ClientUnix, queue: &BatchQueue) -> Vec<(usize, BatchResult)> {
+    let mut sent = Vec::new();
+    loop {
+        let Some((index, request)) = queue.lock().expect("batch queue lock").pop_front() else {
+            return sent;
+        };
+        sent.push((index, send_batch_request(client, &request).await));
+    }
+}
+
+async fn send_batch_request(client: &mut ClientUnix, request: &BatchRequest) -> BatchResult {
+    let headers: Vec<(&str, &str)> = request
+        .headers
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    client
+        .send_request_bytes(
+            &request.endpoint,
+            request.method.clone(),
+            &headers,
+            request.body.clone(),
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StatusCode, testing::MockServer};
+    use futures_util::StreamExt;
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    /// Requests handled by the server at once, and the most of them.
+    #[derive(Default)]
+    struct InFlight {
+        current: AtomicUsize,
+        max: AtomicUsize,
+    }
+
+    /// Server answering `/sleep/{millis}` after `millis`, counting the requests in flight.
+    async fn start_sleep_server() -> (MockServer, ClientUnix, Arc<InFlight>) {
+        let in_flight = Arc::new(InFlight::default());
+        let counted = in_flight.clone();
+        let router = axum::Router::new()
+            .route(
+                "/sleep/{millis}",
+                axum::routing::get(
+                    move |axum::extract::Path(millis): axum::extract::Path<u64>| async move {
+                        let current = counted.current.fetch_add(1, Ordering::SeqCst) + 1;
+                        counted.max.fetch_max(current, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(millis)).await;
+                        counted.current.fetch_sub(1, Ordering::SeqCst);
+                        format!("Slept {}ms", millis)
+                    },
+                ),
+            )
+            .route("/nolanv", axum::routing::get(|| async { "Hello nolanv" }));
+        let server = MockServer::start(router).await.expect("MockServer::start");
+        let client = ClientUnix::try_new(&server.socket_path().to_string_lossy())
+            .await
+            .expect("ClientUnix::try_new");
+        (server, client, in_flight)
+    }
+
+    #[tokio::test]
+    async fn send_all_in_order() {
+        let (_server, mut client, in_flight) = start_sleep_server().await;
+
+        let requests = (0..10).map(|i| match i {
+            3 => BatchRequest::new(Method::POST, "/nolanv"),
+            i => BatchRequest::new(Method::GET, &format!("/sleep/{}", 100 - i * 10)),
+        });
+        let results = client.send_all(requests).await;
+
+        let max = in_flight.max.load(Ordering::SeqCst);
+        assert!((2..=DEFAULT_BATCH_CONNECTIONS).contains(&max), "{}", max);
+        assert_eq!(results.len(), 10);
+        for (i, result) in results.into_iter().enumerate() {
+            match i {
+                3 => assert!(matches!(
+                    result,
+                    Err(ErrorAndResponse::ResponseUnsuccessful(
+                        StatusCode::METHOD_NOT_ALLOWED,
+                        _
+                    ))
+                )),
+                i => assert_eq!(
+                    result.expect("send_all").into_body(),
+                    format!("Slept {}ms", 100 - i * 10).as_bytes()
+                ),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn send_all_on_batch_connections() {
+        let (server, _, in_flight) = start_sleep_server().await;
+        let mut client = ClientUnix::builder(&server.socket_path().to_string_lossy())
+            .batch_connections(2)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let requests = (0..4).map(|_| BatchRequest::new(Method::GET, "/sleep/200"));
+        let results = client.send_all(requests).await;
+
+        assert_eq!(in_flight.max.load(Ordering::SeqCst), 2);
+        assert!(results.iter().all(Result::is_ok));
+    }
+
+    #[tokio::test]
+    async fn fan_out_in_order() {
+        let (_server, client, in_flight) = start_sleep_server().await;
+
+        let requests =
+            (0..6).map(|i| BatchRequest::new(Method::GET, &format!("/sleep/{}", 60 - i * 10)));
+        let mut results = client.fan_out(requests, 3);
+        let mut bodies = Vec::new();
+        while let Some(result) = results.next().await {
+            bodies.push(result.expect("fan_out").into_body());
+        }
+
+        let max = in_flight.max.load(Ordering::SeqCst);
+        assert!((2..=3).contains(&max), "{}", max);
+        let expected: Vec<Vec<u8>> = (0..6)
+            .map(|i| format!("Slept {}ms", 60 - i * 10).into_bytes())
+            .collect();
+        assert_eq!(bodies, expected);
+    }
+}
@@ -0,0 +1,205 @@
+//! `curl --unix-socket` like command line client (feature=cli).
+//!
+//! ```text
+//! ucurl --unix-socket /var/run/docker.sock -H 'Accept: application/json' http://localhost/version
+//! ```
+
+use http_client_unix_domain_socket::{Body, ClientUnix, Method};
+use std::{io::Write, path::PathBuf, process::ExitCode, sync::Mutex};
+
+const USAGE: &str = "Usage: ucurl --unix-socket <path> [options] <url>
+
+Options:
+  -X, --request <method>  Request method, GET or POST with --data by default
+  -H, --header <header>   Header `Name: value`, repeatable
+  -d, --data <data>       Request body, `@file` to read it from a file
+  -o, --output <file>     Write the response body to a file instead of stdout
+  -i, --include           Print the response status and headers
+  -f, --fail              Exit with code 22 on an unsuccessful response
+  -v, --verbose           Dump the raw bytes sent and received to stderr
+  -h, --help              Print this help";
+
+/// Exit code of curl for `--fail`.
+const EXIT_HTTP_ERROR: u8 = 22;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Args {
+    socket_path: String,
+    method: Option<Method>,
+    headers: Vec<(String, String)>,
+    data: Option<String>,
+    output: Option<PathBuf>,
+    include: bool,
+    fail: bool,
+    verbose: bool,
+    url: String,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut url = None;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("missing value of {}", name));
+        match arg.as_str() {
+            "--unix-socket" => parsed.socket_path = value(&arg)?,
+            "-X" | "--request" => {
+                parsed.method = Some(
+                    value(&arg)?
+                        .parse()
+                        .map_err(|_| "invalid request method".to_string())?,
+                )
+            }
+            "-H" | "--header" => {
+                let header = value(&arg)?;
+                let (name, value) = header
+                    .split_once(':')
+                    .ok_or(format!("invalid header {:?}", header))?;
+                parsed
+                    .headers
+                    .push((name.trim().to_string(), value.trim().to_string()));
+            }
+            "-d" | "--data" => parsed.data = Some(value(&arg)?),
+            "-o" | "--output" => parsed.output = Some(value(&arg)?.into()),
+            "-i" | "--include" => parsed.include = true,
+            "-f" | "--fail" => parsed.fail = true,
+            "-v" | "--verbose" => parsed.verbose = true,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ => url = Some(arg),
+        }
+    }
+    if parsed.socket_path.is_empty() {
+        return Err("missing --unix-socket".to_string());
+    }
+    parsed.url = url.ok_or("missing url")?;
+    Ok(parsed)
+}
+
+/// Path and query of the url, the socket replaces its scheme and authority.
+fn endpoint(url: &str) -> &str {
+    match url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |path| &rest[path..]),
+        None if url.starts_with('/') => url,
+        None => "/",
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::from(2);
+        }
+    };
+    match run(args).await {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("ucurl: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<ExitCode, String> {
+    let data = match args.data.as_deref() {
+        Some(data) => Some(match data.strip_prefix('@') {
+            Some(path) => std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?,
+            None => data.as_bytes().to_vec(),
+        }),
+        None => None,
+    };
+    let method = args.method.clone().unwrap_or(match data {
+        Some(_) => Method::POST,
+        None => Method::GET,
+    });
+
+    let mut builder = ClientUnix::builder(&args.socket_path);
+    if args.verbose {
+        builder = builder.wire_dump(Mutex::new(std::io::stderr()));
+    }
+    let mut client = builder.build().await.map_err(|e| e.to_string())?;
+
+    let headers: Vec<(&str, &str)> = args
+        .headers
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    let result = client
+        .send_request_full(endpoint(&args.url), method, &headers, data.map(Body::from))
+        .await;
+
+    let (status_code, mut head, body) = match result {
+        Ok(response) => {
+            let mut head = format!("{:?} {}\r\n", response.version(), response.status());
+            for (name, value) in response.headers() {
+                head += &format!(
+                    "{}: {}\r\n",
+                    name,
+                    String::from_utf8_lossy(value.as_bytes())
+                );
+            }
+            (response.status(), head, response.into_body())
+        }
+        Err(error) => {
+            let (status_code, body) = error.into_response().map_err(|e| e.to_string())?;
+            (status_code, format!("HTTP/1.1 {}\r\n", status_code), body)
+        }
+    };
+    head += "\r\n";
+
+    if args.fail && !status_code.is_success() {
+        eprintln!("ucurl: the requested URL returned error: {}", status_code);
+        return Ok(ExitCode::from(EXIT_HTTP_ERROR));
+    }
+    let write = |writer: &mut dyn Write| {
+        if args.include {
+            writer.write_all(head.as_bytes())?;
+        }
+        writer.write_all(&body)?;
+        writer.flush()
+    };
+    match &args.output {
+        Some(path) => std::fs::File::create(path).and_then(|mut file| write(&mut file)),
+        None => write(&mut std::io::stdout().lock()),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_curl_args() {
+        let args = parse_args(
+            [
+                "--unix-socket",
+                "/var/run/docker.sock",
+                "-X",
+                "PUT",
+                "-H",
+                "Accept: application/json",
+                "-d",
+                "{}",
+                "-i",
+                "http://localhost/v1.43/version?all=1",
+            ]
+            .into_iter()
+            .map(String::from),
+        )
+        .expect("parse_args");
+
+        assert_eq!(args.socket_path, "/var/run/docker.sock");
+        assert_eq!(args.method, Some(Method::PUT));
+        assert_eq!(
+            args.headers,
+            [("Accept".to_string(), "application/json".to_string())]
+        );
+        assert!(args.include && !args.fail);
+        assert_eq!(endpoint(&args.url), "/v1.43/version?all=1");
+        assert!(parse_args(["http://localhost/".to_string()].into_iter()).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::client::Options;
+use http_body_util::BodyExt;
+use hyper::{
+    Response,
+    body::{Body, Incoming},
+    header::CONTENT_LENGTH,
+};
+
+/// Largest allocation made up front from a size hint by default, the buffer grows past it as the
+/// body arrives, so a wrong `Content-Length` doesn't allocate it all at once.
+pub(crate) const DEFAULT_MAX_PREALLOCATION: u64 = 16 * 1024 * 1024;
+
+/// Size of a response body known before collecting it, from the size hint of its stream or its
+/// `Content-Length` header, to choose between buffering, streaming and spooling it to disk.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::{BodySize, ClientUnix, Method};
+///
+/// # async fn run(mut client: ClientUnix) {
+/// let response = client
+///     .send_request_streaming("/images/nolanv/get", Method::GET, &[], None)
+///     .await
+///     .expect("client.send_request_streaming");
+/// if BodySize::of(&response).fits_in(64 * 1024 * 1024) {
+///     // Buffer it, else stream it to a file
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodySize {
+    pub lower: u64,
+    /// `None` when unknown, such as a chunked body.
+    pub upper: Option<u64>,
+}
+
+impl BodySize {
+    pub fn of<B: Body>(response: &Response<B>) -> Self {
+        let hint = response.body().size_hint();
+        if hint.exact().is_none()
+            && let Some(length) = response
+                .headers()
+                .get(CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok()?.parse().ok())
+        {
+            return BodySize {
+                lower: length,
+                upper: Some(length),
+            };
+        }
+        BodySize {
+            lower: hint.lower(),
+            upper: hint.upper(),
+        }
+    }
+
+    /// The size when both bounds are equal, as with a `Content-Length`.
+    pub fn exact(&self) -> Option<u64> {
+        self.upper.filter(|upper| *upper == self.lower)
+    }
+
+    /// Whether the body is known to be at most `limit` bytes.
+    pub fn fits_in(&self, limit: u64) -> bool {
+        self.upper.is_some_and(|upper| upper <= limit)
+    }
+}
+
+/// Collect the body into a buffer allocated once for its `Content-Length`, up to the
+/// `max_preallocation` of the client, and taken from its [`BufferPool`](crate::BufferPool) if any.
+pub(crate) async fn collect(
+    mut body: Incoming,
+    options: &Options,
+) -> Result<Vec<u8>, hyper::Error> {
+    let max_preallocation = options
+        .max_preallocation
+        .unwrap_or(DEFAULT_MAX_PREALLOCATION);
+    let capacity = body.size_hint().lower().min(max_preallocation) as usize;
+    let mut buffer = match &options.buffer_pool {
+        Some(pool) => pool.take(capacity),
+        None => Vec::with_capacity(capacity),
+    };
+    while let Some(frame) = body.frame().await {
+        if let Ok(data) = frame?.into_data() {
+            buffer.extend_from_slice(&data);
+        }
+    }
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, Method, test_helpers::util::*};
+    use http_body_util::Full;
+    use hyper::body::Bytes;
+
+    #[test]
+    fn content_length_when_no_exact_hint() {
+        let response = Response::builder()
+            .header(CONTENT_LENGTH, "42")
+            .body(http_body_util::StreamBody::new(
+                futures_util::stream::empty::<Result<hyper::body::Frame<Bytes>, hyper::Error>>(),
+            ))
+            .unwrap();
+        let size = BodySize::of(&response);
+        assert_eq!(size.exact(), Some(42));
+        assert!(size.fits_in(42));
+        assert!(!size.fits_in(41));
+
+        let size = BodySize::of(&Response::new(Full::new(Bytes::from("nolanv"))));
+        assert_eq!(size.exact(), Some(6));
+    }
+
+    #[tokio::test]
+    async fn streamed_response_size() {
+        let (_server, mut client) = make_client_server("streamed_response_size").await;
+
+        let response = client
+            .send_request_streaming("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request_streaming");
+        assert_eq!(
+            BodySize::of(&response).exact(),
+            Some("Hello nolanv".len() as u64)
+        );
+        let body = collect(response.into_body(), &Options::default())
+            .await
+            .expect("collect");
+        assert_eq!(body, b"Hello nolanv");
+        assert_eq!(body.capacity(), body.len());
+    }
+
+    #[tokio::test]
+    async fn preallocate_multi_megabyte_body() {
+        const LEN: usize = 3 * 1024 * 1024;
+        let server = crate::testing::MockServer::start(
+            axum::Router::new().route("/layer", axum::routing::get(|| async { vec![42u8; LEN] })),
+        )
+        .await
+        .expect("MockServer::start");
+
+        let mut client = server.client().await.expect("server.client");
+        let (_, body) = client
+            .send_request("/layer", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body.len(), LEN);
+        assert_eq!(body.capacity(), LEN);
+
+        let mut client = ClientUnix::builder(&server.socket_path().to_string_lossy())
+            .max_preallocation(1024)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+        let (_, body) = client
+            .send_request("/layer", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body.len(), LEN);
+    }
+}
@@ -0,0 +1,325 @@
+use crate::ClientUnix;
+use axum::{
+    Router,
+    body::Body,
+    extract::Request,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::any,
+};
+use std::{
+    future::Future,
+    io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{
+    net::{TcpListener, TcpStream, ToSocketAddrs, UnixListener, UnixStream},
+    sync::Mutex,
+    task::JoinHandle,
+};
+
+/// Headers not forwarded: they describe the connection or the framing of one side only.
+const HOP_BY_HOP_HEADERS: [&str; 5] = [
+    "connection",
+    "content-length",
+    "transfer-encoding",
+    "keep-alive",
+    "host",
+];
+
+/// HTTP proxy listening on a TCP port and forwarding the requests through a [`ClientUnix`], to
+/// expose a local daemon to the tools which only speak TCP (feature=bridge).
+///
+/// The responses are collected before being forwarded, so the streamed endpoints (logs, events)
+/// only get their body once the daemon closes it.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::{ClientUnix, TcpBridge};
+///
+/// # async fn run() {
+/// let client = ClientUnix::try_new("/var/run/docker.sock")
+///     .await
+///     .expect("ClientUnix::try_new");
+/// let bridge = TcpBridge::builder(client)
+///     .allow_path("/version")
+///     .allow_path("/containers")
+///     .bind("127.0.0.1:2375")
+///     .await
+///     .expect("TcpBridgeBuilder::bind");
+/// bridge.serve().await.expect("bridge.serve");
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TcpBridge {
+    listener: TcpListener,
+    router: Router,
+}
+
+impl TcpBridge {
+    pub fn builder(client: ClientUnix) -> TcpBridgeBuilder {
+        TcpBridgeBuilder {
+            client,
+            allowed_paths: Vec::new(),
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Forward the requests until an error occurs on the listener.
+    pub async fn serve(self) -> io::Result<()> {
+        axum::serve(self.listener, self.router.into_make_service()).await
+    }
+
+    /// Forward the requests until `signal` completes, then wait for the requests in progress.
+    pub async fn serve_with_shutdown(
+        self,
+        signal: impl Future<Output = ()> + Send + 'static,
+    ) -> io::Result<()> {
+        axum::serve(self.listener, self.router.into_make_service())
+            .with_graceful_shutdown(signal)
+            .await
+    }
+}
+
+pub struct TcpBridgeBuilder {
+    client: ClientUnix,
+    allowed_paths: Vec<String>,
+}
+
+impl TcpBridgeBuilder {
+    /// Only forward the requests on `path` or below it, e.g. `/containers` allows
+    /// `/containers/json` but not `/containersx`. The others are answered with
+    /// `403 Forbidden`. Every path is forwarded when none is allowed.
+    pub fn allow_path(mut self, path: impl Into<String>) -> Self {
+        self.allowed_paths
+            .push(path.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    pub async fn bind(self, addr: impl ToSocketAddrs) -> io::Result<TcpBridge> {
+        let listener = TcpListener::bind(addr).await?;
+        let client = Arc::new(Mutex::new(self.client));
+        let allowed_paths = Arc::new(self.allowed_paths);
+        let router = Router::new().fallback(any(move |request: Request| {
+            let (client, allowed_paths) = (client.clone(), allowed_paths.clone());
+            async move { forward(&client, &allowed_paths, request).await }
+        }));
+        Ok(TcpBridge { listener, router })
+    }
+}
+
+fn is_allowed(allowed_paths: &[String], path: &str) -> bool {
+    allowed_paths.is_empty()
+        || allowed_paths.iter().any(|allowed| {
+            path.strip_prefix(allowed.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+}
+
+async fn forward(
+    client: &Mutex<ClientUnix>,
+    allowed_paths: &[String],
+    request: Request,
+) -> Response {
+    let (parts, body) = request.into_parts();
+    if !is_allowed(allowed_paths, parts.uri.path()) {
+        return (StatusCode::FORBIDDEN, "path not allowed").into_response();
+    }
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    let mut headers = parts.headers;
+    for name in HOP_BY_HOP_HEADERS {
+        headers.remove(name);
+    }
+    let body = (!body.is_empty()).then_some(body);
+
+    let response = {
+        let mut client = client.lock().await;
+        match client.request_target(&parts.uri, &parts.method) {
+            Ok(endpoint) => {
+                client
+                    .send_buffered_headers(&endpoint, parts.method, &headers, body.into())
+                    .await
+            }
+            Err(e) => Err(e),
+        }
+    };
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => return (StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+    };
+    let (parts, body) = response.into_parts();
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = parts.status;
+    for (name, value) in &parts.headers {
+        if !HOP_BY_HOP_HEADERS.contains(&name.as_str()) {
+            response.headers_mut().append(name, value.clone());
+        }
+    }
+    response
+}
+
+/// Forwarder accepting the connections on a unix socket and forwarding their bytes to a TCP
+/// server, to present a TCP service behind a socket path to the code under test
+/// (feature=bridge). It runs until dropped, which also removes its socket.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::{ClientUnix, UnixForwarder};
+///
+/// # async fn run() {
+/// let forwarder = UnixForwarder::start("/tmp/nolanv/api.sock", "127.0.0.1:8080")
+///     .await
+///     .expect("UnixForwarder::start");
+/// let client = ClientUnix::try_new(&forwarder.socket_path().to_string_lossy()).await;
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct UnixForwarder {
+    socket_path: PathBuf,
+    handle: JoinHandle<()>,
+}
+
+impl UnixForwarder {
+    /// Listen on `socket_path`, replacing an existing socket, and forward each connection to a
+    /// new connection to `target`, e.g. `127.0.0.1:8080`.
+    pub async fn start(
+        socket_path: impl Into<PathBuf>,
+        target: impl Into<String>,
+    ) -> io::Result<Self> {
+        let (socket_path, target) = (socket_path.into(), Arc::new(target.into()));
+        if tokio::fs::try_exists(&socket_path).await? {
+            tokio::fs::remove_file(&socket_path).await?;
+        }
+        let listener = UnixListener::bind(&socket_path)?;
+        let handle = tokio::spawn(async move {
+            while let Ok((client, _)) = listener.accept().await {
+                tokio::spawn(forward_to_tcp(client, target.clone()));
+            }
+        });
+        Ok(UnixForwarder {
+            socket_path,
+            handle,
+        })
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+}
+
+impl Drop for UnixForwarder {
+    fn drop(&mut self) {
+        self.handle.abort();
+        let _ = std::fs::remove_file(&self.socket_path);
+    }
+}
+
+async fn forward_to_tcp(mut client: UnixStream, target: Arc<String>) {
+    let Ok(mut server) = TcpStream::connect(target.as_str()).await else {
+        return;
+    };
+    let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{server::Server, util::make_socket_path_test};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn send_tcp(addr: SocketAddr, request: &str) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr)
+            .await
+            .expect("TcpStream::connect");
+        stream
+            .write_all(request.as_bytes())
+            .await
+            .expect("stream.write_all");
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .await
+            .expect("stream.read_to_string");
+        response
+    }
+
+    #[test]
+    fn allowed_paths() {
+        let allowed = ["/containers".to_string(), "/version".to_string()];
+        assert!(is_allowed(&allowed, "/containers"));
+        assert!(is_allowed(&allowed, "/containers/json"));
+        assert!(is_allowed(&allowed, "/version"));
+        assert!(!is_allowed(&allowed, "/containersx"));
+        assert!(!is_allowed(&allowed, "/images/json"));
+        assert!(is_allowed(&[], "/images/json"));
+    }
+
+    #[tokio::test]
+    async fn forward_tcp_to_socket() {
+        let socket_path = make_socket_path_test("bridge", "forward_tcp_to_socket");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let client = ClientUnix::try_new(&socket_path)
+            .await
+            .expect("ClientUnix::try_new");
+        let bridge = TcpBridge::builder(client)
+            .allow_path("/nolanv/")
+            .bind("127.0.0.1:0")
+            .await
+            .expect("TcpBridgeBuilder::bind");
+        let addr = bridge.local_addr().expect("bridge.local_addr");
+        tokio::spawn(bridge.serve());
+
+        let response = send_tcp(
+            addr,
+            "GET /nolanv HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n",
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert!(response.ends_with("Hello nolanv"), "{}", response);
+
+        let response = send_tcp(
+            addr,
+            "GET /_ping HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n",
+        )
+        .await;
+        assert!(
+            response.starts_with("HTTP/1.1 403 Forbidden"),
+            "{}",
+            response
+        );
+    }
+
+    #[tokio::test]
+    async fn forward_socket_to_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("TcpListener::bind");
+        let addr = listener.local_addr().expect("listener.local_addr");
+        let router = Router::new().route("/_ping", any(|| async { "OK from TCP" }));
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let socket_path = make_socket_path_test("bridge", "forward_socket_to_tcp");
+        let forwarder = UnixForwarder::start(&socket_path, addr.to_string())
+            .await
+            .expect("UnixForwarder::start");
+        let mut client = ClientUnix::try_new(&socket_path)
+            .await
+            .expect("ClientUnix::try_new");
+        let (_, body) = client
+            .send_request("/_ping", crate::Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"OK from TCP");
+
+        drop(forwarder);
+        assert!(!Path::new(&socket_path).exists());
+    }
+}
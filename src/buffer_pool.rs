@@ -0,0 +1,146 @@
+use std::sync::{Arc, Mutex};
+
+/// Largest buffer kept by default, the bigger ones are freed instead of holding their memory.
+const DEFAULT_MAX_CAPACITY: usize = 1024 * 1024;
+
+/// Pool of the buffers the response bodies are collected into, to reuse their allocations across
+/// requests, set with [`ClientUnixBuilder::buffer_pool`](crate::ClientUnixBuilder::buffer_pool).
+///
+/// The bodies parsed by `send_request_json` go back to the pool, the bodies returned to the
+/// caller do once given to [`BufferPool::recycle`]. Clones share the same buffers, so several
+/// clients can use one pool.
+///
+/// ```rust
+/// use http_client_unix_domain_socket::{BufferPool, ClientUnix, Method};
+///
+/// pub async fn poll_status(pool: BufferPool) {
+///     let mut client = ClientUnix::builder("/tmp/unix.socket")
+///         .buffer_pool(pool.clone())
+///         .build()
+///         .await
+///         .expect("ClientUnixBuilder::build");
+///     loop {
+///         let (_, body) = client
+///             .send_request("/status", Method::GET, &[], None)
+///             .await
+///             .expect("client.send_request");
+///         // Read the body, then give its buffer back
+///         pool.recycle(body);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufferPool {
+    buffers: Arc<Mutex<Vec<Vec<u8>>>>,
+    max_buffers: usize,
+    max_capacity: usize,
+}
+
+impl BufferPool {
+    /// Keep at most `max_buffers` idle buffers.
+    pub fn new(max_buffers: usize) -> Self {
+        BufferPool {
+            buffers: Arc::new(Mutex::new(Vec::with_capacity(max_buffers))),
+            max_buffers,
+            max_capacity: DEFAULT_MAX_CAPACITY,
+        }
+    }
+
+    /// Free the recycled buffers bigger than `bytes` instead of keeping them, 1 MiB by default.
+    pub fn max_capacity(mut self, bytes: usize) -> Self {
+        self.max_capacity = bytes;
+        self
+    }
+
+    /// Give back a buffer, such as the body of a response once read.
+    pub fn recycle(&self, mut buffer: Vec<u8>) {
+        if buffer.capacity() == 0 || buffer.capacity() > self.max_capacity {
+            return;
+        }
+        buffer.clear();
+        if let Ok(mut buffers) = self.buffers.lock()
+            && buffers.len() < self.max_buffers
+        {
+            buffers.push(buffer);
+        }
+    }
+
+    /// Idle buffers in the pool.
+    pub fn len(&self) -> usize {
+        self.buffers.lock().map_or(0, |buffers| buffers.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// An empty buffer with room for at least `capacity` bytes, reused when one is idle.
+    pub(crate) fn take(&self, capacity: usize) -> Vec<u8> {
+        let buffer = self
+            .buffers
+            .lock()
+            .ok()
+            .and_then(|mut buffers| buffers.pop());
+        match buffer {
+            Some(mut buffer) => {
+                buffer.reserve(capacity);
+                buffer
+            }
+            None => Vec::with_capacity(capacity),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, Method, test_helpers::server::Server, test_helpers::util::*};
+
+    #[test]
+    fn recycle_within_limits() {
+        let pool = BufferPool::new(1).max_capacity(64);
+        pool.recycle(Vec::with_capacity(128));
+        assert!(pool.is_empty());
+
+        let mut buffer = Vec::with_capacity(32);
+        buffer.extend_from_slice(b"nolanv");
+        let pointer = buffer.as_ptr();
+        pool.recycle(buffer);
+        pool.recycle(Vec::with_capacity(32));
+        assert_eq!(pool.len(), 1);
+
+        let buffer = pool.take(16);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.as_ptr(), pointer);
+        assert!(pool.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reuse_response_buffers() {
+        let socket_path = make_socket_path_test("buffer_pool", "reuse_response_buffers");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let pool = BufferPool::new(4);
+        let mut client = ClientUnix::builder(&socket_path)
+            .buffer_pool(pool.clone())
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let (_, body) = client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        let pointer = body.as_ptr();
+        pool.recycle(body);
+
+        let (_, body) = client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"Hello nolanv");
+        assert_eq!(body.as_ptr(), pointer);
+        assert!(pool.is_empty());
+    }
+}
@@ -0,0 +1,447 @@
+use crate::{
+    ClientPool, ClientUnix, Error, PoolSizing, RequestCoalescer, RequestHistory, ResponseCache,
+    ShardedClient,
+    client::{DEFAULT_USER_AGENT, MIN_READ_BUFFER, Options, ReadBuffer},
+    hooks::{ErrorEvent, RequestEvent, ResponseEvent},
+    interceptor::Interceptor,
+    rate_limit::RateLimiter,
+    wire::WireSink,
+};
+use hyper::{
+    StatusCode,
+    header::{AUTHORIZATION, HeaderName, HeaderValue},
+    http::uri::Scheme,
+};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+/// Configure a [`ClientUnix`] before connecting, created with [`ClientUnix::builder`].
+///
+/// Every setting is kept across [`ClientUnix::try_reconnect`].
+pub struct ClientUnixBuilder {
+    socket_path: PathBuf,
+    options: Options,
+}
+
+impl ClientUnixBuilder {
+    pub(crate) fn new(socket_path: &str) -> Self {
+        ClientUnixBuilder {
+            socket_path: PathBuf::from(socket_path),
+            options: Options {
+                user_agent: Some(HeaderValue::from_static(DEFAULT_USER_AGENT)),
+                ..Options::default()
+            },
+        }
+    }
+
+    /// Register an [`Interceptor`] called on every request.
+    pub fn interceptor(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.options.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// Tee every raw byte written to and read from the socket into `sink`, for debugging.
+    pub fn wire_dump(mut self, sink: impl WireSink + 'static) -> Self {
+        self.options.wire_sink = Some(Arc::new(sink));
+        self
+    }
+
+    /// Mask the value of `header` in every log and debug output, such as [`wire_dump`](Self::wire_dump).
+    pub fn redact_header(mut self, header: HeaderName) -> Self {
+        self.options.redactor.add(header);
+        self
+    }
+
+    /// Send `Authorization: Basic` with the base64 encoded credentials on every request which
+    /// doesn't already set it. The header is redacted from debug outputs.
+    pub fn basic_auth(self, user: &str, password: Option<&str>) -> Self {
+        self.redact_header(AUTHORIZATION)
+            .interceptor(crate::BasicAuth::new(user, password))
+    }
+
+    /// Send `Authorization: Bearer` with a token asked to `provider` before every request which
+    /// doesn't already set it. The header is redacted from debug outputs.
+    pub fn bearer_auth(self, provider: impl crate::TokenProvider + 'static) -> Self {
+        self.redact_header(AUTHORIZATION)
+            .interceptor(crate::BearerAuth::new(provider))
+    }
+
+    /// Answer `WWW-Authenticate: Digest` challenges (MD5 or SHA-256, RFC 7616) by sending the
+    /// request once more, and reuse the challenge for the following requests. The header is
+    /// redacted from debug outputs.
+    #[cfg(feature = "digest")]
+    pub fn digest_auth(mut self, user: &str, password: &str) -> Self {
+        self.options.digest_auth = Some(crate::digest::DigestAuth::new(user, password));
+        self.redact_header(AUTHORIZATION)
+    }
+
+    /// Hash the response bodies which have a `Content-Digest` (`sha-256` or `sha-512`) or a
+    /// `Content-MD5` header, the strongest one, and fail with [`Error::DigestMismatch`] when they
+    /// don't match, such as a truncated download. Streamed responses are only checked by
+    /// [`send_request_to_writer`](ClientUnix::send_request_to_writer), once written.
+    #[cfg(feature = "integrity")]
+    pub fn verify_content_digest(mut self, enabled: bool) -> Self {
+        self.options.verify_digest = enabled;
+        self
+    }
+
+    /// Inject the current OpenTelemetry context into every request with the global propagator.
+    ///
+    /// Use [`TraceContextPropagation::with_propagator`](crate::TraceContextPropagation::with_propagator)
+    /// with [`interceptor`](Self::interceptor) to choose the propagator.
+    #[cfg(feature = "opentelemetry")]
+    pub fn propagate_trace_context(self) -> Self {
+        self.interceptor(crate::TraceContextPropagation::global())
+    }
+
+    /// Send a generated UUID v4 as `header` on every request which doesn't already set it.
+    ///
+    /// The id is added to the tracing spans (feature=tracing) and to the errors of the request,
+    /// see [`RequestId`](crate::RequestId).
+    pub fn request_id_header(mut self, header: HeaderName) -> Self {
+        self.options.request_id_header = Some(header);
+        self
+    }
+
+    /// Record the traffic into `recorder`, to be exported as a HAR file.
+    #[cfg(feature = "har")]
+    pub fn har_recorder(mut self, recorder: crate::HarRecorder) -> Self {
+        self.options.har_recorder = Some(recorder);
+        self
+    }
+
+    /// Consider the responses whose status matches `predicate` as successful instead of the 2xx
+    /// ones, such as a 404 for a resource which must be absent. The other responses are returned
+    /// as [`ResponseUnsuccessful`](crate::ErrorAndResponse::ResponseUnsuccessful), and with
+    /// `send_request_json` the body of the successful ones is deserialized as the response type.
+    ///
+    /// ```rust
+    /// use http_client_unix_domain_socket::{ClientUnix, StatusCode};
+    ///
+    /// pub async fn ensure_absent() {
+    ///     let _client = ClientUnix::builder("/var/run/docker.sock")
+    ///         .success_predicate(|status| status.is_success() || status == StatusCode::NOT_FOUND)
+    ///         .build()
+    ///         .await
+    ///         .expect("ClientUnixBuilder::build");
+    /// }
+    /// ```
+    pub fn success_predicate(
+        mut self,
+        predicate: impl Fn(StatusCode) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.options.success = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Deserialize the body of the unsuccessful responses into `E`, the error type of the API,
+    /// returned as [`ErrorAndResponse::Api`](crate::ErrorAndResponse::Api). The bodies which
+    /// aren't an `E` are still returned as
+    /// [`ResponseUnsuccessful`](crate::ErrorAndResponse::ResponseUnsuccessful) (feature=json).
+    ///
+    /// ```rust,no_run
+    /// use http_client_unix_domain_socket::{ClientUnix, ErrorAndResponse, Method};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct DockerError {
+    ///     message: String,
+    /// }
+    ///
+    /// # async fn run() {
+    /// let mut client = ClientUnix::builder("/var/run/docker.sock")
+    ///     .api_error::<DockerError>()
+    ///     .build()
+    ///     .await
+    ///     .expect("ClientUnixBuilder::build");
+    /// match client.send_request("/containers/nolanv/json", Method::GET, &[], None).await {
+    ///     Err(ErrorAndResponse::Api(_, error)) => {
+    ///         let error = error.downcast_ref::<DockerError>().expect("a DockerError");
+    ///         eprintln!("{}", error.message)
+    ///     }
+    ///     result => println!("{:?}", result),
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn api_error<E>(mut self) -> Self
+    where
+        E: serde::de::DeserializeOwned + std::fmt::Debug + Send + Sync + 'static,
+    {
+        self.options.api_error = Some(Arc::new(|body: &[u8]| {
+            crate::client::json_from_slice::<E>(body)
+                .ok()
+                .map(|error| Box::new(error) as Box<dyn crate::error::ApiValue>)
+        }));
+        self
+    }
+
+    /// Send `Accept: application/json` with the requests of `send_request_json` and
+    /// `stream_json_array` which don't set an `Accept` header, directly or with
+    /// [`default_header`](Self::default_header) (feature=json). Enabled by default.
+    #[cfg(feature = "json")]
+    pub fn auto_accept(mut self, enabled: bool) -> Self {
+        self.options.manual_accept = !enabled;
+        self
+    }
+
+    /// Check the `Content-Type` of the responses before `send_request_json` deserializes them,
+    /// failing with [`Error::UnexpectedContentType`] rather than a parsing error on an HTML error
+    /// page (feature=json). Every body is deserialized by default.
+    #[cfg(feature = "json")]
+    pub fn json_content_type(mut self, check: crate::ContentTypeCheck) -> Self {
+        self.options.json_content_type = check;
+        self
+    }
+
+    /// Call `hook` with the metadata of every request, after the interceptors.
+    pub fn on_request(mut self, hook: impl Fn(&RequestEvent<'_>) + Send + Sync + 'static) -> Self {
+        self.options.hooks.on_request.push(Arc::new(hook));
+        self
+    }
+
+    /// Call `hook` with the status code and latency of every response, once its body is read, or
+    /// once its head is received for the streamed ones. A request whose body can't be read gets
+    /// the error event instead.
+    pub fn on_response(
+        mut self,
+        hook: impl Fn(&ResponseEvent<'_>) + Send + Sync + 'static,
+    ) -> Self {
+        self.options.hooks.on_response.push(Arc::new(hook));
+        self
+    }
+
+    /// Call `hook` with the error of every failed request.
+    pub fn on_error(mut self, hook: impl Fn(&ErrorEvent<'_>) + Send + Sync + 'static) -> Self {
+        self.options.hooks.on_error.push(Arc::new(hook));
+        self
+    }
+
+    /// Keep the outcome of the last requests in `history`, see [`RequestHistory`].
+    pub fn request_history(mut self, history: RequestHistory) -> Self {
+        let (on_response, on_error) = (history.clone(), history.clone());
+        self.options
+            .hooks
+            .on_response
+            .push(Arc::new(move |event| on_response.record_response(event)));
+        self.options
+            .hooks
+            .on_error
+            .push(Arc::new(move |event| on_error.record_error(event)));
+        self.options.request_history = Some(history);
+        self
+    }
+
+    /// Transparently reconnect before sending a request when the connection was closed, for
+    /// example by the server after an idle timeout. Enabled by default.
+    ///
+    /// The client always reconnects after a response with `Connection: close`, as sent by the
+    /// servers which answer a single request per connection.
+    pub fn reconnect_when_closed(mut self, enabled: bool) -> Self {
+        self.options.manual_reconnect = !enabled;
+        self
+    }
+
+    /// Send again the requests which failed because of the connection, see [`RetryPolicy`].
+    pub fn retry(mut self, policy: crate::RetryPolicy) -> Self {
+        self.options.retry = Some(policy);
+        self
+    }
+
+    /// Send at most `per_second` requests per second, with bursts of up to `burst` requests.
+    /// Requests over the limit wait for their turn, retries included.
+    /// A `per_second` which isn't over zero, NaN included, only lets the first burst through.
+    pub fn rate_limit(mut self, per_second: f64, burst: u32) -> Self {
+        self.options.rate_limiter = Some(Arc::new(RateLimiter::new(per_second, burst)));
+        self
+    }
+
+    /// Write at most `bytes_per_second` to the socket on average, request heads and bodies
+    /// included, with bursts of a tenth of a second. Shared by the reconnections of the client.
+    pub fn upload_limit(mut self, bytes_per_second: u32) -> Self {
+        self.options.bandwidth.upload = Some(Arc::new(RateLimiter::new(
+            f64::from(bytes_per_second.max(1)),
+            bytes_per_second / 10,
+        )));
+        self
+    }
+
+    /// Read at most `bytes_per_second` from the socket on average, response heads and bodies
+    /// included, with bursts of a tenth of a second. Shared by the reconnections of the client.
+    pub fn download_limit(mut self, bytes_per_second: u32) -> Self {
+        self.options.bandwidth.download = Some(Arc::new(RateLimiter::new(
+            f64::from(bytes_per_second.max(1)),
+            bytes_per_second / 10,
+        )));
+        self
+    }
+
+    /// Fail the connections, and reconnections, not accepted by the socket within `timeout` with
+    /// [`Error::ConnectTimeout`]. No timeout by default.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.options.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Fail the first request of a connection, or reconnection, when the server accepted the
+    /// connection but sends nothing back within `timeout`, with [`Error::HandshakeTimeout`],
+    /// to tell a wedged server from a missing one. HTTP/1 has no handshake of its own, so the
+    /// connection is established once its first response starts: the timeout stops at its first
+    /// byte, however long the rest takes. A first request which the server takes longer than
+    /// `timeout` to start answering fails too, so keep it above the slowest one; the following
+    /// requests are only bound by the [`request_timeout`](Self::request_timeout). No timeout by
+    /// default.
+    pub fn handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.options.handshake_timeout = Some(timeout);
+        self
+    }
+
+    /// Resume up to `max_resumes` times the downloads of
+    /// [`send_request_to_writer`](ClientUnix::send_request_to_writer) interrupted by the
+    /// connection: the client reconnects and asks the rest of the body with `Range` and
+    /// `If-Range`, without the writer noticing. Only `GET` responses `200 OK` with a strong
+    /// `ETag` are resumed, the download fails with [`Error::ResumeRejected`] when the server
+    /// doesn't send the same resource from the same offset. Disabled by default.
+    pub fn resume_downloads(mut self, max_resumes: u32) -> Self {
+        self.options.resume_downloads = max_resumes;
+        self
+    }
+
+    /// Set `SO_SNDBUF` of the socket, for large request bodies when the default kernel buffer
+    /// limits the throughput. The kernel doubles it, within `net.core.wmem_max`.
+    pub fn send_buffer_size(mut self, bytes: usize) -> Self {
+        self.options.send_buffer_size = Some(bytes);
+        self
+    }
+
+    /// Set `SO_RCVBUF` of the socket, for large response bodies. The kernel doubles it, within
+    /// `net.core.rmem_max`.
+    pub fn recv_buffer_size(mut self, bytes: usize) -> Self {
+        self.options.recv_buffer_size = Some(bytes);
+        self
+    }
+
+    /// Send the requests of [`ClientUnix::send_all`] on up to `connections`, at least one, this
+    /// connection included, 8 by default. One sends the requests one after the other.
+    pub fn batch_connections(mut self, connections: usize) -> Self {
+        self.options.batch_connections = Some(connections.max(1));
+        self
+    }
+
+    /// Fail requests which don't complete within `timeout`, retries and response body included,
+    /// with [`Error::Timeout`].
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.options.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Send the time remaining before the [`request_timeout`](Self::request_timeout) as `header`
+    /// on every attempt which doesn't already set it, so the server can abandon work the client
+    /// won't wait for.
+    pub fn propagate_deadline(mut self, header: HeaderName, format: crate::DeadlineFormat) -> Self {
+        self.options.deadline_header = Some((header, format));
+        self
+    }
+
+    /// Collect the response bodies into the buffers of `pool`, see [`BufferPool`](crate::BufferPool).
+    pub fn buffer_pool(mut self, pool: crate::BufferPool) -> Self {
+        self.options.buffer_pool = Some(pool);
+        self
+    }
+
+    /// Send `header` on every request which doesn't set it, encoded once rather than per request.
+    /// Repeated for several values of the same header.
+    pub fn default_header(mut self, header: HeaderName, value: HeaderValue) -> Self {
+        self.options.default_headers.append(header, value);
+        self
+    }
+
+    /// Allocate at most `bytes` up front for a response body with a `Content-Length`, so a
+    /// wrong length from the server doesn't allocate it all at once, 16 MiB by default. The bodies
+    /// up to this size are read into a buffer allocated once, the bigger ones grow past it.
+    pub fn max_preallocation(mut self, bytes: u64) -> Self {
+        self.options.max_preallocation = Some(bytes);
+        self
+    }
+
+    /// Write the head and the body chunks of a request in one vectored write rather than copying
+    /// them into one buffer first, faster for large streamed uploads. By default hyper uses
+    /// vectored writes when the socket supports them, as unix sockets do.
+    pub fn vectored_writes(mut self, enabled: bool) -> Self {
+        self.options.vectored_writes = Some(enabled);
+        self
+    }
+
+    /// Let the read buffer of the connection grow up to `bytes`, at least 8 KiB, to read a large
+    /// response body in fewer syscalls. The buffer grows with the reads which fill it, up to about
+    /// 400 KiB by default.
+    pub fn max_buf_size(mut self, bytes: usize) -> Self {
+        self.options.read_buffer = Some(ReadBuffer::Max(bytes.max(MIN_READ_BUFFER)));
+        self
+    }
+
+    /// Read from the connection into a buffer of exactly `bytes` rather than one growing with the
+    /// reads, to bound the memory of the connection. Replaces [`max_buf_size`](Self::max_buf_size).
+    pub fn read_buf_exact_size(mut self, bytes: usize) -> Self {
+        self.options.read_buffer = Some(ReadBuffer::Exact(bytes));
+        self
+    }
+
+    /// Share one request between the identical GET requests sent at the same time by the clients
+    /// using `coalescer`, to spare a slow daemon a burst of the same request.
+    pub fn coalesce_gets(mut self, coalescer: RequestCoalescer) -> Self {
+        self.options.coalescer = Some(coalescer);
+        self
+    }
+
+    /// Answer the GET requests of the endpoints chosen in `cache` from the responses it keeps,
+    /// shared with the other clients using it.
+    pub fn response_cache(mut self, cache: ResponseCache) -> Self {
+        self.options.response_cache = Some(cache);
+        self
+    }
+
+    /// Keep at most `bytes` of a response body of
+    /// [`send_request_spooled`](crate::ClientUnix::send_request_spooled) in memory, 16 MiB by
+    /// default, the remainder is written to a temporary file.
+    #[cfg(feature = "spool")]
+    pub fn spool_threshold(mut self, bytes: u64) -> Self {
+        self.options.spool_threshold = Some(bytes);
+        self
+    }
+
+    /// Send the requests with `scheme` in their absolute URI, `http` by default, for the servers
+    /// building absolute URLs from it, such as `https` behind a TLS-terminating proxy.
+    pub fn uri_scheme(mut self, scheme: Scheme) -> Self {
+        self.options.scheme = Some(scheme);
+        self
+    }
+
+    /// `User-Agent` sent when a request doesn't set one, `http-client-unix-domain-socket/<version>`
+    /// by default. `None` sends no `User-Agent`.
+    pub fn user_agent(mut self, user_agent: Option<HeaderValue>) -> Self {
+        self.options.user_agent = user_agent;
+        self
+    }
+
+    pub async fn build(self) -> Result<ClientUnix, Error> {
+        ClientUnix::try_connect(self.socket_path, self.options).await
+    }
+
+    /// Keep these settings for the process-wide client of [`ClientUnix::global`], connected on
+    /// its first use. Returns `false`, leaving the settings unchanged, when they were already set.
+    pub fn set_global(self) -> bool {
+        crate::global::configure(self.socket_path, self.options)
+    }
+
+    /// Open `shards` connections, at least one, for the threads of a multi-threaded runtime to
+    /// send their requests on different ones, see [`ShardedClient`].
+    pub async fn build_sharded(self, shards: usize) -> Result<ShardedClient, Error> {
+        ShardedClient::try_connect(self.socket_path, self.options, shards).await
+    }
+
+    /// Open the minimum connections of `sizing`, and more as requests wait for one, see
+    /// [`ClientPool`].
+    pub async fn build_pool(self, sizing: PoolSizing) -> Result<ClientPool, Error> {
+        ClientPool::try_connect(self.socket_path, self.options, sizing).await
+    }
+}
@@ -0,0 +1,215 @@
+//! Typed wrappers of the [Caddy admin API](https://caddyserver.com/docs/api) (feature=caddy), on
+//! the socket of `admin unix//run/caddy/admin.sock`.
+//!
+//! The configuration is traversed by paths such as `apps/http/servers/srv0/listen`, and the
+//! errors are `{"error": "..."}` bodies.
+//!
+//! ```rust,no_run
+//! use http_client_unix_domain_socket::caddy::Caddy;
+//! use serde_json::json;
+//!
+//! # async fn run() {
+//! let mut caddy = Caddy::connect("/run/caddy/admin.sock")
+//!     .await
+//!     .expect("Caddy::connect");
+//! caddy
+//!     .load(&json!({"apps": {"http": {"servers": {"srv0": {"listen": [":443"]}}}}}))
+//!     .await
+//!     .expect("caddy.load");
+//! caddy
+//!     .patch_config("apps/http/servers/srv0/listen", &json!([":8443"]))
+//!     .await
+//!     .expect("caddy.patch_config");
+//! let listen: Vec<String> = caddy
+//!     .config("apps/http/servers/srv0/listen")
+//!     .await
+//!     .expect("caddy.config");
+//! # }
+//! ```
+
+use crate::{ClientUnix, Error, ErrorAndResponseJson, Method};
+use axum_core::body::Body;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// Body of the Caddy error responses.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CaddyError {
+    pub error: String,
+}
+
+pub type CaddyResult<T> = Result<T, ErrorAndResponseJson<CaddyError>>;
+
+/// Upstream of the reverse proxies, see [`Caddy::upstreams`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Upstream {
+    pub address: String,
+    /// Requests in progress.
+    pub num_requests: u64,
+    /// Recent failures, within the `fail_duration` of the passive health checks.
+    pub fails: u64,
+}
+
+/// Caddy admin API client.
+pub struct Caddy {
+    client: ClientUnix,
+}
+
+impl Caddy {
+    pub fn new(client: ClientUnix) -> Self {
+        Caddy { client }
+    }
+
+    pub async fn connect(socket_path: &str) -> Result<Self, Error> {
+        Ok(Caddy::new(ClientUnix::try_new(socket_path).await?))
+    }
+
+    /// The underlying client, for the endpoints without a wrapper.
+    pub fn client(&mut self) -> &mut ClientUnix {
+        &mut self.client
+    }
+
+    /// Replace the whole configuration, rolled back by Caddy if it fails to apply.
+    pub async fn load<IN: Serialize>(&mut self, config: &IN) -> CaddyResult<()> {
+        self.send_json("/load", Method::POST, Some(config))
+            .await
+            .map(|_| ())
+    }
+
+    /// The configuration at `path`, the whole configuration for an empty path.
+    pub async fn config<OUT: DeserializeOwned>(&mut self, path: &str) -> CaddyResult<OUT> {
+        let body = self
+            .send_json::<()>(&config_endpoint(path), Method::GET, None)
+            .await?;
+        crate::client::json_from_slice(&body)
+            .map_err(|e| ErrorAndResponseJson::InternalError(Error::ResponseParsing(e, body)))
+    }
+
+    /// Set the object at `path`, creating it if needed, or append to the array at `path`.
+    pub async fn set_config<IN: Serialize>(&mut self, path: &str, value: &IN) -> CaddyResult<()> {
+        self.send_json(&config_endpoint(path), Method::POST, Some(value))
+            .await
+            .map(|_| ())
+    }
+
+    /// Create the object at `path`, which must not exist, or insert into an array at the
+    /// position of `path`.
+    pub async fn put_config<IN: Serialize>(&mut self, path: &str, value: &IN) -> CaddyResult<()> {
+        self.send_json(&config_endpoint(path), Method::PUT, Some(value))
+            .await
+            .map(|_| ())
+    }
+
+    /// Replace the existing value at `path`.
+    pub async fn patch_config<IN: Serialize>(&mut self, path: &str, value: &IN) -> CaddyResult<()> {
+        self.send_json(&config_endpoint(path), Method::PATCH, Some(value))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn delete_config(&mut self, path: &str) -> CaddyResult<()> {
+        self.send_json::<()>(&config_endpoint(path), Method::DELETE, None)
+            .await
+            .map(|_| ())
+    }
+
+    /// Stop the apps and exit the process.
+    pub async fn stop(&mut self) -> CaddyResult<()> {
+        self.send_json::<()>("/stop", Method::POST, None)
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn upstreams(&mut self) -> CaddyResult<Vec<Upstream>> {
+        let body = self
+            .send_json::<()>("/reverse_proxy/upstreams", Method::GET, None)
+            .await?;
+        crate::client::json_from_slice(&body)
+            .map_err(|e| ErrorAndResponseJson::InternalError(Error::ResponseParsing(e, body)))
+    }
+
+    async fn send_json<IN: Serialize>(
+        &mut self,
+        endpoint: &str,
+        method: Method,
+        body: Option<&IN>,
+    ) -> CaddyResult<Vec<u8>> {
+        let body = match body {
+            Some(body) => Some(Body::from(serde_json::to_vec(body).map_err(|e| {
+                ErrorAndResponseJson::InternalError(Error::RequestParsing(e))
+            })?)),
+            None => None,
+        };
+        match self
+            .client
+            .send_request(
+                endpoint,
+                method,
+                &[("Content-Type", "application/json")],
+                body,
+            )
+            .await
+        {
+            Ok((_, body)) => Ok(body),
+            Err(error) => match error.into_response() {
+                Err(e) => Err(ErrorAndResponseJson::InternalError(e)),
+                Ok((status_code, body)) => {
+                    let error = serde_json::from_slice(&body).unwrap_or_else(|_| CaddyError {
+                        error: String::from_utf8_lossy(&body).into_owned(),
+                    });
+                    Err(ErrorAndResponseJson::ResponseUnsuccessful(
+                        status_code,
+                        error,
+                    ))
+                }
+            },
+        }
+    }
+}
+
+fn config_endpoint(path: &str) -> String {
+    format!("/config/{}", path.trim_start_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StatusCode, test_helpers::server::Server, test_helpers::util::*};
+    use serde_json::json;
+
+    async fn make_caddy(test_function: &str) -> (Server, Caddy) {
+        let socket_path = make_socket_path_test("caddy", test_function);
+        let server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let caddy = Caddy::connect(&socket_path).await.expect("Caddy::connect");
+        (server, caddy)
+    }
+
+    #[tokio::test]
+    async fn load_and_read_config() {
+        let (_server, mut caddy) = make_caddy("load_and_read_config").await;
+
+        caddy
+            .load(&json!({"apps": {"http": {}}}))
+            .await
+            .expect("caddy.load");
+        let listen: Vec<String> = caddy
+            .config("/apps/http/servers/srv0/listen")
+            .await
+            .expect("caddy.config");
+        assert_eq!(listen, vec![":443"]);
+    }
+
+    #[tokio::test]
+    async fn error_body() {
+        let (_server, mut caddy) = make_caddy("error_body").await;
+
+        let result = caddy.patch_config("apps/nolanv", &json!({})).await;
+
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponseJson::ResponseUnsuccessful(StatusCode::BAD_REQUEST, e))
+                if e.error == "invalid traversal path at: config/apps/nolanv"
+        ));
+    }
+}
@@ -1,191 +1,2365 @@
+use crate::{
+    BufferPool, ClientUnixBuilder, DeadlineFormat, Endpoint, Error, RequestCoalescer,
+    RequestHistory, ResponseCache,
+    error::{ErrorAndResponse, ErrorContext},
+    hooks::{ErrorEvent, Hooks, RequestEvent, ResponseEvent},
+    interceptor::{DynInterceptor, Interceptor},
+    rate_limit::RateLimiter,
+    redact::Redactor,
+    request_id::{self, RequestId},
+    retry::RetryPolicy,
+    stats::{ConnectionCounters, ConnectionStats},
+    status::{ConnectionStatus, StatusSender},
+    timings::RequestTimings,
+    wire::{Bandwidth, WireSink, WireStream},
+};
 #[cfg(feature = "json")]
-use crate::error::ErrorAndResponseJson;
-use crate::{Error, error::ErrorAndResponse};
+use crate::{
+    error::{ApiValue, ErrorAndResponseJson},
+    problem::ProblemDetails,
+};
 use axum_core::body::Body;
 use http_body_util::BodyExt;
 use hyper::{
-    Method, Request, StatusCode,
+    Method, Request, Response, StatusCode,
+    body::{Bytes, Incoming},
     client::conn::http1::{self, SendRequest},
+    header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT},
+    http::uri::{Authority, PathAndQuery, Scheme, Uri},
 };
 use hyper_util::rt::TokioIo;
 #[cfg(feature = "json")]
 use serde::{Serialize, de::DeserializeOwned};
-use std::path::PathBuf;
-use tokio::{net::UnixStream, task::JoinHandle};
+use std::{
+    panic::AssertUnwindSafe,
+    path::{Path, PathBuf},
+    pin::{Pin, pin},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::{io::AsyncWrite, net::UnixStream, sync::watch, task::JoinHandle};
+
+pub struct ClientUnix {
+    socket_path: PathBuf,
+    options: Options,
+    sender: SendRequest<Body>,
+    join_handle: JoinHandle<Error>,
+    counters: Arc<ConnectionCounters>,
+    status: Arc<StatusSender>,
+    /// Generation of the connection in `status`.
+    generation: u64,
+    /// Message of the panic of the connection task, taken by the next request.
+    panic: Arc<Mutex<Option<String>>>,
+    /// The server announced that it closes the connection after the last response.
+    closing: bool,
+}
+
+pub(crate) const DEFAULT_USER_AGENT: &str =
+    concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Settings kept across reconnections.
+#[derive(Clone, Default)]
+pub(crate) struct Options {
+    pub(crate) interceptors: Vec<Arc<dyn DynInterceptor>>,
+    pub(crate) wire_sink: Option<Arc<dyn WireSink>>,
+    pub(crate) redactor: Redactor,
+    pub(crate) request_id_header: Option<HeaderName>,
+    pub(crate) hooks: Hooks,
+    /// Don't reconnect before sending a request on a closed connection.
+    pub(crate) manual_reconnect: bool,
+    /// Don't add an `Accept` header to the requests of the typed helpers.
+    #[cfg(feature = "json")]
+    pub(crate) manual_accept: bool,
+    pub(crate) user_agent: Option<HeaderValue>,
+    /// Scheme of the request URIs, `http` when `None`.
+    pub(crate) scheme: Option<Scheme>,
+    /// Headers encoded once, sent by every request which doesn't set them.
+    pub(crate) default_headers: HeaderMap,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+    pub(crate) bandwidth: Bandwidth,
+    pub(crate) connect_timeout: Option<Duration>,
+    /// Times a download to a writer is resumed, see `ClientUnixBuilder::resume_downloads`.
+    pub(crate) resume_downloads: u32,
+    /// Time for a new connection to answer its first request.
+    pub(crate) handshake_timeout: Option<Duration>,
+    /// `SO_SNDBUF` of the socket, the kernel default when `None`.
+    pub(crate) send_buffer_size: Option<usize>,
+    /// `SO_RCVBUF` of the socket, the kernel default when `None`.
+    pub(crate) recv_buffer_size: Option<usize>,
+    /// Connections of `send_all`, `DEFAULT_BATCH_CONNECTIONS` when `None`.
+    pub(crate) batch_connections: Option<usize>,
+    pub(crate) request_timeout: Option<Duration>,
+    pub(crate) deadline_header: Option<(HeaderName, DeadlineFormat)>,
+    pub(crate) buffer_pool: Option<BufferPool>,
+    pub(crate) max_preallocation: Option<u64>,
+    /// `None` lets hyper choose from the socket.
+    pub(crate) vectored_writes: Option<bool>,
+    pub(crate) read_buffer: Option<ReadBuffer>,
+    pub(crate) coalescer: Option<RequestCoalescer>,
+    pub(crate) response_cache: Option<ResponseCache>,
+    pub(crate) request_history: Option<RequestHistory>,
+    /// Statuses of the successful responses, `StatusCode::is_success` when `None`.
+    pub(crate) success: Option<Arc<dyn Fn(StatusCode) -> bool + Send + Sync>>,
+    /// Deserializer of the unsuccessful bodies into the type of `ClientUnixBuilder::api_error`.
+    #[cfg(feature = "json")]
+    pub(crate) api_error: Option<Arc<ApiErrorParser>>,
+    #[cfg(feature = "json")]
+    pub(crate) json_content_type: crate::ContentTypeCheck,
+    #[cfg(feature = "spool")]
+    pub(crate) spool_threshold: Option<u64>,
+    #[cfg(feature = "digest")]
+    pub(crate) digest_auth: Option<crate::digest::DigestAuth>,
+    #[cfg(feature = "integrity")]
+    pub(crate) verify_digest: bool,
+    #[cfg(feature = "har")]
+    pub(crate) har_recorder: Option<crate::HarRecorder>,
+}
+
+#[cfg(feature = "json")]
+pub(crate) type ApiErrorParser = dyn Fn(&[u8]) -> Option<Box<dyn ApiValue>> + Send + Sync;
+
+impl Options {
+    /// [`ErrorAndResponse::Api`] when the body is the registered error type, else
+    /// [`ErrorAndResponse::ResponseUnsuccessful`].
+    pub(crate) fn unsuccessful(&self, status: StatusCode, body: Vec<u8>) -> ErrorAndResponse {
+        #[cfg(feature = "json")]
+        if let Some(parse) = &self.api_error
+            && let Some(value) = parse(&body)
+        {
+            return ErrorAndResponse::Api(status, crate::error::ApiError::new(value, body));
+        }
+        ErrorAndResponse::ResponseUnsuccessful(status, body)
+    }
+
+    /// `response` when its status code is a success, else its [`unsuccessful`](Self::unsuccessful)
+    /// error.
+    pub(crate) fn successful(
+        &self,
+        response: Response<Vec<u8>>,
+    ) -> Result<Response<Vec<u8>>, ErrorAndResponse> {
+        match self.is_success(response.status()) {
+            true => Ok(response),
+            false => Err(self.unsuccessful(response.status(), response.into_body())),
+        }
+    }
+
+    /// `headers` with `Accept: accept` added, unless they or the default headers already have
+    /// one or the automatic `Accept` headers are disabled.
+    #[cfg(feature = "json")]
+    pub(crate) fn with_accept<'a>(
+        &self,
+        headers: &[(&'a str, &'a str)],
+        accept: &'static str,
+    ) -> Vec<(&'a str, &'a str)> {
+        let mut headers = headers.to_vec();
+        if !self.manual_accept
+            && !self.default_headers.contains_key(hyper::header::ACCEPT)
+            && !headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("accept"))
+        {
+            headers.push(("Accept", accept));
+        }
+        headers
+    }
+
+    pub(crate) fn is_success(&self, status: StatusCode) -> bool {
+        match &self.success {
+            Some(success) => success(status),
+            None => status.is_success(),
+        }
+    }
+}
+
+/// Body of a request, kept as [`Bytes`] when given so, for the attempts to share its buffer.
+pub(crate) enum RequestBody {
+    Bytes(Option<Bytes>),
+    Stream(Body),
+}
+
+impl From<Option<Body>> for RequestBody {
+    fn from(body: Option<Body>) -> Self {
+        match body {
+            Some(body) => RequestBody::Stream(body),
+            None => RequestBody::Bytes(None),
+        }
+    }
+}
+
+impl From<Option<Bytes>> for RequestBody {
+    fn from(body: Option<Bytes>) -> Self {
+        RequestBody::Bytes(body)
+    }
+}
+
+/// Smallest read buffer accepted by hyper.
+pub(crate) const MIN_READ_BUFFER: usize = 8 * 1024;
+
+/// Sizing of the read buffer of the connection.
+#[derive(Clone, Copy)]
+pub(crate) enum ReadBuffer {
+    /// Grows with the reads up to this size.
+    Max(usize),
+    Exact(usize),
+}
+
+impl ClientUnix {
+    pub async fn try_new(socket_path: &str) -> Result<Self, Error> {
+        ClientUnix::builder(socket_path).build().await
+    }
+
+    pub fn builder(socket_path: &str) -> ClientUnixBuilder {
+        ClientUnixBuilder::new(socket_path)
+    }
+
+    pub async fn try_reconnect(self) -> Result<Self, Error> {
+        let socket_path = self.socket_path.clone();
+        let options = self.options.clone();
+        let status = self.status.clone();
+        self.abort().await;
+        let result = ClientUnix::try_connect_with_status(socket_path, options, status).await;
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_reconnect(result.is_ok());
+        result
+    }
+
+    /// Replace the closed connection by a new one, keeping the same client.
+    pub(crate) async fn reconnect_in_place(&mut self) -> Result<(), Error> {
+        let result = ClientUnix::try_connect_with_status(
+            self.socket_path.clone(),
+            self.options.clone(),
+            self.status.clone(),
+        )
+        .await;
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_reconnect(result.is_ok());
+        #[cfg(feature = "tracing")]
+        tracing::debug!(success = result.is_ok(), "connection closed, reconnecting");
+        #[cfg(feature = "log")]
+        log::debug!(
+            "connection to {} closed, reconnecting (success: {})",
+            self.socket_path.display(),
+            result.is_ok()
+        );
+
+        let old = std::mem::replace(self, result?);
+        old.abort().await;
+        Ok(())
+    }
+
+    /// Stop the connection and return the error which closed it, if it was already closed.
+    /// Dropping the client also stops it, without the error.
+    pub async fn abort(mut self) -> Option<Error> {
+        self.join_handle.abort();
+        self.status
+            .disconnected(self.generation, "connection aborted".to_string());
+        (&mut self.join_handle).await.ok()
+    }
+
+    /// Connect to `socket_path`, send `request`, collect its response and close the connection
+    /// before returning, for the occasional requests such as health pings which don't need a
+    /// client kept around. Only the path and query of the URI of `request` are sent, with
+    /// `Connection: close` unless the request sets it.
+    ///
+    /// ```rust,no_run
+    /// use http_client_unix_domain_socket::{Body, ClientUnix, Request};
+    ///
+    /// # async fn run() {
+    /// let request = Request::get("/_ping").body(Body::empty()).unwrap();
+    /// let response = ClientUnix::oneshot("/var/run/docker.sock", request)
+    ///     .await
+    ///     .expect("ClientUnix::oneshot");
+    /// # }
+    /// ```
+    pub async fn oneshot(
+        socket_path: &str,
+        request: Request<Body>,
+    ) -> Result<Response<Vec<u8>>, ErrorAndResponse> {
+        let mut client = ClientUnix::builder(socket_path)
+            .reconnect_when_closed(false)
+            .build()
+            .await
+            .map_err(ErrorAndResponse::InternalError)?;
+        let (mut parts, body) = request.into_parts();
+        parts
+            .headers
+            .entry(hyper::header::CONNECTION)
+            .or_insert(HeaderValue::from_static("close"));
+        let result = match client.request_target(&parts.uri, &parts.method) {
+            Ok(endpoint) => {
+                client
+                    .send_buffered_headers(
+                        &endpoint,
+                        parts.method,
+                        &parts.headers,
+                        RequestBody::Stream(body),
+                    )
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        let result = result
+            .map_err(ErrorAndResponse::InternalError)
+            .and_then(|response| client.options.successful(response));
+        client.abort().await;
+        result
+    }
+
+    /// Message of the panic of the connection task, reported once.
+    fn take_panic(&self) -> Option<String> {
+        self.panic.lock().ok()?.take()
+    }
+
+    /// Follow the state of the connection, across the reconnections of the client, to react to
+    /// its closing without sending a request.
+    ///
+    /// ```rust,no_run
+    /// use http_client_unix_domain_socket::{ClientUnix, ConnectionStatus};
+    ///
+    /// # async fn run(client: ClientUnix) {
+    /// let mut status = client.status();
+    /// while status.changed().await.is_ok() {
+    ///     if let ConnectionStatus::Disconnected { reason } = &*status.borrow() {
+    ///         eprintln!("disconnected: {}", reason);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn status(&self) -> watch::Receiver<ConnectionStatus> {
+        self.status.subscribe()
+    }
+
+    /// Register an [`Interceptor`] called on every following request, kept across reconnections.
+    pub fn add_interceptor(&mut self, interceptor: impl Interceptor + 'static) {
+        self.options.interceptors.push(Arc::new(interceptor));
+    }
+
+    pub(crate) fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// The history set with
+    /// [`ClientUnixBuilder::request_history`](crate::ClientUnixBuilder::request_history).
+    pub fn request_history(&self) -> Option<&RequestHistory> {
+        self.options.request_history.as_ref()
+    }
+
+    pub(crate) fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Socket and options to open another connection configured as this one.
+    pub(crate) fn connection_settings(&self) -> (PathBuf, Options) {
+        (self.socket_path.clone(), self.options.clone())
+    }
+
+    /// Counters of the current connection.
+    pub fn stats(&self) -> ConnectionStats {
+        self.counters.snapshot()
+    }
+
+    pub(crate) async fn try_connect(socket_path: PathBuf, options: Options) -> Result<Self, Error> {
+        ClientUnix::try_connect_with_status(socket_path, options, Arc::new(StatusSender::new()))
+            .await
+    }
+
+    /// Connect and report it to `status`, shared with the previous connections of the client.
+    async fn try_connect_with_status(
+        socket_path: PathBuf,
+        options: Options,
+        status: Arc<StatusSender>,
+    ) -> Result<Self, Error> {
+        let generation = status.connecting();
+        let result = ClientUnix::connect(socket_path, options, status.clone(), generation).await;
+        if let Err(e) = &result {
+            status.disconnected(generation, e.to_string());
+        }
+        result
+    }
+
+    async fn connect(
+        socket_path: PathBuf,
+        options: Options,
+        status: Arc<StatusSender>,
+        generation: u64,
+    ) -> Result<Self, Error> {
+        let context = || ErrorContext {
+            socket_path: socket_path.clone(),
+            method: None,
+            endpoint: None,
+            request_id: None,
+        };
+        let counters = Arc::new(ConnectionCounters::new());
+        let connect = UnixStream::connect(socket_path.clone());
+        let stream = match options.connect_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, connect)
+                .await
+                .map_err(|_| Error::ConnectTimeout(timeout).with_context(context()))?,
+            None => connect.await,
+        };
+        let stream = stream
+            .and_then(|stream| set_buffer_sizes(&stream, &options).map(|()| stream))
+            .map_err(|e| Error::SocketConnectionInitiation(e).with_context(context()))?;
+        let stream = TokioIo::new(WireStream::new(
+            stream,
+            options.wire_sink.clone(),
+            options.redactor.clone(),
+            counters.clone(),
+            options.bandwidth.clone(),
+        ));
+
+        let mut builder = http1::Builder::new();
+        if let Some(vectored_writes) = options.vectored_writes {
+            builder.writev(vectored_writes);
+        }
+        match options.read_buffer {
+            Some(ReadBuffer::Max(bytes)) => {
+                builder.max_buf_size(bytes);
+            }
+            Some(ReadBuffer::Exact(bytes)) => {
+                builder.read_buf_exact_size(Some(bytes));
+            }
+            None => {}
+        }
+        let (sender, connection) = builder
+            .handshake(stream)
+            .await
+            .map_err(|e| Error::Handhsake(e).with_context(context()))?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(socket_path = %socket_path.display(), "connected");
+        #[cfg(feature = "log")]
+        log::debug!("connected to {}", socket_path.display());
+
+        let (connection_status, panic) = (status.clone(), Arc::new(Mutex::new(None)));
+        let connection_panic = panic.clone();
+        let join_handle = tokio::task::spawn(async move {
+            let error = match CatchPanic(std::pin::pin!(connection)).await {
+                Ok(result) => Error::SocketConnectionClosed(result.err()),
+                Err(message) => {
+                    if let Ok(mut panic) = connection_panic.lock() {
+                        *panic = Some(message.clone());
+                    }
+                    Error::ConnectionTaskPanicked(message)
+                }
+            };
+            let reason = match &error {
+                Error::SocketConnectionClosed(Some(e)) => e.to_string(),
+                e => e.to_string(),
+            };
+            connection_status.disconnected(generation, reason);
+            error
+        });
+        status.connected(generation);
+
+        Ok(ClientUnix {
+            socket_path,
+            options,
+            sender,
+            join_handle,
+            counters,
+            status,
+            generation,
+            panic,
+            closing: false,
+        })
+    }
+
+    pub async fn send_request(
+        &mut self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Body>,
+    ) -> Result<(StatusCode, Vec<u8>), ErrorAndResponse> {
+        let response = self
+            .send_request_full(endpoint, method, headers, body_request)
+            .await?;
+        Ok((response.status(), response.into_body()))
+    }
+
+    /// Like [`send_request`](Self::send_request) but return the whole response: status code,
+    /// headers, body and extensions such as [`RequestTimings`].
+    pub async fn send_request_full(
+        &mut self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Body>,
+    ) -> Result<Response<Vec<u8>>, ErrorAndResponse> {
+        let endpoint = self
+            .request_target(&endpoint, &method)
+            .map_err(ErrorAndResponse::InternalError)?;
+        let response = self
+            .send_buffered(&endpoint, method, headers, body_request.into())
+            .await
+            .map_err(ErrorAndResponse::InternalError)?;
+        self.options.successful(response)
+    }
+
+    /// Like [`send_request_full`](Self::send_request_full) with a body already in memory, sent as
+    /// is by every attempt of the request rather than read into a new buffer first.
+    ///
+    /// ```rust,no_run
+    /// use http_client_unix_domain_socket::{ClientUnix, Method};
+    /// use hyper::body::Bytes;
+    ///
+    /// # async fn run(mut client: ClientUnix, archive: Vec<u8>) {
+    /// let archive = Bytes::from(archive);
+    /// let response = client
+    ///     .send_request_bytes("/build", Method::POST, &[], Some(archive))
+    ///     .await
+    ///     .expect("client.send_request_bytes");
+    /// # }
+    /// ```
+    pub async fn send_request_bytes(
+        &mut self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Bytes>,
+    ) -> Result<Response<Vec<u8>>, ErrorAndResponse> {
+        let endpoint = self
+            .request_target(&endpoint, &method)
+            .map_err(ErrorAndResponse::InternalError)?;
+        let response = self
+            .send_buffered(&endpoint, method, headers, body_request.into())
+            .await
+            .map_err(ErrorAndResponse::InternalError)?;
+        self.options.successful(response)
+    }
+
+    /// Like [`send_request_full`](Self::send_request_full) with headers already parsed, so the
+    /// requests reusing the same headers don't validate them again.
+    ///
+    /// ```rust,no_run
+    /// use http_client_unix_domain_socket::{ClientUnix, Method};
+    /// use hyper::header::{ACCEPT, HeaderMap, HeaderValue};
+    ///
+    /// # async fn run(mut client: ClientUnix) {
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+    /// for _ in 0..100 {
+    ///     let response = client
+    ///         .send_request_with_headers("/_ping", Method::GET, &headers, None)
+    ///         .await
+    ///         .expect("client.send_request_with_headers");
+    /// }
+    /// # }
+    /// ```
+    pub async fn send_request_with_headers(
+        &mut self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &HeaderMap,
+        body_request: Option<Body>,
+    ) -> Result<Response<Vec<u8>>, ErrorAndResponse> {
+        let endpoint = self
+            .request_target(&endpoint, &method)
+            .map_err(ErrorAndResponse::InternalError)?;
+        let response = self
+            .send_buffered_headers(&endpoint, method, headers, body_request.into())
+            .await
+            .map_err(ErrorAndResponse::InternalError)?;
+        self.options.successful(response)
+    }
+
+    /// Like [`send_request_full`](Self::send_request_full) but return the response once its head
+    /// is received, to read the body as it arrives. The body of unsuccessful responses is still
+    /// collected into [`ErrorAndResponse::ResponseUnsuccessful`].
+    ///
+    /// Retries, Digest authentication and the request timeout don't apply to streamed responses.
+    /// [`BodySize::of`](crate::BodySize::of) gives the size of the body before reading it. The
+    /// body is read by the connection of the client, which must be kept until the end of it.
+    pub async fn send_request_streaming(
+        &mut self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Body>,
+    ) -> Result<Response<Incoming>, ErrorAndResponse> {
+        let endpoint = self
+            .request_target(&endpoint, &method)
+            .map_err(ErrorAndResponse::InternalError)?;
+        self.send_streaming(&endpoint, method, headers, body_request)
+            .await
+    }
+
+    /// [`send_request_streaming`](Self::send_request_streaming) to the path and query already
+    /// checked and encoded.
+    pub(crate) async fn send_streaming(
+        &mut self,
+        endpoint: &PathAndQuery,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Body>,
+    ) -> Result<Response<Incoming>, ErrorAndResponse> {
+        let headers = self
+            .header_map(endpoint, &method, headers)
+            .map_err(ErrorAndResponse::InternalError)?;
+        let request = self
+            .build_request(endpoint, &method, &headers, body_request.into(), None)
+            .map_err(ErrorAndResponse::InternalError)?;
+        let start = Instant::now();
+        let uri = request.uri().clone();
+        let response = self
+            .send(request)
+            .await
+            .map_err(ErrorAndResponse::InternalError)?;
+        // The body is read by the caller, the response is complete for the hooks
+        self.options.hooks.response(ResponseEvent {
+            method: &method,
+            uri: &uri,
+            status: response.status(),
+            latency: start.elapsed(),
+        });
+        if self.options.is_success(response.status()) {
+            return Ok(response);
+        }
+
+        let (parts, body) = response.into_parts();
+        let body = crate::body_size::collect(body, &self.options)
+            .await
+            .map_err(|e| {
+                ErrorAndResponse::InternalError(Error::ResponseCollect(e).with_context(
+                    self.error_context(
+                        &method,
+                        Some(endpoint.as_str()),
+                        parts.extensions.get::<RequestId>(),
+                    ),
+                ))
+            })?;
+        Err(self.options.unsuccessful(parts.status, body))
+    }
+
+    /// Like [`send_request_streaming`](Self::send_request_streaming) but copy the body into
+    /// `writer` as it arrives, such as a file or stdout, and return the number of bytes written as
+    /// the body of the response. The next chunk is read once the previous one is written, so a
+    /// slow writer slows the server down rather than buffering the body.
+    ///
+    /// With [`resume_downloads`](crate::ClientUnixBuilder::resume_downloads), a download
+    /// interrupted by the connection is resumed from the last byte written.
+    ///
+    /// ```rust,no_run
+    /// use http_client_unix_domain_socket::{ClientUnix, Method};
+    ///
+    /// # async fn run(mut client: ClientUnix, mut file: tokio::fs::File) {
+    /// let response = client
+    ///     .send_request_to_writer("/images/nolanv/get", Method::GET, &[], None, &mut file)
+    ///     .await
+    ///     .expect("client.send_request_to_writer");
+    /// println!("{} bytes written", response.body());
+    /// # }
+    /// ```
+    pub async fn send_request_to_writer<W: AsyncWrite + Unpin + ?Sized>(
+        &mut self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Body>,
+        writer: &mut W,
+    ) -> Result<Response<u64>, ErrorAndResponse> {
+        let endpoint = self
+            .request_target(&endpoint, &method)
+            .map_err(ErrorAndResponse::InternalError)?;
+        let response = self
+            .send_streaming(&endpoint, method.clone(), headers, body_request)
+            .await?;
+        let (parts, mut body) = response.into_parts();
+        let context = self.error_context(
+            &method,
+            Some(endpoint.as_str()),
+            parts.extensions.get::<RequestId>(),
+        );
+        let etag = crate::resume::resumable_etag(&self.options, &method, &parts);
+        let mut resumes = 0;
+
+        #[cfg(feature = "integrity")]
+        let mut verifier = self
+            .options
+            .verify_digest
+            .then(|| crate::integrity::Verifier::of(&method, parts.status, &parts.headers))
+            .flatten();
+        let mut written = 0;
+        while let Some(frame) = body.frame().await {
+            let frame = match (frame, &etag) {
+                (Ok(frame), _) => frame,
+                (Err(_), Some(etag)) if resumes < self.options.resume_downloads => {
+                    resumes += 1;
+                    body = self
+                        .resume_download(&endpoint, headers, etag, written)
+                        .await
+                        .map_err(|e| {
+                            ErrorAndResponse::InternalError(e.with_context(context.clone()))
+                        })?;
+                    continue;
+                }
+                (Err(e), _) => {
+                    return Err(ErrorAndResponse::InternalError(
+                        Error::ResponseCollect(e).with_context(context),
+                    ));
+                }
+            };
+            let Ok(data) = frame.into_data() else {
+                continue;
+            };
+            write_all(writer, &data).await.map_err(|e| {
+                ErrorAndResponse::InternalError(
+                    Error::ResponseWrite(e).with_context(context.clone()),
+                )
+            })?;
+            written += data.len() as u64;
+            #[cfg(feature = "integrity")]
+            if let Some(verifier) = &mut verifier {
+                verifier.update(&data);
+            }
+        }
+        #[cfg(feature = "integrity")]
+        if let Some(verifier) = verifier {
+            verifier
+                .finish()
+                .map_err(|e| ErrorAndResponse::InternalError(e.with_context(context.clone())))?;
+        }
+        std::future::poll_fn(|cx| Pin::new(&mut *writer).poll_flush(cx))
+            .await
+            .map_err(|e| {
+                ErrorAndResponse::InternalError(Error::ResponseWrite(e).with_context(context))
+            })?;
+        Ok(Response::from_parts(parts, written))
+    }
+
+    /// Send a request and collect its response, whatever its status code.
+    pub(crate) async fn send_buffered(
+        &mut self,
+        endpoint: &PathAndQuery,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: RequestBody,
+    ) -> Result<Response<Vec<u8>>, Error> {
+        let headers = self.header_map(endpoint, &method, headers)?;
+        self.send_buffered_headers(endpoint, method, &headers, body_request)
+            .await
+    }
+
+    pub(crate) async fn send_buffered_headers(
+        &mut self,
+        endpoint: &PathAndQuery,
+        method: Method,
+        headers: &HeaderMap,
+        body_request: RequestBody,
+    ) -> Result<Response<Vec<u8>>, Error> {
+        let is_bodyless_get =
+            method == Method::GET && matches!(body_request, RequestBody::Bytes(None));
+        let cache = match &self.options.response_cache {
+            Some(cache) if is_bodyless_get && cache.caches(&method, endpoint.as_str()) => {
+                Some(cache.clone())
+            }
+            _ => None,
+        };
+        if let Some(cache) = &cache
+            && let Some(response) = cache.get(self, &method, endpoint.as_str())
+        {
+            return Ok(response);
+        }
+
+        let result = match self.options.coalescer.clone() {
+            Some(coalescer) if is_bodyless_get => coalescer.send(self, endpoint, headers).await,
+            _ => {
+                self.send_uncoalesced(endpoint, method.clone(), headers, body_request)
+                    .await
+            }
+        };
+        if let (Some(cache), Ok(response)) = (&cache, &result) {
+            cache.insert(self, &method, endpoint.as_str(), response);
+        }
+        result
+    }
+
+    /// Send a GET request without body, as shared by a [`RequestCoalescer`].
+    pub(crate) async fn send_get(
+        &mut self,
+        endpoint: &PathAndQuery,
+        headers: &HeaderMap,
+    ) -> Result<Response<Vec<u8>>, Error> {
+        self.send_uncoalesced(endpoint, Method::GET, headers, RequestBody::Bytes(None))
+            .await
+    }
+
+    async fn send_uncoalesced(
+        &mut self,
+        endpoint: &PathAndQuery,
+        method: Method,
+        headers: &HeaderMap,
+        body_request: RequestBody,
+    ) -> Result<Response<Vec<u8>>, Error> {
+        #[cfg(feature = "metrics")]
+        let request_metrics = crate::metrics::RequestMetrics::start();
+
+        let deadline = self
+            .options
+            .request_timeout
+            .map(|timeout| (timeout, Instant::now() + timeout));
+        let future = self.send_attempts(
+            endpoint,
+            method.clone(),
+            headers,
+            body_request,
+            deadline.map(|(_, deadline)| deadline),
+        );
+        let result = match deadline {
+            Some((timeout, deadline)) => tokio::time::timeout_at(deadline.into(), future)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(Error::Timeout(timeout).with_context(self.error_context(
+                        &method,
+                        Some(endpoint.as_str()),
+                        None,
+                    )))
+                }),
+            None => future.await,
+        };
+
+        #[cfg(feature = "metrics")]
+        request_metrics.finish(result.as_ref().ok().map(|response| response.status()));
+
+        result
+    }
+
+    async fn send_attempts(
+        &mut self,
+        endpoint: &PathAndQuery,
+        method: Method,
+        headers: &HeaderMap,
+        body_request: RequestBody,
+        deadline: Option<Instant>,
+    ) -> Result<Response<Vec<u8>>, Error> {
+        #[cfg(feature = "digest")]
+        if let Some(digest) = self.options.digest_auth.clone() {
+            return self
+                .send_digest(&digest, endpoint, method, headers, body_request, deadline)
+                .await;
+        }
+        self.send_retryable(endpoint, method, headers, body_request, deadline)
+            .await
+    }
+
+    /// Answer the Digest challenge of an unauthorized response by sending the request once more.
+    #[cfg(feature = "digest")]
+    async fn send_digest(
+        &mut self,
+        digest: &crate::digest::DigestAuth,
+        endpoint: &PathAndQuery,
+        method: Method,
+        headers: &HeaderMap,
+        body_request: RequestBody,
+        deadline: Option<Instant>,
+    ) -> Result<Response<Vec<u8>>, Error> {
+        if headers.contains_key(hyper::header::AUTHORIZATION) {
+            return self
+                .send_retryable(endpoint, method, headers, body_request, deadline)
+                .await;
+        }
+        let body_request = self.buffer_body(&method, endpoint, body_request).await?;
+
+        // The `uri` of the response is the request target as sent
+        let uri = endpoint.as_str();
+        let mut authorization = digest.authorization(&method, uri);
+        let mut challenged = false;
+        loop {
+            let mut authorized_headers = headers.clone();
+            if let Some(authorization) = &authorization {
+                let authorization = HeaderValue::from_str(authorization).map_err(|e| {
+                    Error::RequestBuild(e.into()).with_context(self.error_context(
+                        &method,
+                        Some(endpoint.as_str()),
+                        None,
+                    ))
+                })?;
+                authorized_headers.insert(hyper::header::AUTHORIZATION, authorization);
+            }
+            let response = self
+                .send_retryable(
+                    endpoint,
+                    method.clone(),
+                    &authorized_headers,
+                    body_request.clone().into(),
+                    deadline,
+                )
+                .await?;
+            if challenged
+                || response.status() != StatusCode::UNAUTHORIZED
+                || !digest.update(response.headers())
+            {
+                return Ok(response);
+            }
+            challenged = true;
+            authorization = digest.authorization(&method, uri);
+        }
+    }
+
+    async fn send_retryable(
+        &mut self,
+        endpoint: &PathAndQuery,
+        method: Method,
+        headers: &HeaderMap,
+        body_request: RequestBody,
+        deadline: Option<Instant>,
+    ) -> Result<Response<Vec<u8>>, Error> {
+        match self.options.retry.clone() {
+            Some(policy) => {
+                self.send_retried(&policy, endpoint, method, headers, body_request, deadline)
+                    .await
+            }
+            None => {
+                self.send_buffered_inner(endpoint, method, headers, body_request, deadline)
+                    .await
+            }
+        }
+    }
+
+    /// Read the whole request body, to send it several times.
+    #[cfg(feature = "digest")]
+    async fn buffer_body(
+        &self,
+        method: &Method,
+        endpoint: &PathAndQuery,
+        body_request: RequestBody,
+    ) -> Result<Option<Bytes>, Error> {
+        match body_request {
+            RequestBody::Bytes(body) => Ok(body),
+            RequestBody::Stream(body) => Ok(Some(
+                body.collect()
+                    .await
+                    .map_err(|e| {
+                        Error::RequestBody(e).with_context(self.error_context(
+                            method,
+                            Some(endpoint.as_str()),
+                            None,
+                        ))
+                    })?
+                    .to_bytes(),
+            )),
+        }
+    }
+
+    async fn send_retried(
+        &mut self,
+        policy: &RetryPolicy,
+        endpoint: &PathAndQuery,
+        method: Method,
+        headers: &HeaderMap,
+        body_request: RequestBody,
+        deadline: Option<Instant>,
+    ) -> Result<Response<Vec<u8>>, Error> {
+        let mut headers = headers.clone();
+        if let Some(header) = &policy.idempotency_key
+            && !headers.contains_key(header)
+            && let Ok(idempotency_key) = HeaderValue::from_str(&uuid::Uuid::new_v4().to_string())
+        {
+            headers.insert(header, idempotency_key);
+        }
+        let body_request = match body_request {
+            RequestBody::Stream(body) => crate::retry::replayable(body, policy.max_replay_body)
+                .await
+                .map_err(|e| {
+                    Error::RequestBody(e).with_context(self.error_context(
+                        &method,
+                        Some(endpoint.as_str()),
+                        None,
+                    ))
+                })?,
+            body_request => body_request,
+        };
+        let body_request = match body_request {
+            RequestBody::Bytes(body) => body,
+            // Too large to be kept for the retries
+            body_request => {
+                return self
+                    .send_buffered_inner(endpoint, method, &headers, body_request, deadline)
+                    .await;
+            }
+        };
+
+        policy.record_request();
+        let mut attempt = 0;
+        loop {
+            // Retries reconnect even with `reconnect_when_closed(false)`
+            let result = match attempt > 0 && self.sender.is_closed() {
+                true => self.reconnect_in_place().await,
+                false => Ok(()),
+            };
+            let result = match result {
+                Ok(()) => {
+                    self.send_buffered_inner(
+                        endpoint,
+                        method.clone(),
+                        &headers,
+                        body_request.clone().into(),
+                        deadline,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+            match result {
+                Err(e) if policy.should_retry(attempt, &method, e.kind()) => {
+                    let backoff = policy.delay_for(attempt);
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(error = %e, attempt, ?backoff, "retrying request");
+                    #[cfg(feature = "log")]
+                    log::debug!(
+                        "retrying request in {:?} (attempt {}): {}",
+                        backoff,
+                        attempt,
+                        e
+                    );
+                    #[cfg(not(any(feature = "tracing", feature = "log")))]
+                    let _ = e;
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Parse the headers given as strings, once for all the attempts of the request.
+    fn header_map(
+        &self,
+        endpoint: &PathAndQuery,
+        method: &Method,
+        headers: &[(&str, &str)],
+    ) -> Result<HeaderMap, Error> {
+        let parse = || -> Result<HeaderMap, hyper::http::Error> {
+            let mut header_map = HeaderMap::with_capacity(headers.len());
+            for (name, value) in headers {
+                header_map.append(
+                    HeaderName::from_bytes(name.as_bytes())?,
+                    HeaderValue::from_str(value)?,
+                );
+            }
+            Ok(header_map)
+        };
+        parse().map_err(|e| {
+            Error::RequestBuild(e).with_context(self.error_context(
+                method,
+                Some(endpoint.as_str()),
+                None,
+            ))
+        })
+    }
+
+    fn build_request(
+        &self,
+        endpoint: &PathAndQuery,
+        method: &Method,
+        headers: &HeaderMap,
+        body_request: RequestBody,
+        deadline: Option<Instant>,
+    ) -> Result<Request<Body>, Error> {
+        let mut request = Request::new(match body_request {
+            RequestBody::Bytes(Some(body)) => Body::from(body),
+            RequestBody::Bytes(None) => Body::empty(),
+            RequestBody::Stream(body) => body,
+        });
+        *request.method_mut() = method.clone();
+        let build = |request: &mut Request<Body>| -> Result<(), hyper::http::Error> {
+            *request.uri_mut() = request_uri(self.options.scheme.as_ref(), endpoint.clone())?;
+            Ok(())
+        };
+        build(&mut request).map_err(|e| {
+            Error::RequestBuild(e).with_context(self.error_context(
+                method,
+                Some(endpoint.as_str()),
+                None,
+            ))
+        })?;
+        *request.headers_mut() = headers.clone();
+
+        if let (Some((header, format)), Some(deadline)) = (&self.options.deadline_header, deadline)
+        {
+            request.headers_mut().entry(header).or_insert_with(|| {
+                format.encode(deadline.saturating_duration_since(Instant::now()))
+            });
+        }
+        Ok(request)
+    }
+
+    async fn send_buffered_inner(
+        &mut self,
+        endpoint: &PathAndQuery,
+        method: Method,
+        headers: &HeaderMap,
+        body_request: RequestBody,
+        deadline: Option<Instant>,
+    ) -> Result<Response<Vec<u8>>, Error> {
+        let request = self.build_request(endpoint, &method, headers, body_request, deadline)?;
+
+        let start = Instant::now();
+        let (method, uri) = (request.method().clone(), request.uri().clone());
+        let response = self.send(request).await?;
+        let latency = start.elapsed();
+
+        let (mut parts, body) = response.into_parts();
+        let body_read_start = Instant::now();
+        let body_response = crate::body_size::collect(body, &self.options)
+            .await
+            .map_err(Error::ResponseCollect);
+        #[cfg(feature = "integrity")]
+        let body_response = body_response.and_then(|body_response| {
+            if self.options.verify_digest
+                && let Some(mut verifier) =
+                    crate::integrity::Verifier::of(&method, parts.status, &parts.headers)
+            {
+                verifier.update(&body_response);
+                verifier.finish()?;
+            }
+            Ok(body_response)
+        });
+        // A single event per request, once its body is read
+        let body_response = match body_response {
+            Ok(body_response) => {
+                self.options.hooks.response(ResponseEvent {
+                    method: &method,
+                    uri: &uri,
+                    status: parts.status,
+                    latency,
+                });
+                body_response
+            }
+            Err(e) => {
+                self.options.hooks.error(ErrorEvent {
+                    method: &method,
+                    uri: &uri,
+                    error: &e,
+                    latency: start.elapsed(),
+                });
+                return Err(e.with_context(self.error_context(
+                    &method,
+                    uri.path_and_query().map(|p| p.as_str()),
+                    parts.extensions.get::<RequestId>(),
+                )));
+            }
+        };
+        let body_read = body_read_start.elapsed();
+        if let Some(timings) = parts.extensions.get_mut::<RequestTimings>() {
+            timings.body_read = Some(body_read);
+        }
+        #[cfg(feature = "har")]
+        if let (Some(recorder), Some(har_entry)) = (
+            &self.options.har_recorder,
+            parts.extensions.get::<crate::har::HarEntryId>(),
+        ) {
+            recorder.finish(*har_entry, &body_response, body_read);
+        }
+
+        Ok(Response::from_parts(parts, body_response))
+    }
+
+    async fn send(&mut self, mut request: Request<Body>) -> Result<Response<Incoming>, Error> {
+        let request_id = self
+            .options
+            .request_id_header
+            .as_ref()
+            .map(|header| request_id::ensure(header, &mut request));
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "request",
+            method = %request.method(),
+            uri = %request.uri(),
+            request_id = request_id.as_ref().map(|id| id.0.as_str()),
+        );
+        let (method, uri) = (request.method().clone(), request.uri().clone());
+        let start = Instant::now();
+        let future = self.send_intercepted(request);
+        #[cfg(feature = "tracing")]
+        let future = tracing::Instrument::instrument(future, span);
+        let result = future.await;
+
+        #[cfg(feature = "log")]
+        match &result {
+            Ok(response) => log::debug!("{} {}: {}", method, uri, response.status()),
+            Err(error) => log::warn!("{} {} failed: {}", method, uri, error),
+        }
+        // The response event is left to the caller, once the body is read when it is collected
+        if let Err(error) = &result {
+            self.options.hooks.error(ErrorEvent {
+                method: &method,
+                uri: &uri,
+                error,
+                latency: start.elapsed(),
+            });
+        }
+
+        match result {
+            Ok(mut response) => {
+                if let Some(request_id) = request_id {
+                    response.extensions_mut().insert(request_id);
+                }
+                Ok(response)
+            }
+            Err(e) => Err(e.with_context(self.error_context(
+                &method,
+                uri.path_and_query().map(|p| p.as_str()),
+                request_id.as_ref(),
+            ))),
+        }
+    }
+
+    pub(crate) fn error_context(
+        &self,
+        method: &Method,
+        endpoint: Option<&str>,
+        request_id: Option<&RequestId>,
+    ) -> ErrorContext {
+        ErrorContext {
+            socket_path: self.socket_path.clone(),
+            method: Some(method.clone()),
+            endpoint: endpoint.map(String::from),
+            request_id: request_id.map(|id| id.0.clone()),
+        }
+    }
+
+    /// The path and query sent for `endpoint`, checked and percent-encoded once for all the
+    /// attempts of the request.
+    pub(crate) fn request_target(
+        &self,
+        endpoint: &(impl Endpoint + ?Sized),
+        method: &Method,
+    ) -> Result<PathAndQuery, Error> {
+        let context = || self.error_context(method, Some(endpoint.as_endpoint()), None);
+        validate_endpoint(endpoint.as_endpoint()).map_err(|e| e.with_context(context()))?;
+        endpoint
+            .to_path_and_query()
+            .map_err(|e| Error::RequestBuild(e.into()).with_context(context()))
+    }
+
+    async fn send_intercepted(
+        &mut self,
+        mut request: Request<Body>,
+    ) -> Result<Response<Incoming>, Error> {
+        if let Some(user_agent) = &self.options.user_agent {
+            request
+                .headers_mut()
+                .entry(USER_AGENT)
+                .or_insert_with(|| user_agent.clone());
+        }
+        for name in self.options.default_headers.keys() {
+            if !request.headers().contains_key(name) {
+                for value in self.options.default_headers.get_all(name) {
+                    request.headers_mut().append(name, value.clone());
+                }
+            }
+        }
+        for interceptor in &self.options.interceptors {
+            interceptor.before(&mut request).await?;
+        }
+        self.options.hooks.request(RequestEvent {
+            method: request.method(),
+            uri: request.uri(),
+            headers: request.headers(),
+        });
+        #[cfg(feature = "har")]
+        let har = self
+            .options
+            .har_recorder
+            .as_ref()
+            .map(|recorder| recorder.start(&mut request, &self.options.redactor));
+
+        if let Some(rate_limiter) = &self.options.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
+        if self.sender.is_closed()
+            && let Some(message) = self.take_panic()
+        {
+            return Err(Error::ConnectionTaskPanicked(message));
+        }
+        let mut connect = Duration::ZERO;
+        // The connection announced as closed may not be yet, whatever `reconnect_when_closed`
+        if self.closing || !self.options.manual_reconnect && self.sender.is_closed() {
+            let connect_start = Instant::now();
+            self.reconnect_in_place().await?;
+            connect = connect_start.elapsed();
+        }
+
+        let queue_start = Instant::now();
+        if let Err(e) = self.sender.ready().await {
+            return Err(self
+                .take_panic()
+                .map_or(Error::RequestSend(e), Error::ConnectionTaskPanicked));
+        }
+        let queue = queue_start.elapsed();
+
+        // HTTP/1 has no handshake of its own, a connection is established once the server
+        // starts answering
+        let handshake_timeout = self
+            .options
+            .handshake_timeout
+            .filter(|_| self.counters.snapshot().bytes_received == 0);
+        self.counters.add_request();
+        let send_start = Instant::now();
+        let mut response = pin!(self.sender.send_request(request));
+        let response = match handshake_timeout {
+            Some(timeout) => {
+                let counters = self.counters.clone();
+                let mut first_byte = pin!(tokio::time::timeout(timeout, counters.first_byte()));
+                let answered = std::future::poll_fn(|cx| match response.as_mut().poll(cx) {
+                    Poll::Ready(response) => Poll::Ready(Ok(response)),
+                    Poll::Pending => first_byte.as_mut().poll(cx).map(Err),
+                })
+                .await;
+                match answered {
+                    Ok(response) => Ok(response),
+                    Err(Ok(())) => Ok(response.await),
+                    Err(Err(_)) => {
+                        // The next request connects again rather than waiting behind this one
+                        self.join_handle.abort();
+                        let e = Error::HandshakeTimeout(timeout);
+                        self.status.disconnected(self.generation, e.to_string());
+                        Err(e)
+                    }
+                }
+            }
+            None => Ok(response.await),
+        };
+        let response = response.and_then(|response| {
+            response.map_err(|e| {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %e, "request failed");
+                self.take_panic()
+                    .map_or(Error::RequestSend(e), Error::ConnectionTaskPanicked)
+            })
+        });
+        let mut response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                #[cfg(feature = "har")]
+                if let Some(har) = har {
+                    har.error(&e);
+                }
+                return Err(e);
+            }
+        };
+        self.closing = closes_connection(&response);
+        response.extensions_mut().insert(RequestTimings {
+            queue,
+            connect,
+            time_to_first_byte: send_start.elapsed(),
+            body_read: None,
+        });
+        #[cfg(feature = "tracing")]
+        tracing::debug!(status = %response.status(), "response received");
+        #[cfg(feature = "har")]
+        if let Some(har) = har {
+            har.response(&mut response, &self.options.redactor);
+        }
+
+        for interceptor in self.options.interceptors.iter().rev() {
+            interceptor.after(&mut response).await?;
+        }
+
+        Ok(response)
+    }
+
+    /// Send `body_request` as JSON and deserialize the response body as `OUT`, or as `ERR` when
+    /// unsuccessful (feature=json). An empty body, as of a `204 No Content`, is deserialized from
+    /// `null`, so a `DELETE` can expect `()`, or an `Option` which is `None`.
+    #[cfg(feature = "json")]
+    pub async fn send_request_json<IN: Serialize, OUT: DeserializeOwned, ERR: DeserializeOwned>(
+        &mut self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<&IN>,
+    ) -> Result<(StatusCode, OUT), ErrorAndResponseJson<ERR>> {
+        let response = self
+            .send_request_json_full(endpoint, method, headers, body_request)
+            .await?;
+        Ok((response.status(), response.into_body()))
+    }
+
+    /// Like [`send_request_json`](Self::send_request_json) but return the whole response, such
+    /// as its headers.
+    #[cfg(feature = "json")]
+    pub async fn send_request_json_full<
+        IN: Serialize,
+        OUT: DeserializeOwned,
+        ERR: DeserializeOwned,
+    >(
+        &mut self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<&IN>,
+    ) -> Result<Response<OUT>, ErrorAndResponseJson<ERR>> {
+        let endpoint = self
+            .request_target(&endpoint, &method)
+            .map_err(ErrorAndResponseJson::InternalError)?;
+        let mut headers = self.options.with_accept(headers, "application/json");
+        headers.push(("Content-Type", "application/json"));
+        let context = self.error_context(&method, Some(endpoint.as_str()), None);
+
+        let body_request = match body_request {
+            Some(body_request) => Some(Bytes::from(serde_json::to_vec(body_request).map_err(
+                |e| {
+                    ErrorAndResponseJson::InternalError(
+                        Error::RequestParsing(e).with_context(context.clone()),
+                    )
+                },
+            )?)),
+            None => None,
+        };
+
+        let response = self
+            .send_buffered(&endpoint, method, &headers, body_request.into())
+            .await
+            .map_err(ErrorAndResponseJson::InternalError)?;
+        let status_code = response.status();
+        let is_problem = ProblemDetails::is_problem(response.headers());
+        if let Some(content_type) = self.options.json_content_type.rejects(response.headers()) {
+            return Err(ErrorAndResponseJson::InternalError(
+                Error::UnexpectedContentType(content_type, response.into_body())
+                    .with_context(context),
+            ));
+        }
+        let (parts, response) = response.into_parts();
+        let parsing_error = |e, response| {
+            ErrorAndResponseJson::InternalError(
+                Error::ResponseParsing(e, response).with_context(context),
+            )
+        };
+
+        if self.options.is_success(status_code) {
+            let body = parse_json(response, self.options.buffer_pool.as_ref())
+                .map_err(|(e, response)| parsing_error(e, response))?;
+            return Ok(Response::from_parts(parts, body));
+        }
+        if is_problem {
+            return Err(ErrorAndResponseJson::ResponseProblem(
+                status_code,
+                parse_json(response, self.options.buffer_pool.as_ref())
+                    .map_err(|(e, response)| parsing_error(e, response))?,
+            ));
+        }
+        Err(ErrorAndResponseJson::ResponseUnsuccessful(
+            status_code,
+            parse_json(response, self.options.buffer_pool.as_ref())
+                .map_err(|(e, response)| parsing_error(e, response))?,
+        ))
+    }
+}
+
+/// Stops the connection, the body of a response streamed from it included.
+impl Drop for ClientUnix {
+    fn drop(&mut self) {
+        self.join_handle.abort();
+        self.status
+            .disconnected(self.generation, "connection aborted".to_string());
+    }
+}
+
+/// Future of a connection, resolving to the message of its panic instead of unwinding the task.
+struct CatchPanic<F>(F);
+
+impl<F: Future + Unpin> Future for CatchPanic<F> {
+    type Output = Result<F::Output, String>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match std::panic::catch_unwind(AssertUnwindSafe(|| Pin::new(&mut self.0).poll(cx))) {
+            Ok(poll) => poll.map(Ok),
+            Err(payload) => Poll::Ready(Err(match payload.downcast::<String>() {
+                Ok(message) => *message,
+                Err(payload) => payload
+                    .downcast_ref::<&str>()
+                    .map_or("unknown panic payload", |message| message)
+                    .to_string(),
+            })),
+        }
+    }
+}
+
+/// Reject the endpoints which would be sent otherwise than written, before percent-encoding them.
+fn validate_endpoint(endpoint: &str) -> Result<(), Error> {
+    if !endpoint.starts_with('/') {
+        return Err(Error::InvalidEndpoint("must start with `/`"));
+    }
+    if endpoint.contains(['\r', '\n']) {
+        return Err(Error::InvalidEndpoint("must not contain CR or LF"));
+    }
+    if endpoint.contains('#') {
+        return Err(Error::InvalidEndpoint(
+            "must not contain a fragment, `#` is sent as `%23`",
+        ));
+    }
+    Ok(())
+}
+
+/// Whether the server closes the connection after `response`: `Connection: close`, or HTTP/1.0
+/// without `Connection: keep-alive`.
+fn closes_connection<B>(response: &Response<B>) -> bool {
+    let mut options = response
+        .headers()
+        .get_all(hyper::header::CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim);
+    match response.version() {
+        hyper::Version::HTTP_10 => !options.any(|option| option.eq_ignore_ascii_case("keep-alive")),
+        _ => options.any(|option| option.eq_ignore_ascii_case("close")),
+    }
+}
+
+fn set_buffer_sizes(stream: &UnixStream, options: &Options) -> std::io::Result<()> {
+    let socket = socket2::SockRef::from(stream);
+    if let Some(size) = options.send_buffer_size {
+        socket.set_send_buffer_size(size)?;
+    }
+    if let Some(size) = options.recv_buffer_size {
+        socket.set_recv_buffer_size(size)?;
+    }
+    Ok(())
+}
+
+/// Write the whole of `data`, without the `io-util` feature of tokio.
+async fn write_all<W: AsyncWrite + Unpin + ?Sized>(
+    writer: &mut W,
+    mut data: &[u8],
+) -> std::io::Result<()> {
+    while !data.is_empty() {
+        match std::future::poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, data)).await? {
+            0 => return Err(std::io::ErrorKind::WriteZero.into()),
+            n => data = &data[n..],
+        }
+    }
+    Ok(())
+}
+
+/// `http://unix.socket<endpoint>`, or with `scheme`, built from its parts rather than parsed from
+/// a formatted string, with the bytes of the endpoint invalid in a request target percent-encoded.
+fn request_uri(
+    scheme: Option<&Scheme>,
+    path_and_query: PathAndQuery,
+) -> Result<Uri, hyper::http::Error> {
+    let mut parts = hyper::http::uri::Parts::default();
+    parts.scheme = Some(scheme.cloned().unwrap_or(Scheme::HTTP));
+    parts.authority = Some(Authority::from_static("unix.socket"));
+    parts.path_and_query = Some(path_and_query);
+    Ok(Uri::from_parts(parts)?)
+}
+
+/// Deserialize a JSON body, with `simd-json` when enabled (feature=simd-json). The bodies it
+/// rejects are parsed again by `serde_json`, which gives the error.
+#[cfg(feature = "json")]
+pub(crate) fn json_from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T, serde_json::Error> {
+    #[cfg(feature = "simd-json")]
+    {
+        // Parsed in place, the body is kept for the errors
+        let mut scratch = body.to_vec();
+        if let Ok(value) = simd_json::serde::from_slice(&mut scratch) {
+            return Ok(value);
+        }
+    }
+    serde_json::from_slice(body)
+}
+
+/// Parse a collected body and give its buffer back to `pool`, or return it with the error.
+/// An empty body, as of a `204 No Content`, is parsed as `null`.
+#[cfg(feature = "json")]
+fn parse_json<T: DeserializeOwned>(
+    body: Vec<u8>,
+    pool: Option<&BufferPool>,
+) -> Result<T, (serde_json::Error, Vec<u8>)> {
+    let json = match body.is_empty() {
+        true => &b"null"[..],
+        false => &body,
+    };
+    match json_from_slice(json) {
+        Ok(value) => {
+            if let Some(pool) = pool {
+                pool.recycle(body);
+            }
+            Ok(value)
+        }
+        Err(e) => Err((e, body)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{server::Server, util::*};
+    use hyper::Method;
+
+    #[tokio::test]
+    async fn simple_request() {
+        let (_, mut client) = make_client_server("simple_request").await;
+
+        let (status_code, response) = client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+
+        assert_eq!(status_code, StatusCode::OK);
+        assert_eq!(response, "Hello nolanv".as_bytes())
+    }
+
+    #[tokio::test]
+    async fn simple_404_request() {
+        let (_, mut client) = make_client_server("simple_404_request").await;
+
+        let result = client
+            .send_request("/nolanv/nope", Method::GET, &[], None)
+            .await;
+
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponse::ResponseUnsuccessful(status_code, _))
+                if status_code == StatusCode::NOT_FOUND
+        ));
+    }
+
+    #[tokio::test]
+    async fn multiple_request() {
+        let (_, mut client) = make_client_server("multiple_request").await;
+
+        for i in 0..20 {
+            let (status_code, response) = client
+                .send_request(&format!("/nolanv{}", i), Method::GET, &[], None)
+                .await
+                .expect("client.send_request");
+
+            assert_eq!(status_code, StatusCode::OK);
+
+            assert_eq!(response, format!("Hello nolanv{}", i).as_bytes())
+        }
+    }
+
+    #[tokio::test]
+    async fn full_response_with_timings() {
+        let (_, mut client) = make_client_server("full_response_with_timings").await;
+
+        let response = client
+            .send_request_full("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request_full");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response
+                .headers()
+                .get("content-length")
+                .map(|v| v.as_bytes()),
+            Some("12".as_bytes())
+        );
+        assert_eq!(response.body(), "Hello nolanv".as_bytes());
+        let timings = response
+            .extensions()
+            .get::<RequestTimings>()
+            .expect("RequestTimings");
+        assert!(timings.time_to_first_byte > Duration::ZERO);
+        assert!(timings.body_read.is_some());
+    }
+
+    #[tokio::test]
+    async fn streaming_request() {
+        let (_, mut client) = make_client_server("streaming_request").await;
+
+        let response = client
+            .send_request_streaming("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request_streaming");
+        let body = response.into_body().collect().await.expect("collect");
+
+        assert_eq!(body.to_bytes(), "Hello nolanv".as_bytes());
+        assert!(matches!(
+            client
+                .send_request_streaming("/nolanv/nop", Method::GET, &[], None)
+                .await
+                .err(),
+            Some(ErrorAndResponse::ResponseUnsuccessful(
+                StatusCode::NOT_FOUND,
+                _
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn default_user_agent() {
+        let (_, mut client) = make_client_server("default_user_agent").await;
+
+        let (_, response) = client
+            .send_request("/header/user-agent", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(response, DEFAULT_USER_AGENT.as_bytes());
+
+        let (_, response) = client
+            .send_request(
+                "/header/user-agent",
+                Method::GET,
+                &[("User-Agent", "nolanv")],
+                None,
+            )
+            .await
+            .expect("client.send_request");
+        assert_eq!(response, "nolanv".as_bytes());
+    }
+
+    #[tokio::test]
+    async fn default_headers() {
+        let socket_path = make_socket_path_test("client", "default_headers");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .default_header(
+                HeaderName::from_static("x-tenant"),
+                HeaderValue::from_static("nolanv"),
+            )
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let (_, response) = client
+            .send_request("/header/x-tenant", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(response, "nolanv".as_bytes());
+
+        let (_, response) = client
+            .send_request(
+                "/header/x-tenant",
+                Method::GET,
+                &[("X-Tenant", "other")],
+                None,
+            )
+            .await
+            .expect("client.send_request");
+        assert_eq!(response, "other".as_bytes());
+    }
 
-pub struct ClientUnix {
-    socket_path: PathBuf,
-    sender: SendRequest<Body>,
-    join_handle: JoinHandle<Error>,
-}
+    #[tokio::test]
+    async fn parsed_headers() {
+        let (_, mut client) = make_client_server("parsed_headers").await;
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("x-tenant"),
+            HeaderValue::from_static("nolanv"),
+        );
 
-impl ClientUnix {
-    pub async fn try_new(socket_path: &str) -> Result<Self, Error> {
-        let socket_path = PathBuf::from(socket_path);
-        ClientUnix::try_connect(socket_path).await
+        for _ in 0..2 {
+            let response = client
+                .send_request_with_headers("/header/x-tenant", Method::GET, &headers, None)
+                .await
+                .expect("client.send_request_with_headers");
+            assert_eq!(response.into_body(), "nolanv".as_bytes());
+        }
     }
 
-    pub async fn try_reconnect(self) -> Result<Self, Error> {
-        let socket_path = self.socket_path.clone();
-        self.abort().await;
-        ClientUnix::try_connect(socket_path).await
+    #[tokio::test]
+    async fn read_buffer_sizes() {
+        const LEN: usize = 1024 * 1024;
+        let server = crate::testing::MockServer::start(
+            axum::Router::new().route("/layer", axum::routing::get(|| async { vec![42u8; LEN] })),
+        )
+        .await
+        .expect("MockServer::start");
+        let socket_path = server.socket_path().to_string_lossy().into_owned();
+
+        for builder in [
+            ClientUnix::builder(&socket_path).max_buf_size(4 * 1024 * 1024),
+            ClientUnix::builder(&socket_path).max_buf_size(0),
+            ClientUnix::builder(&socket_path).read_buf_exact_size(1024),
+        ] {
+            let mut client = builder.build().await.expect("ClientUnixBuilder::build");
+            let (_, body) = client
+                .send_request("/layer", Method::GET, &[], None)
+                .await
+                .expect("client.send_request");
+            assert_eq!(body.len(), LEN);
+        }
     }
 
-    pub async fn abort(self) -> Option<Error> {
-        self.join_handle.abort();
-        self.join_handle.await.ok()
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_body() {
+        let body = br#"[{"Id":"nolanv","Names":["/web\u00e9"]}]"#;
+        let containers: Vec<serde_json::Value> = json_from_slice(body).expect("json_from_slice");
+        assert_eq!(containers[0]["Names"][0], "/web\u{e9}");
+        assert!(json_from_slice::<serde_json::Value>(b"{\"Id\":").is_err());
     }
 
-    async fn try_connect(socket_path: PathBuf) -> Result<Self, Error> {
-        let stream = TokioIo::new(
-            UnixStream::connect(socket_path.clone())
-                .await
-                .map_err(Error::SocketConnectionInitiation)?,
+    #[test]
+    fn uri_from_endpoint() {
+        assert_eq!(
+            request_uri(
+                None,
+                "/containers/json?all=true"
+                    .to_path_and_query()
+                    .expect("to_path_and_query")
+            )
+            .expect("request_uri")
+            .to_string(),
+            "http://unix.socket/containers/json?all=true"
+        );
+        assert_eq!(
+            request_uri(
+                None,
+                "/volumes/nolanv data"
+                    .to_path_and_query()
+                    .expect("to_path_and_query")
+            )
+            .expect("request_uri")
+            .to_string(),
+            "http://unix.socket/volumes/nolanv%20data"
+        );
+        assert_eq!(
+            request_uri(Some(&Scheme::HTTPS), PathAndQuery::from_static("/_ping"))
+                .expect("request_uri")
+                .to_string(),
+            "https://unix.socket/_ping"
         );
+    }
 
-        let (sender, connection) = http1::handshake(stream).await.map_err(Error::Handhsake)?;
+    #[tokio::test]
+    async fn invalid_endpoints() {
+        let (_, mut client) = make_client_server("invalid_endpoints").await;
 
-        let join_handle =
-            tokio::task::spawn(
-                async move { Error::SocketConnectionClosed(connection.await.err()) },
-            );
+        for endpoint in ["nolanv", "", "/nolanv\r\nX-Injected: 1", "/nolanv#top"] {
+            let error = client
+                .send_request(endpoint, Method::GET, &[], None)
+                .await
+                .expect_err("client.send_request");
+            let ErrorAndResponse::InternalError(Error::WithContext(context, e)) = error else {
+                panic!("{:?}", error)
+            };
+            assert!(matches!(*e, Error::InvalidEndpoint(_)), "{:?}", e);
+            assert_eq!(e.kind(), crate::ErrorKind::InvalidRequest);
+            assert_eq!(context.endpoint.as_deref(), Some(endpoint));
+        }
+        let error = client
+            .send_request_streaming("nolanv", Method::GET, &[], None)
+            .await
+            .expect_err("client.send_request_streaming");
+        assert!(
+            error
+                .to_string()
+                .contains("invalid endpoint: must start with `/`")
+        );
+    }
 
-        Ok(ClientUnix {
-            socket_path,
-            sender,
-            join_handle,
-        })
+    #[tokio::test]
+    async fn connect_timeout() {
+        let socket_path = make_socket_path_test("client", "connect_timeout");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let client = ClientUnix::builder(&socket_path)
+            .connect_timeout(Duration::from_secs(1))
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+        let mut client = client.try_reconnect().await.expect("client.try_reconnect");
+        assert_eq!(
+            client.options().connect_timeout,
+            Some(Duration::from_secs(1))
+        );
+        client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
     }
 
-    pub async fn send_request(
-        &mut self,
-        endpoint: &str,
-        method: Method,
-        headers: &[(&str, &str)],
-        body_request: Option<Body>,
-    ) -> Result<(StatusCode, Vec<u8>), ErrorAndResponse> {
-        let mut request_builder = Request::builder();
-        for header in headers {
-            request_builder = request_builder.header(header.0, header.1);
-        }
-        let request = request_builder
-            .method(method)
-            .uri(format!("http://unix.socket{}", endpoint))
-            .body(body_request.unwrap_or(Body::empty()))
-            .map_err(|e| ErrorAndResponse::InternalError(Error::RequestBuild(e)))?;
+    #[tokio::test]
+    async fn handshake_timeout() {
+        let socket_path = make_socket_path_test("client", "handshake_timeout");
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = tokio::net::UnixListener::bind(&socket_path).expect("UnixListener::bind");
+        let wedged = tokio::spawn(async move {
+            let (_stream, _) = listener.accept().await.expect("listener.accept");
+            std::future::pending::<()>().await;
+        });
+        let mut client = ClientUnix::builder(&socket_path)
+            .connect_timeout(Duration::from_secs(1))
+            .handshake_timeout(Duration::from_millis(100))
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
 
-        let response = self
-            .sender
-            .send_request(request)
+        let error = client
+            .send_request("/nolanv", Method::GET, &[], None)
             .await
-            .map_err(|e| ErrorAndResponse::InternalError(Error::RequestSend(e)))?;
+            .expect_err("client.send_request");
+        let ErrorAndResponse::InternalError(error) = error else {
+            panic!("{:?}", error)
+        };
+        assert!(
+            matches!(error.inner(), Error::HandshakeTimeout(timeout) if *timeout == Duration::from_millis(100)),
+            "{:?}",
+            error
+        );
+        assert_eq!(error.kind(), crate::ErrorKind::Timeout);
+        wedged.abort();
 
-        let status_code = response.status();
-        let body_response = response
-            .collect()
+        // The timeout stops at the first byte of the response, the rest of it may take longer
+        let socket_path = make_socket_path_test("client", "handshake_timeout_slow_head");
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = tokio::net::UnixListener::bind(&socket_path).expect("UnixListener::bind");
+        let slow = tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+            let (mut stream, _) = listener.accept().await.expect("listener.accept");
+            let _ = stream.read(&mut [0; 1024]).await;
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\n")
+                .await
+                .expect("stream.write_all");
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            stream
+                .write_all(b"content-length: 6\r\n\r\nnolanv")
+                .await
+                .expect("stream.write_all");
+            std::future::pending::<()>().await;
+        });
+        let mut client = ClientUnix::builder(&socket_path)
+            .handshake_timeout(Duration::from_millis(100))
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+        let (_, body) = client
+            .send_request("/nolanv", Method::GET, &[], None)
             .await
-            .map_err(|e| ErrorAndResponse::InternalError(Error::ResponseCollect(e)))?
-            .to_bytes();
+            .expect("client.send_request");
+        assert_eq!(body, b"nolanv");
+        slow.abort();
 
-        if !status_code.is_success() {
-            return Err(ErrorAndResponse::ResponseUnsuccessful(
-                status_code,
-                body_response.to_vec(),
-            ));
+        // Only the first response of a connection is bound by the timeout
+        let socket_path = make_socket_path_test("client", "handshake_timeout_answered");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .handshake_timeout(Duration::from_millis(100))
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+        for endpoint in ["/nolanv", "/sleep/300"] {
+            client
+                .send_request(endpoint, Method::GET, &[], None)
+                .await
+                .expect("client.send_request");
         }
-        Ok((status_code, body_response.to_vec()))
+    }
+
+    #[tokio::test]
+    async fn oneshot_request() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let socket_path = make_socket_path_test("client", "oneshot_request");
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = tokio::net::UnixListener::bind(&socket_path).expect("UnixListener::bind");
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.expect("listener.accept");
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                let mut buffer = [0; 1024];
+                let read = stream.read(&mut buffer).await.expect("stream.read");
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 12\r\n\r\nHello nolanv")
+                .await
+                .expect("stream.write_all");
+            // The client closes its end once the response is read
+            let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut [0; 16]))
+                .await
+                .expect("connection released")
+                .expect("stream.read");
+            (String::from_utf8(request).expect("String::from_utf8"), read)
+        });
+
+        let request = Request::get("http://localhost/nolanv")
+            .body(Body::empty())
+            .expect("Request::get");
+        let response = ClientUnix::oneshot(&socket_path, request)
+            .await
+            .expect("ClientUnix::oneshot");
+        assert_eq!(response.into_body(), b"Hello nolanv");
+
+        let (request, read) = server.await.expect("server");
+        assert!(
+            request.starts_with("GET http://unix.socket/nolanv HTTP/1.1\r\n"),
+            "{}",
+            request
+        );
+        assert!(request.contains("connection: close\r\n"), "{}", request);
+        assert_eq!(read, 0);
+    }
+
+    #[tokio::test]
+    async fn socket_buffer_sizes() {
+        let (stream, _) = UnixStream::pair().expect("UnixStream::pair");
+        let options = Options {
+            send_buffer_size: Some(512 * 1024),
+            recv_buffer_size: Some(256 * 1024),
+            ..Options::default()
+        };
+        set_buffer_sizes(&stream, &options).expect("set_buffer_sizes");
+        // Linux doubles the size for its bookkeeping, within the limits of the system
+        let socket = socket2::SockRef::from(&stream);
+        let (default_stream, _) = UnixStream::pair().expect("UnixStream::pair");
+        let default_socket = socket2::SockRef::from(&default_stream);
+        assert!(
+            socket.send_buffer_size().expect("send_buffer_size")
+                >= default_socket.send_buffer_size().expect("send_buffer_size")
+        );
+        assert!(socket.recv_buffer_size().expect("recv_buffer_size") >= 256 * 1024);
+
+        let socket_path = make_socket_path_test("client", "socket_buffer_sizes");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .send_buffer_size(512 * 1024)
+            .recv_buffer_size(256 * 1024)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+        client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+    }
+
+    #[tokio::test]
+    async fn send_request_to_writer() {
+        let (_, mut client) = make_client_server("send_request_to_writer").await;
+        let mut sink = Vec::new();
+        let response = client
+            .send_request_to_writer("/nolanv", Method::GET, &[], None, &mut sink)
+            .await
+            .expect("client.send_request_to_writer");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(*response.body(), 12);
+        assert_eq!(sink, b"Hello nolanv");
+
+        let error = client
+            .send_request_to_writer("/not/found", Method::GET, &[], None, &mut sink)
+            .await
+            .expect_err("client.send_request_to_writer");
+        assert!(matches!(
+            error,
+            ErrorAndResponse::ResponseUnsuccessful(StatusCode::NOT_FOUND, _)
+        ));
+        assert_eq!(sink, b"Hello nolanv");
     }
 
     #[cfg(feature = "json")]
-    pub async fn send_request_json<IN: Serialize, OUT: DeserializeOwned, ERR: DeserializeOwned>(
-        &mut self,
-        endpoint: &str,
-        method: Method,
-        headers: &[(&str, &str)],
-        body_request: Option<&IN>,
-    ) -> Result<(StatusCode, OUT), ErrorAndResponseJson<ERR>> {
-        let mut headers = headers.to_vec();
-        headers.push(("Content-Type", "application/json"));
+    #[tokio::test]
+    async fn api_error() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct ApiError {
+            message: String,
+        }
 
-        let body_request = match body_request {
-            Some(body_request) => Body::from(
-                serde_json::to_vec(body_request)
-                    .map_err(|e| ErrorAndResponseJson::InternalError(Error::RequestParsing(e)))?,
-            ),
-            None => Body::empty(),
+        let server = crate::testing::MockServer::start(
+            axum::Router::new()
+                .route(
+                    "/json",
+                    axum::routing::get(|| async {
+                        (StatusCode::CONFLICT, r#"{"message":"nolanv is running"}"#)
+                    }),
+                )
+                .route(
+                    "/text",
+                    axum::routing::get(|| async { (StatusCode::BAD_GATEWAY, "nolanv is down") }),
+                ),
+        )
+        .await
+        .expect("MockServer::start");
+        let mut client = ClientUnix::builder(&server.socket_path().to_string_lossy())
+            .api_error::<ApiError>()
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let error = client
+            .send_request("/json", Method::GET, &[], None)
+            .await
+            .expect_err("client.send_request");
+        let ErrorAndResponse::Api(StatusCode::CONFLICT, api_error) = &error else {
+            panic!("{:?}", error)
         };
+        assert_eq!(
+            api_error.downcast_ref::<ApiError>(),
+            Some(&ApiError {
+                message: "nolanv is running".to_string()
+            })
+        );
+        assert_eq!(
+            error.body(),
+            Some(&br#"{"message":"nolanv is running"}"#[..])
+        );
+        assert_eq!(
+            error.api::<serde_json::Value>(),
+            Some(serde_json::json!({"message": "nolanv is running"}))
+        );
 
-        match self
-            .send_request(endpoint, method, &headers, Some(body_request))
+        let error = client
+            .send_request("/text", Method::GET, &[], None)
             .await
-        {
-            Ok((status_code, response)) => Ok((
-                status_code,
-                serde_json::from_slice(&response).map_err(|e| {
-                    ErrorAndResponseJson::InternalError(Error::ResponseParsing(e, response))
-                })?,
-            )),
-            Err(ErrorAndResponse::InternalError(e)) => Err(ErrorAndResponseJson::InternalError(e)),
-            Err(ErrorAndResponse::ResponseUnsuccessful(status_code, response)) => {
-                Err(ErrorAndResponseJson::ResponseUnsuccessful(
-                    status_code,
-                    serde_json::from_slice(&response).map_err(|e| {
-                        ErrorAndResponseJson::InternalError(Error::ResponseParsing(e, response))
-                    })?,
-                ))
-            }
+            .expect_err("client.send_request");
+        assert!(matches!(
+            error,
+            ErrorAndResponse::ResponseUnsuccessful(StatusCode::BAD_GATEWAY, _)
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn automatic_accept_header() {
+        let socket_path = make_socket_path_test("client", "automatic_accept_header");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let accepted = Arc::new(Mutex::new(Vec::new()));
+        let hook_accepted = accepted.clone();
+        let mut client = ClientUnix::builder(&socket_path)
+            .on_request(move |event| {
+                hook_accepted
+                    .lock()
+                    .unwrap()
+                    .push(event.headers.get(hyper::header::ACCEPT).cloned());
+            })
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        for headers in [&[][..], &[("accept", "application/vnd.nolanv+json")]] {
+            let _ = client
+                .send_request_json::<(), serde_json::Value, serde_json::Value>(
+                    "/nolanv",
+                    Method::GET,
+                    headers,
+                    None,
+                )
+                .await;
         }
+        let _ = client.send_request("/nolanv", Method::GET, &[], None).await;
+        assert_eq!(
+            *accepted.lock().unwrap(),
+            [
+                Some(HeaderValue::from_static("application/json")),
+                Some(HeaderValue::from_static("application/vnd.nolanv+json")),
+                None,
+            ]
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::test_helpers::{server::Server, util::*};
-    use hyper::Method;
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn json_no_content() {
+        let server = crate::testing::MockServer::start(axum::Router::new().route(
+            "/containers/nolanv",
+            axum::routing::delete(|| async { StatusCode::NO_CONTENT }),
+        ))
+        .await
+        .expect("MockServer::start");
+        let mut client = ClientUnix::try_new(&server.socket_path().to_string_lossy())
+            .await
+            .expect("ClientUnix::try_new");
+
+        let (status, ()) = client
+            .send_request_json::<(), (), serde_json::Value>(
+                "/containers/nolanv",
+                Method::DELETE,
+                &[],
+                None,
+            )
+            .await
+            .expect("client.send_request_json");
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (_, body) = client
+            .send_request_json::<(), Option<Vec<String>>, serde_json::Value>(
+                "/containers/nolanv",
+                Method::DELETE,
+                &[],
+                None,
+            )
+            .await
+            .expect("client.send_request_json");
+        assert_eq!(body, None);
+    }
 
     #[tokio::test]
-    async fn simple_request() {
-        let (_, mut client) = make_client_server("simple_request").await;
+    async fn custom_success_predicate() {
+        let socket_path = make_socket_path_test("client", "custom_success_predicate");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .success_predicate(|status| status == StatusCode::NOT_FOUND)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
 
-        let (status_code, response) = client
-            .send_request("/nolanv", Method::GET, &[], None)
+        let (status, _) = client
+            .send_request("/not/found", Method::GET, &[], None)
             .await
             .expect("client.send_request");
-
-        assert_eq!(status_code, StatusCode::OK);
-        assert_eq!(response, "Hello nolanv".as_bytes())
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let error = client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect_err("client.send_request");
+        assert!(matches!(
+            error,
+            ErrorAndResponse::ResponseUnsuccessful(StatusCode::OK, _)
+        ));
     }
 
     #[tokio::test]
-    async fn simple_404_request() {
-        let (_, mut client) = make_client_server("simple_404_request").await;
+    async fn custom_uri_scheme() {
+        let socket_path = make_socket_path_test("client", "custom_uri_scheme");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sink = sent.clone();
+        let mut client = ClientUnix::builder(&socket_path)
+            .uri_scheme(Scheme::HTTPS)
+            .wire_dump(move |direction, bytes: &[u8]| {
+                if direction == crate::wire::Direction::Sent {
+                    sink.lock().unwrap().extend_from_slice(bytes);
+                }
+            })
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
 
-        let result = client
-            .send_request("/nolanv/nope", Method::GET, &[], None)
-            .await;
+        client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert!(
+            String::from_utf8_lossy(&sent.lock().unwrap())
+                .starts_with("GET https://unix.socket/nolanv HTTP/1.1\r\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn connection_task_panicked() {
+        let socket_path = make_socket_path_test("client", "connection_task_panicked");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .wire_dump(|_, _: &[u8]| panic!("nolanv sink"))
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
 
+        let error = client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect_err("client.send_request");
+        let ErrorAndResponse::InternalError(error) = error else {
+            panic!("{:?}", error)
+        };
+        assert!(
+            matches!(error.inner(), Error::ConnectionTaskPanicked(message) if message == "nolanv sink"),
+            "{:?}",
+            error
+        );
+        assert_eq!(error.kind(), crate::ErrorKind::ConnectionClosed);
         assert!(matches!(
-            result.err(),
-            Some(ErrorAndResponse::ResponseUnsuccessful(status_code, _))
-                if status_code == StatusCode::NOT_FOUND
+            client.abort().await,
+            Some(Error::ConnectionTaskPanicked(_))
         ));
     }
 
-    #[tokio::test]
-    async fn multiple_request() {
-        let (_, mut client) = make_client_server("multiple_request").await;
+    #[test]
+    fn closing_responses() {
+        let response = |version, connection: Option<&'static str>| {
+            let mut response = Response::builder().version(version);
+            if let Some(connection) = connection {
+                response = response.header(hyper::header::CONNECTION, connection);
+            }
+            response.body(()).expect("Response::builder")
+        };
+        assert!(closes_connection(&response(
+            hyper::Version::HTTP_11,
+            Some("Close")
+        )));
+        assert!(closes_connection(&response(
+            hyper::Version::HTTP_11,
+            Some("upgrade, close")
+        )));
+        assert!(!closes_connection(&response(hyper::Version::HTTP_11, None)));
+        assert!(closes_connection(&response(hyper::Version::HTTP_10, None)));
+        assert!(!closes_connection(&response(
+            hyper::Version::HTTP_10,
+            Some("keep-alive")
+        )));
+    }
 
-        for i in 0..20 {
-            let (status_code, response) = client
-                .send_request(&format!("/nolanv{}", i), Method::GET, &[], None)
+    // On several threads, the connection may not be closed yet when the next request is sent
+    #[tokio::test(flavor = "multi_thread")]
+    async fn connection_close_responses() {
+        let server = crate::testing::MockServer::start(axum::Router::new().route(
+            "/nolanv",
+            axum::routing::get(|| async { ([(hyper::header::CONNECTION, "close")], "nolanv") }),
+        ))
+        .await
+        .expect("MockServer::start");
+        let mut client = server.client().await.expect("server.client");
+
+        for _ in 0..50 {
+            let (_, body) = client
+                .send_request("/nolanv", Method::GET, &[], None)
                 .await
                 .expect("client.send_request");
+            assert_eq!(body, b"nolanv");
+            assert_eq!(client.stats().requests, 1);
+        }
+    }
 
-            assert_eq!(status_code, StatusCode::OK);
+    #[tokio::test]
+    async fn drop_aborts_connection() {
+        let server = crate::testing::MockServer::start(axum::Router::new().route(
+            "/logs",
+            axum::routing::get(|| async {
+                let chunks = futures_util::stream::unfold(0, |i| async move {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    Some((
+                        Ok::<_, std::io::Error>(Bytes::from(format!("line {}\n", i))),
+                        i + 1,
+                    ))
+                });
+                Body::from_stream(chunks)
+            }),
+        ))
+        .await
+        .expect("MockServer::start");
+        let mut client = server.client().await.expect("server.client");
+        let response = client
+            .send_request_streaming("/logs", Method::GET, &[], None)
+            .await
+            .expect("client.send_request_streaming");
+        let status = client.status();
 
-            assert_eq!(response, format!("Hello nolanv{}", i).as_bytes())
-        }
+        drop(client);
+        assert!(matches!(
+            *status.borrow(),
+            ConnectionStatus::Disconnected { .. }
+        ));
+        // The endless body was read by the aborted connection task
+        let body = tokio::time::timeout(Duration::from_secs(1), response.into_body().collect());
+        assert!(body.await.is_ok());
     }
 
     #[tokio::test]
@@ -193,22 +2367,37 @@ mod tests {
         let socket_path = make_socket_path_test("client", "server_not_started");
 
         let client = ClientUnix::try_new(&socket_path).await;
+        let error = client.err().expect("client.err");
         assert!(matches!(
-            client.err(),
-            Some(Error::SocketConnectionInitiation(_))
+            error.inner(),
+            Error::SocketConnectionInitiation(_)
         ));
+        assert_eq!(error.kind(), crate::ErrorKind::SocketNotFound);
+        assert_eq!(
+            error.context().map(|c| c.socket_path.clone()),
+            Some(PathBuf::from(&socket_path))
+        );
     }
 
     #[tokio::test]
     async fn server_stopped() {
-        let (server, mut client) = make_client_server("server_stopped").await;
+        let socket_path = make_socket_path_test("client", "server_stopped");
+        let server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .reconnect_when_closed(false)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
         server.abort().await;
 
         let response_result = client.send_request("/nolanv", Method::GET, &[], None).await;
         assert!(matches!(
             response_result.err(),
-            Some(ErrorAndResponse::InternalError(Error::RequestSend(e)))
-                if e.is_canceled()
+            Some(ErrorAndResponse::InternalError(e))
+                if matches!(e.inner(), Error::RequestSend(e) if e.is_canceled())
+                    && e.context().and_then(|c| c.endpoint.as_deref()) == Some("/nolanv")
         ));
 
         let _ = Server::try_new(&make_socket_path_test("client", "server_stopped"))
@@ -224,6 +2413,31 @@ mod tests {
         assert_eq!(status_code, StatusCode::OK);
         assert_eq!(response, "Hello nolanv".as_bytes())
     }
+
+    #[tokio::test]
+    async fn server_restarted() {
+        let (server, mut client) = make_client_server("server_restarted").await;
+        server.abort().await;
+        let _server = Server::try_new(&make_socket_path_test("client", "server_restarted"))
+            .await
+            .expect("Server::try_new");
+        while !client.sender.is_closed() {
+            tokio::task::yield_now().await;
+        }
+
+        let response = client
+            .send_request_full("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request_full");
+
+        assert_eq!(response.body(), "Hello nolanv".as_bytes());
+        assert!(
+            response
+                .extensions()
+                .get::<RequestTimings>()
+                .is_some_and(|timings| timings.connect > Duration::ZERO)
+        );
+    }
 }
 
 #[cfg(feature = "json")]
@@ -269,6 +2483,23 @@ mod json_tests {
         ));
     }
 
+    #[tokio::test]
+    async fn problem_json_request() {
+        let (_, mut client) = make_client_server("problem_json_request").await;
+
+        let result = client
+            .send_request_json::<(), Value, ErrorJson>("/json/problem", Method::GET, &[], None)
+            .await;
+
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponseJson::ResponseProblem(status_code, problem))
+                if status_code == StatusCode::CONFLICT
+                    && problem.title.as_deref() == Some("Conflict")
+                    && problem.extensions.get("container") == Some(&json!("nolanv"))
+        ));
+    }
+
     #[tokio::test]
     async fn simple_post_request() {
         let (_, mut client) = make_client_server("simple_post_request").await;
@@ -326,3 +2557,63 @@ mod json_tests {
         ));
     }
 }
+
+#[cfg(feature = "metrics")]
+#[cfg(test)]
+mod metrics_tests {
+    use crate::{
+        metrics::{REQUESTS_IN_FLIGHT, REQUESTS_TOTAL},
+        test_helpers::util::make_client_server,
+    };
+    use hyper::Method;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    #[test]
+    fn requests_are_counted_by_status_class() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Runtime::build")
+                .block_on(async {
+                    let (_, mut client) =
+                        make_client_server("requests_are_counted_by_status_class").await;
+                    let _ = client.send_request("/nolanv", Method::GET, &[], None).await;
+                    let _ = client
+                        .send_request("/nolanv/nope", Method::GET, &[], None)
+                        .await;
+                })
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let value_of = |name: &str, status_class: Option<&str>| {
+            snapshot
+                .iter()
+                .find(|(key, _, _, _)| {
+                    key.key().name() == name
+                        && status_class.is_none_or(|status_class| {
+                            key.key()
+                                .labels()
+                                .any(|l| l.key() == "status_class" && l.value() == status_class)
+                        })
+                })
+                .map(|(_, _, _, value)| value)
+        };
+
+        assert_eq!(
+            value_of(REQUESTS_TOTAL, Some("2xx")),
+            Some(&DebugValue::Counter(1))
+        );
+        assert_eq!(
+            value_of(REQUESTS_TOTAL, Some("4xx")),
+            Some(&DebugValue::Counter(1))
+        );
+        assert!(matches!(
+            value_of(REQUESTS_IN_FLIGHT, None),
+            Some(DebugValue::Gauge(gauge)) if gauge.into_inner() == 0.0
+        ));
+    }
+}
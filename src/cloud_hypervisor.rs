@@ -0,0 +1,339 @@
+//! Typed wrappers of the [cloud-hypervisor API](https://github.com/cloud-hypervisor/cloud-hypervisor/blob/main/docs/api.md)
+//! (feature=cloud-hypervisor), the endpoints used by `ch-remote` on the `--api-socket` of a VMM.
+//!
+//! The actions answer `204 No Content`, and the errors are plain text bodies.
+//!
+//! ```rust,no_run
+//! use http_client_unix_domain_socket::cloud_hypervisor::{CloudHypervisor, Resize, VmConfig};
+//!
+//! # async fn run() {
+//! let mut vm = CloudHypervisor::connect("/tmp/cloud-hypervisor.socket")
+//!     .await
+//!     .expect("CloudHypervisor::connect");
+//! vm.create(&VmConfig::new("/srv/vmlinux", 2, 1 << 30))
+//!     .await
+//!     .expect("vm.create");
+//! vm.boot().await.expect("vm.boot");
+//! vm.resize(&Resize::vcpus(4)).await.expect("vm.resize");
+//! # }
+//! ```
+
+use crate::{ClientUnix, Error, ErrorAndResponseJson, Method};
+use axum_core::body::Body;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Text body of the cloud-hypervisor error responses.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CloudHypervisorError {
+    pub message: String,
+}
+
+pub type CloudHypervisorResult<T> = Result<T, ErrorAndResponseJson<CloudHypervisorError>>;
+
+/// Response of [`CloudHypervisor::ping`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct VmmPing {
+    pub build_version: String,
+    pub version: String,
+    #[serde(default)]
+    pub pid: Option<u32>,
+}
+
+/// Response of [`CloudHypervisor::info`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct VmInfo {
+    /// `Created`, `Running`, `Shutdown`, `Paused` or `BreakPoint`.
+    pub state: String,
+    #[serde(default)]
+    pub memory_actual_size: Option<u64>,
+    /// The configuration of the VM as created, with its defaults.
+    pub config: Value,
+}
+
+/// Body of [`CloudHypervisor::create`], the unset fields use the VMM defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VmConfig {
+    pub cpus: CpusConfig,
+    pub memory: MemoryConfig,
+    pub payload: PayloadConfig,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub disks: Vec<DiskConfig>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub net: Vec<NetConfig>,
+}
+
+impl VmConfig {
+    /// A VM booting `kernel` with `vcpus` cpus and `memory` bytes of RAM.
+    pub fn new(kernel: &str, vcpus: u8, memory: u64) -> Self {
+        VmConfig {
+            cpus: CpusConfig {
+                boot_vcpus: vcpus,
+                max_vcpus: vcpus,
+            },
+            memory: MemoryConfig {
+                size: memory,
+                hotplug_size: None,
+            },
+            payload: PayloadConfig {
+                kernel: Some(kernel.to_string()),
+                cmdline: None,
+                initramfs: None,
+            },
+            disks: Vec::new(),
+            net: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CpusConfig {
+    pub boot_vcpus: u8,
+    /// Upper bound of [`Resize::desired_vcpus`].
+    pub max_vcpus: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MemoryConfig {
+    /// Bytes.
+    pub size: u64,
+    /// Bytes which can be added by [`CloudHypervisor::resize`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hotplug_size: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PayloadConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kernel: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cmdline: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initramfs: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiskConfig {
+    pub path: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub readonly: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NetConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tap: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mac: Option<String>,
+}
+
+/// Body of [`CloudHypervisor::resize`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Resize {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desired_vcpus: Option<u8>,
+    /// Bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desired_ram: Option<u64>,
+}
+
+impl Resize {
+    pub fn vcpus(desired_vcpus: u8) -> Self {
+        Resize {
+            desired_vcpus: Some(desired_vcpus),
+            ..Resize::default()
+        }
+    }
+
+    pub fn ram(desired_ram: u64) -> Self {
+        Resize {
+            desired_ram: Some(desired_ram),
+            ..Resize::default()
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Snapshot<'a> {
+    destination_url: &'a str,
+}
+
+#[derive(Serialize)]
+struct Restore<'a> {
+    source_url: &'a str,
+    prefault: bool,
+}
+
+/// cloud-hypervisor API client.
+pub struct CloudHypervisor {
+    client: ClientUnix,
+}
+
+impl CloudHypervisor {
+    pub fn new(client: ClientUnix) -> Self {
+        CloudHypervisor { client }
+    }
+
+    pub async fn connect(socket_path: &str) -> Result<Self, Error> {
+        Ok(CloudHypervisor::new(
+            ClientUnix::try_new(socket_path).await?,
+        ))
+    }
+
+    /// The underlying client, for the endpoints without a wrapper.
+    pub fn client(&mut self) -> &mut ClientUnix {
+        &mut self.client
+    }
+
+    pub async fn ping(&mut self) -> CloudHypervisorResult<VmmPing> {
+        self.get("vmm.ping").await
+    }
+
+    pub async fn info(&mut self) -> CloudHypervisorResult<VmInfo> {
+        self.get("vm.info").await
+    }
+
+    pub async fn create(&mut self, config: &VmConfig) -> CloudHypervisorResult<()> {
+        self.put("vm.create", Some(config)).await
+    }
+
+    pub async fn boot(&mut self) -> CloudHypervisorResult<()> {
+        self.put::<()>("vm.boot", None).await
+    }
+
+    pub async fn shutdown(&mut self) -> CloudHypervisorResult<()> {
+        self.put::<()>("vm.shutdown", None).await
+    }
+
+    pub async fn reboot(&mut self) -> CloudHypervisorResult<()> {
+        self.put::<()>("vm.reboot", None).await
+    }
+
+    pub async fn pause(&mut self) -> CloudHypervisorResult<()> {
+        self.put::<()>("vm.pause", None).await
+    }
+
+    pub async fn resume(&mut self) -> CloudHypervisorResult<()> {
+        self.put::<()>("vm.resume", None).await
+    }
+
+    /// Delete the VM, the VMM keeps running.
+    pub async fn delete(&mut self) -> CloudHypervisorResult<()> {
+        self.put::<()>("vm.delete", None).await
+    }
+
+    /// Hotplug vcpus or memory, within the maximums of the [`VmConfig`].
+    pub async fn resize(&mut self, resize: &Resize) -> CloudHypervisorResult<()> {
+        self.put("vm.resize", Some(resize)).await
+    }
+
+    /// Snapshot the paused VM to `destination_url`, such as `file:///srv/snapshot`.
+    pub async fn snapshot(&mut self, destination_url: &str) -> CloudHypervisorResult<()> {
+        self.put("vm.snapshot", Some(&Snapshot { destination_url }))
+            .await
+    }
+
+    /// Restore a VM from a snapshot, the VMM must have no VM.
+    pub async fn restore(&mut self, source_url: &str, prefault: bool) -> CloudHypervisorResult<()> {
+        self.put(
+            "vm.restore",
+            Some(&Restore {
+                source_url,
+                prefault,
+            }),
+        )
+        .await
+    }
+
+    async fn get<OUT: serde::de::DeserializeOwned>(
+        &mut self,
+        action: &str,
+    ) -> CloudHypervisorResult<OUT> {
+        let body = self
+            .send(&format!("/api/v1/{}", action), Method::GET, None)
+            .await?;
+        crate::client::json_from_slice(&body)
+            .map_err(|e| ErrorAndResponseJson::InternalError(Error::ResponseParsing(e, body)))
+    }
+
+    async fn put<IN: Serialize>(
+        &mut self,
+        action: &str,
+        body: Option<&IN>,
+    ) -> CloudHypervisorResult<()> {
+        let body = match body {
+            Some(body) => Some(Body::from(serde_json::to_vec(body).map_err(|e| {
+                ErrorAndResponseJson::InternalError(Error::RequestParsing(e))
+            })?)),
+            None => None,
+        };
+        self.send(&format!("/api/v1/{}", action), Method::PUT, body)
+            .await
+            .map(|_| ())
+    }
+
+    async fn send(
+        &mut self,
+        endpoint: &str,
+        method: Method,
+        body: Option<Body>,
+    ) -> CloudHypervisorResult<Vec<u8>> {
+        match self
+            .client
+            .send_request(
+                endpoint,
+                method,
+                &[("Content-Type", "application/json")],
+                body,
+            )
+            .await
+        {
+            Ok((_, body)) => Ok(body),
+            Err(error) => match error.into_response() {
+                Err(e) => Err(ErrorAndResponseJson::InternalError(e)),
+                Ok((status_code, body)) => Err(ErrorAndResponseJson::ResponseUnsuccessful(
+                    status_code,
+                    CloudHypervisorError {
+                        message: String::from_utf8_lossy(&body).into_owned(),
+                    },
+                )),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StatusCode, test_helpers::server::Server, test_helpers::util::*};
+
+    #[tokio::test]
+    async fn ping_and_resize() {
+        let socket_path = make_socket_path_test("cloud_hypervisor", "ping_and_resize");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut vm = CloudHypervisor::connect(&socket_path)
+            .await
+            .expect("CloudHypervisor::connect");
+
+        let ping = vm.ping().await.expect("vm.ping");
+        assert_eq!(ping.version, "v44.0");
+
+        vm.resize(&Resize::vcpus(4)).await.expect("vm.resize");
+        let result = vm.resize(&Resize::vcpus(16)).await;
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponseJson::ResponseUnsuccessful(StatusCode::INTERNAL_SERVER_ERROR, e))
+                if e.message.contains("resizing")
+        ));
+    }
+
+    #[test]
+    fn vm_config_body() {
+        assert_eq!(
+            serde_json::to_string(&VmConfig::new("/srv/vmlinux", 2, 1024)).unwrap(),
+            r#"{"cpus":{"boot_vcpus":2,"max_vcpus":2},"memory":{"size":1024},"payload":{"kernel":"/srv/vmlinux"}}"#
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use crate::{ClientUnix, Error};
+use hyper::{HeaderMap, Response, StatusCode, Version, http::uri::PathAndQuery};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::watch;
+
+type SharedReceiver = watch::Receiver<Option<Arc<SharedResponse>>>;
+
+/// Shares one request between the identical GET requests sent at the same time, set with
+/// [`ClientUnixBuilder::coalesce_gets`](crate::ClientUnixBuilder::coalesce_gets).
+///
+/// A GET request without body, sent while the same one (socket, endpoint, headers and default
+/// headers) is in flight, waits for its response instead of reaching the server. Requests are
+/// only shared between clients using the same interceptors and Digest credentials, so responses
+/// never cross credentials. The requests sharing a response
+/// get its status code, headers and body, but not its extensions such as the timings. When the
+/// request in flight fails, the waiting ones are sent on their own. Clones share the requests in
+/// flight, so several clients can use one coalescer.
+///
+/// ```rust
+/// use http_client_unix_domain_socket::{ClientUnix, Method, RequestCoalescer};
+///
+/// pub async fn inspect_twice() {
+///     let coalescer = RequestCoalescer::new();
+///     let mut first = ClientUnix::builder("/var/run/docker.sock")
+///         .coalesce_gets(coalescer.clone())
+///         .build()
+///         .await
+///         .expect("ClientUnixBuilder::build");
+///     let mut second = ClientUnix::builder("/var/run/docker.sock")
+///         .coalesce_gets(coalescer)
+///         .build()
+///         .await
+///         .expect("ClientUnixBuilder::build");
+///     // Only one request reaches the daemon
+///     let (first, second) = tokio::join!(
+///         first.send_request("/containers/json", Method::GET, &[], None),
+///         second.send_request("/containers/json", Method::GET, &[], None),
+///     );
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RequestCoalescer {
+    in_flight: Arc<Mutex<HashMap<String, SharedReceiver>>>,
+}
+
+/// Response given to several requests, without its extensions.
+#[derive(Debug)]
+pub(crate) struct SharedResponse {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl SharedResponse {
+    pub(crate) fn new(response: &Response<Vec<u8>>) -> Self {
+        SharedResponse {
+            status: response.status(),
+            version: response.version(),
+            headers: response.headers().clone(),
+            body: response.body().clone(),
+        }
+    }
+
+    pub(crate) fn to_response(&self) -> Response<Vec<u8>> {
+        let mut response = Response::new(self.body.clone());
+        *response.status_mut() = self.status;
+        *response.version_mut() = self.version;
+        *response.headers_mut() = self.headers.clone();
+        response
+    }
+}
+
+/// Removes the request from the ones in flight, even when its future is dropped.
+struct InFlightGuard<'a> {
+    coalescer: &'a RequestCoalescer,
+    key: &'a str,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut in_flight) = self.coalescer.in_flight.lock() {
+            in_flight.remove(self.key);
+        }
+    }
+}
+
+impl RequestCoalescer {
+    pub fn new() -> Self {
+        RequestCoalescer::default()
+    }
+
+    /// Requests in flight which the identical ones would share.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().map_or(0, |in_flight| in_flight.len())
+    }
+
+    /// Send the GET request, or wait for the response of the identical one in flight.
+    pub(crate) async fn send(
+        &self,
+        client: &mut ClientUnix,
+        endpoint: &PathAndQuery,
+        headers: &HeaderMap,
+    ) -> Result<Response<Vec<u8>>, Error> {
+        let key = coalesce_key(client, endpoint.as_str(), headers);
+        let joined = self
+            .in_flight
+            .lock()
+            .ok()
+            .map(|mut in_flight| match in_flight.get(&key) {
+                Some(receiver) => Err(receiver.clone()),
+                None => {
+                    let (sender, receiver) = watch::channel(None);
+                    in_flight.insert(key.clone(), receiver);
+                    Ok(sender)
+                }
+            });
+        let Some(joined) = joined else {
+            return client.send_get(endpoint, headers).await;
+        };
+
+        match joined {
+            Ok(sender) => {
+                let _guard = InFlightGuard {
+                    coalescer: self,
+                    key: &key,
+                };
+                let response = client.send_get(endpoint, headers).await?;
+                sender.send_replace(Some(Arc::new(SharedResponse::new(&response))));
+                Ok(response)
+            }
+            Err(mut receiver) => {
+                let shared = receiver
+                    .wait_for(Option::is_some)
+                    .await
+                    .ok()
+                    .and_then(|shared| shared.clone());
+                match shared {
+                    Some(shared) => Ok(shared.to_response()),
+                    // The request in flight failed
+                    None => client.send_get(endpoint, headers).await,
+                }
+            }
+        }
+    }
+}
+
+/// Key of the request as it will be sent: the default headers it will get are part of it, and
+/// since interceptors and Digest authentication may add credentials, only the requests going
+/// through the same ones are shared.
+fn coalesce_key(client: &ClientUnix, endpoint: &str, headers: &HeaderMap) -> String {
+    let options = client.options();
+    let defaults = options
+        .default_headers
+        .iter()
+        .filter(|(name, _)| !headers.contains_key(*name));
+    let mut headers: Vec<(&str, &[u8])> = headers
+        .iter()
+        .chain(defaults)
+        .map(|(name, value)| (name.as_str(), value.as_bytes()))
+        .collect();
+    headers.sort_unstable();
+    let mut key = format!("{}\n{}", client.socket_path().display(), endpoint);
+    for (name, value) in headers {
+        key.push('\n');
+        key.push_str(name);
+        key.push(':');
+        key.push_str(&String::from_utf8_lossy(value));
+    }
+    for interceptor in &options.interceptors {
+        key.push_str(&format!("\n@{:p}", Arc::as_ptr(interceptor).cast::<()>()));
+    }
+    #[cfg(feature = "digest")]
+    if let Some(digest) = &options.digest_auth {
+        key.push_str(&format!("\n@{:p}", digest.id()));
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Method, test_helpers::server::Server, test_helpers::util::*};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn share_identical_gets() {
+        let socket_path = make_socket_path_test("coalesce", "share_identical_gets");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let coalescer = RequestCoalescer::new();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let mut clients = Vec::new();
+        for _ in 0..3 {
+            let on_request = requests.clone();
+            clients.push(
+                ClientUnix::builder(&socket_path)
+                    .coalesce_gets(coalescer.clone())
+                    .on_request(move |_| {
+                        on_request.fetch_add(1, Ordering::SeqCst);
+                    })
+                    .build()
+                    .await
+                    .expect("ClientUnixBuilder::build"),
+            );
+        }
+        let [first, second, third] = &mut clients[..] else {
+            unreachable!()
+        };
+
+        let (first, second, third) = tokio::join!(
+            first.send_request("/sleep/50", Method::GET, &[], None),
+            second.send_request("/sleep/50", Method::GET, &[], None),
+            third.send_request("/sleep/50", Method::GET, &[("X-Tenant", "nolanv")], None),
+        );
+
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        for result in [first, second, third] {
+            let (status, body) = result.expect("client.send_request");
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body, b"Slept 50ms");
+        }
+        assert_eq!(coalescer.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn keep_credentials_apart() {
+        let server = crate::testing::MockServer::start(axum::Router::new().route(
+            "/whoami",
+            axum::routing::get(|headers: HeaderMap| async move {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                headers
+                    .get(hyper::header::AUTHORIZATION)
+                    .map(|value| value.as_bytes().to_vec())
+                    .unwrap_or_default()
+            }),
+        ))
+        .await
+        .expect("MockServer::start");
+        let socket_path = server.socket_path().to_string_lossy().to_string();
+        let coalescer = RequestCoalescer::new();
+        let mut first = ClientUnix::builder(&socket_path)
+            .coalesce_gets(coalescer.clone())
+            .bearer_auth("first-token".to_string())
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+        let mut second = ClientUnix::builder(&socket_path)
+            .coalesce_gets(coalescer.clone())
+            .bearer_auth("second-token".to_string())
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+        let mut third = ClientUnix::builder(&socket_path)
+            .coalesce_gets(coalescer)
+            .default_header(
+                hyper::header::AUTHORIZATION,
+                hyper::header::HeaderValue::from_static("Bearer third-token"),
+            )
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let (first, second, third) = tokio::join!(
+            first.send_request("/whoami", Method::GET, &[], None),
+            second.send_request("/whoami", Method::GET, &[], None),
+            third.send_request("/whoami", Method::GET, &[], None),
+        );
+
+        assert_eq!(first.expect("client.send_request").1, b"Bearer first-token");
+        assert_eq!(
+            second.expect("client.send_request").1,
+            b"Bearer second-token"
+        );
+        assert_eq!(third.expect("client.send_request").1, b"Bearer third-token");
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{ClientUnix, ClientUnixBuilder, Error, RetryPolicy};
+use hyper::header::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
+
+/// Settings of a client which can be read from a configuration file or the environment with
+/// serde (feature=json), for deployments to tune the client without code changes. Every field
+/// but `socket_path` is optional.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::{ClientConfig, ClientUnix};
+///
+/// # async fn run() {
+/// let config: ClientConfig = serde_json::from_str(
+///     r#"{
+///         "socket_path": "/var/run/docker.sock",
+///         "request_timeout_ms": 5000,
+///         "retry": { "max_retries": 3 },
+///         "headers": { "x-tenant": "nolanv" }
+///     }"#,
+/// )
+/// .expect("serde_json::from_str");
+/// let client = ClientUnix::from_config(&config)
+///     .await
+///     .expect("ClientUnix::from_config");
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    pub socket_path: String,
+    /// See [`ClientUnixBuilder::connect_timeout`].
+    pub connect_timeout_ms: Option<u64>,
+    /// See [`ClientUnixBuilder::handshake_timeout`].
+    pub handshake_timeout_ms: Option<u64>,
+    /// See [`ClientUnixBuilder::request_timeout`].
+    pub request_timeout_ms: Option<u64>,
+    pub retry: Option<RetryConfig>,
+    /// Sent by every request which doesn't set them, see [`ClientUnixBuilder::default_header`].
+    pub headers: BTreeMap<String, String>,
+    /// Replaces the default `User-Agent`.
+    pub user_agent: Option<String>,
+    /// Requests per second, see [`ClientUnixBuilder::rate_limit`].
+    pub rate_limit: Option<f64>,
+    /// Burst of the rate limit, 1 by default.
+    pub rate_limit_burst: Option<u32>,
+}
+
+/// [`RetryPolicy`] of a [`ClientConfig`], with the defaults of [`RetryPolicy::new`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+    pub initial_backoff_ms: Option<u64>,
+    pub max_backoff_ms: Option<u64>,
+    pub jitter: Option<bool>,
+    /// See [`RetryPolicy::max_replay_body`].
+    pub max_replay_body: Option<usize>,
+}
+
+impl ClientConfig {
+    /// A builder with these settings, to add the ones which can't be configured, such as the
+    /// hooks.
+    ///
+    /// Fails with [`Error::RequestBuild`] when a header or the `User-Agent` is invalid.
+    pub fn builder(&self) -> Result<ClientUnixBuilder, Error> {
+        let mut builder = ClientUnix::builder(&self.socket_path);
+        if let Some(timeout) = self.connect_timeout_ms {
+            builder = builder.connect_timeout(Duration::from_millis(timeout));
+        }
+        if let Some(timeout) = self.handshake_timeout_ms {
+            builder = builder.handshake_timeout(Duration::from_millis(timeout));
+        }
+        if let Some(timeout) = self.request_timeout_ms {
+            builder = builder.request_timeout(Duration::from_millis(timeout));
+        }
+        if let Some(retry) = &self.retry {
+            let mut policy = RetryPolicy::new(retry.max_retries);
+            if retry.initial_backoff_ms.is_some() || retry.max_backoff_ms.is_some() {
+                let initial = retry
+                    .initial_backoff_ms
+                    .map_or(policy.initial_backoff, Duration::from_millis);
+                let max = retry
+                    .max_backoff_ms
+                    .map_or(policy.max_backoff, Duration::from_millis);
+                policy = policy.backoff(initial, max);
+            }
+            if let Some(jitter) = retry.jitter {
+                policy = policy.jitter(jitter);
+            }
+            if let Some(bytes) = retry.max_replay_body {
+                policy = policy.max_replay_body(bytes);
+            }
+            builder = builder.retry(policy);
+        }
+        for (name, value) in &self.headers {
+            builder = builder.default_header(
+                HeaderName::try_from(name.as_str()).map_err(|e| Error::RequestBuild(e.into()))?,
+                HeaderValue::try_from(value.as_str()).map_err(|e| Error::RequestBuild(e.into()))?,
+            );
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(Some(
+                HeaderValue::try_from(user_agent.as_str())
+                    .map_err(|e| Error::RequestBuild(e.into()))?,
+            ));
+        }
+        if let Some(per_second) = self.rate_limit {
+            builder = builder.rate_limit(per_second, self.rate_limit_burst.unwrap_or(1));
+        }
+        Ok(builder)
+    }
+}
+
+impl ClientUnix {
+    /// Connect a client with the settings of `config`, see [`ClientConfig::builder`].
+    pub async fn from_config(config: &ClientConfig) -> Result<Self, Error> {
+        config.builder()?.build().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Method, test_helpers::server::Server, test_helpers::util::*};
+
+    #[tokio::test]
+    async fn connect_from_config() {
+        let socket_path = make_socket_path_test("config", "connect_from_config");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let config: ClientConfig = serde_json::from_value(serde_json::json!({
+            "socket_path": socket_path,
+            "request_timeout_ms": 5000,
+            "retry": { "max_retries": 2, "initial_backoff_ms": 10 },
+            "headers": { "x-tenant": "nolanv" },
+        }))
+        .expect("serde_json::from_value");
+        assert_eq!(
+            config.retry,
+            Some(RetryConfig {
+                max_retries: 2,
+                initial_backoff_ms: Some(10),
+                ..RetryConfig::default()
+            })
+        );
+
+        let mut client = ClientUnix::from_config(&config)
+            .await
+            .expect("ClientUnix::from_config");
+        let (_, body) = client
+            .send_request("/header/x-tenant", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"nolanv");
+        assert_eq!(
+            client.options().request_timeout,
+            Some(Duration::from_secs(5))
+        );
+
+        let invalid = ClientConfig {
+            headers: BTreeMap::from([("x tenant".to_string(), "nolanv".to_string())]),
+            ..config
+        };
+        assert!(matches!(invalid.builder(), Err(Error::RequestBuild(_))));
+        assert!(serde_json::from_str::<ClientConfig>(r#"{"socket": "/nolanv.sock"}"#).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! [`UnixConnector`] plugs the socket into clients generated for `hyper` (feature=connector).
+
+use crate::Error;
+use hyper::Uri;
+use hyper_util::{
+    client::legacy::{
+        Client,
+        connect::{Connected, Connection},
+    },
+    rt::{TokioExecutor, TokioIo},
+};
+use std::{
+    future::Future,
+    io,
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::UnixStream,
+};
+
+/// Connector of the `hyper_util` legacy [`Client`], as expected by the `hyper` flavor of the
+/// OpenAPI generated clients, with every request sent to the same socket whatever its uri.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::connector::UnixConnector;
+///
+/// # fn run() {
+/// let client = UnixConnector::new("/var/run/nolanv.sock").client::<String>();
+/// // let configuration = nolanv_api::apis::configuration::Configuration::new(client);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct UnixConnector {
+    socket_path: Arc<PathBuf>,
+}
+
+impl UnixConnector {
+    pub fn new(socket_path: impl Into<PathBuf>) -> Self {
+        UnixConnector {
+            socket_path: Arc::new(socket_path.into()),
+        }
+    }
+
+    /// A pooled HTTP/1 client on this connector, run on the tokio runtime.
+    pub fn client<B>(self) -> Client<UnixConnector, B>
+    where
+        B: hyper::body::Body + Send + 'static + Unpin,
+        B::Data: Send,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Client::builder(TokioExecutor::new()).build(self)
+    }
+}
+
+impl tower_service::Service<Uri> for UnixConnector {
+    type Response = TokioIo<UnixConnection>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Error>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: Uri) -> Self::Future {
+        let socket_path = self.socket_path.clone();
+        Box::pin(async move {
+            UnixStream::connect(socket_path.as_path())
+                .await
+                .map(|stream| TokioIo::new(UnixConnection(stream)))
+                .map_err(Error::SocketConnectionInitiation)
+        })
+    }
+}
+
+/// Stream of a [`UnixConnector`] connection.
+#[derive(Debug)]
+pub struct UnixConnection(UnixStream);
+
+impl Connection for UnixConnection {
+    fn connected(&self) -> Connected {
+        Connected::new()
+    }
+}
+
+impl AsyncRead for UnixConnection {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for UnixConnection {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{server::Server, util::make_socket_path_test};
+    use http_body_util::{BodyExt, Full};
+    use hyper::body::Bytes;
+
+    #[tokio::test]
+    async fn legacy_client_requests() {
+        let socket_path = make_socket_path_test("connector", "legacy_client_requests");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let client = UnixConnector::new(&socket_path).client::<Full<Bytes>>();
+
+        for _ in 0..2 {
+            let response = client
+                .get(Uri::from_static("http://localhost/nolanv"))
+                .await
+                .expect("client.get");
+            let body = response.into_body().collect().await.expect("collect");
+
+            assert_eq!(body.to_bytes(), "Hello nolanv");
+        }
+    }
+}
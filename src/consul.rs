@@ -0,0 +1,511 @@
+//! Typed wrappers of the [Consul agent API](https://developer.hashicorp.com/consul/api-docs/agent)
+//! (feature=consul), on the socket of `addresses { http = "unix:///..." }`.
+//!
+//! The bodies use the `PascalCase` fields of Consul, the errors are plain text bodies and the
+//! KV values are base64 encoded. The ACL token is sent by [`BearerAuth`](crate::BearerAuth).
+//!
+//! ```rust,no_run
+//! use http_client_unix_domain_socket::consul::{CheckDefinition, Consul, ServiceRegistration};
+//!
+//! # async fn run() {
+//! let mut consul = Consul::connect("/run/consul/http.sock")
+//!     .await
+//!     .expect("Consul::connect");
+//! consul
+//!     .register_service(
+//!         &ServiceRegistration::new("web")
+//!             .port(8080)
+//!             .check(CheckDefinition::ttl("web-ttl", "30s")),
+//!     )
+//!     .await
+//!     .expect("consul.register_service");
+//! consul
+//!     .kv_put("web/config", b"{\"workers\": 4}")
+//!     .await
+//!     .expect("consul.kv_put");
+//! # }
+//! ```
+
+use crate::{ClientUnix, Error, ErrorAndResponseJson, Method, StatusCode};
+use axum_core::body::Body;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::collections::HashMap;
+
+/// Text body of the Consul error responses.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ConsulError {
+    pub message: String,
+}
+
+pub type ConsulResult<T> = Result<T, ErrorAndResponseJson<ConsulError>>;
+
+/// Service registered on the agent, see [`Consul::services`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AgentService {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub service: String,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub port: u16,
+    #[serde(default)]
+    pub meta: Option<HashMap<String, String>>,
+}
+
+/// Check registered on the agent, see [`Consul::checks`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AgentCheck {
+    #[serde(rename = "CheckID")]
+    pub check_id: String,
+    pub name: String,
+    /// `passing`, `warning` or `critical`.
+    pub status: String,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub output: String,
+    #[serde(rename = "ServiceID", default)]
+    pub service_id: String,
+    #[serde(default)]
+    pub service_name: String,
+}
+
+/// Body of [`Consul::register_service`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServiceRegistration {
+    /// Defaults to the name.
+    #[serde(rename = "ID", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub meta: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check: Option<CheckDefinition>,
+}
+
+impl ServiceRegistration {
+    pub fn new(name: &str) -> Self {
+        ServiceRegistration {
+            id: None,
+            name: name.to_string(),
+            tags: Vec::new(),
+            address: None,
+            port: None,
+            meta: HashMap::new(),
+            check: None,
+        }
+    }
+
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    pub fn check(mut self, check: CheckDefinition) -> Self {
+        self.check = Some(check);
+        self
+    }
+}
+
+/// Body of [`Consul::register_check`], or the check of a [`ServiceRegistration`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CheckDefinition {
+    #[serde(rename = "CheckID", skip_serializing_if = "Option::is_none")]
+    pub check_id: Option<String>,
+    pub name: String,
+    /// Service of a check registered on its own.
+    #[serde(rename = "ServiceID", skip_serializing_if = "Option::is_none")]
+    pub service_id: Option<String>,
+    /// Duration such as `30s` before a check not updated by [`Consul::update_check`] is critical.
+    #[serde(rename = "TTL", skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<String>,
+    /// Url polled every `interval`, healthy on a `2xx`.
+    #[serde(rename = "HTTP", skip_serializing_if = "Option::is_none")]
+    pub http: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deregister_critical_service_after: Option<String>,
+}
+
+impl CheckDefinition {
+    fn new(name: &str) -> Self {
+        CheckDefinition {
+            check_id: None,
+            name: name.to_string(),
+            service_id: None,
+            ttl: None,
+            http: None,
+            interval: None,
+            deregister_critical_service_after: None,
+        }
+    }
+
+    /// Check updated by the service itself before `ttl` elapses.
+    pub fn ttl(name: &str, ttl: &str) -> Self {
+        CheckDefinition {
+            ttl: Some(ttl.to_string()),
+            ..CheckDefinition::new(name)
+        }
+    }
+
+    /// Check polling `url` every `interval`.
+    pub fn http(name: &str, url: &str, interval: &str) -> Self {
+        CheckDefinition {
+            http: Some(url.to_string()),
+            interval: Some(interval.to_string()),
+            ..CheckDefinition::new(name)
+        }
+    }
+
+    pub fn check_id(mut self, check_id: &str) -> Self {
+        self.check_id = Some(check_id.to_string());
+        self
+    }
+}
+
+/// Status of a TTL check, see [`Consul::update_check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Passing,
+    Warning,
+    Critical,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct CheckUpdate<'a> {
+    status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<&'a str>,
+}
+
+/// Entry of the KV store, see [`Consul::kv_get`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct KvPair {
+    pub key: String,
+    /// Base64 encoded, `None` for a folder, see [`KvPair::value_bytes`].
+    pub value: Option<String>,
+    #[serde(default)]
+    pub flags: u64,
+    #[serde(default)]
+    pub create_index: u64,
+    /// Index to pass as `cas` to update the entry only if unchanged.
+    #[serde(default)]
+    pub modify_index: u64,
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+impl KvPair {
+    /// The decoded value, empty for a folder or an invalid encoding.
+    pub fn value_bytes(&self) -> Vec<u8> {
+        self.value
+            .as_deref()
+            .and_then(|value| STANDARD.decode(value).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Consul agent API client.
+pub struct Consul {
+    client: ClientUnix,
+}
+
+impl Consul {
+    pub fn new(client: ClientUnix) -> Self {
+        Consul { client }
+    }
+
+    pub async fn connect(socket_path: &str) -> Result<Self, Error> {
+        Ok(Consul::new(ClientUnix::try_new(socket_path).await?))
+    }
+
+    /// The underlying client, for the endpoints without a wrapper.
+    pub fn client(&mut self) -> &mut ClientUnix {
+        &mut self.client
+    }
+
+    /// Services registered on this agent, by id.
+    pub async fn services(&mut self) -> ConsulResult<HashMap<String, AgentService>> {
+        self.get("/v1/agent/services").await
+    }
+
+    /// Register the service, or update it if its id is already registered.
+    pub async fn register_service(
+        &mut self,
+        registration: &ServiceRegistration,
+    ) -> ConsulResult<()> {
+        self.put("/v1/agent/service/register", Some(registration))
+            .await
+            .map(|_| ())
+    }
+
+    /// Deregister the service and its checks.
+    pub async fn deregister_service(&mut self, id: &str) -> ConsulResult<()> {
+        self.put::<()>(&format!("/v1/agent/service/deregister/{}", id), None)
+            .await
+            .map(|_| ())
+    }
+
+    /// Checks registered on this agent, by id.
+    pub async fn checks(&mut self) -> ConsulResult<HashMap<String, AgentCheck>> {
+        self.get("/v1/agent/checks").await
+    }
+
+    pub async fn register_check(&mut self, check: &CheckDefinition) -> ConsulResult<()> {
+        self.put("/v1/agent/check/register", Some(check))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn deregister_check(&mut self, id: &str) -> ConsulResult<()> {
+        self.put::<()>(&format!("/v1/agent/check/deregister/{}", id), None)
+            .await
+            .map(|_| ())
+    }
+
+    /// Set the status of a TTL check, with `output` shown in its details.
+    pub async fn update_check(
+        &mut self,
+        id: &str,
+        status: CheckStatus,
+        output: Option<&str>,
+    ) -> ConsulResult<()> {
+        self.put(
+            &format!("/v1/agent/check/update/{}", id),
+            Some(&CheckUpdate { status, output }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// The entry of `key`, `None` if it doesn't exist.
+    pub async fn kv_get(&mut self, key: &str) -> ConsulResult<Option<KvPair>> {
+        let pairs: Option<Vec<KvPair>> = self.get_optional(&kv_endpoint(key, "")).await?;
+        Ok(pairs.and_then(|pairs| pairs.into_iter().next()))
+    }
+
+    /// The keys starting with `prefix`, empty if none.
+    pub async fn kv_keys(&mut self, prefix: &str) -> ConsulResult<Vec<String>> {
+        self.get_optional(&kv_endpoint(prefix, "?keys"))
+            .await
+            .map(Option::unwrap_or_default)
+    }
+
+    /// Set the value of `key`, creating it if needed.
+    pub async fn kv_put(&mut self, key: &str, value: &[u8]) -> ConsulResult<bool> {
+        self.kv_write(&kv_endpoint(key, ""), Method::PUT, Some(value))
+            .await
+    }
+
+    /// Set the value of `key` only if its [`KvPair::modify_index`] is still `index`, `0` to
+    /// create it only if it doesn't exist. Return whether the value was set.
+    pub async fn kv_cas(&mut self, key: &str, value: &[u8], index: u64) -> ConsulResult<bool> {
+        self.kv_write(
+            &kv_endpoint(key, &format!("?cas={}", index)),
+            Method::PUT,
+            Some(value),
+        )
+        .await
+    }
+
+    /// Delete `key`, or every key starting with it when `recurse`.
+    pub async fn kv_delete(&mut self, key: &str, recurse: bool) -> ConsulResult<bool> {
+        let query = if recurse { "?recurse" } else { "" };
+        self.kv_write(&kv_endpoint(key, query), Method::DELETE, None)
+            .await
+    }
+
+    async fn kv_write(
+        &mut self,
+        endpoint: &str,
+        method: Method,
+        value: Option<&[u8]>,
+    ) -> ConsulResult<bool> {
+        let body = self
+            .send(
+                endpoint,
+                method,
+                &[],
+                value.map(|value| Body::from(value.to_vec())),
+            )
+            .await?;
+        Ok(body.trim_ascii() == b"true")
+    }
+
+    async fn get<OUT: DeserializeOwned>(&mut self, endpoint: &str) -> ConsulResult<OUT> {
+        let body = self.send(endpoint, Method::GET, &[], None).await?;
+        crate::client::json_from_slice(&body)
+            .map_err(|e| ErrorAndResponseJson::InternalError(Error::ResponseParsing(e, body)))
+    }
+
+    /// Like [`get`](Self::get) but `None` on a `404 Not Found`.
+    async fn get_optional<OUT: DeserializeOwned>(
+        &mut self,
+        endpoint: &str,
+    ) -> ConsulResult<Option<OUT>> {
+        match self.get(endpoint).await {
+            Ok(value) => Ok(Some(value)),
+            Err(ErrorAndResponseJson::ResponseUnsuccessful(StatusCode::NOT_FOUND, _)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn put<IN: Serialize>(
+        &mut self,
+        endpoint: &str,
+        body: Option<&IN>,
+    ) -> ConsulResult<Vec<u8>> {
+        let body = match body {
+            Some(body) => Some(Body::from(serde_json::to_vec(body).map_err(|e| {
+                ErrorAndResponseJson::InternalError(Error::RequestParsing(e))
+            })?)),
+            None => None,
+        };
+        self.send(
+            endpoint,
+            Method::PUT,
+            &[("Content-Type", "application/json")],
+            body,
+        )
+        .await
+    }
+
+    async fn send(
+        &mut self,
+        endpoint: &str,
+        method: Method,
+        headers: &[(&str, &str)],
+        body: Option<Body>,
+    ) -> ConsulResult<Vec<u8>> {
+        match self
+            .client
+            .send_request(endpoint, method, headers, body)
+            .await
+        {
+            Ok((_, body)) => Ok(body),
+            Err(error) => match error.into_response() {
+                Err(e) => Err(ErrorAndResponseJson::InternalError(e)),
+                Ok((status_code, body)) => Err(ErrorAndResponseJson::ResponseUnsuccessful(
+                    status_code,
+                    ConsulError {
+                        message: String::from_utf8_lossy(&body).into_owned(),
+                    },
+                )),
+            },
+        }
+    }
+}
+
+/// `/v1/kv/` followed by the percent-encoded segments of `key`, and `query`.
+fn kv_endpoint(key: &str, query: &str) -> String {
+    let key: Vec<String> = key
+        .trim_start_matches('/')
+        .split('/')
+        .map(crate::query::percent_encode)
+        .collect();
+    format!("/v1/kv/{}{}", key.join("/"), query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_helpers::server::Server, test_helpers::util::*};
+
+    async fn make_consul(test_function: &str) -> (Server, Consul) {
+        let socket_path = make_socket_path_test("consul", test_function);
+        let server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let consul = Consul::connect(&socket_path)
+            .await
+            .expect("Consul::connect");
+        (server, consul)
+    }
+
+    #[tokio::test]
+    async fn register_and_list_services() {
+        let (_server, mut consul) = make_consul("register_and_list_services").await;
+
+        consul
+            .register_service(
+                &ServiceRegistration::new("nolanv")
+                    .port(8080)
+                    .check(CheckDefinition::ttl("nolanv-ttl", "30s")),
+            )
+            .await
+            .expect("consul.register_service");
+        let services = consul.services().await.expect("consul.services");
+        assert_eq!(services["nolanv"].port, 8080);
+
+        let result = consul.register_service(&ServiceRegistration::new("")).await;
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponseJson::ResponseUnsuccessful(StatusCode::BAD_REQUEST, e))
+                if e.message == "Missing service name"
+        ));
+    }
+
+    #[tokio::test]
+    async fn kv_put_and_get() {
+        let (_server, mut consul) = make_consul("kv_put_and_get").await;
+
+        assert!(
+            consul
+                .kv_put("nolanv/config", b"nolanv")
+                .await
+                .expect("consul.kv_put")
+        );
+        let pair = consul
+            .kv_get("nolanv/config")
+            .await
+            .expect("consul.kv_get")
+            .expect("existing key");
+        assert_eq!(pair.key, "nolanv/config");
+        assert_eq!(pair.value_bytes(), b"nolanv");
+
+        assert_eq!(consul.kv_get("missing").await.expect("consul.kv_get"), None);
+    }
+
+    #[test]
+    fn registration_body() {
+        assert_eq!(
+            serde_json::to_string(
+                &ServiceRegistration::new("web").check(CheckDefinition::ttl("web-ttl", "30s"))
+            )
+            .unwrap(),
+            r#"{"Name":"web","Check":{"Name":"web-ttl","TTL":"30s"}}"#
+        );
+        assert_eq!(
+            kv_endpoint("/web/my config", "?keys"),
+            "/v1/kv/web/my%20config?keys"
+        );
+    }
+}
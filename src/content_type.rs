@@ -0,0 +1,125 @@
+use hyper::header::{CONTENT_TYPE, HeaderMap};
+
+/// Check of the `Content-Type` of the responses deserialized by `send_request_json`, see
+/// [`ClientUnixBuilder::json_content_type`](crate::ClientUnixBuilder::json_content_type).
+///
+/// A JSON content type is `application/json` or a `+json` suffix, as `application/problem+json`,
+/// whatever its parameters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContentTypeCheck {
+    /// Deserialize every body, the default.
+    #[default]
+    Any,
+    /// Reject the bodies with a content type which isn't JSON, such as an HTML error page.
+    AllowMissing,
+    /// Also reject the bodies without content type.
+    Required,
+}
+
+impl ContentTypeCheck {
+    /// The content type of `headers` when rejected, empty when missing.
+    pub(crate) fn rejects(self, headers: &HeaderMap) -> Option<String> {
+        let content_type = headers.get(CONTENT_TYPE);
+        let accepted = match (self, content_type) {
+            (ContentTypeCheck::Any, _) => true,
+            (ContentTypeCheck::AllowMissing, None) => true,
+            (ContentTypeCheck::Required, None) => false,
+            (_, Some(content_type)) => content_type.to_str().is_ok_and(is_json),
+        };
+        (!accepted).then(|| {
+            content_type.map_or_else(String::new, |v| {
+                String::from_utf8_lossy(v.as_bytes()).into()
+            })
+        })
+    }
+}
+
+fn is_json(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    let Some((kind, subtype)) = essence.split_once('/') else {
+        return false;
+    };
+    kind.eq_ignore_ascii_case("application")
+        && (subtype.eq_ignore_ascii_case("json")
+            || subtype.len() > 5 && subtype[subtype.len() - 5..].eq_ignore_ascii_case("+json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, Error, ErrorAndResponseJson, Method};
+    use hyper::{StatusCode, header::HeaderValue};
+    use serde_json::Value;
+
+    #[test]
+    fn json_content_types() {
+        let headers = |content_type: Option<&'static str>| {
+            let mut headers = HeaderMap::new();
+            if let Some(content_type) = content_type {
+                headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+            }
+            headers
+        };
+        for (content_type, allow_missing, required) in [
+            (Some("application/json"), true, true),
+            (Some("Application/JSON; charset=utf-8"), true, true),
+            (Some("application/problem+json"), true, true),
+            (Some("text/html"), false, false),
+            (Some("application/jsonp"), false, false),
+            (None, true, false),
+        ] {
+            let headers = headers(content_type);
+            assert!(ContentTypeCheck::Any.rejects(&headers).is_none());
+            assert_eq!(
+                ContentTypeCheck::AllowMissing.rejects(&headers).is_none(),
+                allow_missing,
+                "{:?}",
+                content_type
+            );
+            assert_eq!(
+                ContentTypeCheck::Required.rejects(&headers).is_none(),
+                required,
+                "{:?}",
+                content_type
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn reject_html_error_page() {
+        let server = crate::testing::MockServer::start(axum::Router::new().route(
+            "/containers/json",
+            axum::routing::get(|| async {
+                (
+                    StatusCode::BAD_GATEWAY,
+                    axum::response::Html("<html>502 Bad Gateway</html>"),
+                )
+            }),
+        ))
+        .await
+        .expect("MockServer::start");
+        let mut client = ClientUnix::builder(&server.socket_path().to_string_lossy())
+            .json_content_type(ContentTypeCheck::AllowMissing)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let error = client
+            .send_request_json::<(), Value, Value>("/containers/json", Method::GET, &[], None)
+            .await
+            .expect_err("client.send_request_json");
+        let ErrorAndResponseJson::InternalError(error) = error else {
+            panic!("{:?}", error)
+        };
+        assert!(
+            matches!(
+                error.inner(),
+                Error::UnexpectedContentType(content_type, body)
+                    if content_type == "text/html; charset=utf-8"
+                        && body == b"<html>502 Bad Gateway</html>"
+            ),
+            "{:?}",
+            error
+        );
+    }
+}
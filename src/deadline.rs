@@ -0,0 +1,93 @@
+use hyper::header::HeaderValue;
+use std::time::Duration;
+
+/// Encoding of the remaining time of a request sent to the server, see
+/// [`ClientUnixBuilder::propagate_deadline`](crate::ClientUnixBuilder::propagate_deadline).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadlineFormat {
+    /// Whole milliseconds, as in `X-Request-Timeout-Ms: 1500`.
+    Milliseconds,
+    /// gRPC `<value><unit>` format, as in `grpc-timeout: 1500m`.
+    GrpcTimeout,
+}
+
+impl DeadlineFormat {
+    pub(crate) fn encode(self, remaining: Duration) -> HeaderValue {
+        let millis = remaining.as_millis();
+        match self {
+            DeadlineFormat::Milliseconds => HeaderValue::from(millis as u64),
+            // At most 8 digits per unit
+            DeadlineFormat::GrpcTimeout if millis < 100_000_000 => {
+                HeaderValue::try_from(format!("{}m", millis)).expect("valid header value")
+            }
+            DeadlineFormat::GrpcTimeout => {
+                HeaderValue::try_from(format!("{}S", remaining.as_secs().min(99_999_999)))
+                    .expect("valid header value")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::util::*;
+    use crate::{ClientUnix, ErrorAndResponse, ErrorKind, test_helpers::server::Server};
+    use hyper::{Method, header::HeaderName};
+
+    #[test]
+    fn encode_formats() {
+        let remaining = Duration::from_millis(1500);
+        assert_eq!(DeadlineFormat::Milliseconds.encode(remaining), "1500");
+        assert_eq!(DeadlineFormat::GrpcTimeout.encode(remaining), "1500m");
+        assert_eq!(
+            DeadlineFormat::GrpcTimeout.encode(Duration::from_secs(200_000)),
+            "200000S"
+        );
+    }
+
+    #[tokio::test]
+    async fn remaining_time_is_sent() {
+        let socket_path = make_socket_path_test("deadline", "remaining_time_is_sent");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .request_timeout(Duration::from_secs(10))
+            .propagate_deadline(
+                HeaderName::from_static("x-request-timeout-ms"),
+                DeadlineFormat::Milliseconds,
+            )
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let (_, response) = client
+            .send_request("/header/x-request-timeout-ms", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        let remaining: u64 = String::from_utf8(response).unwrap().parse().unwrap();
+        assert!(remaining > 9_000 && remaining <= 10_000);
+    }
+
+    #[tokio::test]
+    async fn request_times_out() {
+        let socket_path = make_socket_path_test("deadline", "request_times_out");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .request_timeout(Duration::from_millis(50))
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let result = client
+            .send_request("/sleep/1000", Method::GET, &[], None)
+            .await;
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponse::InternalError(e)) if e.kind() == ErrorKind::Timeout
+        ));
+    }
+}
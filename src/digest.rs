@@ -0,0 +1,299 @@
+use hyper::{
+    HeaderMap, Method,
+    header::{HeaderValue, WWW_AUTHENTICATE},
+};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex};
+
+/// HTTP Digest authentication (RFC 7616) state shared by a client and its reconnections, see
+/// [`ClientUnixBuilder::digest_auth`](crate::ClientUnixBuilder::digest_auth).
+///
+/// The last challenge is kept to authenticate the following requests without a round trip.
+#[derive(Clone)]
+pub(crate) struct DigestAuth {
+    user: String,
+    password: String,
+    challenge: Arc<Mutex<Option<Challenge>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Challenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    algorithm: Algorithm,
+    /// Only `auth` is supported, `auth-int` responses are sent without quality of protection.
+    qop_auth: bool,
+    /// Requests sent with `nonce`.
+    nc: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    Md5,
+    Md5Sess,
+    Sha256,
+    Sha256Sess,
+}
+
+impl Algorithm {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_uppercase().as_str() {
+            "MD5" => Some(Algorithm::Md5),
+            "MD5-SESS" => Some(Algorithm::Md5Sess),
+            "SHA-256" => Some(Algorithm::Sha256),
+            "SHA-256-SESS" => Some(Algorithm::Sha256Sess),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "MD5",
+            Algorithm::Md5Sess => "MD5-sess",
+            Algorithm::Sha256 => "SHA-256",
+            Algorithm::Sha256Sess => "SHA-256-sess",
+        }
+    }
+
+    fn hash(self, data: &str) -> String {
+        match self {
+            Algorithm::Md5 | Algorithm::Md5Sess => format!("{:x}", Md5::digest(data)),
+            Algorithm::Sha256 | Algorithm::Sha256Sess => format!("{:x}", Sha256::digest(data)),
+        }
+    }
+}
+
+impl DigestAuth {
+    pub(crate) fn new(user: &str, password: &str) -> Self {
+        DigestAuth {
+            user: user.to_string(),
+            password: password.to_string(),
+            challenge: Arc::default(),
+        }
+    }
+
+    /// Identifies the credentials, the same for the clones.
+    pub(crate) fn id(&self) -> *const () {
+        Arc::as_ptr(&self.challenge).cast()
+    }
+
+    /// `Authorization` answering the last challenge, if any.
+    pub(crate) fn authorization(&self, method: &Method, uri: &str) -> Option<String> {
+        let mut challenge = self.challenge.lock().unwrap_or_else(|e| e.into_inner());
+        let challenge = challenge.as_mut()?;
+        challenge.nc += 1;
+        let cnonce = format!("{:016x}", fastrand::u64(..));
+        Some(self.answer(challenge, method, uri, &cnonce))
+    }
+
+    /// Keep the Digest challenge of an unauthorized response, returns false if there is none.
+    pub(crate) fn update(&self, headers: &HeaderMap) -> bool {
+        let Some(challenge) = headers
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .find_map(parse_challenge)
+        else {
+            return false;
+        };
+        *self.challenge.lock().unwrap_or_else(|e| e.into_inner()) = Some(challenge);
+        true
+    }
+
+    fn answer(&self, challenge: &Challenge, method: &Method, uri: &str, cnonce: &str) -> String {
+        let algorithm = challenge.algorithm;
+        let nc = format!("{:08x}", challenge.nc);
+        let mut ha1 = algorithm.hash(&format!(
+            "{}:{}:{}",
+            self.user, challenge.realm, self.password
+        ));
+        if matches!(algorithm, Algorithm::Md5Sess | Algorithm::Sha256Sess) {
+            ha1 = algorithm.hash(&format!("{}:{}:{}", ha1, challenge.nonce, cnonce));
+        }
+        let ha2 = algorithm.hash(&format!("{}:{}", method, uri));
+        let response = match challenge.qop_auth {
+            true => algorithm.hash(&format!(
+                "{}:{}:{}:{}:auth:{}",
+                ha1, challenge.nonce, nc, cnonce, ha2
+            )),
+            false => algorithm.hash(&format!("{}:{}:{}", ha1, challenge.nonce, ha2)),
+        };
+
+        let mut authorization = format!(
+            r#"Digest username="{}", realm="{}", nonce="{}", uri="{}", algorithm={}, response="{}""#,
+            self.user,
+            challenge.realm,
+            challenge.nonce,
+            uri,
+            algorithm.name(),
+            response
+        );
+        if challenge.qop_auth {
+            authorization.push_str(&format!(r#", qop=auth, nc={}, cnonce="{}""#, nc, cnonce));
+        }
+        if let Some(opaque) = &challenge.opaque {
+            authorization.push_str(&format!(r#", opaque="{}""#, opaque));
+        }
+        authorization
+    }
+}
+
+fn parse_challenge(value: &HeaderValue) -> Option<Challenge> {
+    let value = value.to_str().ok()?;
+    let (scheme, params) = value.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("digest") {
+        return None;
+    }
+
+    let mut challenge = Challenge {
+        realm: String::new(),
+        nonce: String::new(),
+        opaque: None,
+        algorithm: Algorithm::Md5,
+        qop_auth: false,
+        nc: 0,
+    };
+    for (name, value) in parse_params(params) {
+        match name.to_ascii_lowercase().as_str() {
+            "realm" => challenge.realm = value,
+            "nonce" => challenge.nonce = value,
+            "opaque" => challenge.opaque = Some(value),
+            "algorithm" => challenge.algorithm = Algorithm::parse(&value)?,
+            "qop" => challenge.qop_auth = value.split(',').any(|qop| qop.trim() == "auth"),
+            _ => {}
+        }
+    }
+    (!challenge.nonce.is_empty()).then_some(challenge)
+}
+
+/// Split `name=value, name="quoted, value"` parameters.
+fn parse_params(params: &str) -> Vec<(String, String)> {
+    let mut parsed = Vec::new();
+    let mut rest = params.trim();
+    while let Some((name, after)) = rest.split_once('=') {
+        let name = name.trim().trim_start_matches(',').trim().to_string();
+        let after = after.trim_start();
+        let (value, next) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                (
+                    quoted[..end].to_string(),
+                    &quoted[(end + 1).min(quoted.len())..],
+                )
+            }
+            None => {
+                let end = after.find(',').unwrap_or(after.len());
+                (after[..end].trim().to_string(), &after[end..])
+            }
+        };
+        parsed.push((name, value));
+        rest = next;
+    }
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, test_helpers::server::Server, test_helpers::util::*};
+    use hyper::StatusCode;
+
+    #[test]
+    fn rfc_2617_example() {
+        let auth = DigestAuth::new("Mufasa", "Circle Of Life");
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            WWW_AUTHENTICATE,
+            HeaderValue::from_static(
+                r#"Digest realm="testrealm@host.com", qop="auth,auth-int", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093", opaque="5ccc069c403ebaf9f0171e9517f40e41""#,
+            ),
+        );
+        assert!(auth.update(&headers));
+
+        let mut challenge = auth.challenge.lock().unwrap().clone().unwrap();
+        challenge.nc = 1;
+        let authorization = auth.answer(&challenge, &Method::GET, "/dir/index.html", "0a4f113b");
+        assert!(authorization.contains(r#"response="6629fae49393a05397450978507c4ef1""#));
+        assert!(authorization.contains("nc=00000001"));
+        assert!(authorization.contains(r#"opaque="5ccc069c403ebaf9f0171e9517f40e41""#));
+    }
+
+    #[test]
+    fn ignore_other_schemes() {
+        let auth = DigestAuth::new("nolanv", "nolanv");
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            WWW_AUTHENTICATE,
+            HeaderValue::from_static("Basic realm=\"nolanv\""),
+        );
+        assert!(!auth.update(&headers));
+        assert_eq!(auth.authorization(&Method::GET, "/"), None);
+    }
+
+    #[tokio::test]
+    async fn challenge_then_authenticated() {
+        let socket_path = make_socket_path_test("digest", "challenge_then_authenticated");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .digest_auth("nolanv", "nolanv")
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        for _ in 0..2 {
+            let (status_code, response) = client
+                .send_request("/digest/nolanv", Method::GET, &[], None)
+                .await
+                .expect("client.send_request");
+            assert_eq!(status_code, StatusCode::OK);
+            assert_eq!(response, "Hello nolanv".as_bytes());
+        }
+        // The second request reused the challenge of the first one
+        assert_eq!(client.stats().requests, 3);
+    }
+
+    #[tokio::test]
+    async fn answer_encoded_uri() {
+        let server = crate::testing::MockServer::start(axum::Router::new().route(
+            "/digest/{name}",
+            axum::routing::get(|uri: hyper::Uri, headers: HeaderMap| async move {
+                let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or_default();
+                match headers.get(hyper::header::AUTHORIZATION) {
+                    Some(authorization) => {
+                        let authorization = authorization.to_str().unwrap_or_default();
+                        match authorization.contains(&format!("uri=\"{}\"", path)) {
+                            true => (StatusCode::OK, HeaderMap::new()),
+                            false => (StatusCode::BAD_REQUEST, HeaderMap::new()),
+                        }
+                    }
+                    None => {
+                        let mut challenge = HeaderMap::new();
+                        challenge.insert(
+                            WWW_AUTHENTICATE,
+                            HeaderValue::from_static(
+                                "Digest realm=\"nolanv\", qop=\"auth\", nonce=\"nolanv-nonce\"",
+                            ),
+                        );
+                        (StatusCode::UNAUTHORIZED, challenge)
+                    }
+                }
+            }),
+        ))
+        .await
+        .expect("MockServer::start");
+        let mut client = ClientUnix::builder(&server.socket_path().to_string_lossy())
+            .digest_auth("nolanv", "nolanv")
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let (status_code, _) = client
+            .send_request("/digest/nolan v?name=nolan v", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(status_code, StatusCode::OK);
+    }
+}
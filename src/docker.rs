@@ -0,0 +1,786 @@
+//! Typed wrappers of the most used [Docker Engine API](https://docs.docker.com/engine/api/)
+//! endpoints (feature=docker).
+//!
+//! ```rust,no_run
+//! use http_client_unix_domain_socket::docker::{ContainerConfig, Docker};
+//!
+//! # async fn run() {
+//! let mut docker = Docker::connect_default().await.expect("Docker::connect_default");
+//! println!("{}", docker.version().await.expect("docker.version").version);
+//!
+//! let container = docker
+//!     .create_container(Some("nolanv"), &ContainerConfig::new("alpine"))
+//!     .await
+//!     .expect("docker.create_container");
+//! docker.start_container(&container.id).await.expect("docker.start_container");
+//! # }
+//! ```
+
+use crate::{
+    ClientUnix, Error, ErrorAndResponse, ErrorAndResponseJson, Method, client::Options,
+    query::percent_encode,
+};
+use futures_core::Stream;
+use http_body_util::BodyExt;
+use hyper::body::{Body as _, Bytes, Incoming};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    pin::Pin,
+    task::{Context, Poll, ready},
+    time::Duration,
+};
+use tokio::sync::mpsc;
+
+/// Socket of the Docker daemon on Linux.
+pub const DEFAULT_SOCKET_PATH: &str = "/var/run/docker.sock";
+
+/// Body of the Docker error responses.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DockerError {
+    pub message: String,
+}
+
+pub type DockerResult<T> = Result<T, ErrorAndResponseJson<DockerError>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Version {
+    pub version: String,
+    pub api_version: String,
+    #[serde(default)]
+    pub min_api_version: Option<String>,
+    pub os: String,
+    pub arch: String,
+    #[serde(default)]
+    pub kernel_version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Info {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub name: String,
+    pub server_version: String,
+    pub operating_system: String,
+    pub containers: u64,
+    pub containers_running: u64,
+    pub images: u64,
+}
+
+/// Container as listed by [`Docker::containers`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerSummary {
+    pub id: String,
+    #[serde(default)]
+    pub names: Vec<String>,
+    pub image: String,
+    pub state: String,
+    pub status: String,
+    /// Seconds since the epoch.
+    pub created: i64,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+/// Image as listed by [`Docker::images`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ImageSummary {
+    pub id: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub repo_tags: Vec<String>,
+    pub size: i64,
+    /// Seconds since the epoch.
+    pub created: i64,
+}
+
+/// Body of [`Docker::create_container`], unset fields use the image defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerConfig {
+    pub image: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cmd: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entrypoint: Option<Vec<String>>,
+    /// `NAME=value` variables.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, String>,
+}
+
+impl ContainerConfig {
+    pub fn new(image: &str) -> Self {
+        ContainerConfig {
+            image: image.to_string(),
+            ..ContainerConfig::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerCreated {
+    pub id: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub warnings: Vec<String>,
+}
+
+/// Event of [`Docker::events`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Event {
+    /// `container`, `image`, `network`, `volume`, ...
+    #[serde(rename = "Type")]
+    pub event_type: String,
+    /// `create`, `start`, `die`, ...
+    #[serde(rename = "Action")]
+    pub action: String,
+    #[serde(rename = "Actor")]
+    pub actor: EventActor,
+    #[serde(default)]
+    pub scope: String,
+    /// Seconds since the epoch.
+    pub time: i64,
+    /// Nanoseconds since the epoch.
+    #[serde(rename = "timeNano")]
+    pub time_nano: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EventActor {
+    #[serde(rename = "ID")]
+    pub id: String,
+    #[serde(rename = "Attributes", default)]
+    pub attributes: HashMap<String, String>,
+}
+
+fn null_as_default<'de, D: serde::Deserializer<'de>, T: Default + Deserialize<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Docker Engine API client.
+pub struct Docker {
+    client: ClientUnix,
+    /// `/v1.43` to pin the API version, empty for the latest version of the daemon.
+    prefix: String,
+}
+
+impl Docker {
+    pub fn new(client: ClientUnix) -> Self {
+        Docker {
+            client,
+            prefix: String::new(),
+        }
+    }
+
+    /// Connect to [`DEFAULT_SOCKET_PATH`].
+    pub async fn connect_default() -> Result<Self, Error> {
+        Ok(Docker::new(ClientUnix::try_new(DEFAULT_SOCKET_PATH).await?))
+    }
+
+    /// Pin the API version, e.g. `1.43`, instead of using the latest one of the daemon.
+    pub fn api_version(mut self, version: &str) -> Self {
+        self.prefix = format!("/v{}", version.trim_start_matches('v'));
+        self
+    }
+
+    /// The underlying client, for the endpoints without a wrapper.
+    pub fn client(&mut self) -> &mut ClientUnix {
+        &mut self.client
+    }
+
+    pub async fn version(&mut self) -> DockerResult<Version> {
+        self.get("/version").await
+    }
+
+    pub async fn info(&mut self) -> DockerResult<Info> {
+        self.get("/info").await
+    }
+
+    /// Running containers, or every container with `all`.
+    pub async fn containers(&mut self, all: bool) -> DockerResult<Vec<ContainerSummary>> {
+        self.get(&format!("/containers/json?all={}", all)).await
+    }
+
+    pub async fn create_container(
+        &mut self,
+        name: Option<&str>,
+        config: &ContainerConfig,
+    ) -> DockerResult<ContainerCreated> {
+        let endpoint = match name {
+            Some(name) => format!(
+                "{}/containers/create?name={}",
+                self.prefix,
+                percent_encode(name)
+            ),
+            None => format!("{}/containers/create", self.prefix),
+        };
+        self.client
+            .send_request_json(&endpoint, Method::POST, &[], Some(config))
+            .await
+            .map(|(_, created)| created)
+    }
+
+    pub async fn start_container(&mut self, id: &str) -> DockerResult<()> {
+        self.send_empty(
+            &format!("/containers/{}/start", percent_encode(id)),
+            Method::POST,
+        )
+        .await
+    }
+
+    pub async fn stop_container(&mut self, id: &str) -> DockerResult<()> {
+        self.send_empty(
+            &format!("/containers/{}/stop", percent_encode(id)),
+            Method::POST,
+        )
+        .await
+    }
+
+    pub async fn remove_container(&mut self, id: &str) -> DockerResult<()> {
+        self.send_empty(
+            &format!("/containers/{}", percent_encode(id)),
+            Method::DELETE,
+        )
+        .await
+    }
+
+    /// Logs of a container, multiplexed unless it was created with a TTY. With `follow` the stream
+    /// stays open until the container stops.
+    pub async fn logs(&mut self, id: &str, follow: bool) -> DockerResult<Multiplexed> {
+        self.send_streaming(&format!(
+            "/containers/{}/logs?stdout=true&stderr=true&follow={}",
+            percent_encode(id),
+            follow
+        ))
+        .await
+        .map(Multiplexed::new)
+    }
+
+    /// Endless stream of the daemon events matching `filters`, such as
+    /// `{"type": ["container"], "event": ["start", "die"]}`.
+    ///
+    /// The events are read from a dedicated connection. When it is closed, the stream reconnects
+    /// and asks for the events since the last one received, so none is lost or repeated.
+    pub fn events(&self, filters: &HashMap<String, Vec<String>>) -> Events {
+        let (socket_path, options) = self.client.connection_settings();
+        let filters = serde_json::to_string(filters).unwrap_or_default();
+        let state = EventsState {
+            socket_path,
+            options,
+            endpoint: format!(
+                "{}/events?filters={}",
+                self.prefix,
+                percent_encode(&filters)
+            ),
+            last_time_nano: None,
+            connection: None,
+            buffer: Vec::new(),
+            // The first connection doesn't wait
+            received: true,
+        };
+        Events(Box::pin(futures_util::stream::unfold(state, next_event)))
+    }
+
+    pub async fn images(&mut self) -> DockerResult<Vec<ImageSummary>> {
+        self.get("/images/json").await
+    }
+
+    async fn get<OUT: DeserializeOwned>(&mut self, endpoint: &str) -> DockerResult<OUT> {
+        self.client
+            .send_request_json::<(), _, _>(
+                &format!("{}{}", self.prefix, endpoint),
+                Method::GET,
+                &[],
+                None,
+            )
+            .await
+            .map(|(_, response)| response)
+    }
+
+    /// Send a request answered with an empty body, such as `204 No Content`.
+    async fn send_empty(&mut self, endpoint: &str, method: Method) -> DockerResult<()> {
+        self.client
+            .send_request(&format!("{}{}", self.prefix, endpoint), method, &[], None)
+            .await
+            .map(|_| ())
+            .map_err(docker_error)
+    }
+
+    async fn send_streaming(&mut self, endpoint: &str) -> DockerResult<Incoming> {
+        self.client
+            .send_request_streaming(
+                &format!("{}{}", self.prefix, endpoint),
+                Method::GET,
+                &[],
+                None,
+            )
+            .await
+            .map(|response| response.into_body())
+            .map_err(docker_error)
+    }
+}
+
+fn docker_error(error: ErrorAndResponse) -> ErrorAndResponseJson<DockerError> {
+    match error.into_response() {
+        Err(e) => ErrorAndResponseJson::InternalError(e),
+        Ok((status_code, body)) => {
+            let error = serde_json::from_slice(&body).unwrap_or_else(|_| DockerError {
+                message: String::from_utf8_lossy(&body).into_owned(),
+            });
+            ErrorAndResponseJson::ResponseUnsuccessful(status_code, error)
+        }
+    }
+}
+
+/// [`Stream`] of [`Docker::events`].
+pub struct Events(Pin<Box<dyn Stream<Item = DockerResult<Event>> + Send>>);
+
+impl Stream for Events {
+    type Item = DockerResult<Event>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.as_mut().poll_next(cx)
+    }
+}
+
+/// Wait before reconnecting when the previous connection failed or received nothing.
+const EVENTS_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+struct EventsState {
+    socket_path: PathBuf,
+    options: Options,
+    endpoint: String,
+    last_time_nano: Option<i64>,
+    connection: Option<(ClientUnix, Incoming)>,
+    /// Bytes of the incomplete line.
+    buffer: Vec<u8>,
+    /// Whether the current connection received at least one event, or none was opened yet.
+    received: bool,
+}
+
+async fn next_event(mut state: EventsState) -> Option<(DockerResult<Event>, EventsState)> {
+    loop {
+        if let Some(end) = state.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = state.buffer.drain(..=end).collect();
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let event = match crate::client::json_from_slice::<Event>(&line) {
+                Ok(event) => event,
+                Err(e) => {
+                    let e = Error::ResponseParsing(e, line);
+                    return Some((Err(ErrorAndResponseJson::InternalError(e)), state));
+                }
+            };
+            // `since` includes the events of the last one's timestamp
+            if state
+                .last_time_nano
+                .is_some_and(|last| event.time_nano <= last)
+            {
+                continue;
+            }
+            state.last_time_nano = Some(event.time_nano);
+            state.received = true;
+            return Some((Ok(event), state));
+        }
+
+        let Some((_, body)) = &mut state.connection else {
+            if let Err(e) = state.connect().await {
+                return Some((Err(e), state));
+            }
+            continue;
+        };
+        match body.frame().await {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    state.buffer.extend_from_slice(data);
+                }
+            }
+            Some(Err(e)) => {
+                state.disconnect().await;
+                let e = ErrorAndResponseJson::InternalError(Error::ResponseCollect(e));
+                return Some((Err(e), state));
+            }
+            None => state.disconnect().await,
+        }
+    }
+}
+
+impl EventsState {
+    async fn connect(&mut self) -> DockerResult<()> {
+        if !self.received {
+            tokio::time::sleep(EVENTS_RECONNECT_DELAY).await;
+        }
+        self.received = false;
+
+        let mut client = ClientUnix::try_connect(self.socket_path.clone(), self.options.clone())
+            .await
+            .map_err(ErrorAndResponseJson::InternalError)?;
+        let endpoint = match self.last_time_nano {
+            Some(nanos) => format!(
+                "{}&since={}.{:09}",
+                self.endpoint,
+                nanos.div_euclid(1_000_000_000),
+                nanos.rem_euclid(1_000_000_000)
+            ),
+            None => self.endpoint.clone(),
+        };
+        let response = client
+            .send_request_streaming(&endpoint, Method::GET, &[], None)
+            .await
+            .map_err(docker_error)?;
+        self.connection = Some((client, response.into_body()));
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) {
+        self.buffer.clear();
+        if let Some((client, _)) = self.connection.take() {
+            client.abort().await;
+        }
+    }
+}
+
+/// Stream of a [`LogFrame`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamType {
+    Stdin,
+    Stdout,
+    Stderr,
+}
+
+/// Payload of one frame of a multiplexed attach, exec or logs stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFrame {
+    pub stream: StreamType,
+    pub payload: Bytes,
+}
+
+/// Decode the frames of a multiplexed stream: a `[stream, 0, 0, 0, size (u32 big endian)]`
+/// header followed by `size` bytes of payload.
+#[derive(Debug, Default)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// The next complete frame, if every byte of it was pushed.
+    pub fn next_frame(&mut self) -> Result<Option<LogFrame>, Error> {
+        let Some(header) = self.buffer.get(..8) else {
+            return Ok(None);
+        };
+        let stream = match header[0] {
+            0 => StreamType::Stdin,
+            1 => StreamType::Stdout,
+            2 => StreamType::Stderr,
+            _ => return Err(Error::ResponseFraming("unknown stream type")),
+        };
+        let size = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        if self.buffer.len() < 8 + size {
+            return Ok(None);
+        }
+        let payload = Bytes::copy_from_slice(&self.buffer[8..8 + size]);
+        self.buffer.drain(..8 + size);
+        Ok(Some(LogFrame { stream, payload }))
+    }
+
+    /// Whether a frame was only partially pushed.
+    pub fn is_incomplete(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+}
+
+/// Encode `payload` as one frame of `stream`, the inverse of [`FrameDecoder`].
+pub fn encode_frame(stream: StreamType, payload: &[u8]) -> Bytes {
+    let mut frame = Vec::with_capacity(8 + payload.len());
+    frame.push(match stream {
+        StreamType::Stdin => 0,
+        StreamType::Stdout => 1,
+        StreamType::Stderr => 2,
+    });
+    frame.extend_from_slice(&[0, 0, 0]);
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    frame.extend_from_slice(payload);
+    Bytes::from(frame)
+}
+
+/// [`Stream`] of the frames of a multiplexed response body, see [`Docker::logs`].
+pub struct Multiplexed {
+    body: Incoming,
+    decoder: FrameDecoder,
+}
+
+impl Multiplexed {
+    pub fn new(body: Incoming) -> Self {
+        Multiplexed {
+            body,
+            decoder: FrameDecoder::default(),
+        }
+    }
+
+    /// Forward the payloads to separate stdout and stderr streams from a spawned task. A body
+    /// error ends both streams and is given to the stdout one.
+    pub fn split(mut self) -> (OutputStream, OutputStream) {
+        let (stdout, stdout_receiver) = mpsc::channel(16);
+        let (stderr, stderr_receiver) = mpsc::channel(16);
+        tokio::spawn(async move {
+            while let Some(frame) =
+                std::future::poll_fn(|cx| Pin::new(&mut self).poll_next(cx)).await
+            {
+                let sent = match frame {
+                    Ok(LogFrame {
+                        stream: StreamType::Stderr,
+                        payload,
+                    }) => stderr.send(Ok(payload)).await,
+                    Ok(LogFrame { payload, .. }) => stdout.send(Ok(payload)).await,
+                    Err(e) => {
+                        let _ = stdout.send(Err(e)).await;
+                        return;
+                    }
+                };
+                if sent.is_err() && stdout.is_closed() && stderr.is_closed() {
+                    return;
+                }
+            }
+        });
+        (OutputStream(stdout_receiver), OutputStream(stderr_receiver))
+    }
+}
+
+impl Stream for Multiplexed {
+    type Item = Result<LogFrame, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(frame) = self.decoder.next_frame().transpose() {
+                return Poll::Ready(Some(frame));
+            }
+            match ready!(Pin::new(&mut self.body).poll_frame(cx)) {
+                Some(Ok(frame)) => {
+                    if let Some(data) = frame.data_ref() {
+                        self.decoder.push(data);
+                    }
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(Error::ResponseCollect(e)))),
+                None if self.decoder.is_incomplete() => {
+                    self.decoder = FrameDecoder::default();
+                    return Poll::Ready(Some(Err(Error::ResponseFraming("truncated frame"))));
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// Payloads of one of the streams of [`Multiplexed::split`].
+pub struct OutputStream(mpsc::Receiver<Result<Bytes, Error>>);
+
+impl OutputStream {
+    /// Concatenate every payload until the end of the stream.
+    pub async fn collect(mut self) -> Result<Vec<u8>, Error> {
+        let mut output = Vec::new();
+        while let Some(payload) = self.0.recv().await {
+            output.extend_from_slice(&payload?);
+        }
+        Ok(output)
+    }
+}
+
+impl Stream for OutputStream {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StatusCode, test_helpers::server::Server, test_helpers::util::*};
+    use futures_util::StreamExt;
+
+    async fn make_docker(test_function: &str) -> (Server, Docker) {
+        let socket_path = make_socket_path_test("docker", test_function);
+        let server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let client = ClientUnix::try_new(&socket_path)
+            .await
+            .expect("ClientUnix::try_new");
+        (server, Docker::new(client).api_version("1.43"))
+    }
+
+    #[tokio::test]
+    async fn version() {
+        let (_server, mut docker) = make_docker("version").await;
+
+        let version = docker.version().await.expect("docker.version");
+
+        assert_eq!(version.version, "27.0.0-nolanv");
+        assert_eq!(version.api_version, "1.43");
+    }
+
+    #[tokio::test]
+    async fn start_container() {
+        let (_server, mut docker) = make_docker("start_container").await;
+
+        docker
+            .start_container("nolanv")
+            .await
+            .expect("docker.start_container");
+        let result = docker.start_container("missing").await;
+
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponseJson::ResponseUnsuccessful(StatusCode::NOT_FOUND, e))
+                if e.message == "No such container: missing"
+        ));
+    }
+
+    #[tokio::test]
+    async fn encode_ids_and_names() {
+        let targets = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = targets.clone();
+        let record = move |uri: hyper::Uri| {
+            let recorded = recorded.clone();
+            async move {
+                let target = uri
+                    .path_and_query()
+                    .map(|p| p.to_string())
+                    .unwrap_or_default();
+                recorded.lock().unwrap().push(target);
+                axum::Json(serde_json::json!({"Id": "nolanv", "Warnings": null}))
+            }
+        };
+        let server = crate::testing::MockServer::start(
+            axum::Router::new()
+                .route("/containers/create", axum::routing::post(record.clone()))
+                .route(
+                    "/containers/{id}/start",
+                    axum::routing::post(record.clone()),
+                )
+                .route("/containers/{id}", axum::routing::delete(record)),
+        )
+        .await
+        .expect("MockServer::start");
+        let client = ClientUnix::try_new(&server.socket_path().to_string_lossy())
+            .await
+            .expect("ClientUnix::try_new");
+        let mut docker = Docker::new(client);
+
+        docker
+            .create_container(Some("nolanv&force=1"), &ContainerConfig::default())
+            .await
+            .expect("docker.create_container");
+        docker
+            .start_container("../images/nolanv?x")
+            .await
+            .expect("docker.start_container");
+        docker
+            .remove_container("nolanv/../..")
+            .await
+            .expect("docker.remove_container");
+
+        assert_eq!(
+            *targets.lock().unwrap(),
+            vec![
+                "/containers/create?name=nolanv%26force%3D1",
+                "/containers/..%2Fimages%2Fnolanv%3Fx/start",
+                "/containers/nolanv%2F..%2F..",
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_frames() {
+        let mut decoder = FrameDecoder::default();
+        let stdout = encode_frame(StreamType::Stdout, b"hello");
+        decoder.push(&stdout[..6]);
+        assert_eq!(decoder.next_frame().unwrap(), None);
+        decoder.push(&stdout[6..]);
+        decoder.push(&encode_frame(StreamType::Stderr, b"nolanv"));
+
+        assert_eq!(
+            decoder.next_frame().unwrap(),
+            Some(LogFrame {
+                stream: StreamType::Stdout,
+                payload: Bytes::from_static(b"hello")
+            })
+        );
+        assert_eq!(
+            decoder.next_frame().unwrap().map(|frame| frame.stream),
+            Some(StreamType::Stderr)
+        );
+        assert!(!decoder.is_incomplete());
+        decoder.push(&[7, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(decoder.next_frame().is_err());
+    }
+
+    #[tokio::test]
+    async fn split_logs() {
+        let (_server, mut docker) = make_docker("split_logs").await;
+
+        let logs = docker.logs("nolanv", false).await.expect("docker.logs");
+        let (stdout, stderr) = logs.split();
+
+        assert_eq!(stdout.collect().await.expect("stdout"), b"hello nolanv\n");
+        assert_eq!(stderr.collect().await.expect("stderr"), b"warning\n");
+    }
+
+    #[tokio::test]
+    async fn stream_logs() {
+        let (_server, mut docker) = make_docker("stream_logs").await;
+
+        let logs = docker.logs("nolanv", false).await.expect("docker.logs");
+        let frames: Vec<_> = logs.collect().await;
+
+        assert_eq!(frames.len(), 3);
+        assert!(frames.iter().all(Result::is_ok));
+    }
+
+    #[tokio::test]
+    async fn events_resume_since_last() {
+        let (_server, docker) = make_docker("events_resume_since_last").await;
+        let filters = HashMap::from([("type".to_string(), vec!["container".to_string()])]);
+
+        let start = std::time::Instant::now();
+        let mut events = docker.events(&filters);
+        let first = events.next().await.expect("events.next").expect("event");
+        assert!(start.elapsed() < EVENTS_RECONNECT_DELAY);
+        let events: Vec<_> = events
+            .take(2)
+            .map(|event| event.expect("event").action)
+            .collect()
+            .await;
+
+        // The third event is only sent when reconnecting with `since`
+        assert_eq!(first.action, "create");
+        assert_eq!(events, ["start", "die"]);
+    }
+
+    #[test]
+    fn container_config_skips_defaults() {
+        let config = ContainerConfig {
+            cmd: Some(vec!["true".into()]),
+            ..ContainerConfig::new("alpine")
+        };
+
+        assert_eq!(
+            serde_json::to_string(&config).unwrap(),
+            r#"{"Image":"alpine","Cmd":["true"]}"#
+        );
+    }
+}
@@ -0,0 +1,215 @@
+#[cfg(feature = "json")]
+use crate::Error;
+use hyper::{
+    Uri,
+    http::uri::{InvalidUri, PathAndQuery},
+};
+
+/// Endpoint of a request: a `&str` or `String` path and query, a [`PathAndQuery`], or a [`Uri`]
+/// of which only the path and query are sent, its scheme and authority are ignored.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::{ClientUnix, Method, uri::PathAndQuery};
+///
+/// # async fn run(mut client: ClientUnix) {
+/// let ping = PathAndQuery::from_static("/_ping");
+/// client
+///     .send_request(&ping, Method::GET, &[], None)
+///     .await
+///     .expect("client.send_request");
+///
+/// let next: hyper::Uri = "http://localhost/containers/json?all=true".parse().unwrap();
+/// client
+///     .send_request(&next, Method::GET, &[], None)
+///     .await
+///     .expect("client.send_request");
+/// # }
+/// ```
+pub trait Endpoint {
+    /// Path and query of the request.
+    fn as_endpoint(&self) -> &str;
+
+    /// Path and query as sent: a string is percent-encoded first, a [`PathAndQuery`] or [`Uri`]
+    /// is sent as is.
+    fn to_path_and_query(&self) -> Result<PathAndQuery, InvalidUri> {
+        PathAndQuery::try_from(crate::query::encode_endpoint(self.as_endpoint()).as_ref())
+    }
+
+    /// The endpoint with the query parameters of `params` appended (feature=json), a struct or a
+    /// map whose fields are sent sorted by name. A field which is an array of scalars is repeated,
+    /// a nested map or array is sent as JSON, as the `filters` of Docker, and a `None` is skipped.
+    ///
+    /// ```rust,no_run
+    /// use http_client_unix_domain_socket::{ClientUnix, Endpoint, Method};
+    /// use serde::Serialize;
+    /// use std::collections::HashMap;
+    ///
+    /// #[derive(Serialize)]
+    /// struct ListContainers {
+    ///     all: bool,
+    ///     filters: HashMap<&'static str, Vec<&'static str>>,
+    /// }
+    ///
+    /// # async fn run(mut client: ClientUnix) {
+    /// let endpoint = "/containers/json"
+    ///     .query_struct(&ListContainers {
+    ///         all: true,
+    ///         filters: HashMap::from([("label", vec!["env=prod"])]),
+    ///     })
+    ///     .expect("query_struct");
+    /// client
+    ///     .send_request(&endpoint, Method::GET, &[], None)
+    ///     .await
+    ///     .expect("client.send_request");
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    fn query_struct<T: serde::Serialize + ?Sized>(&self, params: &T) -> Result<String, Error> {
+        let query = crate::query::query_string(params).map_err(Error::RequestParsing)?;
+        let endpoint = self.as_endpoint();
+        Ok(match (query.is_empty(), endpoint.contains('?')) {
+            (true, _) => endpoint.to_string(),
+            (false, true) => format!("{}&{}", endpoint, query),
+            (false, false) => format!("{}?{}", endpoint, query),
+        })
+    }
+}
+
+impl Endpoint for str {
+    fn as_endpoint(&self) -> &str {
+        self
+    }
+}
+
+impl Endpoint for String {
+    fn as_endpoint(&self) -> &str {
+        self
+    }
+}
+
+impl Endpoint for PathAndQuery {
+    fn as_endpoint(&self) -> &str {
+        self.as_str()
+    }
+
+    fn to_path_and_query(&self) -> Result<PathAndQuery, InvalidUri> {
+        Ok(self.clone())
+    }
+}
+
+/// `/` when the URI has no path, as `http://localhost`.
+impl Endpoint for Uri {
+    fn as_endpoint(&self) -> &str {
+        self.path_and_query().map_or("/", PathAndQuery::as_str)
+    }
+
+    fn to_path_and_query(&self) -> Result<PathAndQuery, InvalidUri> {
+        Ok(self
+            .path_and_query()
+            .cloned()
+            .unwrap_or_else(|| PathAndQuery::from_static("/")))
+    }
+}
+
+impl<T: Endpoint + ?Sized> Endpoint for &T {
+    fn as_endpoint(&self) -> &str {
+        (**self).as_endpoint()
+    }
+
+    fn to_path_and_query(&self) -> Result<PathAndQuery, InvalidUri> {
+        (**self).to_path_and_query()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, Method, test_helpers::server::Server, test_helpers::util::*};
+
+    #[test]
+    fn path_and_query_of_uris() {
+        for (uri, endpoint) in [
+            ("/nolanv?all=true", "/nolanv?all=true"),
+            ("http://localhost/nolanv?all=true", "/nolanv?all=true"),
+            ("https://example.com", "/"),
+        ] {
+            assert_eq!(
+                uri.parse::<Uri>().expect("Uri::parse").as_endpoint(),
+                endpoint
+            );
+        }
+    }
+
+    #[test]
+    fn typed_endpoints_sent_as_is() {
+        assert_eq!(
+            "/files/100%"
+                .to_path_and_query()
+                .expect("to_path_and_query"),
+            "/files/100%25"
+        );
+        let path = PathAndQuery::from_static("/files/100%");
+        assert_eq!(path.to_path_and_query().expect("to_path_and_query"), path);
+        let uri = Uri::from_static("http://localhost/files/100%?all=true");
+        assert_eq!(
+            uri.to_path_and_query().expect("to_path_and_query"),
+            "/files/100%?all=true"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn append_query_struct() {
+        #[derive(serde::Serialize)]
+        struct Logs {
+            tail: u32,
+            stdout: bool,
+        }
+
+        let logs = Logs {
+            tail: 10,
+            stdout: true,
+        };
+        assert_eq!(
+            "/containers/nolanv/logs"
+                .query_struct(&logs)
+                .expect("query_struct"),
+            "/containers/nolanv/logs?stdout=true&tail=10"
+        );
+        assert_eq!(
+            PathAndQuery::from_static("/containers/nolanv/logs?follow=true")
+                .query_struct(&logs)
+                .expect("query_struct"),
+            "/containers/nolanv/logs?follow=true&stdout=true&tail=10"
+        );
+        assert!(matches!(
+            "/containers/json".query_struct(&"nolanv"),
+            Err(Error::RequestParsing(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn send_typed_endpoints() {
+        let socket_path = make_socket_path_test("endpoint", "send_typed_endpoints");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::try_new(&socket_path)
+            .await
+            .expect("ClientUnix::try_new");
+
+        let path = PathAndQuery::from_static("/nolanv");
+        let (_, body) = client
+            .send_request(&path, Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"Hello nolanv");
+
+        let uri = Uri::from_static("http://localhost/nolanv");
+        let (_, body) = client
+            .send_request(uri, Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"Hello nolanv");
+    }
+}
@@ -1,25 +1,480 @@
-use hyper::StatusCode;
+#[cfg(feature = "json")]
+use crate::ProblemDetails;
+use hyper::{Method, StatusCode};
 #[cfg(feature = "json")]
 use serde::de::DeserializeOwned;
+use std::{fmt, path::PathBuf, time::Duration};
 
 #[derive(Debug)]
 pub enum Error {
     SocketConnectionInitiation(std::io::Error),
     SocketConnectionClosed(Option<hyper::Error>),
+    /// The task driving the connection panicked, with the message of the panic.
+    ConnectionTaskPanicked(String),
+    /// [`ClientUnix::global`](crate::ClientUnix::global) was called before
+    /// [`ClientUnixBuilder::set_global`](crate::ClientUnixBuilder::set_global).
+    GlobalNotConfigured,
     Handhsake(hyper::Error),
     RequestSend(hyper::Error),
     RequestBuild(hyper::http::Error),
+    /// The endpoint can't be the target of a request, for the given reason.
+    InvalidEndpoint(&'static str),
+    /// The request body can't be read to be buffered.
+    RequestBody(axum_core::Error),
     #[cfg(feature = "json")]
     RequestParsing(serde_json::Error),
     ResponseCollect(hyper::Error),
+    /// The response body doesn't follow the expected framing.
+    ResponseFraming(&'static str),
     #[cfg(feature = "json")]
     ResponseParsing(serde_json::Error, Vec<u8>),
+    /// The response to deserialize isn't JSON, with its content type, empty when missing, and
+    /// its body.
+    #[cfg(feature = "json")]
+    UnexpectedContentType(String, Vec<u8>),
+    #[cfg(feature = "prometheus")]
+    MetricsParsing(crate::prometheus::ParseError),
+    /// The websocket handshake of an LXD operation failed.
+    #[cfg(feature = "lxd")]
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+    /// The response body can't be spooled to a temporary file.
+    #[cfg(feature = "spool")]
+    Spool(std::io::Error),
+    /// The response body can't be written to the sink.
+    ResponseWrite(std::io::Error),
+    /// An interrupted download can't be resumed, for the given reason.
+    ResumeRejected(&'static str),
+    /// The response body doesn't match its digest header, with the algorithm of the digest.
+    #[cfg(feature = "integrity")]
+    DigestMismatch(&'static str),
+    Interceptor(Box<dyn std::error::Error + Send + Sync>),
+    /// The request didn't complete within the configured timeout.
+    Timeout(Duration),
+    /// The socket didn't accept the connection within the configured timeout, the server is
+    /// likely missing or overloaded.
+    ConnectTimeout(Duration),
+    /// The server accepted the connection but didn't answer its first request within the
+    /// configured timeout, it is likely wedged.
+    HandshakeTimeout(Duration),
+    /// The inner error, with the connection and request which failed.
+    WithContext(Box<ErrorContext>, Box<Error>),
+}
+
+/// Connection and request of an [`Error::WithContext`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorContext {
+    pub socket_path: PathBuf,
+    pub method: Option<Method>,
+    /// Path and query of the request.
+    pub endpoint: Option<String>,
+    /// See [`RequestId`](crate::RequestId).
+    pub request_id: Option<String>,
+}
+
+/// Stable classification of an [`Error`], for programmatic matching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The socket file doesn't exist.
+    SocketNotFound,
+    /// Nothing is listening on the socket.
+    ConnectionRefused,
+    PermissionDenied,
+    Timeout,
+    /// The connection was closed or the request canceled.
+    ConnectionClosed,
+    /// The connection was closed while the response body was read, after its head was received.
+    /// The request reached the server, so it isn't retried.
+    ResponseInterrupted,
+    /// Any other error while connecting to the socket.
+    Connect,
+    /// The server doesn't speak valid HTTP/1.
+    Protocol,
+    /// The request can't be built or sent as is (invalid header, endpoint, ...), or the global
+    /// client isn't configured.
+    InvalidRequest,
+    /// A JSON body can't be serialized or deserialized, or a metrics body parsed.
+    Serialization,
+    /// An [`Interceptor`](crate::Interceptor) aborted the request.
+    Interceptor,
+    /// The response status code is not successful.
+    UnsuccessfulResponse,
+    /// A local file can't be written or read.
+    Io,
+}
+
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::SocketConnectionInitiation(e) => match e.kind() {
+                std::io::ErrorKind::NotFound => ErrorKind::SocketNotFound,
+                std::io::ErrorKind::ConnectionRefused => ErrorKind::ConnectionRefused,
+                std::io::ErrorKind::PermissionDenied => ErrorKind::PermissionDenied,
+                std::io::ErrorKind::TimedOut => ErrorKind::Timeout,
+                _ => ErrorKind::Connect,
+            },
+            Error::SocketConnectionClosed(None) | Error::ConnectionTaskPanicked(_) => {
+                ErrorKind::ConnectionClosed
+            }
+            Error::SocketConnectionClosed(Some(e))
+            | Error::Handhsake(e)
+            | Error::RequestSend(e) => hyper_error_kind(e),
+            Error::ResponseCollect(e) => response_collect_kind(e),
+            Error::GlobalNotConfigured
+            | Error::RequestBuild(_)
+            | Error::InvalidEndpoint(_)
+            | Error::RequestBody(_) => ErrorKind::InvalidRequest,
+            Error::ResponseFraming(_) => ErrorKind::Protocol,
+            #[cfg(feature = "json")]
+            Error::RequestParsing(_) | Error::ResponseParsing(_, _) => ErrorKind::Serialization,
+            #[cfg(feature = "json")]
+            Error::UnexpectedContentType(_, _) => ErrorKind::Serialization,
+            #[cfg(feature = "prometheus")]
+            Error::MetricsParsing(_) => ErrorKind::Serialization,
+            #[cfg(feature = "lxd")]
+            Error::WebSocket(_) => ErrorKind::Protocol,
+            #[cfg(feature = "spool")]
+            Error::Spool(_) => ErrorKind::Io,
+            Error::ResponseWrite(_) => ErrorKind::Io,
+            Error::ResumeRejected(_) => ErrorKind::Protocol,
+            #[cfg(feature = "integrity")]
+            Error::DigestMismatch(_) => ErrorKind::Protocol,
+            Error::Interceptor(_) => ErrorKind::Interceptor,
+            Error::Timeout(_) | Error::ConnectTimeout(_) | Error::HandshakeTimeout(_) => {
+                ErrorKind::Timeout
+            }
+            Error::WithContext(_, e) => e.kind(),
+        }
+    }
+
+    /// The error without its [`ErrorContext`].
+    pub fn inner(&self) -> &Error {
+        match self {
+            Error::WithContext(_, e) => e.inner(),
+            e => e,
+        }
+    }
+
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::WithContext(context, _) => Some(context),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn with_context(self, context: ErrorContext) -> Error {
+        match self {
+            Error::WithContext(_, _) => self,
+            e => Error::WithContext(Box::new(context), Box::new(e)),
+        }
+    }
+}
+
+/// A body cut short by the connection, as opposed to an invalid one, is interrupted.
+fn response_collect_kind(e: &hyper::Error) -> ErrorKind {
+    let io_error = std::error::Error::source(e).and_then(|e| e.downcast_ref::<std::io::Error>());
+    match hyper_error_kind(e) {
+        ErrorKind::ConnectionClosed => ErrorKind::ResponseInterrupted,
+        ErrorKind::Protocol
+            if io_error.is_some_and(|e| e.kind() != std::io::ErrorKind::InvalidData) =>
+        {
+            ErrorKind::ResponseInterrupted
+        }
+        kind => kind,
+    }
+}
+
+fn hyper_error_kind(e: &hyper::Error) -> ErrorKind {
+    if e.is_timeout() {
+        ErrorKind::Timeout
+    } else if e.is_canceled() || e.is_closed() || e.is_incomplete_message() {
+        ErrorKind::ConnectionClosed
+    } else if e.is_user() {
+        ErrorKind::InvalidRequest
+    } else {
+        ErrorKind::Protocol
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::SocketConnectionInitiation(_) => write!(f, "failed to connect to the socket"),
+            Error::SocketConnectionClosed(_) => write!(f, "connection closed"),
+            Error::ConnectionTaskPanicked(message) => {
+                write!(f, "connection task panicked: {}", message)
+            }
+            Error::GlobalNotConfigured => write!(f, "global client not configured"),
+            Error::Handhsake(_) => write!(f, "HTTP/1 handshake failed"),
+            Error::RequestSend(_) => write!(f, "failed to send the request"),
+            Error::RequestBuild(_) => write!(f, "failed to build the request"),
+            Error::InvalidEndpoint(reason) => write!(f, "invalid endpoint: {}", reason),
+            Error::RequestBody(_) => write!(f, "failed to read the request body"),
+            #[cfg(feature = "json")]
+            Error::RequestParsing(_) => write!(f, "failed to serialize the request body"),
+            Error::ResponseCollect(_) => write!(f, "failed to read the response body"),
+            Error::ResponseFraming(reason) => write!(f, "invalid response body: {}", reason),
+            #[cfg(feature = "json")]
+            Error::ResponseParsing(_, _) => write!(f, "failed to deserialize the response body"),
+            #[cfg(feature = "json")]
+            Error::UnexpectedContentType(content_type, _) if content_type.is_empty() => {
+                write!(f, "response without content type, expected JSON")
+            }
+            #[cfg(feature = "json")]
+            Error::UnexpectedContentType(content_type, _) => {
+                write!(f, "unexpected response content type `{}`", content_type)
+            }
+            #[cfg(feature = "prometheus")]
+            Error::MetricsParsing(_) => write!(f, "failed to parse the Prometheus metrics"),
+            #[cfg(feature = "lxd")]
+            Error::WebSocket(_) => write!(f, "websocket handshake failed"),
+            #[cfg(feature = "spool")]
+            Error::Spool(_) => write!(f, "failed to spool the response body to a file"),
+            Error::ResponseWrite(_) => write!(f, "failed to write the response body"),
+            Error::ResumeRejected(reason) => write!(f, "failed to resume the download: {}", reason),
+            #[cfg(feature = "integrity")]
+            Error::DigestMismatch(algorithm) => {
+                write!(f, "response body doesn't match its {} digest", algorithm)
+            }
+            Error::Interceptor(_) => write!(f, "request aborted by an interceptor"),
+            Error::Timeout(timeout) => write!(f, "request timed out after {:?}", timeout),
+            Error::ConnectTimeout(timeout) => {
+                write!(f, "connection not accepted within {:?}", timeout)
+            }
+            Error::HandshakeTimeout(timeout) => {
+                write!(
+                    f,
+                    "connection accepted but not answered within {:?}",
+                    timeout
+                )
+            }
+            Error::WithContext(context, e) => write!(f, "{}: {}", context, e),
+        }
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(method) = &self.method {
+            write!(f, "{} ", method)?;
+        }
+        if let Some(endpoint) = &self.endpoint {
+            write!(f, "{} ", endpoint)?;
+        }
+        write!(f, "on {}", self.socket_path.display())?;
+        if let Some(request_id) = &self.request_id {
+            write!(f, " (request id {})", request_id)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::SocketConnectionInitiation(e) => Some(e),
+            Error::SocketConnectionClosed(e) => e.as_ref().map(|e| e as _),
+            Error::Handhsake(e) | Error::RequestSend(e) | Error::ResponseCollect(e) => Some(e),
+            Error::RequestBuild(e) => Some(e),
+            Error::RequestBody(e) => Some(e),
+            Error::ConnectionTaskPanicked(_)
+            | Error::GlobalNotConfigured
+            | Error::InvalidEndpoint(_)
+            | Error::ResponseFraming(_) => None,
+            #[cfg(feature = "json")]
+            Error::RequestParsing(e) | Error::ResponseParsing(e, _) => Some(e),
+            #[cfg(feature = "json")]
+            Error::UnexpectedContentType(_, _) => None,
+            #[cfg(feature = "prometheus")]
+            Error::MetricsParsing(e) => Some(e),
+            #[cfg(feature = "lxd")]
+            Error::WebSocket(e) => Some(e.as_ref()),
+            #[cfg(feature = "spool")]
+            Error::Spool(e) => Some(e),
+            Error::ResponseWrite(e) => Some(e),
+            Error::ResumeRejected(_) => None,
+            #[cfg(feature = "integrity")]
+            Error::DigestMismatch(_) => None,
+            Error::Interceptor(e) => Some(e.as_ref()),
+            Error::Timeout(_) | Error::ConnectTimeout(_) | Error::HandshakeTimeout(_) => None,
+            // The inner error is already part of the message
+            Error::WithContext(_, e) => e.source(),
+        }
+    }
+}
+
+/// The matching [`std::io::ErrorKind`], `Other` when there is none.
+impl From<ErrorKind> for std::io::ErrorKind {
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::SocketNotFound => std::io::ErrorKind::NotFound,
+            ErrorKind::ConnectionRefused => std::io::ErrorKind::ConnectionRefused,
+            ErrorKind::PermissionDenied => std::io::ErrorKind::PermissionDenied,
+            ErrorKind::Timeout => std::io::ErrorKind::TimedOut,
+            ErrorKind::ConnectionClosed => std::io::ErrorKind::ConnectionAborted,
+            ErrorKind::ResponseInterrupted => std::io::ErrorKind::UnexpectedEof,
+            ErrorKind::Protocol | ErrorKind::Serialization => std::io::ErrorKind::InvalidData,
+            ErrorKind::InvalidRequest => std::io::ErrorKind::InvalidInput,
+            ErrorKind::Connect
+            | ErrorKind::Interceptor
+            | ErrorKind::UnsuccessfulResponse
+            | ErrorKind::Io => std::io::ErrorKind::Other,
+        }
+    }
+}
+
+/// Keeps the kind of the underlying I/O error if any, and the error as the inner one.
+impl From<Error> for std::io::Error {
+    fn from(error: Error) -> Self {
+        let kind = match error.inner() {
+            Error::SocketConnectionInitiation(e) => e.kind(),
+            #[cfg(feature = "spool")]
+            Error::Spool(e) => e.kind(),
+            Error::ResponseWrite(e) => e.kind(),
+            e => e.kind().into(),
+        };
+        std::io::Error::new(kind, error)
+    }
 }
 
 #[derive(Debug)]
 pub enum ErrorAndResponse {
     InternalError(Error),
     ResponseUnsuccessful(StatusCode, Vec<u8>),
+    /// Unsuccessful response whose body was deserialized into the type registered with
+    /// [`ClientUnixBuilder::api_error`](crate::ClientUnixBuilder::api_error). The bodies which
+    /// can't be are returned as [`ResponseUnsuccessful`](Self::ResponseUnsuccessful).
+    #[cfg(feature = "json")]
+    Api(StatusCode, ApiError),
+}
+
+impl ErrorAndResponse {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ErrorAndResponse::InternalError(e) => e.kind(),
+            ErrorAndResponse::ResponseUnsuccessful(_, _) => ErrorKind::UnsuccessfulResponse,
+            #[cfg(feature = "json")]
+            ErrorAndResponse::Api(_, _) => ErrorKind::UnsuccessfulResponse,
+        }
+    }
+
+    /// Status code of the unsuccessful response, `None` for an internal error.
+    pub fn status_code(&self) -> Option<StatusCode> {
+        match self {
+            ErrorAndResponse::InternalError(_) => None,
+            ErrorAndResponse::ResponseUnsuccessful(status_code, _) => Some(*status_code),
+            #[cfg(feature = "json")]
+            ErrorAndResponse::Api(status_code, _) => Some(*status_code),
+        }
+    }
+
+    /// Raw body of the unsuccessful response, `None` for an internal error.
+    pub fn body(&self) -> Option<&[u8]> {
+        match self {
+            ErrorAndResponse::InternalError(_) => None,
+            ErrorAndResponse::ResponseUnsuccessful(_, body) => Some(body),
+            #[cfg(feature = "json")]
+            ErrorAndResponse::Api(_, error) => Some(error.body()),
+        }
+    }
+
+    /// Status code and raw body of the unsuccessful response, or the internal error.
+    pub fn into_response(self) -> Result<(StatusCode, Vec<u8>), Error> {
+        match self {
+            ErrorAndResponse::InternalError(e) => Err(e),
+            ErrorAndResponse::ResponseUnsuccessful(status_code, body) => Ok((status_code, body)),
+            #[cfg(feature = "json")]
+            ErrorAndResponse::Api(status_code, error) => Ok((status_code, error.body)),
+        }
+    }
+
+    /// The body of the unsuccessful response deserialized into `E`, for the requests whose error
+    /// type differs from the one of the client.
+    #[cfg(feature = "json")]
+    pub fn api<E: DeserializeOwned>(&self) -> Option<E> {
+        crate::client::json_from_slice(self.body()?).ok()
+    }
+}
+
+impl fmt::Display for ErrorAndResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorAndResponse::InternalError(e) => write!(f, "{}", e),
+            ErrorAndResponse::ResponseUnsuccessful(status_code, _) => {
+                write!(f, "unsuccessful response: {}", status_code)
+            }
+            #[cfg(feature = "json")]
+            ErrorAndResponse::Api(status_code, error) => {
+                write!(
+                    f,
+                    "unsuccessful response: {}: {:?}",
+                    status_code, error.value
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ErrorAndResponse {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ErrorAndResponse::InternalError(e) => std::error::Error::source(e),
+            _ => None,
+        }
+    }
+}
+
+/// Value of an [`ErrorAndResponse::Api`], get it back with [`downcast_ref`](Self::downcast_ref)
+/// or [`downcast`](Self::downcast) with the registered type.
+#[cfg(feature = "json")]
+#[derive(Debug)]
+pub struct ApiError {
+    value: Box<dyn ApiValue>,
+    body: Vec<u8>,
+}
+
+#[cfg(feature = "json")]
+pub(crate) trait ApiValue: std::any::Any + fmt::Debug + Send + Sync {}
+
+#[cfg(feature = "json")]
+impl<T: std::any::Any + fmt::Debug + Send + Sync> ApiValue for T {}
+
+#[cfg(feature = "json")]
+impl ApiError {
+    pub(crate) fn new(value: Box<dyn ApiValue>, body: Vec<u8>) -> Self {
+        ApiError { value, body }
+    }
+
+    pub fn downcast_ref<E: 'static>(&self) -> Option<&E> {
+        (&*self.value as &dyn std::any::Any).downcast_ref()
+    }
+
+    /// The value, or the error back when it isn't an `E`.
+    pub fn downcast<E: 'static>(self) -> Result<E, Self> {
+        if !self.is::<E>() {
+            return Err(self);
+        }
+        let value: Box<dyn std::any::Any> = self.value;
+        Ok(*value.downcast().expect("checked by is"))
+    }
+
+    pub fn is<E: 'static>(&self) -> bool {
+        self.downcast_ref::<E>().is_some()
+    }
+
+    /// The raw body of the response.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+}
+
+impl From<ErrorAndResponse> for std::io::Error {
+    fn from(error: ErrorAndResponse) -> Self {
+        match error {
+            ErrorAndResponse::InternalError(e) => e.into(),
+            e => std::io::Error::other(e),
+        }
+    }
 }
 
 #[cfg(feature = "json")]
@@ -27,4 +482,108 @@ pub enum ErrorAndResponse {
 pub enum ErrorAndResponseJson<ERR: DeserializeOwned> {
     InternalError(Error),
     ResponseUnsuccessful(StatusCode, ERR),
+    /// Unsuccessful response with a `Content-Type: application/problem+json` body (RFC 7807).
+    ResponseProblem(StatusCode, ProblemDetails),
+}
+
+#[cfg(feature = "json")]
+impl<ERR: DeserializeOwned> ErrorAndResponseJson<ERR> {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ErrorAndResponseJson::InternalError(e) => e.kind(),
+            ErrorAndResponseJson::ResponseUnsuccessful(_, _)
+            | ErrorAndResponseJson::ResponseProblem(_, _) => ErrorKind::UnsuccessfulResponse,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl<ERR: DeserializeOwned> fmt::Display for ErrorAndResponseJson<ERR> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorAndResponseJson::InternalError(e) => write!(f, "{}", e),
+            ErrorAndResponseJson::ResponseUnsuccessful(status_code, _) => {
+                write!(f, "unsuccessful response: {}", status_code)
+            }
+            ErrorAndResponseJson::ResponseProblem(status_code, problem) => {
+                write!(f, "unsuccessful response: {}", status_code)?;
+                if let Some(title) = &problem.title {
+                    write!(f, ": {}", title)?;
+                }
+                if let Some(detail) = &problem.detail {
+                    write!(f, ": {}", detail)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl<ERR: DeserializeOwned + fmt::Debug> std::error::Error for ErrorAndResponseJson<ERR> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ErrorAndResponseJson::InternalError(e) => std::error::Error::source(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn error_kind_and_display() {
+        let error = Error::SocketConnectionInitiation(std::io::Error::from(
+            std::io::ErrorKind::ConnectionRefused,
+        ))
+        .with_context(ErrorContext {
+            socket_path: "/tmp/nolanv.socket".into(),
+            method: Some(Method::GET),
+            endpoint: Some("/nolanv".into()),
+            request_id: None,
+        });
+
+        assert_eq!(error.kind(), ErrorKind::ConnectionRefused);
+        assert!(matches!(
+            error.inner(),
+            Error::SocketConnectionInitiation(_)
+        ));
+        assert_eq!(
+            error.to_string(),
+            "GET /nolanv on /tmp/nolanv.socket: failed to connect to the socket"
+        );
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn unsuccessful_response_kind() {
+        let error = ErrorAndResponse::ResponseUnsuccessful(StatusCode::NOT_FOUND, Vec::new());
+
+        assert_eq!(error.kind(), ErrorKind::UnsuccessfulResponse);
+        assert_eq!(error.to_string(), "unsuccessful response: 404 Not Found");
+    }
+
+    #[test]
+    fn into_io_error() {
+        let error: std::io::Error = Error::SocketConnectionInitiation(std::io::Error::from(
+            std::io::ErrorKind::AddrNotAvailable,
+        ))
+        .into();
+        assert_eq!(error.kind(), std::io::ErrorKind::AddrNotAvailable);
+
+        let error: std::io::Error = Error::InvalidEndpoint("must start with `/`").into();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "invalid endpoint: must start with `/`");
+        assert!(matches!(
+            error.into_inner().map(|e| e.downcast::<Error>()),
+            Some(Ok(e)) if matches!(*e, Error::InvalidEndpoint(_))
+        ));
+
+        let error: std::io::Error =
+            ErrorAndResponse::ResponseUnsuccessful(StatusCode::NOT_FOUND, Vec::new()).into();
+        assert_eq!(error.kind(), std::io::ErrorKind::Other);
+    }
 }
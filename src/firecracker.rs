@@ -0,0 +1,285 @@
+//! Typed wrappers of the [Firecracker API](https://github.com/firecracker-microvm/firecracker/blob/main/src/firecracker/swagger/firecracker.yaml)
+//! (feature=firecracker).
+//!
+//! The VMM rejects unknown fields and `null` values, so the unset optional fields are left out of
+//! the bodies, and the configuration endpoints answer `204 No Content`.
+//!
+//! ```rust,no_run
+//! use http_client_unix_domain_socket::firecracker::{
+//!     Action, BootSource, Drive, Firecracker, MachineConfig,
+//! };
+//!
+//! # async fn run() {
+//! let mut vm = Firecracker::connect("/tmp/firecracker.socket")
+//!     .await
+//!     .expect("Firecracker::connect");
+//! vm.put_machine_config(&MachineConfig::new(2, 1024))
+//!     .await
+//!     .expect("vm.put_machine_config");
+//! vm.put_boot_source(&BootSource::new("/srv/vmlinux").boot_args("console=ttyS0"))
+//!     .await
+//!     .expect("vm.put_boot_source");
+//! vm.put_drive(&Drive::root("rootfs", "/srv/rootfs.ext4"))
+//!     .await
+//!     .expect("vm.put_drive");
+//! vm.action(Action::InstanceStart).await.expect("vm.action");
+//! # }
+//! ```
+
+use crate::{ClientUnix, Error, ErrorAndResponseJson, Method};
+use axum_core::body::Body;
+use serde::{Deserialize, Serialize};
+
+/// Body of the Firecracker error responses.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FirecrackerError {
+    pub fault_message: String,
+}
+
+pub type FirecrackerResult<T> = Result<T, ErrorAndResponseJson<FirecrackerError>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct InstanceInfo {
+    pub id: String,
+    /// `Not started`, `Running` or `Paused`.
+    pub state: String,
+    pub vmm_version: String,
+    #[serde(default)]
+    pub app_name: String,
+}
+
+/// Body of [`Firecracker::put_machine_config`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MachineConfig {
+    pub vcpu_count: u8,
+    pub mem_size_mib: u32,
+    /// Simultaneous multithreading, x86_64 only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smt: Option<bool>,
+    /// Required by the diff snapshots.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track_dirty_pages: Option<bool>,
+}
+
+impl MachineConfig {
+    pub fn new(vcpu_count: u8, mem_size_mib: u32) -> Self {
+        MachineConfig {
+            vcpu_count,
+            mem_size_mib,
+            smt: None,
+            track_dirty_pages: None,
+        }
+    }
+}
+
+/// Body of [`Firecracker::put_boot_source`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BootSource {
+    pub kernel_image_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub boot_args: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initrd_path: Option<String>,
+}
+
+impl BootSource {
+    pub fn new(kernel_image_path: &str) -> Self {
+        BootSource {
+            kernel_image_path: kernel_image_path.to_string(),
+            boot_args: None,
+            initrd_path: None,
+        }
+    }
+
+    pub fn boot_args(mut self, boot_args: &str) -> Self {
+        self.boot_args = Some(boot_args.to_string());
+        self
+    }
+}
+
+/// Body of [`Firecracker::put_drive`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Drive {
+    pub drive_id: String,
+    pub path_on_host: String,
+    pub is_root_device: bool,
+    pub is_read_only: bool,
+}
+
+impl Drive {
+    pub fn new(drive_id: &str, path_on_host: &str) -> Self {
+        Drive {
+            drive_id: drive_id.to_string(),
+            path_on_host: path_on_host.to_string(),
+            is_root_device: false,
+            is_read_only: false,
+        }
+    }
+
+    /// Drive of the root filesystem.
+    pub fn root(drive_id: &str, path_on_host: &str) -> Self {
+        Drive {
+            is_root_device: true,
+            ..Drive::new(drive_id, path_on_host)
+        }
+    }
+}
+
+/// Body of [`Firecracker::put_network_interface`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NetworkInterface {
+    pub iface_id: String,
+    /// Tap device on the host.
+    pub host_dev_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guest_mac: Option<String>,
+}
+
+/// Action of [`Firecracker::action`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Action {
+    InstanceStart,
+    SendCtrlAltDel,
+    FlushMetrics,
+}
+
+#[derive(Serialize)]
+struct ActionBody {
+    action_type: Action,
+}
+
+/// Firecracker API client, on the `--api-sock` of a VMM.
+pub struct Firecracker {
+    client: ClientUnix,
+}
+
+impl Firecracker {
+    pub fn new(client: ClientUnix) -> Self {
+        Firecracker { client }
+    }
+
+    pub async fn connect(socket_path: &str) -> Result<Self, Error> {
+        Ok(Firecracker::new(ClientUnix::try_new(socket_path).await?))
+    }
+
+    /// The underlying client, for the endpoints without a wrapper.
+    pub fn client(&mut self) -> &mut ClientUnix {
+        &mut self.client
+    }
+
+    pub async fn instance_info(&mut self) -> FirecrackerResult<InstanceInfo> {
+        self.client
+            .send_request_json::<(), _, _>("/", Method::GET, &[], None)
+            .await
+            .map(|(_, info)| info)
+    }
+
+    pub async fn put_machine_config(&mut self, config: &MachineConfig) -> FirecrackerResult<()> {
+        self.put("/machine-config", config).await
+    }
+
+    pub async fn put_boot_source(&mut self, boot_source: &BootSource) -> FirecrackerResult<()> {
+        self.put("/boot-source", boot_source).await
+    }
+
+    pub async fn put_drive(&mut self, drive: &Drive) -> FirecrackerResult<()> {
+        self.put(&format!("/drives/{}", drive.drive_id), drive)
+            .await
+    }
+
+    pub async fn put_network_interface(
+        &mut self,
+        interface: &NetworkInterface,
+    ) -> FirecrackerResult<()> {
+        self.put(
+            &format!("/network-interfaces/{}", interface.iface_id),
+            interface,
+        )
+        .await
+    }
+
+    pub async fn action(&mut self, action: Action) -> FirecrackerResult<()> {
+        self.put(
+            "/actions",
+            &ActionBody {
+                action_type: action,
+            },
+        )
+        .await
+    }
+
+    /// Send a JSON body and expect the `204 No Content` of the configuration endpoints.
+    async fn put<IN: Serialize>(&mut self, endpoint: &str, body: &IN) -> FirecrackerResult<()> {
+        let body = serde_json::to_vec(body)
+            .map_err(|e| ErrorAndResponseJson::InternalError(Error::RequestParsing(e)))?;
+        match self
+            .client
+            .send_request(
+                endpoint,
+                Method::PUT,
+                &[("Content-Type", "application/json")],
+                Some(Body::from(body)),
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(error) => match error.into_response() {
+                Err(e) => Err(ErrorAndResponseJson::InternalError(e)),
+                Ok((status_code, body)) => {
+                    let error =
+                        serde_json::from_slice(&body).unwrap_or_else(|_| FirecrackerError {
+                            fault_message: String::from_utf8_lossy(&body).into_owned(),
+                        });
+                    Err(ErrorAndResponseJson::ResponseUnsuccessful(
+                        status_code,
+                        error,
+                    ))
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StatusCode, test_helpers::server::Server, test_helpers::util::*};
+
+    #[tokio::test]
+    async fn configure_and_start() {
+        let socket_path = make_socket_path_test("firecracker", "configure_and_start");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut vm = Firecracker::connect(&socket_path)
+            .await
+            .expect("Firecracker::connect");
+
+        vm.put_machine_config(&MachineConfig::new(2, 1024))
+            .await
+            .expect("vm.put_machine_config");
+        vm.action(Action::InstanceStart).await.expect("vm.action");
+
+        let result = vm.put_machine_config(&MachineConfig::new(0, 1024)).await;
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponseJson::ResponseUnsuccessful(StatusCode::BAD_REQUEST, e))
+                if e.fault_message == "The vCPU number is invalid!"
+        ));
+    }
+
+    #[test]
+    fn unset_fields_are_omitted() {
+        assert_eq!(
+            serde_json::to_string(&MachineConfig::new(2, 1024)).unwrap(),
+            r#"{"vcpu_count":2,"mem_size_mib":1024}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&ActionBody {
+                action_type: Action::InstanceStart
+            })
+            .unwrap(),
+            r#"{"action_type":"InstanceStart"}"#
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{ClientUnix, Error, client::Options};
+use std::{
+    path::PathBuf,
+    sync::{Mutex as StdMutex, OnceLock},
+};
+use tokio::sync::{Mutex, MutexGuard, OnceCell};
+
+/// Settings given to [`ClientUnixBuilder::set_global`](crate::ClientUnixBuilder::set_global).
+static SETTINGS: OnceLock<StdMutex<(PathBuf, Options)>> = OnceLock::new();
+static CLIENT: OnceCell<Mutex<ClientUnix>> = OnceCell::const_new();
+
+/// Keep the settings of the global client, unless already set.
+pub(crate) fn configure(socket_path: PathBuf, options: Options) -> bool {
+    SETTINGS.set(StdMutex::new((socket_path, options))).is_ok()
+}
+
+fn settings(
+    settings: &OnceLock<StdMutex<(PathBuf, Options)>>,
+) -> Result<(PathBuf, Options), Error> {
+    Ok(settings
+        .get()
+        .ok_or(Error::GlobalNotConfigured)?
+        .lock()
+        .map(|settings| settings.clone())
+        .unwrap_or_else(|poisoned| poisoned.into_inner().clone()))
+}
+
+impl ClientUnix {
+    /// The process-wide client configured by
+    /// [`ClientUnixBuilder::set_global`](crate::ClientUnixBuilder::set_global), connected by
+    /// the first call, for the tools which don't pass a client around. The concurrent callers
+    /// wait for each other, a failed connection is attempted again by the next call. The
+    /// connection is driven by the runtime of the call which connects it, which must run as long
+    /// as the client is used, as the main one. Fails with [`Error::GlobalNotConfigured`] before
+    /// the global client is configured.
+    ///
+    /// ```rust,no_run
+    /// use http_client_unix_domain_socket::{ClientUnix, Method};
+    ///
+    /// async fn ping() {
+    ///     let (_, body) = ClientUnix::global()
+    ///         .await
+    ///         .expect("ClientUnix::global")
+    ///         .send_request("/_ping", Method::GET, &[], None)
+    ///         .await
+    ///         .expect("client.send_request");
+    /// }
+    ///
+    /// # async fn run() {
+    /// ClientUnix::builder("/var/run/docker.sock").set_global();
+    /// ping().await;
+    /// # }
+    /// ```
+    pub async fn global() -> Result<MutexGuard<'static, ClientUnix>, Error> {
+        let client = CLIENT
+            .get_or_try_init(|| async {
+                let (socket_path, options) = settings(&SETTINGS)?;
+                Ok(Mutex::new(
+                    ClientUnix::try_connect(socket_path, options).await?,
+                ))
+            })
+            .await?;
+        Ok(client.lock().await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Method, test_helpers::server::Server, test_helpers::util::*};
+
+    #[test]
+    fn not_configured() {
+        let unset = OnceLock::new();
+        let Err(error) = settings(&unset) else {
+            panic!("configured")
+        };
+        assert!(matches!(error, Error::GlobalNotConfigured));
+        assert_eq!(error.kind(), crate::ErrorKind::InvalidRequest);
+    }
+
+    #[tokio::test]
+    async fn configure_once() {
+        let socket_path = make_socket_path_test("global", "configure_once");
+        assert!(ClientUnix::builder(&socket_path).set_global());
+        assert!(!ClientUnix::builder("/nolanv.sock").set_global());
+        assert!(ClientUnix::global().await.is_err());
+
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let (_, body) = ClientUnix::global()
+            .await
+            .expect("ClientUnix::global")
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"Hello nolanv");
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{ClientUnix, Error, ErrorKind};
+use axum_core::body::Body;
+use hyper::{Method, StatusCode};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// Error of a GraphQL response, as defined by the GraphQL specification (feature=json).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GraphQlError {
+    pub message: String,
+    #[serde(default)]
+    pub locations: Vec<GraphQlLocation>,
+    /// Path of the field in error, made of field names and list indices.
+    #[serde(default)]
+    pub path: Vec<Value>,
+    #[serde(default)]
+    pub extensions: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct GraphQlLocation {
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug)]
+pub enum ErrorAndResponseGraphQl {
+    InternalError(Error),
+    /// Unsuccessful response without a GraphQL body.
+    ResponseUnsuccessful(StatusCode, Vec<u8>),
+    /// The `errors` of the response, with its partial `data` if any.
+    GraphQl(Vec<GraphQlError>, Option<Value>),
+}
+
+impl ErrorAndResponseGraphQl {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ErrorAndResponseGraphQl::InternalError(e) => e.kind(),
+            ErrorAndResponseGraphQl::ResponseUnsuccessful(_, _)
+            | ErrorAndResponseGraphQl::GraphQl(_, _) => ErrorKind::UnsuccessfulResponse,
+        }
+    }
+}
+
+impl fmt::Display for ErrorAndResponseGraphQl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorAndResponseGraphQl::InternalError(e) => write!(f, "{}", e),
+            ErrorAndResponseGraphQl::ResponseUnsuccessful(status_code, _) => {
+                write!(f, "unsuccessful response: {}", status_code)
+            }
+            ErrorAndResponseGraphQl::GraphQl(errors, _) => {
+                write!(f, "GraphQL errors: ")?;
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", error.message)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ErrorAndResponseGraphQl {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ErrorAndResponseGraphQl::InternalError(e) => std::error::Error::source(e),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct GraphQlRequest<'a, VARS> {
+    query: &'a str,
+    variables: &'a VARS,
+}
+
+#[derive(Deserialize)]
+struct GraphQlResponse {
+    #[serde(default)]
+    data: Option<Value>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+impl ClientUnix {
+    /// POST a GraphQL `query` with its `variables` and deserialize the `data` of the response.
+    /// A response with `errors`, whatever its status code, fails with
+    /// [`ErrorAndResponseGraphQl::GraphQl`].
+    pub async fn graphql<VARS: Serialize, OUT: DeserializeOwned>(
+        &mut self,
+        endpoint: &str,
+        query: &str,
+        variables: &VARS,
+    ) -> Result<OUT, ErrorAndResponseGraphQl> {
+        let body = serde_json::to_vec(&GraphQlRequest { query, variables })
+            .map_err(|e| ErrorAndResponseGraphQl::InternalError(Error::RequestParsing(e)))?;
+        let (status_code, body) = match self
+            .send_request(
+                endpoint,
+                Method::POST,
+                &[
+                    ("Content-Type", "application/json"),
+                    (
+                        "Accept",
+                        "application/graphql-response+json, application/json",
+                    ),
+                ],
+                Some(Body::from(body)),
+            )
+            .await
+        {
+            Ok(response) => response,
+            Err(error) => error
+                .into_response()
+                .map_err(ErrorAndResponseGraphQl::InternalError)?,
+        };
+
+        let response: GraphQlResponse = match crate::client::json_from_slice(&body) {
+            Ok(response) => response,
+            Err(_) if !status_code.is_success() => {
+                return Err(ErrorAndResponseGraphQl::ResponseUnsuccessful(
+                    status_code,
+                    body,
+                ));
+            }
+            Err(e) => {
+                return Err(ErrorAndResponseGraphQl::InternalError(
+                    Error::ResponseParsing(e, body),
+                ));
+            }
+        };
+        match response {
+            GraphQlResponse { errors, data } if !errors.is_empty() => {
+                Err(ErrorAndResponseGraphQl::GraphQl(errors, data))
+            }
+            _ if !status_code.is_success() => Err(ErrorAndResponseGraphQl::ResponseUnsuccessful(
+                status_code,
+                body,
+            )),
+            GraphQlResponse { data, .. } => serde_json::from_value(data.unwrap_or(Value::Null))
+                .map_err(|e| {
+                    ErrorAndResponseGraphQl::InternalError(Error::ResponseParsing(e, body))
+                }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::util::make_client_server;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Hello {
+        hello: String,
+    }
+
+    #[tokio::test]
+    async fn data_and_errors() {
+        let (_, mut client) = make_client_server("graphql_data_and_errors").await;
+
+        let data: Hello = client
+            .graphql(
+                "/graphql",
+                "query($name: String!) { hello(name: $name) }",
+                &json!({"name": "nolanv"}),
+            )
+            .await
+            .expect("client.graphql");
+        assert_eq!(data.hello, "Hello nolanv");
+
+        let result = client
+            .graphql::<_, Hello>("/graphql", "{ nolanv }", &())
+            .await;
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponseGraphQl::GraphQl(errors, None))
+                if errors[0].locations == [GraphQlLocation { line: 1, column: 3 }]
+        ));
+    }
+}
@@ -0,0 +1,396 @@
+//! Record traffic and export it as a [HAR](http://www.softwareishard.com/blog/har-12-spec/) file (feature=har).
+
+use crate::{Error, redact::Redactor};
+use axum_core::body::Body;
+use hyper::{
+    HeaderMap, Request, Response,
+    body::{Body as HttpBody, Bytes, Frame, Incoming, SizeHint},
+};
+use serde_json::{Value, json};
+use std::{
+    path::Path,
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Record requests and responses sent by the clients it is given to with
+/// [`ClientUnixBuilder::har_recorder`](crate::ClientUnixBuilder::har_recorder).
+///
+/// Bodies are truncated to `body_limit` bytes, redacted headers are masked.
+/// Response bodies are only recorded by the buffered helpers such as `send_request`.
+#[derive(Clone)]
+pub struct HarRecorder {
+    body_limit: usize,
+    next_id: Arc<AtomicU64>,
+    entries: Arc<Mutex<Vec<HarEntry>>>,
+}
+
+struct HarEntry {
+    id: u64,
+    started: SystemTime,
+    request: Value,
+    request_mime_type: String,
+    request_body: Arc<Mutex<Vec<u8>>>,
+    response: Option<Value>,
+    response_mime_type: String,
+    response_body: Option<Vec<u8>>,
+    error: Option<String>,
+    wait: Duration,
+    receive: Duration,
+}
+
+/// Id of the [`HarEntry`] of a response, kept in its extensions.
+#[derive(Clone, Copy)]
+pub(crate) struct HarEntryId(u64);
+
+/// A request being recorded.
+pub(crate) struct HarPending {
+    recorder: HarRecorder,
+    id: u64,
+    start: Instant,
+}
+
+impl HarRecorder {
+    pub fn new(body_limit: usize) -> Self {
+        HarRecorder {
+            body_limit,
+            next_id: Arc::new(AtomicU64::new(0)),
+            entries: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().map(|e| e.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    /// Recorded traffic as a HAR 1.2 document.
+    pub fn to_har(&self) -> Value {
+        let entries = self
+            .entries
+            .lock()
+            .map(|entries| entries.iter().map(|e| e.to_har()).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        json!({
+            "log": {
+                "version": "1.2",
+                "creator": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "entries": entries,
+            }
+        })
+    }
+
+    pub fn write_to(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let har = serde_json::to_vec_pretty(&self.to_har()).map_err(std::io::Error::other)?;
+        std::fs::write(path, har)
+    }
+
+    /// Record the head of `request` and tee its body, to be called after the interceptors.
+    pub(crate) fn start(&self, request: &mut Request<Body>, redactor: &Redactor) -> HarPending {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request_body = Arc::new(Mutex::new(Vec::new()));
+        let body = std::mem::take(request.body_mut());
+        *request.body_mut() = Body::new(TeeBody {
+            inner: body,
+            captured: request_body.clone(),
+            limit: self.body_limit,
+        });
+
+        let entry = HarEntry {
+            id,
+            started: SystemTime::now(),
+            request: json!({
+                "method": request.method().as_str(),
+                "url": request.uri().to_string(),
+                "httpVersion": "HTTP/1.1",
+                "cookies": [],
+                "headers": headers_to_har(request.headers(), redactor),
+                "queryString": [],
+                "headersSize": -1,
+            }),
+            request_mime_type: content_type(request.headers()).to_string(),
+            request_body,
+            response: None,
+            response_mime_type: String::new(),
+            response_body: None,
+            error: None,
+            wait: Duration::ZERO,
+            receive: Duration::ZERO,
+        };
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(entry);
+        }
+
+        HarPending {
+            recorder: self.clone(),
+            id,
+            start: Instant::now(),
+        }
+    }
+
+    /// Record the collected body of the response identified by `id`.
+    pub(crate) fn finish(&self, HarEntryId(id): HarEntryId, body: &[u8], receive: Duration) {
+        let limit = self.body_limit;
+        self.with_entry(id, |entry| {
+            entry.response_body = Some(body[..body.len().min(limit)].to_vec());
+            entry.receive = receive;
+        });
+    }
+
+    fn with_entry(&self, id: u64, f: impl FnOnce(&mut HarEntry)) {
+        if let Ok(mut entries) = self.entries.lock()
+            && let Some(entry) = entries.iter_mut().find(|e| e.id == id)
+        {
+            f(entry);
+        }
+    }
+}
+
+impl HarPending {
+    pub(crate) fn response(self, response: &mut Response<Incoming>, redactor: &Redactor) {
+        let wait = self.start.elapsed();
+        let har_response = json!({
+            "status": response.status().as_u16(),
+            "statusText": response.status().canonical_reason().unwrap_or(""),
+            "httpVersion": "HTTP/1.1",
+            "cookies": [],
+            "headers": headers_to_har(response.headers(), redactor),
+            "redirectURL": "",
+            "headersSize": -1,
+        });
+        let mime_type = content_type(response.headers()).to_string();
+        self.recorder.with_entry(self.id, |entry| {
+            entry.response = Some(har_response);
+            entry.response_mime_type = mime_type;
+            entry.wait = wait;
+        });
+        response.extensions_mut().insert(HarEntryId(self.id));
+    }
+
+    pub(crate) fn error(self, error: &Error) {
+        let wait = self.start.elapsed();
+        self.recorder.with_entry(self.id, |entry| {
+            entry.error = Some(format!("{:?}", error));
+            entry.wait = wait;
+        });
+    }
+}
+
+impl HarEntry {
+    fn to_har(&self) -> Value {
+        let millis = |d: Duration| d.as_secs_f64() * 1000.0;
+
+        let mut request = self.request.clone();
+        let request_body = self
+            .request_body
+            .lock()
+            .map(|b| b.clone())
+            .unwrap_or_default();
+        request["bodySize"] = json!(request_body.len());
+        if !request_body.is_empty() {
+            request["postData"] = json!({
+                "mimeType": self.request_mime_type,
+                "text": String::from_utf8_lossy(&request_body),
+            });
+        }
+
+        let mut response = self.response.clone().unwrap_or_else(|| {
+            json!({
+                "status": 0,
+                "statusText": "",
+                "httpVersion": "HTTP/1.1",
+                "cookies": [],
+                "headers": [],
+                "redirectURL": "",
+                "headersSize": -1,
+            })
+        });
+        let response_body = self.response_body.as_deref().unwrap_or_default();
+        response["bodySize"] = match self.response_body {
+            Some(_) => json!(response_body.len()),
+            None => json!(-1),
+        };
+        response["content"] = json!({
+            "size": response_body.len(),
+            "mimeType": self.response_mime_type,
+            "text": String::from_utf8_lossy(response_body),
+        });
+
+        let mut entry = json!({
+            "startedDateTime": rfc3339(self.started),
+            "time": millis(self.wait + self.receive),
+            "request": request,
+            "response": response,
+            "cache": {},
+            "timings": {
+                "send": 0,
+                "wait": millis(self.wait),
+                "receive": millis(self.receive),
+            },
+        });
+        if let Some(error) = &self.error {
+            entry["_error"] = json!(error);
+        }
+        entry
+    }
+}
+
+fn headers_to_har(headers: &HeaderMap, redactor: &Redactor) -> Value {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            json!({
+                "name": name.as_str(),
+                "value": redactor.value(name.as_str(), &String::from_utf8_lossy(value.as_bytes())),
+            })
+        })
+        .collect()
+}
+
+fn content_type(headers: &HeaderMap) -> &str {
+    headers
+        .get(hyper::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+}
+
+/// Format `time` as `YYYY-MM-DDTHH:MM:SS.mmmZ`.
+fn rfc3339(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = ((secs / 86400) as i64, secs % 86400);
+
+    // Civil date from days since epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Request body copying up to `limit` bytes of what is sent.
+struct TeeBody {
+    inner: Body,
+    captured: Arc<Mutex<Vec<u8>>>,
+    limit: usize,
+}
+
+impl HttpBody for TeeBody {
+    type Data = Bytes;
+    type Error = axum_core::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let poll = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll
+            && let (Some(data), Ok(mut captured)) = (frame.data_ref(), self.captured.lock())
+        {
+            let remaining = self.limit.saturating_sub(captured.len());
+            captured.extend_from_slice(&data[..data.len().min(remaining)]);
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, test_helpers::server::Server, test_helpers::util::*};
+    use hyper::{Method, header::AUTHORIZATION};
+
+    #[test]
+    fn rfc3339_format() {
+        assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            rfc3339(UNIX_EPOCH + Duration::from_millis(1709210096789)),
+            "2024-02-29T12:34:56.789Z"
+        );
+    }
+
+    #[tokio::test]
+    async fn har_recording() {
+        let socket_path = make_socket_path_test("har", "har_recording");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let recorder = HarRecorder::new(5);
+        let mut client = ClientUnix::builder(&socket_path)
+            .har_recorder(recorder.clone())
+            .redact_header(AUTHORIZATION)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        client
+            .send_request(
+                "/nolanv",
+                Method::GET,
+                &[("Authorization", "secret")],
+                Some(Body::from("request body")),
+            )
+            .await
+            .expect("client.send_request");
+        let _ = client
+            .send_request("/nolanv/nope", Method::GET, &[], None)
+            .await;
+
+        assert_eq!(recorder.len(), 2);
+        let har = recorder.to_har();
+        let entries = har["log"]["entries"].as_array().expect("entries");
+        assert_eq!(entries[0]["request"]["method"], "GET");
+        assert_eq!(entries[0]["request"]["url"], "http://unix.socket/nolanv");
+        assert_eq!(entries[0]["request"]["postData"]["text"], "reque");
+        assert!(
+            entries[0]["request"]["headers"]
+                .as_array()
+                .expect("headers")
+                .contains(&json!({"name": "authorization", "value": "***"}))
+        );
+        assert_eq!(entries[0]["response"]["status"], 200);
+        assert_eq!(entries[0]["response"]["content"]["text"], "Hello");
+        assert_eq!(entries[1]["response"]["status"], 404);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{ClientUnix, Error};
+use hyper::{Method, StatusCode};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// First delay between two polls of [`ClientUnix::wait_until_healthy`], doubled after each poll.
+const INITIAL_POLL_INTERVAL: Duration = Duration::from_millis(20);
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+impl ClientUnix {
+    /// Poll `endpoint` until `is_healthy` accepts its status code and body, whether successful or
+    /// not, and return that response. Connection errors are retried as well, the connection being
+    /// reopened when the daemon restarts.
+    ///
+    /// Fails with [`Error::Timeout`] when no response is accepted within `timeout`.
+    ///
+    /// ```rust,no_run
+    /// use http_client_unix_domain_socket::ClientUnix;
+    /// use std::time::Duration;
+    ///
+    /// # async fn run() {
+    /// let mut client = ClientUnix::try_new("/tmp/unix.socket")
+    ///     .await
+    ///     .expect("ClientUnix::try_new");
+    /// client
+    ///     .wait_until_healthy(
+    ///         "/_ping",
+    ///         |status_code, body| status_code.is_success() && body == b"OK",
+    ///         Duration::from_secs(30),
+    ///     )
+    ///     .await
+    ///     .expect("client.wait_until_healthy");
+    /// # }
+    /// ```
+    pub async fn wait_until_healthy(
+        &mut self,
+        endpoint: &str,
+        is_healthy: impl Fn(StatusCode, &[u8]) -> bool,
+        timeout: Duration,
+    ) -> Result<(StatusCode, Vec<u8>), Error> {
+        let deadline = Instant::now() + timeout;
+        let mut interval = INITIAL_POLL_INTERVAL;
+        loop {
+            let poll = tokio::time::timeout_at(
+                deadline,
+                self.send_request(endpoint, Method::GET, &[], None),
+            )
+            .await
+            .map_err(|_| Error::Timeout(timeout))?;
+            let response = match poll {
+                Ok(response) => Some(response),
+                Err(error) => error.into_response().ok(),
+            };
+            if let Some((status_code, body)) = response
+                && is_healthy(status_code, &body)
+            {
+                return Ok((status_code, body));
+            }
+
+            if Instant::now() + interval >= deadline {
+                return Err(Error::Timeout(timeout));
+            }
+            tokio::time::sleep(interval).await;
+            interval = (interval * 2).min(MAX_POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::util::make_client_server;
+
+    #[tokio::test]
+    async fn healthy_after_polls() {
+        let (_server, mut client) = make_client_server("healthy_after_polls").await;
+
+        let (status_code, body) = client
+            .wait_until_healthy(
+                "/health",
+                |status_code, _| status_code == StatusCode::OK,
+                Duration::from_secs(5),
+            )
+            .await
+            .expect("client.wait_until_healthy");
+
+        assert_eq!(status_code, StatusCode::OK);
+        assert_eq!(body, b"ready after 3 polls");
+    }
+
+    #[tokio::test]
+    async fn never_healthy() {
+        let (_server, mut client) = make_client_server("never_healthy").await;
+
+        let result = client
+            .wait_until_healthy("/nolanv", |_, _| false, Duration::from_millis(50))
+            .await;
+
+        assert!(matches!(result, Err(Error::Timeout(_))));
+    }
+}
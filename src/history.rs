@@ -0,0 +1,176 @@
+use crate::{ErrorEvent, ResponseEvent};
+use hyper::{Method, StatusCode};
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+/// Outcome of a request kept by a [`RequestHistory`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestRecord {
+    /// When the outcome was known.
+    pub at: SystemTime,
+    pub method: Method,
+    /// Path and query of the request.
+    pub path: String,
+    /// `None` when the request failed before a response head.
+    pub status: Option<StatusCode>,
+    /// Time from the start of the request to its response head or failure.
+    pub latency: Duration,
+    pub error: Option<String>,
+}
+
+/// `GET /containers/json 200 in 1.2ms`, or the error instead of the status code.
+impl fmt::Display for RequestRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ", self.method, self.path)?;
+        match (&self.error, self.status) {
+            (Some(error), _) => write!(f, "failed: {}", error)?,
+            (None, Some(status)) => write!(f, "{}", status.as_u16())?,
+            (None, None) => write!(f, "no response")?,
+        }
+        write!(f, " in {:?}", self.latency)
+    }
+}
+
+/// Bounded in-memory history of the last requests of a client, set with
+/// [`ClientUnixBuilder::request_history`](crate::ClientUnixBuilder::request_history), to dump
+/// what the client was doing when something went wrong.
+///
+/// A record is added when the response head is received or the request fails, the oldest one is
+/// dropped when full. A response whose body can't be read adds an error record after its own.
+/// Clones share the same records, so the history can be kept outside of the client and its
+/// clones.
+///
+/// ```rust
+/// use http_client_unix_domain_socket::{ClientUnix, Method, RequestHistory};
+///
+/// pub async fn ping() {
+///     let history = RequestHistory::new(64);
+///     let mut client = ClientUnix::builder("/var/run/docker.sock")
+///         .request_history(history.clone())
+///         .build()
+///         .await
+///         .expect("ClientUnixBuilder::build");
+///     if client.send_request("/_ping", Method::GET, &[], None).await.is_err() {
+///         for record in history.records() {
+///             eprintln!("{}", record);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestHistory {
+    records: Arc<Mutex<VecDeque<RequestRecord>>>,
+    capacity: usize,
+}
+
+impl RequestHistory {
+    /// Keep the last `capacity` requests.
+    pub fn new(capacity: usize) -> Self {
+        RequestHistory {
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// The records, from the oldest to the most recent.
+    pub fn records(&self) -> Vec<RequestRecord> {
+        self.records
+            .lock()
+            .map_or_else(|_| Vec::new(), |records| records.iter().cloned().collect())
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut records) = self.records.lock() {
+            records.clear();
+        }
+    }
+
+    pub(crate) fn record_response(&self, event: &ResponseEvent<'_>) {
+        self.push(RequestRecord {
+            at: SystemTime::now(),
+            method: event.method.clone(),
+            path: path(event.uri),
+            status: Some(event.status),
+            latency: event.latency,
+            error: None,
+        });
+    }
+
+    pub(crate) fn record_error(&self, event: &ErrorEvent<'_>) {
+        self.push(RequestRecord {
+            at: SystemTime::now(),
+            method: event.method.clone(),
+            path: path(event.uri),
+            status: None,
+            latency: event.latency,
+            error: Some(event.error.to_string()),
+        });
+    }
+
+    fn push(&self, record: RequestRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Ok(mut records) = self.records.lock() {
+            if records.len() == self.capacity {
+                records.pop_front();
+            }
+            records.push_back(record);
+        }
+    }
+}
+
+fn path(uri: &hyper::Uri) -> String {
+    uri.path_and_query()
+        .map_or_else(|| uri.path().to_string(), |p| p.as_str().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, test_helpers::server::Server, test_helpers::util::*};
+
+    #[tokio::test]
+    async fn keep_last_requests() {
+        let socket_path = make_socket_path_test("history", "keep_last_requests");
+        let server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let history = RequestHistory::new(2);
+        let mut client = ClientUnix::builder(&socket_path)
+            .request_history(history.clone())
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        for endpoint in ["/first", "/nolanv?all=true", "/not/found"] {
+            let _ = client.send_request(endpoint, Method::GET, &[], None).await;
+        }
+        let mut client = client.try_reconnect().await.expect("client.try_reconnect");
+        server.abort().await;
+        let _ = client
+            .send_request("/stopped", Method::GET, &[], None)
+            .await;
+
+        let records = history.records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            (records[0].path.as_str(), records[0].status),
+            ("/not/found", Some(StatusCode::NOT_FOUND))
+        );
+        assert_eq!(
+            (records[1].path.as_str(), records[1].status),
+            ("/stopped", None)
+        );
+        assert!(records[1].error.is_some());
+        assert!(records[0].to_string().starts_with("GET /not/found 404 in "));
+        assert_eq!(
+            client.request_history().map(|history| history.records()),
+            Some(records)
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::Error;
+use hyper::{HeaderMap, Method, StatusCode, Uri};
+use std::{sync::Arc, time::Duration};
+
+/// Request about to be sent, given to [`ClientUnixBuilder::on_request`](crate::ClientUnixBuilder::on_request).
+#[derive(Debug)]
+pub struct RequestEvent<'a> {
+    pub method: &'a Method,
+    pub uri: &'a Uri,
+    pub headers: &'a HeaderMap,
+}
+
+/// Response received, given to [`ClientUnixBuilder::on_response`](crate::ClientUnixBuilder::on_response).
+#[derive(Debug)]
+pub struct ResponseEvent<'a> {
+    pub method: &'a Method,
+    pub uri: &'a Uri,
+    pub status: StatusCode,
+    /// Time from the start of the request to the response head.
+    pub latency: Duration,
+}
+
+/// Request failure, given to [`ClientUnixBuilder::on_error`](crate::ClientUnixBuilder::on_error).
+#[derive(Debug)]
+pub struct ErrorEvent<'a> {
+    pub method: &'a Method,
+    pub uri: &'a Uri,
+    pub error: &'a Error,
+    /// Time from the start of the request to the failure.
+    pub latency: Duration,
+}
+
+pub(crate) type OnRequest = Arc<dyn Fn(&RequestEvent<'_>) + Send + Sync>;
+pub(crate) type OnResponse = Arc<dyn Fn(&ResponseEvent<'_>) + Send + Sync>;
+pub(crate) type OnError = Arc<dyn Fn(&ErrorEvent<'_>) + Send + Sync>;
+
+/// Observation callbacks, lighter than an [`Interceptor`](crate::Interceptor).
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    pub(crate) on_request: Vec<OnRequest>,
+    pub(crate) on_response: Vec<OnResponse>,
+    pub(crate) on_error: Vec<OnError>,
+}
+
+impl Hooks {
+    pub(crate) fn request(&self, event: RequestEvent<'_>) {
+        for hook in &self.on_request {
+            hook(&event);
+        }
+    }
+
+    pub(crate) fn response(&self, event: ResponseEvent<'_>) {
+        for hook in &self.on_response {
+            hook(&event);
+        }
+    }
+
+    pub(crate) fn error(&self, event: ErrorEvent<'_>) {
+        for hook in &self.on_error {
+            hook(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClientUnix, test_helpers::server::Server, test_helpers::util::*};
+    use hyper::{Method, StatusCode};
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn hooks_are_called() {
+        let socket_path = make_socket_path_test("hooks", "hooks_are_called");
+        let server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let (on_request, on_response, on_error) = (events.clone(), events.clone(), events.clone());
+        let mut client = ClientUnix::builder(&socket_path)
+            .on_request(move |event| {
+                on_request.lock().unwrap().push(format!(
+                    "request {} {}",
+                    event.method,
+                    event.uri.path()
+                ))
+            })
+            .on_response(move |event| {
+                on_response
+                    .lock()
+                    .unwrap()
+                    .push(format!("response {}", event.status.as_u16()))
+            })
+            .on_error(move |event| {
+                on_error
+                    .lock()
+                    .unwrap()
+                    .push(format!("error {}", event.uri.path()))
+            })
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let (status_code, _) = client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(status_code, StatusCode::OK);
+
+        let mut client = client.try_reconnect().await.expect("client.try_reconnect");
+        server.abort().await;
+        let _ = client
+            .send_request("/stopped", Method::GET, &[], None)
+            .await;
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "request GET /nolanv",
+                "response 200",
+                "request GET /stopped",
+                "error /stopped",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn interrupted_body_is_an_error() {
+        use futures_util::StreamExt;
+
+        let server = crate::testing::MockServer::start(axum::Router::new().route(
+            "/interrupted",
+            axum::routing::get(|| async {
+                axum::body::Body::from_stream(
+                    futures_util::stream::once(async { Ok("Hello") }).chain(
+                        futures_util::stream::once(async {
+                            // Let the head and the first chunk be flushed
+                            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                            Err(std::io::Error::other("nolanv connection dropped"))
+                        }),
+                    ),
+                )
+            }),
+        ))
+        .await
+        .expect("MockServer::start");
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let (on_response, on_error) = (events.clone(), events.clone());
+        let mut client = ClientUnix::builder(&server.socket_path().to_string_lossy())
+            .on_response(move |event| {
+                on_response
+                    .lock()
+                    .unwrap()
+                    .push(format!("response {}", event.status.as_u16()))
+            })
+            .on_error(move |event| {
+                on_error
+                    .lock()
+                    .unwrap()
+                    .push(format!("error {}", event.uri.path()))
+            })
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let _ = client
+            .send_request("/interrupted", Method::GET, &[], None)
+            .await
+            .expect_err("client.send_request");
+
+        assert_eq!(*events.lock().unwrap(), vec!["error /interrupted"]);
+    }
+}
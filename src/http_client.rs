@@ -0,0 +1,125 @@
+use crate::{Body, ClientUnix, ErrorAndResponse, interceptor::BoxFuture};
+use hyper::{Method, Response};
+
+/// Object safe interface of [`ClientUnix`], for the applications which take a
+/// `&mut dyn UnixHttpClient` so their unit tests can inject a mock instead of starting a server
+/// on a socket.
+///
+/// ```rust
+/// use http_client_unix_domain_socket::{
+///     Body, ErrorAndResponse, Method, Response, StatusCode, UnixHttpClient,
+/// };
+/// use std::{future::Future, pin::Pin};
+///
+/// async fn is_up(client: &mut dyn UnixHttpClient) -> bool {
+///     client
+///         .send_request_full("/_ping", Method::GET, &[], None)
+///         .await
+///         .is_ok()
+/// }
+///
+/// struct Down;
+///
+/// impl UnixHttpClient for Down {
+///     fn send_request_full<'a>(
+///         &'a mut self,
+///         _endpoint: &'a str,
+///         _method: Method,
+///         _headers: &'a [(&'a str, &'a str)],
+///         _body_request: Option<Body>,
+///     ) -> Pin<Box<dyn Future<Output = Result<Response<Vec<u8>>, ErrorAndResponse>> + Send + 'a>>
+///     {
+///         Box::pin(async {
+///             Err(ErrorAndResponse::ResponseUnsuccessful(
+///                 StatusCode::SERVICE_UNAVAILABLE,
+///                 Vec::new(),
+///             ))
+///         })
+///     }
+/// }
+///
+/// # async fn run() {
+/// assert!(!is_up(&mut Down).await);
+/// # }
+/// ```
+pub trait UnixHttpClient: Send {
+    /// See [`ClientUnix::send_request_full`].
+    fn send_request_full<'a>(
+        &'a mut self,
+        endpoint: &'a str,
+        method: Method,
+        headers: &'a [(&'a str, &'a str)],
+        body_request: Option<Body>,
+    ) -> BoxFuture<'a, Result<Response<Vec<u8>>, ErrorAndResponse>>;
+}
+
+impl UnixHttpClient for ClientUnix {
+    fn send_request_full<'a>(
+        &'a mut self,
+        endpoint: &'a str,
+        method: Method,
+        headers: &'a [(&'a str, &'a str)],
+        body_request: Option<Body>,
+    ) -> BoxFuture<'a, Result<Response<Vec<u8>>, ErrorAndResponse>> {
+        Box::pin(ClientUnix::send_request_full(
+            self,
+            endpoint,
+            method,
+            headers,
+            body_request,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::util::make_client_server;
+    use hyper::StatusCode;
+
+    /// Code under test, only knowing the trait.
+    async fn greet(client: &mut dyn UnixHttpClient, name: &str) -> Option<String> {
+        let response = client
+            .send_request_full(&format!("/{}", name), Method::GET, &[], None)
+            .await
+            .ok()?;
+        String::from_utf8(response.into_body()).ok()
+    }
+
+    struct MockClient {
+        endpoints: Vec<String>,
+    }
+
+    impl UnixHttpClient for MockClient {
+        fn send_request_full<'a>(
+            &'a mut self,
+            endpoint: &'a str,
+            _method: Method,
+            _headers: &'a [(&'a str, &'a str)],
+            _body_request: Option<Body>,
+        ) -> BoxFuture<'a, Result<Response<Vec<u8>>, ErrorAndResponse>> {
+            self.endpoints.push(endpoint.to_string());
+            Box::pin(async {
+                Err(ErrorAndResponse::ResponseUnsuccessful(
+                    StatusCode::NOT_FOUND,
+                    Vec::new(),
+                ))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn inject_mock_client() {
+        let mut mock = MockClient {
+            endpoints: Vec::new(),
+        };
+        assert_eq!(greet(&mut mock, "nolanv").await, None);
+        assert_eq!(mock.endpoints, ["/nolanv"]);
+
+        let (_, mut client) = make_client_server("inject_mock_client").await;
+        assert_eq!(
+            greet(&mut client, "nolanv").await.as_deref(),
+            Some("Hello nolanv")
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use crate::Error;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use hyper::{
+    Method, StatusCode,
+    header::{HeaderMap, HeaderName},
+};
+use md5::Md5;
+use sha2::{Digest, Sha256, Sha512};
+
+const CONTENT_DIGEST: HeaderName = HeaderName::from_static("content-digest");
+const CONTENT_MD5: HeaderName = HeaderName::from_static("content-md5");
+
+/// Hash of a response body, compared to its `Content-Digest` (RFC 9530) or `Content-MD5` header,
+/// see [`ClientUnixBuilder::verify_content_digest`](crate::ClientUnixBuilder::verify_content_digest).
+pub(crate) struct Verifier {
+    hasher: Hasher,
+    expected: Vec<u8>,
+}
+
+enum Hasher {
+    Sha512(Sha512),
+    Sha256(Sha256),
+    Md5(Md5),
+}
+
+impl Verifier {
+    /// The verifier of the strongest supported digest of the response, `None` when it has none or
+    /// no body.
+    pub(crate) fn of(method: &Method, status: StatusCode, headers: &HeaderMap) -> Option<Self> {
+        if *method == Method::HEAD
+            || status == StatusCode::NO_CONTENT
+            || status == StatusCode::NOT_MODIFIED
+        {
+            return None;
+        }
+        let mut digests = headers
+            .get_all(CONTENT_DIGEST)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|digest| {
+                let (algorithm, value) = digest.split_once('=')?;
+                let value = value.trim().strip_prefix(':')?.strip_suffix(':')?;
+                let hasher = match algorithm.trim() {
+                    "sha-512" => Hasher::Sha512(Sha512::new()),
+                    "sha-256" => Hasher::Sha256(Sha256::new()),
+                    _ => return None,
+                };
+                Some((hasher, STANDARD.decode(value).ok()?))
+            })
+            .chain(headers.get(CONTENT_MD5).and_then(|value| {
+                Some((
+                    Hasher::Md5(Md5::new()),
+                    STANDARD.decode(value.as_bytes()).ok()?,
+                ))
+            }))
+            .collect::<Vec<_>>();
+        digests.sort_by_key(|(hasher, _)| match hasher {
+            Hasher::Sha512(_) => 0,
+            Hasher::Sha256(_) => 1,
+            Hasher::Md5(_) => 2,
+        });
+        let (hasher, expected) = digests.into_iter().next()?;
+        Some(Verifier { hasher, expected })
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        match &mut self.hasher {
+            Hasher::Sha512(hasher) => hasher.update(data),
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Md5(hasher) => hasher.update(data),
+        }
+    }
+
+    /// Fails with [`Error::DigestMismatch`] when the body read doesn't match the digest.
+    pub(crate) fn finish(self) -> Result<(), Error> {
+        let (algorithm, digest) = match self.hasher {
+            Hasher::Sha512(hasher) => ("sha-512", hasher.finalize().to_vec()),
+            Hasher::Sha256(hasher) => ("sha-256", hasher.finalize().to_vec()),
+            Hasher::Md5(hasher) => ("md5", hasher.finalize().to_vec()),
+        };
+        match digest == self.expected {
+            true => Ok(()),
+            false => Err(Error::DigestMismatch(algorithm)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, ErrorAndResponse};
+    use hyper::header::HeaderValue;
+
+    fn verify(headers: &[(HeaderName, &'static str)], body: &[u8]) -> Option<Result<(), Error>> {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| (name.clone(), HeaderValue::from_static(value)))
+            .collect();
+        let mut verifier = Verifier::of(&Method::GET, StatusCode::OK, &headers)?;
+        verifier.update(body);
+        Some(verifier.finish())
+    }
+
+    #[test]
+    fn strongest_digest() {
+        let sha256 = "sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:";
+        assert!(matches!(
+            verify(&[(CONTENT_DIGEST, sha256)], b"{\"hello\": \"world\"}"),
+            Some(Ok(()))
+        ));
+        assert!(matches!(
+            verify(&[(CONTENT_DIGEST, sha256)], b"{\"hello\": \"wor"),
+            Some(Err(Error::DigestMismatch("sha-256")))
+        ));
+        assert!(matches!(
+            verify(
+                &[
+                    (CONTENT_MD5, "XrY7u+Ae7tCTyyK7j1rNww=="),
+                    (CONTENT_DIGEST, "unixsum=:AAAA:, sha-256=:AAAA:"),
+                ],
+                b"hello world"
+            ),
+            Some(Err(Error::DigestMismatch("sha-256")))
+        ));
+        assert!(matches!(
+            verify(&[(CONTENT_MD5, "XrY7u+Ae7tCTyyK7j1rNww==")], b"hello world"),
+            Some(Ok(()))
+        ));
+        assert!(verify(&[(CONTENT_DIGEST, "unixsum=:AAAA:")], b"hello world").is_none());
+        assert!(verify(&[], b"hello world").is_none());
+    }
+
+    #[tokio::test]
+    async fn reject_truncated_body() {
+        let server = crate::testing::MockServer::start(
+            axum::Router::new()
+                .route(
+                    "/files/nolanv",
+                    axum::routing::get(|| async {
+                        ([(CONTENT_MD5, "XrY7u+Ae7tCTyyK7j1rNww==")], "hello world")
+                    }),
+                )
+                .route(
+                    "/files/truncated",
+                    axum::routing::get(|| async {
+                        ([(CONTENT_MD5, "XrY7u+Ae7tCTyyK7j1rNww==")], "hello")
+                    }),
+                ),
+        )
+        .await
+        .expect("MockServer::start");
+        let mut client = ClientUnix::builder(&server.socket_path().to_string_lossy())
+            .verify_content_digest(true)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let (_, body) = client
+            .send_request("/files/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"hello world");
+
+        let error = client
+            .send_request("/files/truncated", Method::GET, &[], None)
+            .await
+            .expect_err("client.send_request");
+        let ErrorAndResponse::InternalError(error) = error else {
+            panic!("{:?}", error)
+        };
+        assert!(
+            matches!(error.inner(), Error::DigestMismatch("md5")),
+            "{:?}",
+            error
+        );
+
+        let mut written = Vec::new();
+        let error = client
+            .send_request_to_writer("/files/truncated", Method::GET, &[], None, &mut written)
+            .await
+            .expect_err("client.send_request_to_writer");
+        assert!(matches!(
+            error,
+            ErrorAndResponse::InternalError(error)
+                if matches!(error.inner(), Error::DigestMismatch("md5"))
+        ));
+    }
+}
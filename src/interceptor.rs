@@ -0,0 +1,137 @@
+use crate::Error;
+use axum_core::body::Body;
+use hyper::{Request, Response, body::Incoming};
+use std::{future::Future, pin::Pin};
+
+pub(crate) type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Middleware called on every request sent by a [`ClientUnix`](crate::ClientUnix).
+///
+/// Interceptors are called in registration order for `before` and in reverse order for `after`.
+/// Returning an error aborts the request with that error.
+///
+/// ```rust
+/// use http_client_unix_domain_socket::{Body, Error, Interceptor, Request};
+///
+/// struct Auth(String);
+///
+/// impl Interceptor for Auth {
+///     async fn before(&self, request: &mut Request<Body>) -> Result<(), Error> {
+///         let value = format!("Bearer {}", self.0)
+///             .parse()
+///             .map_err(|e| Error::Interceptor(Box::new(e)))?;
+///         request.headers_mut().insert("Authorization", value);
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait Interceptor: Send + Sync {
+    /// Called before the request is sent.
+    fn before(
+        &self,
+        request: &mut Request<Body>,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        let _ = request;
+        async { Ok(()) }
+    }
+
+    /// Called once the response head is received, before its body is collected.
+    fn after(
+        &self,
+        response: &mut Response<Incoming>,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        let _ = response;
+        async { Ok(()) }
+    }
+}
+
+/// Object safe version of [`Interceptor`], used to store heterogeneous interceptors.
+pub(crate) trait DynInterceptor: Send + Sync {
+    fn before<'a>(&'a self, request: &'a mut Request<Body>) -> BoxFuture<'a, Result<(), Error>>;
+    fn after<'a>(
+        &'a self,
+        response: &'a mut Response<Incoming>,
+    ) -> BoxFuture<'a, Result<(), Error>>;
+}
+
+impl<I: Interceptor> DynInterceptor for I {
+    fn before<'a>(&'a self, request: &'a mut Request<Body>) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(Interceptor::before(self, request))
+    }
+
+    fn after<'a>(
+        &'a self,
+        response: &'a mut Response<Incoming>,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(Interceptor::after(self, response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorAndResponse, test_helpers::util::make_client_server};
+    use hyper::{Method, StatusCode};
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    struct InjectHeader;
+
+    impl Interceptor for InjectHeader {
+        async fn before(&self, request: &mut Request<Body>) -> Result<(), Error> {
+            request
+                .headers_mut()
+                .insert("x-name", "intercepted".parse().unwrap());
+            Ok(())
+        }
+    }
+
+    struct CountResponses(Arc<AtomicUsize>);
+
+    impl Interceptor for CountResponses {
+        async fn after(&self, _: &mut Response<Incoming>) -> Result<(), Error> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    struct Reject;
+
+    impl Interceptor for Reject {
+        async fn before(&self, _: &mut Request<Body>) -> Result<(), Error> {
+            Err(Error::Interceptor("rejected".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn interceptor_mutates_request() {
+        let (_, mut client) = make_client_server("interceptor_mutates_request").await;
+        let responses = Arc::new(AtomicUsize::new(0));
+        client.add_interceptor(InjectHeader);
+        client.add_interceptor(CountResponses(responses.clone()));
+
+        let (status_code, response) = client
+            .send_request("/header/x-name", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+
+        assert_eq!(status_code, StatusCode::OK);
+        assert_eq!(response, "intercepted".as_bytes());
+        assert_eq!(responses.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn interceptor_aborts_request() {
+        let (_, mut client) = make_client_server("interceptor_aborts_request").await;
+        client.add_interceptor(Reject);
+
+        let result = client.send_request("/nolanv", Method::GET, &[], None).await;
+
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponse::InternalError(e)) if matches!(e.inner(), Error::Interceptor(_))
+        ));
+    }
+}
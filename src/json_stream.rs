@@ -0,0 +1,279 @@
+//! Deserialize the items of a JSON array response as they arrive (feature=json-stream), without
+//! holding the whole document in memory.
+//!
+//! ```rust,no_run
+//! use futures_util::TryStreamExt;
+//! use http_client_unix_domain_socket::{ClientUnix, Method};
+//! use serde_json::Value;
+//!
+//! # async fn run() {
+//! let mut client = ClientUnix::try_new("/var/run/docker.sock")
+//!     .await
+//!     .expect("ClientUnix::try_new");
+//! let mut containers = client
+//!     .stream_json_array::<Value>("/containers/json?all=true", Method::GET, &[], None)
+//!     .await
+//!     .expect("client.stream_json_array");
+//! while let Some(container) = containers.try_next().await.expect("containers.try_next") {
+//!     println!("{}", container["Id"]);
+//! }
+//! # }
+//! ```
+
+use crate::{ClientUnix, Error, ErrorAndResponse, Method};
+use axum_core::body::Body;
+use futures_core::Stream;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use serde::de::DeserializeOwned;
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Items of [`ClientUnix::stream_json_array`], ending after the last one or the first error.
+pub struct JsonArray<OUT>(Pin<Box<dyn Stream<Item = Result<OUT, Error>> + Send>>);
+
+impl<OUT> Stream for JsonArray<OUT> {
+    type Item = Result<OUT, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.as_mut().poll_next(cx)
+    }
+}
+
+impl ClientUnix {
+    /// Send the request and deserialize the items of the JSON array of its response one at a
+    /// time, as the body arrives. Only the item being read is kept in memory.
+    ///
+    /// As [`send_request_streaming`](Self::send_request_streaming), unsuccessful responses are
+    /// collected into [`ErrorAndResponse::ResponseUnsuccessful`].
+    pub async fn stream_json_array<OUT: DeserializeOwned + Send + 'static>(
+        &mut self,
+        endpoint: &str,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Body>,
+    ) -> Result<JsonArray<OUT>, ErrorAndResponse> {
+        let headers = self.options().with_accept(headers, "application/json");
+        let response = self
+            .send_request_streaming(endpoint, method, &headers, body_request)
+            .await?;
+        let state = ArrayState {
+            body: Some(response.into_body()),
+            splitter: ArraySplitter::default(),
+            items: VecDeque::new(),
+            item: PhantomData,
+        };
+        Ok(JsonArray(Box::pin(futures_util::stream::unfold(
+            state, next_item,
+        ))))
+    }
+}
+
+struct ArrayState<OUT> {
+    /// `None` once read to the end or failed.
+    body: Option<Incoming>,
+    splitter: ArraySplitter,
+    items: VecDeque<Vec<u8>>,
+    item: PhantomData<fn() -> OUT>,
+}
+
+async fn next_item<OUT: DeserializeOwned>(
+    mut state: ArrayState<OUT>,
+) -> Option<(Result<OUT, Error>, ArrayState<OUT>)> {
+    loop {
+        if let Some(item) = state.items.pop_front() {
+            let item =
+                crate::client::json_from_slice(&item).map_err(|e| Error::ResponseParsing(e, item));
+            return Some((item, state));
+        }
+        let frame = state.body.as_mut()?.frame().await;
+        let result = match frame {
+            Some(Ok(frame)) => match frame.into_data() {
+                Ok(data) => state.splitter.push(&data, &mut state.items),
+                Err(_) => Ok(()),
+            },
+            Some(Err(e)) => Err(Error::ResponseCollect(e)),
+            None => {
+                state.body = None;
+                state.splitter.finish()
+            }
+        };
+        if let Err(e) = result {
+            state.body = None;
+            state.items.clear();
+            return Some((Err(e), state));
+        }
+    }
+}
+
+/// Position in the body, relative to the array.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Position {
+    #[default]
+    Before,
+    Inside,
+    After,
+}
+
+/// Splits a JSON array into the bytes of its items, whatever the chunks it arrives in.
+#[derive(Debug, Default)]
+struct ArraySplitter {
+    position: Position,
+    /// Nesting of the objects and arrays in the current item.
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// An item ended with a comma, another one must follow.
+    after_comma: bool,
+    item: Vec<u8>,
+}
+
+impl ArraySplitter {
+    fn push(&mut self, chunk: &[u8], items: &mut VecDeque<Vec<u8>>) -> Result<(), Error> {
+        for &byte in chunk {
+            match self.position {
+                Position::Before => match byte {
+                    b'[' => self.position = Position::Inside,
+                    byte if byte.is_ascii_whitespace() => {}
+                    _ => return Err(Error::ResponseFraming("expected a JSON array")),
+                },
+                Position::After if !byte.is_ascii_whitespace() => {
+                    return Err(Error::ResponseFraming("trailing data after the JSON array"));
+                }
+                Position::After => {}
+                Position::Inside if self.in_string => {
+                    self.item.push(byte);
+                    match byte {
+                        _ if self.escaped => self.escaped = false,
+                        b'\\' => self.escaped = true,
+                        b'"' => self.in_string = false,
+                        _ => {}
+                    }
+                }
+                Position::Inside if self.depth == 0 && matches!(byte, b',' | b']') => {
+                    let item = std::mem::take(&mut self.item);
+                    if !item.iter().all(u8::is_ascii_whitespace) {
+                        items.push_back(item);
+                    } else if byte == b',' || self.after_comma {
+                        return Err(Error::ResponseFraming("empty JSON array item"));
+                    }
+                    self.after_comma = byte == b',';
+                    if byte == b']' {
+                        self.position = Position::After;
+                    }
+                }
+                Position::Inside => {
+                    match byte {
+                        b'"' => self.in_string = true,
+                        b'{' | b'[' => self.depth += 1,
+                        b'}' | b']' => {
+                            self.depth = self
+                                .depth
+                                .checked_sub(1)
+                                .ok_or(Error::ResponseFraming("unbalanced JSON array item"))?;
+                        }
+                        _ => {}
+                    }
+                    self.item.push(byte);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&self) -> Result<(), Error> {
+        match self.position {
+            Position::After => Ok(()),
+            _ => Err(Error::ResponseFraming("truncated JSON array")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::TryStreamExt;
+    use serde_json::{Value, json};
+
+    fn split(body: &[u8], chunk_size: usize) -> Result<Vec<Value>, Error> {
+        let mut splitter = ArraySplitter::default();
+        let mut items = VecDeque::new();
+        for chunk in body.chunks(chunk_size) {
+            splitter.push(chunk, &mut items)?;
+        }
+        splitter.finish()?;
+        Ok(items
+            .into_iter()
+            .map(|item| serde_json::from_slice(&item).expect("serde_json::from_slice"))
+            .collect())
+    }
+
+    #[test]
+    fn split_items_across_chunks() {
+        let body = br#" [{"Names":["/web]"],"Labels":{"a\"}":"{"}}, 42 ,"nolanv",[[]],null] "#;
+        for chunk_size in 1..body.len() {
+            assert_eq!(
+                split(body, chunk_size).expect("split"),
+                vec![
+                    json!({"Names": ["/web]"], "Labels": {"a\"}": "{"}}),
+                    json!(42),
+                    json!("nolanv"),
+                    json!([[]]),
+                    Value::Null
+                ]
+            );
+        }
+        assert!(split(b"[ ]", 1).expect("split").is_empty());
+    }
+
+    #[test]
+    fn invalid_arrays() {
+        for body in [
+            &b"{}"[..],
+            b"[1,]",
+            b"[,1]",
+            b"[1,,2]",
+            b"[1] 2",
+            b"[1, 2",
+            b"[1}]",
+        ] {
+            assert!(
+                matches!(split(body, 2), Err(Error::ResponseFraming(_))),
+                "{}",
+                String::from_utf8_lossy(body)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_large_array() {
+        const LEN: usize = 20_000;
+        let server = crate::testing::MockServer::start(axum::Router::new().route(
+            "/containers/json",
+            axum::routing::get(|| async {
+                let containers: Vec<Value> = (0..LEN)
+                    .map(|i| json!({"Id": i, "Names": [format!("/web-{}", i)]}))
+                    .collect();
+                axum::Json(containers)
+            }),
+        ))
+        .await
+        .expect("MockServer::start");
+        let mut client = server.client().await.expect("server.client");
+
+        let containers: Vec<Value> = client
+            .stream_json_array("/containers/json", Method::GET, &[], None)
+            .await
+            .expect("client.stream_json_array")
+            .try_collect()
+            .await
+            .expect("try_collect");
+
+        assert_eq!(containers.len(), LEN);
+        assert_eq!(containers[LEN - 1]["Names"][0], "/web-19999");
+    }
+}
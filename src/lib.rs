@@ -121,17 +121,191 @@
 //!     assert_eq!(response.hello, "nolanv");
 //! }
 //! ```
+//! ### Dump raw socket traffic
+//! ```rust
+//! use http_client_unix_domain_socket::{ClientUnix, Direction};
+//!
+//! pub async fn debug_traffic() {
+//!     let _client = ClientUnix::builder("/tmp/unix.socket")
+//!         .wire_dump(|direction: Direction, bytes: &[u8]| {
+//!             eprintln!("{:?}: {}", direction, String::from_utf8_lossy(bytes))
+//!         })
+//!         .build()
+//!         .await
+//!         .expect("ClientUnixBuilder::build");
+//! }
+//! ```
 //! ## Feature flags
-//! - `json`: Add `send_request_json` which enable automatic parsing of request/response body with `serde_json` and add `Content-Type` header.
+//! - `json`: Add `send_request_json` which enable automatic parsing of request/response body with `serde_json` and add `Content-Type` header. Unsuccessful `application/problem+json` responses are parsed into [`ProblemDetails`](crate::ProblemDetails). Add `graphql` which sends a GraphQL query and returns its `data` or its typed errors. Add `Endpoint::query_struct` which appends the query parameters serialized from a struct. Add [`ClientConfig`](crate::ClientConfig), the settings of a client deserializable from a configuration file, and `ClientUnix::from_config`.
+//! - `simd-json`: Decode the responses of `send_request_json` and of the typed wrappers with `simd-json`, faster for large bodies such as the Docker or Podman lists, falling back to `serde_json` for the bodies it rejects.
+//! - `docker`: Add [`Docker`](crate::docker::Docker), typed wrappers of the most used Docker Engine API endpoints (containers, images, version, info) on `/var/run/docker.sock`, see [`docker`](crate::docker).
+//! - `har`: Add [`HarRecorder`](crate::har::HarRecorder) which records the traffic (headers, timings, truncated bodies) to be exported as a HAR file.
+//! - `metrics`: Record request counts, latencies, in-flight requests and reconnections through the `metrics` crate facade, see [`metrics`](crate::metrics).
+//! - `tracing`: Emit connection, request and error events inside a `request` span with the `tracing` crate.
+//! - `log`: Emit the same connection, request and error events with the `log` crate, for applications not using `tracing`.
+//! - `lxd`: Add [`Lxd`](crate::lxd::Lxd), typed wrappers of the LXD REST API (server, instances, operations) and `exec` with its stdio websockets, see [`lxd`](crate::lxd).
+//! - `opentelemetry`: Add `ClientUnixBuilder::propagate_trace_context` and `TraceContextPropagation` which inject the current OpenTelemetry context (W3C `traceparent`/`tracestate`, B3, ...) into every request.
+//! - `digest`: Add `ClientUnixBuilder::digest_auth` which answers HTTP Digest authentication challenges.
+//! - `podman`: Add [`Podman`](crate::podman::Podman), typed wrappers of the Podman libpod API (pods, containers, info) with the rootless socket discovery, see [`podman`](crate::podman).
+//! - `prometheus`: Add `ClientUnix::scrape_prometheus` which parses a Prometheus text format endpoint, see [`prometheus`](crate::prometheus).
+//! - `snapd`: Add [`Snapd`](crate::snapd::Snapd), typed wrappers of the snapd `/v2` API (snaps, actions, changes) with its envelope format, see [`snapd`](crate::snapd).
+//! - `firecracker`: Add [`Firecracker`](crate::firecracker::Firecracker), typed wrappers of the Firecracker VMM API (machine config, boot source, drives, network interfaces, actions), see [`firecracker`](crate::firecracker).
+//! - `cloud-hypervisor`: Add [`CloudHypervisor`](crate::cloud_hypervisor::CloudHypervisor), typed wrappers of the cloud-hypervisor API (vm.create, vm.boot, vm.resize, snapshot/restore, ...), see [`cloud_hypervisor`](crate::cloud_hypervisor).
+//! - `caddy`: Add [`Caddy`](crate::caddy::Caddy), typed wrappers of the Caddy admin API (load, traversal of the configuration, upstreams) with its error bodies, see [`caddy`](crate::caddy).
+//! - `consul`: Add [`Consul`](crate::consul::Consul), typed wrappers of the Consul agent API (services, checks, KV store), see [`consul`](crate::consul).
+//! - `pagination`: Add `ClientUnix::paginate` which streams the deserialized pages of an endpoint, following `Link: rel="next"` headers or cursor query parameters, see [`pagination`](crate::pagination).
+//! - `json-stream`: Add `ClientUnix::stream_json_array` which deserializes the items of a JSON array response as they arrive, without holding the whole document in memory, see [`json_stream`](crate::json_stream).
+//! - `multipart`: Add `ClientUnix::stream_multipart` which reads the parts (headers and body) of a `multipart/byteranges` or `multipart/mixed` response as they arrive, see [`multipart`](crate::multipart).
+//! - `connector`: Add [`UnixConnector`](crate::connector::UnixConnector), a connector of the `hyper_util` legacy client, so the clients generated for `hyper` (OpenAPI generator) can use the socket, see [`connector`](crate::connector).
+//! - `cli`: Build the `ucurl` binary, a `curl --unix-socket` like client (method, headers, data, output file, `--verbose` wire dump) built on this crate.
+//! - `server`: Add [`ServerUnix`](crate::ServerUnix) which serves an `axum` `Router` on a unix socket, with the mode and owner of the socket file, stale socket removal and graceful shutdown.
+//! - `bridge`: Add [`TcpBridge`](crate::TcpBridge), an HTTP proxy on a TCP port forwarding to a unix socket through a `ClientUnix`, with an optional allow-list of paths, and [`UnixForwarder`](crate::UnixForwarder) forwarding the connections of a unix socket to a TCP server.
+//! - `test-util`: Add [`MockServer`](crate::testing::MockServer), an `axum` server on a unique unix socket with wiremock-like mocks (matchers, responses, expected calls) to test the code using `ClientUnix`, a fault injection proxy, `assert_status!`/`assert_json_body!`, unique temporary socket paths removed on drop, and with `json` a record and replay proxy of a real server, see [`testing`](crate::testing).
+//! - `spool`: Add `ClientUnix::send_request_spooled` which keeps the response body in memory up to `ClientUnixBuilder::spool_threshold` and writes the remainder to a temporary file, see [`spool`](crate::spool).
+//! - `signing`: Add [`HmacSigner`](crate::HmacSigner), an interceptor signing the method, path and body of every request with an HMAC header.
+//! - `integrity`: Add `ClientUnixBuilder::verify_content_digest` which checks the response bodies against their `Content-Digest` (SHA-256, SHA-512) or `Content-MD5` header.
 
+mod auth;
+mod batch;
+mod body_size;
+#[cfg(any(test, feature = "bridge"))]
+mod bridge;
+mod buffer_pool;
+mod builder;
+#[cfg(feature = "caddy")]
+pub mod caddy;
 mod client;
+#[cfg(feature = "cloud-hypervisor")]
+pub mod cloud_hypervisor;
+mod coalesce;
+#[cfg(feature = "json")]
+mod config;
+#[cfg(feature = "connector")]
+pub mod connector;
+#[cfg(feature = "consul")]
+pub mod consul;
+#[cfg(feature = "json")]
+mod content_type;
+mod deadline;
+#[cfg(feature = "digest")]
+mod digest;
+#[cfg(feature = "docker")]
+pub mod docker;
+mod endpoint;
 mod error;
+#[cfg(feature = "firecracker")]
+pub mod firecracker;
+mod global;
+#[cfg(feature = "json")]
+mod graphql;
+#[cfg(feature = "har")]
+pub mod har;
+mod health;
+mod history;
+mod hooks;
+mod http_client;
+#[cfg(feature = "integrity")]
+mod integrity;
+mod interceptor;
+#[cfg(feature = "json-stream")]
+pub mod json_stream;
+#[cfg(feature = "lxd")]
+pub mod lxd;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "multipart")]
+pub mod multipart;
+#[cfg(feature = "opentelemetry")]
+mod otel;
+#[cfg(feature = "pagination")]
+pub mod pagination;
+#[cfg(feature = "podman")]
+pub mod podman;
+mod pool;
+#[cfg(feature = "json")]
+mod problem;
+mod progress;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+mod query;
+mod rate_limit;
+mod redact;
+mod registry;
+mod request_id;
+mod response_cache;
+mod resume;
+mod retry;
+#[cfg(any(test, feature = "server"))]
+mod server;
+mod sharded;
+#[cfg(feature = "signing")]
+mod signing;
+#[cfg(feature = "snapd")]
+pub mod snapd;
+#[cfg(feature = "spool")]
+pub mod spool;
+mod stats;
+mod status;
+mod template;
 #[cfg(test)]
 pub mod test_helpers;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+mod text;
+mod timings;
+mod wire;
 
+pub use auth::{BasicAuth, BearerAuth, TokenProvider};
+pub use axum_core::body::Body;
+pub use batch::{BatchRequest, FanOut};
+pub use body_size::BodySize;
+#[cfg(any(test, feature = "bridge"))]
+pub use bridge::{TcpBridge, TcpBridgeBuilder, UnixForwarder};
+pub use buffer_pool::BufferPool;
+pub use builder::ClientUnixBuilder;
 pub use client::ClientUnix;
+pub use coalesce::RequestCoalescer;
+#[cfg(feature = "json")]
+pub use config::{ClientConfig, RetryConfig};
 #[cfg(feature = "json")]
-pub use error::ErrorAndResponseJson;
-pub use error::{Error, ErrorAndResponse};
+pub use content_type::ContentTypeCheck;
+pub use deadline::DeadlineFormat;
+pub use endpoint::Endpoint;
+#[cfg(feature = "json")]
+pub use error::{ApiError, ErrorAndResponseJson};
+pub use error::{Error, ErrorAndResponse, ErrorContext, ErrorKind};
+#[cfg(feature = "json")]
+pub use graphql::{ErrorAndResponseGraphQl, GraphQlError, GraphQlLocation};
+#[cfg(feature = "har")]
+pub use har::HarRecorder;
+pub use history::{RequestHistory, RequestRecord};
+pub use hooks::{ErrorEvent, RequestEvent, ResponseEvent};
+pub use http_client::UnixHttpClient;
 pub use hyper::Method;
 pub use hyper::StatusCode;
+pub use hyper::body::Incoming;
+pub use hyper::header;
+pub use hyper::http::uri;
+pub use hyper::{Request, Response};
+pub use interceptor::Interceptor;
+#[cfg(feature = "opentelemetry")]
+pub use otel::TraceContextPropagation;
+pub use pool::{ClientPool, PoolSizing, PooledClient};
+#[cfg(feature = "json")]
+pub use problem::ProblemDetails;
+pub use progress::{Progress, ProgressBody};
+pub use registry::ClientRegistry;
+pub use request_id::RequestId;
+pub use response_cache::ResponseCache;
+pub use retry::{RetryBudget, RetryPolicy};
+#[cfg(any(test, feature = "server"))]
+pub use server::{ServerUnix, ServerUnixBuilder};
+pub use sharded::ShardedClient;
+#[cfg(feature = "signing")]
+pub use signing::{HmacAlgorithm, HmacSigner};
+pub use stats::ConnectionStats;
+pub use status::ConnectionStatus;
+pub use template::RequestTemplate;
+pub use text::ResponseText;
+pub use timings::RequestTimings;
+pub use wire::{Direction, WireSink};
@@ -0,0 +1,337 @@
+//! Typed wrappers of the [LXD REST API](https://documentation.ubuntu.com/lxd/en/latest/rest-api/)
+//! (feature=lxd).
+//!
+//! LXD answers with an envelope whose `metadata` is the actual result, and long running tasks
+//! return an operation to wait for. [`Lxd::exec`] also connects the stdio websockets of the
+//! command, each on its own connection to the socket.
+//!
+//! ```rust,no_run
+//! use http_client_unix_domain_socket::lxd::{ExecRequest, Lxd};
+//!
+//! # async fn run() {
+//! let mut lxd = Lxd::connect_default().await.expect("Lxd::connect_default");
+//! let session = lxd
+//!     .exec("nolanv", &ExecRequest::new(["uname", "-a"]))
+//!     .await
+//!     .expect("lxd.exec");
+//! let return_code = session.wait(&mut lxd).await.expect("session.wait");
+//! # }
+//! ```
+
+use crate::{ClientUnix, Error, ErrorAndResponseJson, Method};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use tokio::net::UnixStream;
+use tokio_tungstenite::{WebSocketStream, client_async, tungstenite::client::IntoClientRequest};
+
+/// Socket of the snap package, the most common installation.
+pub const SNAP_SOCKET_PATH: &str = "/var/snap/lxd/common/lxd/unix.socket";
+/// Socket of the distribution packages.
+pub const SOCKET_PATH: &str = "/var/lib/lxd/unix.socket";
+
+/// Websocket of a command stdio, or of its control channel.
+pub type WebSocket = WebSocketStream<UnixStream>;
+
+/// Body of the LXD error responses.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LxdError {
+    /// HTTP status code.
+    pub error_code: u16,
+    pub error: String,
+}
+
+pub type LxdResult<T> = Result<T, ErrorAndResponseJson<LxdError>>;
+
+/// Envelope of every successful response.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Envelope<T> {
+    /// `sync` or `async`.
+    #[serde(rename = "type")]
+    pub response_type: String,
+    pub status: String,
+    pub status_code: u16,
+    /// Url of the operation of an `async` response.
+    #[serde(default)]
+    pub operation: String,
+    pub metadata: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Server {
+    pub api_version: String,
+    pub api_status: String,
+    /// `trusted` over the unix socket.
+    pub auth: String,
+    #[serde(default)]
+    pub environment: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Instance {
+    pub name: String,
+    pub status: String,
+    pub status_code: u16,
+    /// `container` or `virtual-machine`.
+    #[serde(rename = "type")]
+    pub instance_type: String,
+    #[serde(default)]
+    pub architecture: String,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+/// Background operation, see [`Lxd::wait_operation`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Operation {
+    pub id: String,
+    /// `task`, `websocket` or `token`.
+    pub class: String,
+    #[serde(default)]
+    pub description: String,
+    pub status: String,
+    pub status_code: u16,
+    #[serde(default)]
+    pub metadata: Value,
+    /// Error of a failed operation.
+    #[serde(default)]
+    pub err: String,
+}
+
+impl Operation {
+    pub fn is_running(&self) -> bool {
+        matches!(self.status.as_str(), "Pending" | "Running")
+    }
+}
+
+/// Body of [`Lxd::exec`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExecRequest {
+    pub command: Vec<String>,
+    pub environment: HashMap<String, String>,
+    /// A single websocket for a PTY instead of separate stdin, stdout and stderr ones.
+    pub interactive: bool,
+    wait_for_websocket: bool,
+}
+
+impl ExecRequest {
+    pub fn new<S: Into<String>>(command: impl IntoIterator<Item = S>) -> Self {
+        ExecRequest {
+            command: command.into_iter().map(Into::into).collect(),
+            environment: HashMap::new(),
+            interactive: false,
+            wait_for_websocket: true,
+        }
+    }
+}
+
+/// Running command of [`Lxd::exec`]. The websockets carry the raw bytes of each stream in binary
+/// messages, and the command starts once they are all connected.
+pub struct ExecSession {
+    /// Id of the operation of the command.
+    pub operation: String,
+    /// The PTY of an interactive command.
+    pub stdin: WebSocket,
+    /// `None` for an interactive command.
+    pub stdout: Option<WebSocket>,
+    /// `None` for an interactive command.
+    pub stderr: Option<WebSocket>,
+    /// Window resizes and signals, see the LXD documentation.
+    pub control: WebSocket,
+}
+
+impl ExecSession {
+    /// Wait for the command to exit and return its exit code.
+    pub async fn wait(self, lxd: &mut Lxd) -> LxdResult<i64> {
+        let operation = lxd.wait_operation(&self.operation, None).await?;
+        Ok(operation
+            .metadata
+            .get("return")
+            .and_then(Value::as_i64)
+            .unwrap_or(-1))
+    }
+}
+
+/// `$LXD_DIR/unix.socket` if set, else the first existing of [`SNAP_SOCKET_PATH`] and
+/// [`SOCKET_PATH`].
+pub fn default_socket_path() -> PathBuf {
+    if let Some(dir) = std::env::var_os("LXD_DIR") {
+        return Path::new(&dir).join("unix.socket");
+    }
+    match Path::new(SNAP_SOCKET_PATH).exists() {
+        true => PathBuf::from(SNAP_SOCKET_PATH),
+        false => PathBuf::from(SOCKET_PATH),
+    }
+}
+
+/// LXD REST API client.
+pub struct Lxd {
+    client: ClientUnix,
+    socket_path: PathBuf,
+}
+
+impl Lxd {
+    pub fn new(client: ClientUnix) -> Self {
+        let (socket_path, _) = client.connection_settings();
+        Lxd {
+            client,
+            socket_path,
+        }
+    }
+
+    /// Connect to [`default_socket_path`].
+    pub async fn connect_default() -> Result<Self, Error> {
+        let socket_path = default_socket_path();
+        Ok(Lxd::new(
+            ClientUnix::try_new(&socket_path.to_string_lossy()).await?,
+        ))
+    }
+
+    /// The underlying client, for the endpoints without a wrapper.
+    pub fn client(&mut self) -> &mut ClientUnix {
+        &mut self.client
+    }
+
+    pub async fn server(&mut self) -> LxdResult<Server> {
+        self.get("/1.0").await
+    }
+
+    pub async fn instances(&mut self) -> LxdResult<Vec<Instance>> {
+        self.get("/1.0/instances?recursion=1").await
+    }
+
+    pub async fn operation(&mut self, id: &str) -> LxdResult<Operation> {
+        self.get(&format!("/1.0/operations/{}", id)).await
+    }
+
+    /// Wait for the operation to complete, for at most `timeout` seconds.
+    pub async fn wait_operation(&mut self, id: &str, timeout: Option<u64>) -> LxdResult<Operation> {
+        let timeout = timeout.map_or(-1, |timeout| timeout as i64);
+        self.get(&format!("/1.0/operations/{}/wait?timeout={}", id, timeout))
+            .await
+    }
+
+    /// Run `request` in `instance` and connect the websockets of its stdio.
+    pub async fn exec(&mut self, instance: &str, request: &ExecRequest) -> LxdResult<ExecSession> {
+        let (_, envelope) = self
+            .client
+            .send_request_json::<_, Envelope<Operation>, _>(
+                &format!("/1.0/instances/{}/exec", instance),
+                Method::POST,
+                &[],
+                Some(request),
+            )
+            .await?;
+        let operation = envelope.metadata;
+        let secret = |fd: &str| {
+            operation
+                .metadata
+                .pointer(&format!("/fds/{}", fd))
+                .and_then(Value::as_str)
+                .map(String::from)
+        };
+        let (stdin, stdout, stderr, control) =
+            (secret("0"), secret("1"), secret("2"), secret("control"));
+
+        let stdin = self.websocket(&operation.id, stdin).await?;
+        let stdout = match stdout {
+            Some(secret) => Some(self.websocket(&operation.id, Some(secret)).await?),
+            None => None,
+        };
+        let stderr = match stderr {
+            Some(secret) => Some(self.websocket(&operation.id, Some(secret)).await?),
+            None => None,
+        };
+        let control = self.websocket(&operation.id, control).await?;
+        Ok(ExecSession {
+            operation: operation.id,
+            stdin,
+            stdout,
+            stderr,
+            control,
+        })
+    }
+
+    /// Connect the websocket of an operation on a new connection to the socket.
+    async fn websocket(&self, operation: &str, secret: Option<String>) -> LxdResult<WebSocket> {
+        let secret = secret.ok_or(ErrorAndResponseJson::InternalError(Error::ResponseFraming(
+            "missing websocket secret",
+        )))?;
+        let internal = |e| ErrorAndResponseJson::InternalError(e);
+        let stream = UnixStream::connect(&self.socket_path)
+            .await
+            .map_err(|e| internal(Error::SocketConnectionInitiation(e)))?;
+        let request = format!(
+            "ws://unix.socket/1.0/operations/{}/websocket?secret={}",
+            operation, secret
+        )
+        .into_client_request()
+        .map_err(|e| internal(Error::WebSocket(Box::new(e))))?;
+        let (websocket, _) = client_async(request, stream)
+            .await
+            .map_err(|e| internal(Error::WebSocket(Box::new(e))))?;
+        Ok(websocket)
+    }
+
+    async fn get<OUT: DeserializeOwned>(&mut self, endpoint: &str) -> LxdResult<OUT> {
+        self.client
+            .send_request_json::<(), Envelope<OUT>, _>(endpoint, Method::GET, &[], None)
+            .await
+            .map(|(_, envelope)| envelope.metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StatusCode, test_helpers::server::Server, test_helpers::util::*};
+    use futures_util::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    async fn make_lxd(test_function: &str) -> (Server, Lxd) {
+        let socket_path = make_socket_path_test("lxd", test_function);
+        let server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let client = ClientUnix::try_new(&socket_path)
+            .await
+            .expect("ClientUnix::try_new");
+        (server, Lxd::new(client))
+    }
+
+    #[tokio::test]
+    async fn exec_over_websockets() {
+        let (_server, mut lxd) = make_lxd("exec_over_websockets").await;
+
+        let mut session = lxd
+            .exec("nolanv", &ExecRequest::new(["echo", "hello nolanv"]))
+            .await
+            .expect("lxd.exec");
+        let mut stdout = Vec::new();
+        while let Some(Ok(message)) = session.stdout.as_mut().unwrap().next().await {
+            if let Message::Binary(data) = message {
+                stdout.extend_from_slice(&data);
+            }
+        }
+
+        assert_eq!(stdout, b"hello nolanv\n");
+        assert_eq!(session.wait(&mut lxd).await.expect("session.wait"), 0);
+    }
+
+    #[tokio::test]
+    async fn error_envelope() {
+        let (_server, mut lxd) = make_lxd("error_envelope").await;
+
+        let result = lxd.operation("missing").await;
+
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponseJson::ResponseUnsuccessful(StatusCode::NOT_FOUND, e))
+                if e.error == "Operation not found"
+        ));
+    }
+}
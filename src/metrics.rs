@@ -0,0 +1,75 @@
+//! Metrics recorded through the [`metrics`](https://docs.rs/metrics) facade (feature=metrics).
+//!
+//! Install any `metrics` recorder (Prometheus exporter, statsd, ...) in your application and
+//! the following metrics will be reported:
+//! - [`REQUESTS_TOTAL`]: counter of requests, labelled by `status_class` (`2xx`, `4xx`, ..., `error`)
+//! - [`REQUEST_DURATION_SECONDS`]: histogram of request latencies, labelled by `status_class`
+//! - [`REQUESTS_IN_FLIGHT`]: gauge of requests currently being sent or awaiting a response
+//! - [`RECONNECTS_TOTAL`]: counter of reconnections, labelled by `result` (`success`, `failure`)
+
+use hyper::StatusCode;
+use std::time::{Duration, Instant};
+
+pub const REQUESTS_TOTAL: &str = "http_client_unix_domain_socket_requests_total";
+pub const REQUEST_DURATION_SECONDS: &str =
+    "http_client_unix_domain_socket_request_duration_seconds";
+pub const REQUESTS_IN_FLIGHT: &str = "http_client_unix_domain_socket_requests_in_flight";
+pub const RECONNECTS_TOTAL: &str = "http_client_unix_domain_socket_reconnects_total";
+
+fn status_class(status_code: Option<StatusCode>) -> &'static str {
+    match status_code.map(|s| s.as_u16() / 100) {
+        Some(1) => "1xx",
+        Some(2) => "2xx",
+        Some(3) => "3xx",
+        Some(4) => "4xx",
+        Some(5) => "5xx",
+        _ => "error",
+    }
+}
+
+/// Track one request: increment the in-flight gauge on creation and decrement it on drop.
+pub(crate) struct RequestMetrics {
+    start: Instant,
+}
+
+impl RequestMetrics {
+    pub(crate) fn start() -> Self {
+        ::metrics::gauge!(REQUESTS_IN_FLIGHT).increment(1);
+        RequestMetrics {
+            start: Instant::now(),
+        }
+    }
+
+    /// `None` means the request failed before a status code was received.
+    pub(crate) fn finish(self, status_code: Option<StatusCode>) {
+        let elapsed: Duration = self.start.elapsed();
+        let status_class = status_class(status_code);
+        ::metrics::counter!(REQUESTS_TOTAL, "status_class" => status_class).increment(1);
+        ::metrics::histogram!(REQUEST_DURATION_SECONDS, "status_class" => status_class)
+            .record(elapsed.as_secs_f64());
+    }
+}
+
+impl Drop for RequestMetrics {
+    fn drop(&mut self) {
+        ::metrics::gauge!(REQUESTS_IN_FLIGHT).decrement(1);
+    }
+}
+
+pub(crate) fn record_reconnect(success: bool) {
+    let result = if success { "success" } else { "failure" };
+    ::metrics::counter!(RECONNECTS_TOTAL, "result" => result).increment(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_classes() {
+        assert_eq!(status_class(Some(StatusCode::OK)), "2xx");
+        assert_eq!(status_class(Some(StatusCode::NOT_FOUND)), "4xx");
+        assert_eq!(status_class(Some(StatusCode::BAD_GATEWAY)), "5xx");
+        assert_eq!(status_class(None), "error");
+    }
+}
@@ -0,0 +1,394 @@
+//! Read the parts of a multipart response (feature=multipart), as `multipart/byteranges` or
+//! `multipart/mixed`, as they arrive.
+//!
+//! ```rust,no_run
+//! use futures_util::TryStreamExt;
+//! use http_client_unix_domain_socket::{ClientUnix, Method};
+//!
+//! # async fn run() {
+//! let mut client = ClientUnix::try_new("/run/storage.sock")
+//!     .await
+//!     .expect("ClientUnix::try_new");
+//! let mut ranges = client
+//!     .stream_multipart(
+//!         "/objects/nolanv",
+//!         Method::GET,
+//!         &[("Range", "bytes=0-49,100-149")],
+//!         None,
+//!     )
+//!     .await
+//!     .expect("client.stream_multipart");
+//! while let Some(range) = ranges.try_next().await.expect("ranges.try_next") {
+//!     println!("{:?}: {} bytes", range.headers.get("content-range"), range.body.len());
+//! }
+//! # }
+//! ```
+
+use crate::{ClientUnix, Error, ErrorAndResponse, Method};
+use axum_core::body::Body;
+use futures_core::Stream;
+use http_body_util::BodyExt;
+use hyper::{
+    body::{Bytes, Incoming},
+    header::{CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue},
+};
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Part of a multipart response, with its own headers, such as the `Content-Range` of a
+/// `multipart/byteranges` response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+/// Parts of [`ClientUnix::stream_multipart`], ending after the last one or the first error.
+pub struct Multipart(Pin<Box<dyn Stream<Item = Result<Part, Error>> + Send>>);
+
+impl Stream for Multipart {
+    type Item = Result<Part, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.as_mut().poll_next(cx)
+    }
+}
+
+impl ClientUnix {
+    /// Send the request and read the parts of its multipart response one at a time, as the body
+    /// arrives. Only the part being read is kept in memory. The preamble and epilogue of the
+    /// body are ignored.
+    ///
+    /// Fails with [`Error::ResponseFraming`] when the response has no multipart boundary.
+    /// As [`send_request_streaming`](Self::send_request_streaming), unsuccessful responses are
+    /// collected into [`ErrorAndResponse::ResponseUnsuccessful`].
+    pub async fn stream_multipart(
+        &mut self,
+        endpoint: &str,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Body>,
+    ) -> Result<Multipart, ErrorAndResponse> {
+        let response = self
+            .send_request_streaming(endpoint, method, headers, body_request)
+            .await?;
+        let boundary = boundary(response.headers()).ok_or_else(|| {
+            ErrorAndResponse::InternalError(Error::ResponseFraming("missing multipart boundary"))
+        })?;
+        let state = MultipartState {
+            body: Some(response.into_body()),
+            splitter: PartSplitter::new(&boundary),
+            parts: VecDeque::new(),
+        };
+        Ok(Multipart(Box::pin(futures_util::stream::unfold(
+            state, next_part,
+        ))))
+    }
+}
+
+/// The `boundary` parameter of a `multipart/*` content type.
+fn boundary(headers: &HeaderMap) -> Option<String> {
+    let content_type = headers.get(CONTENT_TYPE)?.to_str().ok()?;
+    let mut params = content_type.split(';');
+    let essence = params.next()?.trim();
+    if !essence
+        .get(..10)
+        .is_some_and(|kind| kind.eq_ignore_ascii_case("multipart/"))
+    {
+        return None;
+    }
+    params.find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+        (name.trim().eq_ignore_ascii_case("boundary") && !value.is_empty())
+            .then(|| value.to_string())
+    })
+}
+
+struct MultipartState {
+    /// `None` once read to the end or failed.
+    body: Option<Incoming>,
+    splitter: PartSplitter,
+    parts: VecDeque<Part>,
+}
+
+async fn next_part(mut state: MultipartState) -> Option<(Result<Part, Error>, MultipartState)> {
+    loop {
+        if let Some(part) = state.parts.pop_front() {
+            return Some((Ok(part), state));
+        }
+        let frame = state.body.as_mut()?.frame().await;
+        let result = match frame {
+            Some(Ok(frame)) => match frame.into_data() {
+                Ok(data) => state.splitter.push(&data, &mut state.parts),
+                Err(_) => Ok(()),
+            },
+            Some(Err(e)) => Err(Error::ResponseCollect(e)),
+            None => {
+                state.body = None;
+                state.splitter.finish()
+            }
+        };
+        if let Err(e) = result {
+            state.body = None;
+            state.parts.clear();
+            return Some((Err(e), state));
+        }
+    }
+}
+
+/// Position in the body, relative to the parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Position {
+    Preamble,
+    /// After a delimiter, before the end of its line.
+    Delimiter,
+    Headers,
+    Body,
+    Epilogue,
+}
+
+/// Splits a multipart body into its parts, whatever the chunks it arrives in.
+#[derive(Debug)]
+struct PartSplitter {
+    /// `\r\n--` and the boundary.
+    delimiter: Vec<u8>,
+    position: Position,
+    buffer: Vec<u8>,
+    /// Start of the search for the next delimiter in `buffer`.
+    searched: usize,
+    headers: HeaderMap,
+}
+
+impl PartSplitter {
+    fn new(boundary: &str) -> Self {
+        PartSplitter {
+            delimiter: [b"\r\n--", boundary.as_bytes()].concat(),
+            // The first delimiter may start the body, without the line break
+            position: Position::Preamble,
+            buffer: b"\r\n".to_vec(),
+            searched: 0,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    fn push(&mut self, chunk: &[u8], parts: &mut VecDeque<Part>) -> Result<(), Error> {
+        self.buffer.extend_from_slice(chunk);
+        loop {
+            let consumed = match self.position {
+                Position::Preamble | Position::Body => {
+                    let Some(start) = self.find_delimiter() else {
+                        break;
+                    };
+                    if self.position == Position::Body {
+                        parts.push_back(Part {
+                            headers: std::mem::take(&mut self.headers),
+                            body: Bytes::copy_from_slice(&self.buffer[..start]),
+                        });
+                    }
+                    self.position = Position::Delimiter;
+                    start + self.delimiter.len()
+                }
+                Position::Delimiter => {
+                    if self.buffer.starts_with(b"--") {
+                        self.position = Position::Epilogue;
+                        self.buffer.clear();
+                        break;
+                    }
+                    let Some(end) = find(&self.buffer, b"\r\n") else {
+                        break;
+                    };
+                    if !self.buffer[..end].iter().all(|&b| b == b' ' || b == b'\t') {
+                        return Err(Error::ResponseFraming("invalid multipart delimiter"));
+                    }
+                    self.position = Position::Headers;
+                    end + 2
+                }
+                Position::Headers => {
+                    let Some(end) = find(&self.buffer, b"\r\n") else {
+                        break;
+                    };
+                    match end {
+                        0 => self.position = Position::Body,
+                        _ => {
+                            let (name, value) = parse_header(&self.buffer[..end])?;
+                            self.headers.append(name, value);
+                        }
+                    }
+                    end + 2
+                }
+                Position::Epilogue => {
+                    self.buffer.clear();
+                    break;
+                }
+            };
+            self.buffer.drain(..consumed);
+            self.searched = 0;
+        }
+        Ok(())
+    }
+
+    /// Start of the next delimiter in the buffer, which must be followed by two bytes to tell
+    /// whether it is the last one.
+    fn find_delimiter(&mut self) -> Option<usize> {
+        let start = find(&self.buffer[self.searched..], &self.delimiter)
+            .map(|start| self.searched + start)
+            .filter(|start| self.buffer.len() >= start + self.delimiter.len() + 2);
+        if start.is_none() {
+            // A delimiter may still start in the last bytes
+            self.searched = self
+                .buffer
+                .len()
+                .saturating_sub(self.delimiter.len() + 1)
+                .max(self.searched);
+        }
+        start
+    }
+
+    fn finish(&self) -> Result<(), Error> {
+        match self.position {
+            Position::Epilogue => Ok(()),
+            _ => Err(Error::ResponseFraming("truncated multipart body")),
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn parse_header(line: &[u8]) -> Result<(HeaderName, HeaderValue), Error> {
+    let invalid = || Error::ResponseFraming("invalid multipart part header");
+    let colon = line.iter().position(|&b| b == b':').ok_or_else(invalid)?;
+    let name = HeaderName::from_bytes(&line[..colon]).map_err(|_| invalid())?;
+    let value = HeaderValue::from_bytes(line[colon + 1..].trim_ascii()).map_err(|_| invalid())?;
+    Ok((name, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::TryStreamExt;
+
+    const BODY: &[u8] = b"preamble\r\n--THIS_STRING_SEPARATES\r\n\
+        Content-Type: application/pdf\r\n\
+        Content-Range: bytes 500-999/8000\r\n\r\n\
+        ...the first range...\r\n\
+        --THIS_STRING_SEPARATES \r\n\
+        Content-Range: bytes 7000-7999/8000\r\n\r\n\
+        ...the\r\n--THIS second range\r\n\
+        --THIS_STRING_SEPARATES--\r\nepilogue";
+
+    fn split(boundary: &str, body: &[u8], chunk_size: usize) -> Result<Vec<Part>, Error> {
+        let mut splitter = PartSplitter::new(boundary);
+        let mut parts = VecDeque::new();
+        for chunk in body.chunks(chunk_size) {
+            splitter.push(chunk, &mut parts)?;
+        }
+        splitter.finish()?;
+        Ok(parts.into())
+    }
+
+    #[test]
+    fn split_parts_across_chunks() {
+        for chunk_size in 1..BODY.len() {
+            let parts = split("THIS_STRING_SEPARATES", BODY, chunk_size).expect("split");
+            assert_eq!(parts.len(), 2);
+            assert_eq!(parts[0].headers["content-type"], "application/pdf");
+            assert_eq!(parts[0].headers["content-range"], "bytes 500-999/8000");
+            assert_eq!(parts[0].body, "...the first range...");
+            assert_eq!(parts[1].headers.len(), 1);
+            assert_eq!(parts[1].body, "...the\r\n--THIS second range");
+        }
+
+        let parts = split("b", b"--b\r\n\r\n\r\n--b--", 1).expect("split");
+        assert_eq!(parts.len(), 1);
+        assert!(parts[0].headers.is_empty() && parts[0].body.is_empty());
+    }
+
+    #[test]
+    fn invalid_bodies() {
+        for body in [
+            &b"--THIS_STRING_SEPARATES\r\nContent-Range: bytes 0-1/2\r\n\r\nno"[..],
+            b"--THIS_STRING_SEPARATES\r\nContent-Range\r\n\r\n\r\n--THIS_STRING_SEPARATES--",
+            b"--THIS_STRING_SEPARATESX\r\n\r\n\r\n--THIS_STRING_SEPARATES--",
+            b"no delimiter",
+        ] {
+            assert!(
+                matches!(
+                    split("THIS_STRING_SEPARATES", body, 3),
+                    Err(Error::ResponseFraming(_))
+                ),
+                "{}",
+                String::from_utf8_lossy(body)
+            );
+        }
+    }
+
+    #[test]
+    fn boundary_parameter() {
+        for (content_type, expected) in [
+            (
+                "multipart/byteranges; boundary=3d6b6a416f9b5",
+                Some("3d6b6a416f9b5"),
+            ),
+            (
+                "Multipart/Mixed; charset=utf-8; BOUNDARY=\"a b\"",
+                Some("a b"),
+            ),
+            ("multipart/mixed", None),
+            ("text/plain; boundary=nolanv", None),
+        ] {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+            assert_eq!(boundary(&headers).as_deref(), expected, "{}", content_type);
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_byteranges() {
+        let server = crate::testing::MockServer::start(axum::Router::new().route(
+            "/objects/nolanv",
+            axum::routing::get(|| async {
+                (
+                    [(
+                        CONTENT_TYPE,
+                        "multipart/byteranges; boundary=THIS_STRING_SEPARATES",
+                    )],
+                    BODY,
+                )
+            }),
+        ))
+        .await
+        .expect("MockServer::start");
+        let mut client = server.client().await.expect("server.client");
+
+        let parts: Vec<Part> = client
+            .stream_multipart("/objects/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.stream_multipart")
+            .try_collect()
+            .await
+            .expect("try_collect");
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[1].headers["content-range"], "bytes 7000-7999/8000");
+
+        let error = client
+            .stream_multipart("/objects/missing", Method::GET, &[], None)
+            .await
+            .err();
+        assert!(matches!(
+            error,
+            Some(ErrorAndResponse::ResponseUnsuccessful(status, _)) if status == 404
+        ));
+    }
+}
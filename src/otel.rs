@@ -0,0 +1,137 @@
+use crate::{Error, Interceptor};
+use axum_core::body::Body;
+use hyper::{
+    HeaderMap, Request,
+    header::{HeaderName, HeaderValue},
+};
+use opentelemetry::{
+    Context,
+    global::get_text_map_propagator,
+    propagation::{Injector, TextMapPropagator},
+};
+use std::sync::Arc;
+
+/// [`Interceptor`] injecting the current OpenTelemetry context into every request (feature=opentelemetry).
+///
+/// By default the globally registered propagator is used (see
+/// `opentelemetry::global::set_text_map_propagator`) so W3C `traceparent`/`tracestate`, B3, or a
+/// composite of them are sent depending on the application setup.
+#[derive(Clone, Default)]
+pub struct TraceContextPropagation {
+    propagator: Option<Arc<dyn TextMapPropagator + Send + Sync>>,
+}
+
+impl TraceContextPropagation {
+    /// Use the globally registered propagator.
+    pub fn global() -> Self {
+        TraceContextPropagation::default()
+    }
+
+    /// Use `propagator` instead of the globally registered one.
+    pub fn with_propagator(propagator: impl TextMapPropagator + Send + Sync + 'static) -> Self {
+        TraceContextPropagation {
+            propagator: Some(Arc::new(propagator)),
+        }
+    }
+
+    fn inject(&self, headers: &mut HeaderMap) {
+        let context = Context::current();
+        let mut injector = HeaderInjector(headers);
+        match &self.propagator {
+            Some(propagator) => propagator.inject_context(&context, &mut injector),
+            None => get_text_map_propagator(|propagator| {
+                propagator.inject_context(&context, &mut injector)
+            }),
+        }
+    }
+}
+
+impl Interceptor for TraceContextPropagation {
+    async fn before(&self, request: &mut Request<Body>) -> Result<(), Error> {
+        self.inject(request.headers_mut());
+        Ok(())
+    }
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, test_helpers::server::Server, test_helpers::util::*};
+    use hyper::Method;
+    use opentelemetry::{
+        propagation::{Extractor, text_map_propagator::FieldIter},
+        trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+    };
+
+    #[derive(Debug)]
+    struct TraceParent;
+
+    impl TextMapPropagator for TraceParent {
+        fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+            let span = cx.span();
+            let span_context = span.span_context();
+            injector.set(
+                "traceparent",
+                format!(
+                    "00-{}-{}-{:02x}",
+                    span_context.trace_id(),
+                    span_context.span_id(),
+                    span_context.trace_flags()
+                ),
+            );
+        }
+
+        fn extract_with_context(&self, cx: &Context, _: &dyn Extractor) -> Context {
+            cx.clone()
+        }
+
+        fn fields(&self) -> FieldIter<'_> {
+            FieldIter::new(&[])
+        }
+    }
+
+    #[tokio::test]
+    async fn traceparent_is_injected() {
+        let socket_path = make_socket_path_test("otel", "traceparent_is_injected");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .interceptor(TraceContextPropagation::with_propagator(TraceParent))
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let context = Context::new().with_remote_span_context(SpanContext::new(
+            TraceId::from(0x4bf92f3577b34da6a3ce929d0e0e4736),
+            SpanId::from(0x00f067aa0ba902b7),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        ));
+        let _guard = context.attach();
+
+        let (_, response) = client
+            .send_request("/header/traceparent", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+
+        assert_eq!(
+            response,
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".as_bytes()
+        );
+    }
+}
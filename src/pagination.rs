@@ -0,0 +1,225 @@
+//! Stream the pages of a paginated endpoint (feature=pagination).
+//!
+//! ```rust,no_run
+//! use futures_util::TryStreamExt;
+//! use http_client_unix_domain_socket::{ClientUnix, pagination::Pagination};
+//!
+//! # async fn run() {
+//! let mut client = ClientUnix::try_new("/tmp/unix.socket")
+//!     .await
+//!     .expect("ClientUnix::try_new");
+//! let pages: Vec<Vec<String>> = client
+//!     .paginate::<_, String>("/items?per_page=100", Pagination::Link)
+//!     .try_collect()
+//!     .await
+//!     .expect("client.paginate");
+//! # }
+//! ```
+
+use crate::{ClientUnix, Error, ErrorAndResponseJson, Method, query::percent_encode};
+use futures_core::Stream;
+use hyper::{HeaderMap, header::LINK};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// How to find the next page of [`ClientUnix::paginate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pagination {
+    /// Follow the `Link: <...>; rel="next"` header (RFC 8288) of each page.
+    Link,
+    /// Set the `param` query parameter to the value at the JSON pointer `pointer` of each page,
+    /// until it is missing, `null` or empty.
+    Cursor { param: String, pointer: String },
+}
+
+impl Pagination {
+    /// e.g. `Pagination::cursor("cursor", "/next_cursor")`.
+    pub fn cursor(param: &str, pointer: &str) -> Self {
+        Pagination::Cursor {
+            param: param.to_string(),
+            pointer: pointer.to_string(),
+        }
+    }
+
+    fn next_endpoint(&self, endpoint: &str, headers: &HeaderMap, page: &Value) -> Option<String> {
+        match self {
+            Pagination::Link => next_link(headers),
+            Pagination::Cursor { param, pointer } => {
+                let cursor = match page.pointer(pointer)? {
+                    Value::String(cursor) if !cursor.is_empty() => cursor.clone(),
+                    Value::Number(cursor) => cursor.to_string(),
+                    _ => return None,
+                };
+                Some(set_query_param(endpoint, param, &cursor))
+            }
+        }
+    }
+}
+
+/// Deserialized pages of [`ClientUnix::paginate`], ending after the last page or the first error.
+pub struct Pages<'a, OUT, ERR: DeserializeOwned>(
+    Pin<Box<dyn Stream<Item = Result<OUT, ErrorAndResponseJson<ERR>>> + Send + 'a>>,
+);
+
+impl<OUT, ERR: DeserializeOwned> Stream for Pages<'_, OUT, ERR> {
+    type Item = Result<OUT, ErrorAndResponseJson<ERR>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.as_mut().poll_next(cx)
+    }
+}
+
+struct PagesState<'a> {
+    client: &'a mut ClientUnix,
+    pagination: Pagination,
+    next: Option<String>,
+}
+
+impl ClientUnix {
+    /// GET `endpoint` then each following page, as described by `pagination`.
+    pub fn paginate<'a, OUT, ERR>(
+        &'a mut self,
+        endpoint: &str,
+        pagination: Pagination,
+    ) -> Pages<'a, OUT, ERR>
+    where
+        OUT: DeserializeOwned + Send + 'a,
+        ERR: DeserializeOwned + Send + 'a,
+    {
+        let state = PagesState {
+            client: self,
+            pagination,
+            next: Some(endpoint.to_string()),
+        };
+        Pages(Box::pin(futures_util::stream::unfold(
+            state,
+            |mut state| async move {
+                let endpoint = state.next.take()?;
+                let response = match state
+                    .client
+                    .send_request_json_full::<(), Value, ERR>(&endpoint, Method::GET, &[], None)
+                    .await
+                {
+                    Ok(response) => response,
+                    Err(e) => return Some((Err(e), state)),
+                };
+                state.next =
+                    state
+                        .pagination
+                        .next_endpoint(&endpoint, response.headers(), response.body());
+                let page = response.into_body();
+                let page = serde_json::from_value(page.clone()).map_err(|e| {
+                    ErrorAndResponseJson::InternalError(Error::ResponseParsing(
+                        e,
+                        page.to_string().into_bytes(),
+                    ))
+                });
+                Some((page, state))
+            },
+        )))
+    }
+}
+
+/// Path and query of the `rel="next"` target of the `Link` headers.
+fn next_link(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(LINK)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .find_map(|link| {
+            let mut parts = link.split(';');
+            let target = parts.next()?.trim().strip_prefix('<')?.strip_suffix('>')?;
+            parts
+                .filter_map(|param| param.trim().strip_prefix("rel="))
+                .any(|rel| {
+                    rel.trim_matches('"')
+                        .split_whitespace()
+                        .any(|rel| rel == "next")
+                })
+                .then(|| path_and_query(target).to_string())
+        })
+}
+
+/// Strip the scheme and authority of an absolute target, the socket is the authority.
+fn path_and_query(target: &str) -> &str {
+    match target.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |path| &rest[path..]),
+        None => target,
+    }
+}
+
+fn set_query_param(endpoint: &str, param: &str, value: &str) -> String {
+    let (path, query) = endpoint.split_once('?').unwrap_or((endpoint, ""));
+    let mut pairs: Vec<String> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty() && pair.split('=').next() != Some(param))
+        .map(String::from)
+        .collect();
+    pairs.push(format!("{}={}", param, percent_encode(value)));
+    format!("{}?{}", path, pairs.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::util::make_client_server;
+    use futures_util::TryStreamExt;
+
+    #[test]
+    fn parse_next_link() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            LINK,
+            "<http://localhost/items?page=1>; rel=\"prev\", <http://localhost/items?page=3>; rel=\"next last\""
+                .parse()
+                .unwrap(),
+        );
+
+        assert_eq!(next_link(&headers).as_deref(), Some("/items?page=3"));
+        assert_eq!(next_link(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn replace_cursor_param() {
+        assert_eq!(
+            set_query_param("/items?limit=2&cursor=a", "cursor", "b c"),
+            "/items?limit=2&cursor=b%20c"
+        );
+        assert_eq!(set_query_param("/items", "cursor", "a"), "/items?cursor=a");
+    }
+
+    #[tokio::test]
+    async fn follow_link_headers() {
+        let (_server, mut client) = make_client_server("follow_link_headers").await;
+
+        let pages: Vec<Vec<u32>> = client
+            .paginate::<_, Value>("/pages/link?page=1", Pagination::Link)
+            .try_collect()
+            .await
+            .expect("client.paginate");
+
+        assert_eq!(pages, [vec![1, 2], vec![3, 4], vec![5]]);
+    }
+
+    #[tokio::test]
+    async fn follow_cursors() {
+        let (_server, mut client) = make_client_server("follow_cursors").await;
+
+        let pages: Vec<Value> = client
+            .paginate::<_, Value>("/pages/cursor", Pagination::cursor("cursor", "/next"))
+            .try_collect()
+            .await
+            .expect("client.paginate");
+
+        let items: Vec<&Value> = pages.iter().map(|page| &page["items"]).collect();
+        assert_eq!(
+            items,
+            [&serde_json::json!(["a"]), &serde_json::json!(["b"])]
+        );
+    }
+}
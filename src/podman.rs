@@ -0,0 +1,377 @@
+//! Typed wrappers of the [Podman libpod API](https://docs.podman.io/en/latest/_static/api.html)
+//! (feature=podman).
+//!
+//! The libpod endpoints are versioned under `/v<version>/libpod` and their bodies differ from the
+//! Docker compatible ones, such as the lowercase fields of [`ContainerSpec`].
+//!
+//! ```rust,no_run
+//! use http_client_unix_domain_socket::podman::{ContainerSpec, Podman};
+//!
+//! # async fn run() {
+//! let mut podman = Podman::connect_default().await.expect("Podman::connect_default");
+//! let pod = podman.create_pod("nolanv").await.expect("podman.create_pod");
+//! let container = ContainerSpec {
+//!     pod: Some(pod.id),
+//!     ..ContainerSpec::new("alpine")
+//! };
+//! podman.create_container(&container).await.expect("podman.create_container");
+//! podman.start_pod("nolanv").await.expect("podman.start_pod");
+//! # }
+//! ```
+
+use crate::{ClientUnix, Error, ErrorAndResponseJson, Method};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Socket of the rootful service.
+pub const ROOTFUL_SOCKET_PATH: &str = "/run/podman/podman.sock";
+
+/// Version of the libpod API in the endpoints, served by every Podman since 4.0.
+pub const DEFAULT_API_VERSION: &str = "4.0.0";
+
+/// Body of the libpod error responses.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PodmanError {
+    #[serde(default)]
+    pub cause: String,
+    pub message: String,
+    /// HTTP status code.
+    #[serde(default)]
+    pub response: u16,
+}
+
+pub type PodmanResult<T> = Result<T, ErrorAndResponseJson<PodmanError>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Info {
+    pub host: HostInfo,
+    pub version: VersionInfo,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct HostInfo {
+    #[serde(default)]
+    pub arch: String,
+    #[serde(default)]
+    pub hostname: String,
+    #[serde(default)]
+    pub kernel: String,
+    #[serde(default)]
+    pub os: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct VersionInfo {
+    #[serde(rename = "Version")]
+    pub version: String,
+    #[serde(rename = "APIVersion")]
+    pub api_version: String,
+}
+
+/// Container as listed by [`Podman::containers`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerSummary {
+    pub id: String,
+    #[serde(default)]
+    pub names: Vec<String>,
+    pub image: String,
+    pub state: String,
+    /// Id of the pod of the container, empty if none.
+    #[serde(default)]
+    pub pod: String,
+    #[serde(default)]
+    pub pod_name: String,
+}
+
+/// Pod as listed by [`Podman::pods`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PodSummary {
+    pub id: String,
+    pub name: String,
+    pub status: String,
+    #[serde(default)]
+    pub containers: Vec<PodContainer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PodContainer {
+    pub id: String,
+    #[serde(default)]
+    pub names: String,
+    #[serde(default)]
+    pub status: String,
+}
+
+/// Body of [`Podman::create_container`] (libpod `SpecGenerator`), unset fields use the image
+/// defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ContainerSpec {
+    pub image: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Id or name of the pod to create the container in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pod: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, String>,
+}
+
+impl ContainerSpec {
+    pub fn new(image: &str) -> Self {
+        ContainerSpec {
+            image: image.to_string(),
+            ..ContainerSpec::default()
+        }
+    }
+}
+
+/// Id of a created container or pod.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Created {
+    #[serde(rename = "Id")]
+    pub id: String,
+}
+
+/// Socket of the Podman service: the `unix://` path of `CONTAINER_HOST`, else the rootless socket
+/// `$XDG_RUNTIME_DIR/podman/podman.sock` if it exists, else [`ROOTFUL_SOCKET_PATH`].
+pub fn default_socket_path() -> PathBuf {
+    discover_socket_path(
+        std::env::var("CONTAINER_HOST").ok(),
+        std::env::var("XDG_RUNTIME_DIR").ok(),
+        Path::exists,
+    )
+}
+
+fn discover_socket_path(
+    container_host: Option<String>,
+    xdg_runtime_dir: Option<String>,
+    exists: impl Fn(&Path) -> bool,
+) -> PathBuf {
+    if let Some(path) = container_host
+        .as_deref()
+        .and_then(|host| host.strip_prefix("unix://"))
+    {
+        return PathBuf::from(path);
+    }
+    if let Some(rootless) =
+        xdg_runtime_dir.map(|dir| Path::new(&dir).join("podman").join("podman.sock"))
+        && exists(&rootless)
+    {
+        return rootless;
+    }
+    PathBuf::from(ROOTFUL_SOCKET_PATH)
+}
+
+/// Podman libpod API client.
+pub struct Podman {
+    client: ClientUnix,
+    /// `/v4.0.0/libpod`.
+    prefix: String,
+}
+
+impl Podman {
+    pub fn new(client: ClientUnix) -> Self {
+        Podman {
+            client,
+            prefix: format!("/v{}/libpod", DEFAULT_API_VERSION),
+        }
+    }
+
+    /// Connect to [`default_socket_path`].
+    pub async fn connect_default() -> Result<Self, Error> {
+        let socket_path = default_socket_path();
+        Ok(Podman::new(
+            ClientUnix::try_new(&socket_path.to_string_lossy()).await?,
+        ))
+    }
+
+    /// Use the endpoints of another libpod API version, e.g. `5.0.0`.
+    pub fn api_version(mut self, version: &str) -> Self {
+        self.prefix = format!("/v{}/libpod", version.trim_start_matches('v'));
+        self
+    }
+
+    /// The underlying client, for the endpoints without a wrapper.
+    pub fn client(&mut self) -> &mut ClientUnix {
+        &mut self.client
+    }
+
+    pub async fn info(&mut self) -> PodmanResult<Info> {
+        self.get("/info").await
+    }
+
+    /// Running containers, or every container with `all`.
+    pub async fn containers(&mut self, all: bool) -> PodmanResult<Vec<ContainerSummary>> {
+        self.get(&format!("/containers/json?all={}", all)).await
+    }
+
+    pub async fn create_container(&mut self, spec: &ContainerSpec) -> PodmanResult<Created> {
+        self.client
+            .send_request_json(
+                &format!("{}/containers/create", self.prefix),
+                Method::POST,
+                &[],
+                Some(spec),
+            )
+            .await
+            .map(|(_, created)| created)
+    }
+
+    pub async fn start_container(&mut self, name: &str) -> PodmanResult<()> {
+        self.send_empty(&format!("/containers/{}/start", name), Method::POST)
+            .await
+    }
+
+    pub async fn stop_container(&mut self, name: &str) -> PodmanResult<()> {
+        self.send_empty(&format!("/containers/{}/stop", name), Method::POST)
+            .await
+    }
+
+    pub async fn remove_container(&mut self, name: &str) -> PodmanResult<()> {
+        self.send_empty(&format!("/containers/{}", name), Method::DELETE)
+            .await
+    }
+
+    pub async fn pods(&mut self) -> PodmanResult<Vec<PodSummary>> {
+        self.get("/pods/json").await
+    }
+
+    pub async fn create_pod(&mut self, name: &str) -> PodmanResult<Created> {
+        self.client
+            .send_request_json(
+                &format!("{}/pods/create", self.prefix),
+                Method::POST,
+                &[],
+                Some(&HashMap::from([("name", name)])),
+            )
+            .await
+            .map(|(_, created)| created)
+    }
+
+    /// Start every container of the pod.
+    pub async fn start_pod(&mut self, name: &str) -> PodmanResult<()> {
+        self.send_empty(&format!("/pods/{}/start", name), Method::POST)
+            .await
+    }
+
+    pub async fn stop_pod(&mut self, name: &str) -> PodmanResult<()> {
+        self.send_empty(&format!("/pods/{}/stop", name), Method::POST)
+            .await
+    }
+
+    /// Remove the pod and, with `force`, its running containers.
+    pub async fn remove_pod(&mut self, name: &str, force: bool) -> PodmanResult<()> {
+        self.send_empty(&format!("/pods/{}?force={}", name, force), Method::DELETE)
+            .await
+    }
+
+    async fn get<OUT: DeserializeOwned>(&mut self, endpoint: &str) -> PodmanResult<OUT> {
+        self.client
+            .send_request_json::<(), _, _>(
+                &format!("{}{}", self.prefix, endpoint),
+                Method::GET,
+                &[],
+                None,
+            )
+            .await
+            .map(|(_, response)| response)
+    }
+
+    /// Send a request whose response body is ignored, such as `204 No Content`.
+    async fn send_empty(&mut self, endpoint: &str, method: Method) -> PodmanResult<()> {
+        match self
+            .client
+            .send_request(&format!("{}{}", self.prefix, endpoint), method, &[], None)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(error) => match error.into_response() {
+                Err(e) => Err(ErrorAndResponseJson::InternalError(e)),
+                Ok((status_code, body)) => {
+                    let error = serde_json::from_slice(&body).unwrap_or_else(|_| PodmanError {
+                        cause: String::new(),
+                        message: String::from_utf8_lossy(&body).into_owned(),
+                        response: status_code.as_u16(),
+                    });
+                    Err(ErrorAndResponseJson::ResponseUnsuccessful(
+                        status_code,
+                        error,
+                    ))
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StatusCode, test_helpers::server::Server, test_helpers::util::*};
+
+    #[test]
+    fn discover_socket() {
+        assert_eq!(
+            discover_socket_path(
+                Some("unix:///tmp/podman.sock".into()),
+                Some("/run/user/1000".into()),
+                |_| true
+            ),
+            PathBuf::from("/tmp/podman.sock")
+        );
+        assert_eq!(
+            discover_socket_path(None, Some("/run/user/1000".into()), |_| true),
+            PathBuf::from("/run/user/1000/podman/podman.sock")
+        );
+        assert_eq!(
+            discover_socket_path(None, Some("/run/user/1000".into()), |_| false),
+            PathBuf::from(ROOTFUL_SOCKET_PATH)
+        );
+    }
+
+    #[tokio::test]
+    async fn pods_and_errors() {
+        let socket_path = make_socket_path_test("podman", "pods_and_errors");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let client = ClientUnix::try_new(&socket_path)
+            .await
+            .expect("ClientUnix::try_new");
+        let mut podman = Podman::new(client);
+
+        let pods = podman.pods().await.expect("podman.pods");
+        assert_eq!(pods.len(), 1);
+        assert_eq!(pods[0].name, "nolanv");
+        assert_eq!(pods[0].containers[0].names, "nolanv-infra");
+
+        let result = podman.start_pod("missing").await;
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponseJson::ResponseUnsuccessful(StatusCode::NOT_FOUND, e))
+                if e.cause == "no such pod" && e.response == 404
+        ));
+    }
+
+    #[test]
+    fn container_spec_is_lowercase() {
+        let spec = ContainerSpec {
+            pod: Some("nolanv".into()),
+            ..ContainerSpec::new("alpine")
+        };
+
+        assert_eq!(
+            serde_json::to_string(&spec).unwrap(),
+            r#"{"image":"alpine","pod":"nolanv"}"#
+        );
+    }
+}
@@ -0,0 +1,401 @@
+use crate::{Body, ClientUnix, Endpoint, Error, ErrorAndResponse, Method, client::Options};
+use hyper::{Response, StatusCode, body::Bytes};
+use std::{
+    ops::{Deref, DerefMut},
+    path::PathBuf,
+    pin::pin,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+use tokio::sync::Notify;
+
+#[cfg(feature = "json")]
+use crate::ErrorAndResponseJson;
+#[cfg(feature = "json")]
+use serde::{Serialize, de::DeserializeOwned};
+
+/// Bounds of the number of connections of a [`ClientPool`], see
+/// [`ClientUnixBuilder::build_pool`](crate::ClientUnixBuilder::build_pool).
+///
+/// The pool starts with `min` connections. A request finding every connection taken opens a new
+/// one, up to `max`, unless the average latency is over the
+/// [`latency_target`](Self::latency_target): a slow server is only loaded more by more
+/// connections, so every request answered over it closes one instead, down to `min`. Connections
+/// idle for longer than the [`idle_timeout`](Self::idle_timeout) are closed, down to `min` too.
+#[derive(Debug, Clone)]
+pub struct PoolSizing {
+    pub(crate) min: usize,
+    pub(crate) max: usize,
+    pub(crate) latency_target: Option<Duration>,
+    pub(crate) idle_timeout: Duration,
+}
+
+impl PoolSizing {
+    /// Between `min`, at least one, and `max` connections, closed after 60s idle, without latency
+    /// target.
+    pub fn new(min: usize, max: usize) -> Self {
+        let min = min.max(1);
+        PoolSizing {
+            min,
+            max: max.max(min),
+            latency_target: None,
+            idle_timeout: Duration::from_secs(60),
+        }
+    }
+
+    /// Stop growing, and shrink, while the average time a connection is held, the duration of the
+    /// request for the `send_request` methods, is over `latency`.
+    pub fn latency_target(mut self, latency: Duration) -> Self {
+        self.latency_target = Some(latency);
+        self
+    }
+
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+}
+
+struct PoolState {
+    /// Most recently released last.
+    idle: Vec<(ClientUnix, Instant)>,
+    /// Connections idle, in use or being opened.
+    open: usize,
+    /// Connections the pool may open, between the bounds of its [`PoolSizing`].
+    limit: usize,
+    /// Moving average of the time connections are held.
+    latency: Option<Duration>,
+}
+
+/// Connections with the same settings whose number follows the load, created with
+/// [`ClientUnixBuilder::build_pool`](crate::ClientUnixBuilder::build_pool).
+///
+/// Where [`ShardedClient`](crate::ShardedClient) keeps a fixed number of connections, the pool
+/// opens connections when requests wait for one and closes them when they are idle or the server
+/// slows down, within the bounds of its [`PoolSizing`]. Every method takes `&self`, share it with
+/// an `Arc`.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::{ClientUnix, Method, PoolSizing};
+/// use std::{sync::Arc, time::Duration};
+///
+/// # async fn run() {
+/// let client = Arc::new(
+///     ClientUnix::builder("/var/run/docker.sock")
+///         .build_pool(PoolSizing::new(1, 16).latency_target(Duration::from_millis(200)))
+///         .await
+///         .expect("ClientUnixBuilder::build_pool"),
+/// );
+/// for _ in 0..1000 {
+///     let client = client.clone();
+///     tokio::spawn(async move {
+///         let (_, body) = client
+///             .send_request("/_ping", Method::GET, &[], None)
+///             .await
+///             .expect("client.send_request");
+///     });
+/// }
+/// # }
+/// ```
+pub struct ClientPool {
+    socket_path: PathBuf,
+    options: Options,
+    sizing: PoolSizing,
+    state: Mutex<PoolState>,
+    released: Notify,
+}
+
+/// Connection of a [`ClientPool`], given back to it when dropped.
+pub struct PooledClient<'a> {
+    pool: &'a ClientPool,
+    client: Option<ClientUnix>,
+    acquired: Instant,
+}
+
+impl ClientPool {
+    /// Open the `min` connections of `sizing`.
+    pub(crate) async fn try_connect(
+        socket_path: PathBuf,
+        options: Options,
+        sizing: PoolSizing,
+    ) -> Result<Self, Error> {
+        let mut idle = Vec::with_capacity(sizing.min);
+        for _ in 0..sizing.min {
+            let client = ClientUnix::try_connect(socket_path.clone(), options.clone()).await?;
+            idle.push((client, Instant::now()));
+        }
+        Ok(ClientPool {
+            socket_path,
+            options,
+            state: Mutex::new(PoolState {
+                idle,
+                open: sizing.min,
+                limit: sizing.min,
+                latency: None,
+            }),
+            sizing,
+            released: Notify::new(),
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Number of connections, idle or in use.
+    pub fn size(&self) -> usize {
+        self.state().open
+    }
+
+    /// Average time a connection is held, `None` before the first one is given back.
+    pub fn latency(&self) -> Option<Duration> {
+        self.state().latency
+    }
+
+    /// An idle connection, a new one when they are all taken and the pool may grow, else the
+    /// first one given back. Fails when the new connection can't be opened.
+    pub async fn client(&self) -> Result<PooledClient<'_>, Error> {
+        loop {
+            let mut released = pin!(self.released.notified());
+            released.as_mut().enable();
+
+            let mut closed = Vec::new();
+            let open = {
+                let mut state = self.state();
+                let now = Instant::now();
+                while state.open > self.sizing.min
+                    && state.idle.first().is_some_and(|(_, since)| {
+                        now.duration_since(*since) > self.sizing.idle_timeout
+                    })
+                {
+                    closed.push(state.idle.remove(0).0);
+                    state.open -= 1;
+                    state.limit = state.limit.min(state.open).max(self.sizing.min);
+                }
+                if let Some((client, _)) = state.idle.pop() {
+                    drop(state);
+                    return Ok(self.pooled(client));
+                }
+                let slow = self
+                    .sizing
+                    .latency_target
+                    .is_some_and(|target| state.latency.is_some_and(|latency| latency > target));
+                if state.open >= state.limit && state.limit < self.sizing.max && !slow {
+                    state.limit += 1;
+                }
+                let open = state.open < state.limit;
+                if open {
+                    state.open += 1;
+                }
+                open
+            };
+            drop(closed);
+
+            if open {
+                let opening = Opening { pool: self };
+                let client =
+                    ClientUnix::try_connect(self.socket_path.clone(), self.options.clone()).await?;
+                std::mem::forget(opening);
+                return Ok(self.pooled(client));
+            }
+            released.await;
+        }
+    }
+
+    fn pooled(&self, client: ClientUnix) -> PooledClient<'_> {
+        PooledClient {
+            pool: self,
+            client: Some(client),
+            acquired: Instant::now(),
+        }
+    }
+
+    /// [`ClientUnix::send_request`] on a connection of the pool.
+    pub async fn send_request(
+        &self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Body>,
+    ) -> Result<(StatusCode, Vec<u8>), ErrorAndResponse> {
+        self.client()
+            .await
+            .map_err(ErrorAndResponse::InternalError)?
+            .send_request(endpoint, method, headers, body_request)
+            .await
+    }
+
+    /// [`ClientUnix::send_request_full`] on a connection of the pool.
+    pub async fn send_request_full(
+        &self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Body>,
+    ) -> Result<Response<Vec<u8>>, ErrorAndResponse> {
+        self.client()
+            .await
+            .map_err(ErrorAndResponse::InternalError)?
+            .send_request_full(endpoint, method, headers, body_request)
+            .await
+    }
+
+    /// [`ClientUnix::send_request_bytes`] on a connection of the pool.
+    pub async fn send_request_bytes(
+        &self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Bytes>,
+    ) -> Result<Response<Vec<u8>>, ErrorAndResponse> {
+        self.client()
+            .await
+            .map_err(ErrorAndResponse::InternalError)?
+            .send_request_bytes(endpoint, method, headers, body_request)
+            .await
+    }
+
+    /// [`ClientUnix::send_request_json`] on a connection of the pool.
+    #[cfg(feature = "json")]
+    pub async fn send_request_json<IN: Serialize, OUT: DeserializeOwned, ERR: DeserializeOwned>(
+        &self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<&IN>,
+    ) -> Result<(StatusCode, OUT), ErrorAndResponseJson<ERR>> {
+        self.client()
+            .await
+            .map_err(ErrorAndResponseJson::InternalError)?
+            .send_request_json(endpoint, method, headers, body_request)
+            .await
+    }
+}
+
+/// Gives the slot of a connection back when it couldn't be opened, or its future was dropped.
+struct Opening<'a> {
+    pool: &'a ClientPool,
+}
+
+impl Drop for Opening<'_> {
+    fn drop(&mut self) {
+        self.pool.state().open -= 1;
+        self.pool.released.notify_one();
+    }
+}
+
+impl Deref for PooledClient<'_> {
+    type Target = ClientUnix;
+
+    fn deref(&self) -> &ClientUnix {
+        self.client
+            .as_ref()
+            .expect("pooled client is set until dropped")
+    }
+}
+
+impl DerefMut for PooledClient<'_> {
+    fn deref_mut(&mut self) -> &mut ClientUnix {
+        self.client
+            .as_mut()
+            .expect("pooled client is set until dropped")
+    }
+}
+
+impl Drop for PooledClient<'_> {
+    fn drop(&mut self) {
+        let Some(client) = self.client.take() else {
+            return;
+        };
+        let held = self.acquired.elapsed();
+        let sizing = &self.pool.sizing;
+        let closed = {
+            let mut state = self.pool.state();
+            let latency = match state.latency {
+                Some(latency) => latency.mul_f64(0.8) + held.mul_f64(0.2),
+                None => held,
+            };
+            state.latency = Some(latency);
+            if sizing.latency_target.is_some_and(|target| latency > target) {
+                state.limit = state.limit.saturating_sub(1).max(sizing.min);
+            }
+            if state.open > state.limit {
+                state.open -= 1;
+                Some(client)
+            } else {
+                state.idle.push((client, Instant::now()));
+                None
+            }
+        };
+        drop(closed);
+        self.pool.released.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{server::Server, util::*};
+    use futures_util::future::join_all;
+
+    #[tokio::test]
+    async fn grow_with_waiting_requests() {
+        let socket_path = make_socket_path_test("pool", "grow_with_waiting_requests");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let client = ClientUnix::builder(&socket_path)
+            .build_pool(PoolSizing::new(1, 4).idle_timeout(Duration::from_millis(100)))
+            .await
+            .expect("ClientUnixBuilder::build_pool");
+        assert_eq!(client.size(), 1);
+
+        let start = Instant::now();
+        let results =
+            join_all((0..8).map(|_| client.send_request("/sleep/50", Method::GET, &[], None)))
+                .await;
+        assert!(start.elapsed() < Duration::from_millis(300));
+        for result in results {
+            let (status, body) = result.expect("client.send_request");
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body, b"Slept 50ms");
+        }
+        assert_eq!(client.size(), 4);
+
+        // The idle connections are closed, down to the minimum
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let (status, _) = client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(client.size(), 1);
+    }
+
+    #[tokio::test]
+    async fn shrink_when_slow() {
+        let socket_path = make_socket_path_test("pool", "shrink_when_slow");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let client = ClientUnix::builder(&socket_path)
+            .build_pool(PoolSizing::new(1, 4).latency_target(Duration::from_millis(30)))
+            .await
+            .expect("ClientUnixBuilder::build_pool");
+
+        // Under the target the pool grows
+        let results =
+            join_all((0..4).map(|_| client.send_request("/sleep/10", Method::GET, &[], None)))
+                .await;
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(client.size(), 4);
+
+        // Over it, every slow request closes a connection and the pool doesn't grow back
+        let results =
+            join_all((0..8).map(|_| client.send_request("/sleep/100", Method::GET, &[], None)))
+                .await;
+        assert!(results.iter().all(Result::is_ok));
+        assert!(client.latency().expect("client.latency") > Duration::from_millis(30));
+        assert_eq!(client.size(), 1);
+    }
+}
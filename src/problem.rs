@@ -0,0 +1,48 @@
+use hyper::{HeaderMap, header::CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Problem details of an HTTP API error, as defined by RFC 7807 (feature=json).
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub problem_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Members specific to the problem type.
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+}
+
+impl ProblemDetails {
+    pub(crate) fn is_problem(headers: &HeaderMap) -> bool {
+        headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(';').next())
+            .is_some_and(|v| v.trim().eq_ignore_ascii_case("application/problem+json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn problem_content_type() {
+        let mut headers = HeaderMap::new();
+        assert!(!ProblemDetails::is_problem(&headers));
+
+        headers.insert(
+            CONTENT_TYPE,
+            "application/problem+json; charset=utf-8".parse().unwrap(),
+        );
+        assert!(ProblemDetails::is_problem(&headers));
+    }
+}
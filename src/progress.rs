@@ -0,0 +1,185 @@
+use hyper::body::{Body, Buf, Frame, SizeHint};
+use std::{
+    pin::Pin,
+    task::{Context, Poll, ready},
+    time::{Duration, Instant},
+};
+
+/// Progress of a body given to the observer of a [`ProgressBody`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Bytes transferred so far.
+    pub bytes: u64,
+    /// Size of the whole body, `None` when unknown, such as a chunked body.
+    pub total: Option<u64>,
+    /// Time since the body was wrapped.
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Part of the body transferred, between 0 and 1, when its size is known.
+    pub fn fraction(&self) -> Option<f64> {
+        self.total.map(|total| match total {
+            0 => 1.0,
+            total => self.bytes as f64 / total as f64,
+        })
+    }
+}
+
+/// Body calling an observer with its [`Progress`] every time a chunk is read from it, to follow
+/// an upload by wrapping the request body, or a download by wrapping the body of a streamed
+/// response. With retries or Digest authentication, the request body is read whole before the
+/// first attempt, so its progress is the one of the buffering.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::{Body, ClientUnix, Method, ProgressBody};
+/// use http_body_util::BodyExt;
+///
+/// # async fn run(mut client: ClientUnix, archive: Vec<u8>) {
+/// let upload = ProgressBody::new(Body::from(archive), |progress| {
+///     println!("sent {} of {:?} bytes", progress.bytes, progress.total)
+/// });
+/// client
+///     .send_request("/images/load", Method::POST, &[], Some(Body::new(upload)))
+///     .await
+///     .expect("client.send_request");
+///
+/// let response = client
+///     .send_request_streaming("/images/nolanv/get", Method::GET, &[], None)
+///     .await
+///     .expect("client.send_request_streaming");
+/// let mut download = ProgressBody::new(response.into_body(), |progress| {
+///     if let Some(fraction) = progress.fraction() {
+///         println!("{:.0}% in {:?}", fraction * 100.0, progress.elapsed)
+///     }
+/// });
+/// while let Some(frame) = download.frame().await {
+///     let frame = frame.expect("frame");
+/// }
+/// # }
+/// ```
+pub struct ProgressBody<B, F> {
+    inner: B,
+    observer: F,
+    bytes: u64,
+    total: Option<u64>,
+    started: Instant,
+}
+
+impl<B: Body, F: FnMut(&Progress)> ProgressBody<B, F> {
+    /// Wrap `body`, its size is taken from its size hint when exact, as with a `Content-Length`.
+    pub fn new(body: B, observer: F) -> Self {
+        ProgressBody {
+            total: body.size_hint().exact(),
+            inner: body,
+            observer,
+            bytes: 0,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B: Body + Unpin, F: FnMut(&Progress) + Unpin> Body for ProgressBody<B, F> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let frame = ready!(Pin::new(&mut self.inner).poll_frame(cx));
+        if let Some(Ok(frame)) = &frame
+            && let Some(data) = frame.data_ref()
+        {
+            self.bytes += data.remaining() as u64;
+            let progress = Progress {
+                bytes: self.bytes,
+                total: self.total,
+                elapsed: self.started.elapsed(),
+            };
+            (self.observer)(&progress);
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, ClientUnix, Method};
+    use http_body_util::BodyExt;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn upload_and_download_progress() {
+        const LEN: usize = 256 * 1024;
+        let server = crate::testing::MockServer::start(
+            axum::Router::new()
+                .route("/layer", axum::routing::get(|| async { vec![0u8; LEN] }))
+                .route(
+                    "/layer",
+                    axum::routing::post(|body: hyper::body::Bytes| async move {
+                        body.len().to_string()
+                    }),
+                ),
+        )
+        .await
+        .expect("MockServer::start");
+        let socket_path = server.socket_path().to_string_lossy().into_owned();
+        let mut client = ClientUnix::try_new(&socket_path)
+            .await
+            .expect("ClientUnix::try_new");
+
+        let uploaded = Arc::new(Mutex::new(Vec::new()));
+        let observed = uploaded.clone();
+        let chunks = (0..4).map(|_| {
+            Ok::<_, std::io::Error>(Frame::data(hyper::body::Bytes::from(vec![0u8; LEN / 4])))
+        });
+        let upload = ProgressBody::new(
+            Body::new(http_body_util::StreamBody::new(futures_util::stream::iter(
+                chunks,
+            ))),
+            move |progress: &Progress| observed.lock().unwrap().push(*progress),
+        );
+        client
+            .send_request("/layer", Method::POST, &[], Some(Body::new(upload)))
+            .await
+            .expect("client.send_request");
+        assert_eq!(
+            uploaded
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|progress| (progress.bytes, progress.total))
+                .collect::<Vec<_>>(),
+            [1, 2, 3, 4].map(|quarters| (quarters * LEN as u64 / 4, None))
+        );
+
+        let response = client
+            .send_request_streaming("/layer", Method::GET, &[], None)
+            .await
+            .expect("client.send_request_streaming");
+        let mut last = None;
+        let download = ProgressBody::new(response.into_body(), |progress: &Progress| {
+            assert!(progress.bytes >= last.map_or(0, |last: Progress| last.bytes));
+            last = Some(*progress);
+        });
+        let body = download.collect().await.expect("collect").to_bytes();
+        assert_eq!(body.len(), LEN);
+        let last = last.expect("a progress");
+        assert_eq!((last.bytes, last.total), (LEN as u64, Some(LEN as u64)));
+        assert_eq!(last.fraction(), Some(1.0));
+    }
+}
@@ -0,0 +1,259 @@
+//! Scrape endpoints exposing metrics in the Prometheus text format (feature=prometheus).
+//!
+//! ```rust,no_run
+//! use http_client_unix_domain_socket::ClientUnix;
+//!
+//! # async fn scrape() {
+//! let mut client = ClientUnix::try_new("/run/sidecar.sock").await.expect("ClientUnix::try_new");
+//! let scrape = client.scrape_prometheus("/metrics").await.expect("client.scrape_prometheus");
+//! for sample in scrape.samples.iter().filter(|s| s.name == "http_requests_total") {
+//!     println!("{:?} {}", sample.label("code"), sample.value);
+//! }
+//! # }
+//! ```
+
+use crate::{ClientUnix, Error, ErrorAndResponse, Method};
+use std::{collections::BTreeMap, fmt};
+
+/// `# TYPE` of a metric family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+    Histogram,
+    Summary,
+    Untyped,
+}
+
+/// One line of the exposition, such as `http_requests_total{code="200"} 1027 1395066363000`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub name: String,
+    pub labels: Vec<(String, String)>,
+    pub value: f64,
+    /// Milliseconds since the epoch.
+    pub timestamp: Option<i64>,
+}
+
+impl Sample {
+    pub fn label(&self, name: &str) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|(label, _)| label == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parsed exposition: every sample in order, with the `# TYPE` and `# HELP` of their family.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scrape {
+    pub samples: Vec<Sample>,
+    pub types: BTreeMap<String, MetricType>,
+    pub help: BTreeMap<String, String>,
+}
+
+/// Invalid line of an exposition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Starting from 1.
+    pub line: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ClientUnix {
+    /// GET `endpoint` and parse the Prometheus text format response.
+    pub async fn scrape_prometheus(&mut self, endpoint: &str) -> Result<Scrape, ErrorAndResponse> {
+        let (_, body) = self
+            .send_request(
+                endpoint,
+                Method::GET,
+                &[("Accept", "text/plain;version=0.0.4")],
+                None,
+            )
+            .await?;
+        let text = String::from_utf8_lossy(&body);
+        parse(&text).map_err(|e| ErrorAndResponse::InternalError(Error::MetricsParsing(e)))
+    }
+}
+
+/// Parse the Prometheus text exposition format.
+pub fn parse(text: &str) -> Result<Scrape, ParseError> {
+    let mut scrape = Scrape::default();
+    for (i, line) in text.lines().enumerate() {
+        let error = |message| ParseError {
+            line: i + 1,
+            message,
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut parts = comment.trim_start().splitn(3, char::is_whitespace);
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("TYPE"), Some(name), Some(metric_type)) => {
+                    let metric_type = match metric_type.trim() {
+                        "counter" => MetricType::Counter,
+                        "gauge" => MetricType::Gauge,
+                        "histogram" => MetricType::Histogram,
+                        "summary" => MetricType::Summary,
+                        "untyped" => MetricType::Untyped,
+                        _ => return Err(error("unknown metric type")),
+                    };
+                    scrape.types.insert(name.to_string(), metric_type);
+                }
+                (Some("HELP"), Some(name), help) => {
+                    scrape
+                        .help
+                        .insert(name.to_string(), unescape(help.unwrap_or_default()));
+                }
+                _ => {}
+            }
+            continue;
+        }
+        scrape.samples.push(parse_sample(line).map_err(error)?);
+    }
+    Ok(scrape)
+}
+
+fn parse_sample(line: &str) -> Result<Sample, &'static str> {
+    let name_end = line
+        .find(|c: char| c == '{' || c.is_whitespace())
+        .ok_or("missing value")?;
+    let name = &line[..name_end];
+    if name.is_empty() {
+        return Err("missing metric name");
+    }
+
+    let mut labels = Vec::new();
+    let mut rest = &line[name_end..];
+    if let Some(after) = rest.strip_prefix('{') {
+        rest = after;
+        loop {
+            rest = rest.trim_start_matches([',', ' ']);
+            if let Some(after) = rest.strip_prefix('}') {
+                rest = after;
+                break;
+            }
+            let (label, after) = rest.split_once('=').ok_or("invalid label")?;
+            let after = after.strip_prefix('"').ok_or("unquoted label value")?;
+            let (value, after) = split_quoted(after).ok_or("unterminated label value")?;
+            labels.push((label.trim().to_string(), value));
+            rest = after;
+        }
+    }
+
+    let mut fields = rest.split_whitespace();
+    let value = match fields.next().ok_or("missing value")? {
+        "+Inf" | "Inf" => f64::INFINITY,
+        "-Inf" => f64::NEG_INFINITY,
+        value => value.parse().map_err(|_| "invalid value")?,
+    };
+    let timestamp = fields
+        .next()
+        .map(|timestamp| timestamp.parse().map_err(|_| "invalid timestamp"))
+        .transpose()?;
+
+    Ok(Sample {
+        name: name.to_string(),
+        labels,
+        value,
+        timestamp,
+    })
+}
+
+/// Split an escaped label value at its closing quote.
+fn split_quoted(text: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &text[i + 1..])),
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                c => value.push(c),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("\\n", "\n").replace("\\\\", "\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::util::make_client_server;
+
+    #[test]
+    fn parse_exposition() {
+        let scrape = parse(
+            r#"# HELP http_requests_total The total number of HTTP requests.
+# TYPE http_requests_total counter
+http_requests_total{method="post",code="200"} 1027 1395066363000
+http_requests_total{method="post",code="400",} 3 1395066363000
+
+# A comment
+msdos_file_access_time_seconds{path="C:\\DIR\\FILE.TXT",error="Cannot find file:\n\"FILE.TXT\""} 1.458255915e9
+http_request_duration_seconds_bucket{le="+Inf"} 144320
+temperature NaN
+"#,
+        )
+        .expect("parse");
+
+        assert_eq!(
+            scrape.types.get("http_requests_total"),
+            Some(&MetricType::Counter)
+        );
+        assert_eq!(
+            scrape.help.get("http_requests_total").map(String::as_str),
+            Some("The total number of HTTP requests.")
+        );
+        assert_eq!(scrape.samples.len(), 5);
+        assert_eq!(scrape.samples[1].label("code"), Some("400"));
+        assert_eq!(scrape.samples[1].timestamp, Some(1395066363000));
+        assert_eq!(scrape.samples[2].label("path"), Some("C:\\DIR\\FILE.TXT"));
+        assert_eq!(
+            scrape.samples[2].label("error"),
+            Some("Cannot find file:\n\"FILE.TXT\"")
+        );
+        assert_eq!(scrape.samples[3].label("le"), Some("+Inf"));
+        assert!(scrape.samples[4].value.is_nan());
+    }
+
+    #[test]
+    fn parse_error_line() {
+        assert_eq!(
+            parse("ok 1\nbroken{label=1} 2").err(),
+            Some(ParseError {
+                line: 2,
+                message: "unquoted label value"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn scrape_endpoint() {
+        let (_, mut client) = make_client_server("scrape_endpoint").await;
+
+        let scrape = client
+            .scrape_prometheus("/metrics")
+            .await
+            .expect("client.scrape_prometheus");
+
+        assert_eq!(scrape.samples.len(), 1);
+        assert_eq!(scrape.samples[0].name, "nolanv_requests_total");
+        assert_eq!(scrape.samples[0].value, 42.0);
+    }
+}
@@ -0,0 +1,150 @@
+use std::borrow::Cow;
+
+/// Percent-encode a query parameter value or a path segment, every byte but the unreserved
+/// characters of RFC 3986.
+pub(crate) fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Query string of `params`, a struct or a map: a parameter per field, sorted by name, with its
+/// scalar value, repeated for an array of scalars, and JSON-encoded for a nested map or array, as
+/// the `filters` of Docker. `None` fields are skipped.
+#[cfg(feature = "json")]
+pub(crate) fn query_string<T: serde::Serialize + ?Sized>(
+    params: &T,
+) -> Result<String, serde_json::Error> {
+    use serde::ser::Error as _;
+    use serde_json::Value;
+
+    let scalar = |value: &Value| match value {
+        Value::String(value) => Some(percent_encode(value)),
+        Value::Number(value) => Some(value.to_string()),
+        Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    };
+    let Value::Object(fields) = serde_json::to_value(params)? else {
+        return Err(serde_json::Error::custom(
+            "query parameters must be a struct or a map",
+        ));
+    };
+
+    let mut pairs = Vec::with_capacity(fields.len());
+    for (name, value) in &fields {
+        let name = percent_encode(name);
+        match &value {
+            Value::Null => {}
+            Value::Array(items) if items.iter().all(|item| scalar(item).is_some()) => {
+                pairs.extend(
+                    items
+                        .iter()
+                        .filter_map(scalar)
+                        .map(|item| format!("{}={}", name, item)),
+                );
+            }
+            Value::Array(_) | Value::Object(_) => {
+                pairs.push(format!("{}={}", name, percent_encode(&value.to_string())))
+            }
+            scalar_value => pairs.extend(scalar(scalar_value).map(|v| format!("{}={}", name, v))),
+        }
+    }
+    Ok(pairs.join("&"))
+}
+
+/// Percent-encode the bytes of an endpoint which aren't valid in a request target, keeping the
+/// existing escapes. The path keeps the characters allowed in its segments and `/`, the query is
+/// only escaped for its spaces, control and non-ASCII bytes.
+pub(crate) fn encode_endpoint(endpoint: &str) -> Cow<'_, str> {
+    let bytes = endpoint.as_bytes();
+    let query_start = endpoint.find('?').unwrap_or(endpoint.len());
+    let is_escape =
+        |i: usize| bytes.len() > i + 2 && bytes[i + 1..i + 3].iter().all(u8::is_ascii_hexdigit);
+    let keep = |i: usize| match bytes[i] {
+        b'%' => is_escape(i),
+        b'!'..=b'~' if i >= query_start => true,
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => true,
+        b => b"-._~!$&'()*+,;=:@/".contains(&b),
+    };
+    if (0..bytes.len()).all(keep) {
+        return Cow::Borrowed(endpoint);
+    }
+
+    let mut encoded = String::with_capacity(endpoint.len() + 16);
+    for (i, &b) in bytes.iter().enumerate() {
+        match keep(i) {
+            true => encoded.push(b as char),
+            false => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    Cow::Owned(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_json() {
+        assert_eq!(
+            percent_encode(r#"{"type":["container"]}"#),
+            "%7B%22type%22%3A%5B%22container%22%5D%7D"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn serialize_query_structs() {
+        use std::collections::BTreeMap;
+
+        #[derive(serde::Serialize)]
+        struct Params {
+            all: bool,
+            limit: Option<u32>,
+            since: Option<&'static str>,
+            ids: Vec<&'static str>,
+            filters: BTreeMap<&'static str, Vec<&'static str>>,
+        }
+
+        let params = Params {
+            all: true,
+            limit: None,
+            since: Some("nolanv web"),
+            ids: vec!["a", "b"],
+            filters: BTreeMap::from([("label", vec!["env=prod"])]),
+        };
+        assert_eq!(
+            query_string(&params).expect("query_string"),
+            "all=true&filters=%7B%22label%22%3A%5B%22env%3Dprod%22%5D%7D&ids=a&ids=b&since=nolanv%20web"
+        );
+        assert!(query_string(&["nolanv"]).is_err());
+    }
+
+    #[test]
+    fn encode_endpoints() {
+        for (endpoint, encoded) in [
+            ("/containers/json?all=true", "/containers/json?all=true"),
+            ("/volumes/my volume", "/volumes/my%20volume"),
+            ("/images/web[1]/json", "/images/web%5B1%5D/json"),
+            ("/images/caf\u{e9}", "/images/caf%C3%A9"),
+            ("/images/nolanv%2Fweb/json", "/images/nolanv%2Fweb/json"),
+            ("/100%", "/100%25"),
+            (
+                "/containers/json?filters=%7B%22name%22%3A%5B%22web%22%5D%7D",
+                "/containers/json?filters=%7B%22name%22%3A%5B%22web%22%5D%7D",
+            ),
+            (
+                "/search?term=my image&limit=5",
+                "/search?term=my%20image&limit=5",
+            ),
+        ] {
+            assert_eq!(encode_endpoint(endpoint), encoded);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Token bucket shared by a client and its reconnections, see
+/// [`ClientUnixBuilder::rate_limit`](crate::ClientUnixBuilder::rate_limit). The tokens are bytes
+/// for [`ClientUnixBuilder::upload_limit`](crate::ClientUnixBuilder::upload_limit) and
+/// [`ClientUnixBuilder::download_limit`](crate::ClientUnixBuilder::download_limit).
+pub(crate) struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    /// Negative when requests are waiting for a token.
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(per_second: f64, burst: u32) -> Self {
+        let burst = f64::from(burst.max(1));
+        RateLimiter {
+            // NaN and the rates under zero never refill the bucket, an infinite one always does
+            per_second: match per_second > 0.0 {
+                true => per_second.min(f64::MAX),
+                false => 0.0,
+            },
+            burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                refilled: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take a token, waiting for it if the bucket is empty.
+    pub(crate) async fn acquire(&self) {
+        let wait = self.reserve(Instant::now(), 1.0);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take `tokens` and return how long to wait before they are available.
+    pub(crate) fn reserve(&self, now: Instant, tokens: f64) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let elapsed = now.saturating_duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.burst) - tokens;
+        bucket.refilled = now;
+        match bucket.tokens < 0.0 {
+            true => Duration::try_from_secs_f64(-bucket.tokens / self.per_second)
+                .unwrap_or(Duration::MAX),
+            false => Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_then_rate() {
+        let limiter = RateLimiter::new(10.0, 2);
+        let now = Instant::now();
+        limiter.bucket.lock().unwrap().refilled = now;
+
+        assert_eq!(limiter.reserve(now, 1.0), Duration::ZERO);
+        assert_eq!(limiter.reserve(now, 1.0), Duration::ZERO);
+        assert_eq!(limiter.reserve(now, 1.0), Duration::from_millis(100));
+        assert_eq!(limiter.reserve(now, 1.0), Duration::from_millis(200));
+        // The waiting requests consumed most of the refill
+        assert_eq!(
+            limiter.reserve(now + Duration::from_millis(300), 1.0),
+            Duration::ZERO
+        );
+        assert_eq!(
+            limiter.reserve(now + Duration::from_millis(300), 1.0),
+            Duration::from_millis(100)
+        );
+    }
+
+    #[test]
+    fn invalid_rates() {
+        for per_second in [0.0, -1.0, f64::NAN] {
+            let limiter = RateLimiter::new(per_second, 1);
+            let now = Instant::now();
+            assert_eq!(limiter.reserve(now, 1.0), Duration::ZERO);
+            assert_eq!(
+                limiter.reserve(now + Duration::from_secs(60), 1.0),
+                Duration::MAX
+            );
+        }
+        let limiter = RateLimiter::new(f64::INFINITY, 1);
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(limiter.reserve(now, 1.0), Duration::ZERO);
+        }
+    }
+}
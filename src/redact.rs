@@ -0,0 +1,429 @@
+use crate::Direction;
+use hyper::header::HeaderName;
+use std::{borrow::Cow, collections::VecDeque, sync::Arc};
+
+pub(crate) const REDACTED: &str = "***";
+
+/// Bytes of a head kept until its end, beyond which it is masked as is.
+const MAX_PENDING_HEAD: usize = 64 * 1024;
+
+/// Headers whose values are masked in every log and debug output of the crate.
+#[derive(Clone, Default)]
+pub(crate) struct Redactor {
+    headers: Arc<Vec<HeaderName>>,
+}
+
+impl Redactor {
+    pub(crate) fn add(&mut self, header: HeaderName) {
+        Arc::make_mut(&mut self.headers).push(header);
+    }
+
+    pub(crate) fn is_redacted(&self, header: &str) -> bool {
+        self.headers
+            .iter()
+            .any(|h| h.as_str().eq_ignore_ascii_case(header))
+    }
+
+    #[cfg_attr(not(feature = "har"), allow(dead_code))]
+    pub(crate) fn value<'a>(&self, header: &str, value: &'a str) -> &'a str {
+        match self.is_redacted(header) {
+            true => REDACTED,
+            false => value,
+        }
+    }
+
+    /// Mask header values in raw HTTP/1 bytes, up to the end of the head.
+    pub(crate) fn bytes<'a>(&self, bytes: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.headers.is_empty() {
+            return Cow::Borrowed(bytes);
+        }
+
+        let mut redacted: Option<Vec<u8>> = None;
+        let mut start = 0;
+        while start < bytes.len() {
+            let end = bytes[start..]
+                .windows(2)
+                .position(|w| w == b"\r\n")
+                .map(|p| start + p)
+                .unwrap_or(bytes.len());
+            let line = &bytes[start..end];
+            if line.is_empty() {
+                break;
+            }
+            if let Some(colon) = line.iter().position(|b| *b == b':') {
+                let name = String::from_utf8_lossy(&line[..colon]);
+                if self.is_redacted(name.trim()) {
+                    let output = redacted.get_or_insert_with(|| bytes[..start].to_vec());
+                    output.extend_from_slice(&line[..=colon]);
+                    output.extend_from_slice(b" ");
+                    output.extend_from_slice(REDACTED.as_bytes());
+                    output.extend_from_slice(&bytes[end..(end + 2).min(bytes.len())]);
+                    start = end + 2;
+                    continue;
+                }
+            }
+            if let Some(output) = redacted.as_mut() {
+                output.extend_from_slice(&bytes[start..(end + 2).min(bytes.len())]);
+            }
+            start = end + 2;
+        }
+
+        match redacted {
+            Some(mut output) => {
+                if start < bytes.len() {
+                    output.extend_from_slice(&bytes[start..]);
+                }
+                Cow::Owned(output)
+            }
+            None => Cow::Borrowed(bytes),
+        }
+    }
+}
+
+/// Whether each request sent without a response yet is a `HEAD` one, whose response has no body
+/// whatever its headers.
+pub(crate) type PendingRequests = VecDeque<bool>;
+
+/// Chunks of one direction of a connection, whose heads are kept until their end to be masked
+/// whole, as a header may be split across reads or writes.
+///
+/// The end of every body is found from the `Content-Length` or the chunks of its message, so the
+/// next head is masked wherever it starts, and a body is never taken for one.
+pub(crate) struct HeadBuffer {
+    direction: Direction,
+    pending: Vec<u8>,
+    /// Bytes of `\r\n\r\n` matched at the end of `pending`.
+    head_end: usize,
+    framing: Framing,
+}
+
+/// Where the bytes of a direction are in its current message.
+enum Framing {
+    Head,
+    /// Bytes left of a body with a `Content-Length`.
+    Length(u64),
+    Chunked(Chunk),
+    /// The body ends with the connection, as a response without length or an upgrade.
+    UntilClose,
+}
+
+enum Chunk {
+    /// The line of the size of the next chunk, as read so far.
+    Size(Vec<u8>),
+    /// Bytes left of the data of a chunk and its `\r\n`.
+    Data(u64),
+    /// Length of the trailer line read so far, the body ends with an empty one.
+    Trailer(usize),
+}
+
+impl HeadBuffer {
+    pub(crate) fn new(direction: Direction) -> Self {
+        HeadBuffer {
+            direction,
+            pending: Vec::new(),
+            head_end: 0,
+            framing: Framing::Head,
+        }
+    }
+
+    /// The bytes to dump for `chunk`, masked, `None` while its head isn't complete. The requests
+    /// sent are pushed to `requests`, which the responses received are popped from.
+    pub(crate) fn redact<'a>(
+        &mut self,
+        redactor: &Redactor,
+        chunk: &'a [u8],
+        requests: &mut PendingRequests,
+    ) -> Option<Cow<'a, [u8]>> {
+        if redactor.headers.is_empty() {
+            return Some(Cow::Borrowed(chunk));
+        }
+
+        // Stays `None` as long as the chunk is only body
+        let mut output: Option<Vec<u8>> = None;
+        let mut i = 0;
+        while i < chunk.len() {
+            let body = match &mut self.framing {
+                Framing::Head => {
+                    let output = output.get_or_insert_with(|| chunk[..i].to_vec());
+                    let start = i;
+                    while i < chunk.len() && self.head_end < 4 {
+                        self.head_end = match (chunk[i], self.head_end) {
+                            (b'\r', 0 | 2) | (b'\n', 1 | 3) => self.head_end + 1,
+                            (b'\r', _) => 1,
+                            _ => 0,
+                        };
+                        i += 1;
+                    }
+                    self.pending.extend_from_slice(&chunk[start..i]);
+                    if self.head_end == 4 {
+                        self.head_end = 0;
+                        let head = std::mem::take(&mut self.pending);
+                        self.framing = self.body_framing(&head, requests);
+                        output.extend_from_slice(&redactor.bytes(&head));
+                    } else if self.pending.len() >= MAX_PENDING_HEAD {
+                        output
+                            .extend_from_slice(&redactor.bytes(&std::mem::take(&mut self.pending)));
+                    }
+                    continue;
+                }
+                Framing::Length(left) => {
+                    let body = (*left).min((chunk.len() - i) as u64);
+                    *left -= body;
+                    if *left == 0 {
+                        self.framing = Framing::Head;
+                    }
+                    body as usize
+                }
+                Framing::Chunked(Chunk::Data(left)) => {
+                    let body = (*left).min((chunk.len() - i) as u64);
+                    *left -= body;
+                    if *left == 0 {
+                        self.framing = Framing::Chunked(Chunk::Size(Vec::new()));
+                    }
+                    body as usize
+                }
+                Framing::Chunked(Chunk::Size(line)) => {
+                    line.push(chunk[i]);
+                    if chunk[i] == b'\n' {
+                        self.framing = match chunk_size(line) {
+                            0 => Framing::Chunked(Chunk::Trailer(0)),
+                            size => Framing::Chunked(Chunk::Data(size.saturating_add(2))),
+                        };
+                    }
+                    1
+                }
+                Framing::Chunked(Chunk::Trailer(len)) => {
+                    match chunk[i] {
+                        b'\n' if *len == 0 => self.framing = Framing::Head,
+                        b'\n' => *len = 0,
+                        b'\r' => {}
+                        _ => *len += 1,
+                    }
+                    1
+                }
+                Framing::UntilClose => chunk.len() - i,
+            };
+            if let Some(output) = output.as_mut() {
+                output.extend_from_slice(&chunk[i..i + body]);
+            }
+            i += body;
+        }
+
+        match output {
+            None => Some(Cow::Borrowed(chunk)),
+            Some(output) if output.is_empty() => None,
+            Some(output) => Some(Cow::Owned(output)),
+        }
+    }
+
+    /// The bytes of the incomplete head, masked, as the connection ends.
+    pub(crate) fn finish(&mut self, redactor: &Redactor) -> Option<Vec<u8>> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let pending = std::mem::take(&mut self.pending);
+        Some(redactor.bytes(&pending).into_owned())
+    }
+
+    /// Framing of the body following `head`.
+    fn body_framing(&self, head: &[u8], requests: &mut PendingRequests) -> Framing {
+        let mut lines = head
+            .split(|b| *b == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line));
+        let start_line = lines.next().unwrap_or_default();
+        let (mut length, mut chunked) = (None, false);
+        for line in lines {
+            let Some(colon) = line.iter().position(|b| *b == b':') else {
+                continue;
+            };
+            let value = String::from_utf8_lossy(&line[colon + 1..]);
+            let value = value.trim();
+            if line[..colon].eq_ignore_ascii_case(b"content-length") {
+                length = value.parse::<u64>().ok();
+            } else if line[..colon].eq_ignore_ascii_case(b"transfer-encoding") {
+                chunked = value.to_ascii_lowercase().ends_with("chunked");
+            }
+        }
+
+        match self.direction {
+            Direction::Sent => {
+                requests.push_back(start_line.starts_with(b"HEAD "));
+                match (chunked, length) {
+                    (true, _) => Framing::Chunked(Chunk::Size(Vec::new())),
+                    (false, Some(length @ 1..)) => Framing::Length(length),
+                    _ => Framing::Head,
+                }
+            }
+            Direction::Received => {
+                let status = start_line
+                    .get(9..12)
+                    .and_then(|status| std::str::from_utf8(status).ok()?.parse::<u16>().ok())
+                    .unwrap_or(200);
+                match status {
+                    101 => return Framing::UntilClose,
+                    100..=199 => return Framing::Head,
+                    _ => {}
+                }
+                let is_head = requests.pop_front().unwrap_or(false);
+                match (chunked, length) {
+                    _ if is_head || status == 204 || status == 304 => Framing::Head,
+                    (true, _) => Framing::Chunked(Chunk::Size(Vec::new())),
+                    (false, Some(0)) => Framing::Head,
+                    (false, Some(length)) => Framing::Length(length),
+                    (false, None) => Framing::UntilClose,
+                }
+            }
+        }
+    }
+}
+
+/// Size of a chunk from its line, hexadecimal before its extensions.
+fn chunk_size(line: &[u8]) -> u64 {
+    let digits = line
+        .iter()
+        .take_while(|b| b.is_ascii_hexdigit())
+        .map(|b| *b as char)
+        .collect::<String>();
+    u64::from_str_radix(&digits, 16).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::AUTHORIZATION;
+
+    fn redactor() -> Redactor {
+        let mut redactor = Redactor::default();
+        redactor.add(AUTHORIZATION);
+        redactor.add(HeaderName::from_static("x-registry-auth"));
+        redactor
+    }
+
+    #[test]
+    fn redact_head() {
+        let bytes = b"GET / HTTP/1.1\r\nauthorization: Bearer secret\r\nhost: unix\r\nX-Registry-Auth:abc\r\n\r\nAuthorization: body";
+
+        assert_eq!(
+            redactor().bytes(bytes).as_ref(),
+            b"GET / HTTP/1.1\r\nauthorization: ***\r\nhost: unix\r\nX-Registry-Auth: ***\r\n\r\nAuthorization: body"
+        );
+    }
+
+    fn dump(head: &mut HeadBuffer, chunks: &[&[u8]], requests: &mut PendingRequests) -> String {
+        let redactor = redactor();
+        let mut dumped = Vec::new();
+        for chunk in chunks {
+            if let Some(bytes) = head.redact(&redactor, chunk, requests) {
+                dumped.extend_from_slice(&bytes);
+            }
+        }
+        assert!(head.finish(&redactor).is_none());
+        String::from_utf8_lossy(&dumped).into_owned()
+    }
+
+    #[test]
+    fn redact_split_head() {
+        let dumped = dump(
+            &mut HeadBuffer::new(Direction::Sent),
+            &[
+                b"POST / HTTP/1.1\r\nauthorization: Bearer se",
+                b"cret\r\ncontent-length: 19\r",
+                b"\n\r\nAuthorization: body",
+                b"GET /next HTTP/1.1\r\nAuthoriz",
+                b"ation: Bearer other\r\n\r\n",
+            ],
+            &mut PendingRequests::new(),
+        );
+
+        assert_eq!(
+            dumped,
+            "POST / HTTP/1.1\r\nauthorization: ***\r\ncontent-length: 19\r\n\r\nAuthorization: body\
+             GET /next HTTP/1.1\r\nAuthorization: ***\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn redact_not_bodies() {
+        let redactor = redactor();
+        let mut head = HeadBuffer::new(Direction::Sent);
+        let mut requests = PendingRequests::new();
+        let sent = head.redact(
+            &redactor,
+            b"POST / HTTP/1.1\r\ncontent-length: 34\r\n\r\n",
+            &mut requests,
+        );
+        assert!(sent.is_some());
+
+        // Read on their own, body chunks looking like heads are passed as is
+        let body = &b"GET x\r\nAuthorization: Bearer body"[..];
+        assert!(matches!(
+            head.redact(&redactor, body, &mut requests),
+            Some(Cow::Borrowed(bytes)) if bytes == body
+        ));
+        assert_eq!(
+            dump(
+                &mut head,
+                &[b"GET / HTTP/1.1\r\nauthorization: secret\r\n\r\n"],
+                &mut requests,
+            ),
+            "GET / HTTP/1.1\r\nauthorization: ***\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn redact_chunked_body() {
+        let dumped = dump(
+            &mut HeadBuffer::new(Direction::Received),
+            &[
+                b"HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n10\r\nAuthorization: x\r",
+                b"\n",
+                b"1c;ext\r\nAuthorization: Bearer  chunk\r\n0\r\nx-trailer: 1\r\n\r",
+                b"\nHTTP/1.1 204 No Content\r\nAuthorization: secret\r\n\r\n",
+                b"HTTP/1.1 200 OK\r\nAuthorization: secret\r\ncontent-length: 4\r\n\r\nbody",
+            ],
+            &mut PendingRequests::new(),
+        );
+
+        assert_eq!(
+            dumped,
+            "HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n10\r\nAuthorization: x\r\n\
+             1c;ext\r\nAuthorization: Bearer  chunk\r\n0\r\nx-trailer: 1\r\n\r\n\
+             HTTP/1.1 204 No Content\r\nAuthorization: ***\r\n\r\n\
+             HTTP/1.1 200 OK\r\nAuthorization: ***\r\ncontent-length: 4\r\n\r\nbody"
+        );
+    }
+
+    #[test]
+    fn redact_head_response() {
+        let mut requests = PendingRequests::new();
+        dump(
+            &mut HeadBuffer::new(Direction::Sent),
+            &[b"HEAD / HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\n"],
+            &mut requests,
+        );
+        let dumped = dump(
+            &mut HeadBuffer::new(Direction::Received),
+            &[
+                b"HTTP/1.1 100 Continue\r\n\r\n",
+                b"HTTP/1.1 200 OK\r\ncontent-length: 4\r\n\r\n",
+                b"HTTP/1.1 200 OK\r\ncontent-length: 4\r\nauthorization: secret\r\n\r\nbody",
+            ],
+            &mut requests,
+        );
+
+        assert_eq!(
+            dumped,
+            "HTTP/1.1 100 Continue\r\n\r\n\
+             HTTP/1.1 200 OK\r\ncontent-length: 4\r\n\r\n\
+             HTTP/1.1 200 OK\r\ncontent-length: 4\r\nauthorization: ***\r\n\r\nbody"
+        );
+        assert!(requests.is_empty());
+    }
+
+    #[test]
+    fn redact_nothing_borrows() {
+        let bytes = b"GET / HTTP/1.1\r\nhost: unix\r\n\r\n";
+
+        assert!(matches!(redactor().bytes(bytes), Cow::Borrowed(_)));
+    }
+}
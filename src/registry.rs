@@ -0,0 +1,147 @@
+use crate::{ClientUnix, ClientUnixBuilder, Error};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex as StdMutex},
+};
+use tokio::sync::{Mutex, OnceCell};
+
+type Entry = Arc<OnceCell<Arc<Mutex<ClientUnix>>>>;
+
+/// Clients of several sockets shared by the application, each connected on its first use.
+///
+/// The clients are created by the builder given to [`with_builder`](Self::with_builder), so
+/// they share the same settings. A client which fails to connect is created again on the next
+/// call. Clones share the same clients.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::{ClientRegistry, ClientUnix, Method};
+/// use std::time::Duration;
+///
+/// # async fn run() {
+/// let registry = ClientRegistry::with_builder(|socket_path| {
+///     ClientUnix::builder(socket_path).request_timeout(Duration::from_secs(5))
+/// });
+/// for socket_path in ["/run/vm/nolanv-1.sock", "/run/vm/nolanv-2.sock"] {
+///     let client = registry.get(socket_path).await.expect("registry.get");
+///     let (_, body) = client
+///         .lock()
+///         .await
+///         .send_request("/vm.info", Method::GET, &[], None)
+///         .await
+///         .expect("client.send_request");
+/// }
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ClientRegistry {
+    builder: Arc<dyn Fn(&str) -> ClientUnixBuilder + Send + Sync>,
+    clients: Arc<StdMutex<HashMap<String, Entry>>>,
+}
+
+impl ClientRegistry {
+    /// Registry of clients with the default settings.
+    pub fn new() -> Self {
+        ClientRegistry::with_builder(ClientUnix::builder)
+    }
+
+    /// Registry of clients created by `builder` from their socket path.
+    pub fn with_builder(
+        builder: impl Fn(&str) -> ClientUnixBuilder + Send + Sync + 'static,
+    ) -> Self {
+        ClientRegistry {
+            builder: Arc::new(builder),
+            clients: Arc::new(StdMutex::new(HashMap::new())),
+        }
+    }
+
+    /// The client of `socket_path`, connected by the first call. The concurrent calls for the
+    /// same socket wait for the same connection.
+    pub async fn get(&self, socket_path: &str) -> Result<Arc<Mutex<ClientUnix>>, Error> {
+        let entry = match self.clients.lock() {
+            Ok(mut clients) => clients.entry(socket_path.to_string()).or_default().clone(),
+            Err(_) => Entry::default(),
+        };
+        entry
+            .get_or_try_init(|| async {
+                let client = (self.builder)(socket_path).build().await?;
+                Ok(Arc::new(Mutex::new(client)))
+            })
+            .await
+            .cloned()
+    }
+
+    /// Forget the client of `socket_path`, its connection is closed once no one holds it.
+    /// Returns whether there was one.
+    pub fn remove(&self, socket_path: &str) -> bool {
+        self.clients
+            .lock()
+            .is_ok_and(|mut clients| clients.remove(socket_path).is_some())
+    }
+
+    /// The socket paths of the connected clients.
+    pub fn socket_paths(&self) -> Vec<String> {
+        self.clients.lock().map_or_else(
+            |_| Vec::new(),
+            |clients| {
+                clients
+                    .iter()
+                    .filter(|(_, entry)| entry.initialized())
+                    .map(|(socket_path, _)| socket_path.clone())
+                    .collect()
+            },
+        )
+    }
+}
+
+impl Default for ClientRegistry {
+    fn default() -> Self {
+        ClientRegistry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Method, test_helpers::server::Server, test_helpers::util::*};
+
+    #[tokio::test]
+    async fn share_clients_by_socket() {
+        let first = make_socket_path_test("registry", "share_clients_by_socket_1");
+        let second = make_socket_path_test("registry", "share_clients_by_socket_2");
+        let missing = make_socket_path_test("registry", "share_clients_by_socket_missing");
+        let _servers = (
+            Server::try_new(&first).await.expect("Server::try_new"),
+            Server::try_new(&second).await.expect("Server::try_new"),
+        );
+        let registry = ClientRegistry::new();
+
+        let client = registry.get(&first).await.expect("registry.get");
+        assert!(Arc::ptr_eq(
+            &client,
+            &registry.clone().get(&first).await.expect("registry.get")
+        ));
+        assert!(!Arc::ptr_eq(
+            &client,
+            &registry.get(&second).await.expect("registry.get")
+        ));
+        let (_, body) = client
+            .lock()
+            .await
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"Hello nolanv");
+
+        assert!(registry.get(&missing).await.is_err());
+        let mut socket_paths = registry.socket_paths();
+        socket_paths.sort();
+        assert_eq!(socket_paths, [first.clone(), second]);
+
+        assert!(registry.remove(&first));
+        assert!(!registry.remove(&first));
+        assert!(!Arc::ptr_eq(
+            &client,
+            &registry.get(&first).await.expect("registry.get")
+        ));
+    }
+}
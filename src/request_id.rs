@@ -0,0 +1,100 @@
+use axum_core::body::Body;
+use hyper::{
+    Request,
+    header::{HeaderName, HeaderValue},
+};
+
+/// Identifier of a request, enabled with [`ClientUnixBuilder::request_id_header`](crate::ClientUnixBuilder::request_id_header).
+///
+/// Inserted in the extensions of the responses returned by the client, included in its tracing
+/// spans (feature=tracing) and in the [`ErrorContext`](crate::ErrorContext) of its errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// Keep the id already set on `request` for `header`, or generate a new UUID v4.
+pub(crate) fn ensure(header: &HeaderName, request: &mut Request<Body>) -> RequestId {
+    if let Some(value) = request
+        .headers()
+        .get(header)
+        .and_then(|value| value.to_str().ok())
+    {
+        return RequestId(value.to_string());
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        request.headers_mut().insert(header.clone(), value);
+    }
+    RequestId(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ClientUnix, Error, ErrorAndResponse, test_helpers::server::Server, test_helpers::util::*,
+    };
+    use hyper::Method;
+
+    const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+    #[test]
+    fn keep_existing_id() {
+        let mut request = Request::builder()
+            .header("x-request-id", "nolanv")
+            .body(Body::empty())
+            .unwrap();
+
+        assert_eq!(
+            ensure(&X_REQUEST_ID, &mut request),
+            RequestId("nolanv".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn request_id_is_sent() {
+        let socket_path = make_socket_path_test("request_id", "request_id_is_sent");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .request_id_header(X_REQUEST_ID)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let (_, first) = client
+            .send_request("/header/x-request-id", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        let (_, second) = client
+            .send_request("/header/x-request-id", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+
+        assert_eq!(first.len(), 36);
+        assert_ne!(first, second);
+    }
+
+    #[tokio::test]
+    async fn request_id_in_errors() {
+        let socket_path = make_socket_path_test("request_id", "request_id_in_errors");
+        let server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let mut client = ClientUnix::builder(&socket_path)
+            .request_id_header(X_REQUEST_ID)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+        server.abort().await;
+
+        let result = client.send_request("/nolanv", Method::GET, &[], None).await;
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponse::InternalError(e))
+                if e.context().and_then(|c| c.request_id.as_ref()).is_some_and(|id| id.len() == 36)
+                    && matches!(e.inner(), Error::RequestSend(_))
+        ));
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{ClientUnix, Method, coalesce::SharedResponse};
+use hyper::Response;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Bounded cache of the successful responses of chosen GET endpoints, kept for a fixed time, set
+/// with [`ClientUnixBuilder::response_cache`](crate::ClientUnixBuilder::response_cache).
+///
+/// Meant for the read-mostly endpoints of local APIs, without the HTTP caching semantics: the
+/// responses are keyed by socket, method and endpoint, query included, whatever the request
+/// headers and the `Cache-Control` of the response. The least recently used one is evicted when
+/// full. A cached response has the status code, headers and body of the original one, but not its
+/// extensions. Clones share the same entries, so several clients can use one cache.
+///
+/// ```rust
+/// use http_client_unix_domain_socket::{ClientUnix, Method, ResponseCache};
+/// use std::time::Duration;
+///
+/// pub async fn version() {
+///     let cache = ResponseCache::new(16, Duration::from_secs(60))
+///         .endpoint("/version")
+///         .endpoint("/info");
+///     let mut client = ClientUnix::builder("/var/run/docker.sock")
+///         .response_cache(cache)
+///         .build()
+///         .await
+///         .expect("ClientUnixBuilder::build");
+///     // Only the first request reaches the daemon for a minute
+///     for _ in 0..10 {
+///         let (_, version) = client
+///             .send_request("/version", Method::GET, &[], None)
+///             .await
+///             .expect("client.send_request");
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    entries: Arc<Mutex<CacheEntries>>,
+    endpoints: Arc<HashSet<String>>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    entries: HashMap<String, CacheEntry>,
+    /// Incremented on every use, to find the least recently used entry.
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    response: SharedResponse,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+impl ResponseCache {
+    /// Keep at most `capacity` responses, each for `ttl`.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        ResponseCache {
+            entries: Arc::new(Mutex::new(CacheEntries::default())),
+            endpoints: Arc::new(HashSet::new()),
+            capacity,
+            ttl,
+        }
+    }
+
+    /// Cache the GET requests to `path`, such as `/version`, whatever their query.
+    pub fn endpoint(mut self, path: &str) -> Self {
+        Arc::make_mut(&mut self.endpoints).insert(path.to_string());
+        self
+    }
+
+    /// Drop the cached responses.
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.entries.clear();
+        }
+    }
+
+    /// Cached responses, the expired ones included until replaced or evicted.
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .map_or(0, |entries| entries.entries.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the responses of the request are cached.
+    pub(crate) fn caches(&self, method: &Method, endpoint: &str) -> bool {
+        let path = endpoint.split_once('?').map_or(endpoint, |(path, _)| path);
+        method == Method::GET && self.capacity > 0 && self.endpoints.contains(path)
+    }
+
+    /// The cached response of the request, if not expired.
+    pub(crate) fn get(
+        &self,
+        client: &ClientUnix,
+        method: &Method,
+        endpoint: &str,
+    ) -> Option<Response<Vec<u8>>> {
+        let key = cache_key(client, method, endpoint);
+        let mut entries = self.entries.lock().ok()?;
+        entries.clock += 1;
+        let clock = entries.clock;
+        let entry = entries.entries.get_mut(&key)?;
+        if entry.expires_at <= Instant::now() {
+            entries.entries.remove(&key);
+            return None;
+        }
+        entry.last_used = clock;
+        Some(entry.response.to_response())
+    }
+
+    /// Cache the response if successful, evicting the least recently used one when full.
+    pub(crate) fn insert(
+        &self,
+        client: &ClientUnix,
+        method: &Method,
+        endpoint: &str,
+        response: &Response<Vec<u8>>,
+    ) {
+        if !response.status().is_success() {
+            return;
+        }
+        let key = cache_key(client, method, endpoint);
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        if !entries.entries.contains_key(&key) && entries.entries.len() >= self.capacity {
+            let now = Instant::now();
+            let evicted = entries
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| (entry.expires_at > now, entry.last_used))
+                .map(|(key, _)| key.clone());
+            if let Some(evicted) = evicted {
+                entries.entries.remove(&evicted);
+            }
+        }
+        entries.clock += 1;
+        let last_used = entries.clock;
+        entries.entries.insert(
+            key,
+            CacheEntry {
+                response: SharedResponse::new(response),
+                expires_at: Instant::now() + self.ttl,
+                last_used,
+            },
+        );
+    }
+}
+
+fn cache_key(client: &ClientUnix, method: &Method, endpoint: &str) -> String {
+    format!(
+        "{}\n{}\n{}",
+        client.socket_path().display(),
+        method,
+        endpoint
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_helpers::server::Server, test_helpers::util::*};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn cache_chosen_endpoints() {
+        let socket_path = make_socket_path_test("response_cache", "cache_chosen_endpoints");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let cache = ResponseCache::new(1, Duration::from_secs(60))
+            .endpoint("/nolanv")
+            .endpoint("/other");
+        let requests = Arc::new(AtomicUsize::new(0));
+        let on_request = requests.clone();
+        let mut client = ClientUnix::builder(&socket_path)
+            .response_cache(cache.clone())
+            .on_request(move |_| {
+                on_request.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        for endpoint in ["/nolanv", "/nolanv", "/nolanv2", "/nolanv2"] {
+            client
+                .send_request(endpoint, Method::GET, &[], None)
+                .await
+                .expect("client.send_request");
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        assert_eq!(cache.len(), 1);
+
+        // Evicts /nolanv
+        let (_, body) = client
+            .send_request("/other", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"Hello other");
+        let (_, body) = client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"Hello nolanv");
+        assert_eq!(requests.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn expire_after_ttl() {
+        let socket_path = make_socket_path_test("response_cache", "expire_after_ttl");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let cache = ResponseCache::new(4, Duration::from_millis(20)).endpoint("/nolanv");
+        let mut client = ClientUnix::builder(&socket_path)
+            .response_cache(cache.clone())
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert!(cache.get(&client, &Method::GET, "/nolanv").is_some());
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(cache.get(&client, &Method::GET, "/nolanv").is_none());
+        assert!(cache.is_empty());
+    }
+}
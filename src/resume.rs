@@ -0,0 +1,212 @@
+use crate::{ClientUnix, Error, ErrorAndResponse, client::Options};
+use hyper::{
+    Method, StatusCode,
+    body::Incoming,
+    header::{CONTENT_RANGE, ETAG},
+    http::{response::Parts, uri::PathAndQuery},
+};
+
+/// Strong `ETag` of a download which can be resumed by
+/// [`ClientUnixBuilder::resume_downloads`](crate::ClientUnixBuilder::resume_downloads).
+pub(crate) fn resumable_etag(options: &Options, method: &Method, parts: &Parts) -> Option<String> {
+    if options.resume_downloads == 0 || *method != Method::GET || parts.status != StatusCode::OK {
+        return None;
+    }
+    let etag = parts.headers.get(ETAG)?.to_str().ok()?;
+    // `If-Range` needs a strong validator
+    (!etag.starts_with("W/")).then(|| etag.to_string())
+}
+
+impl ClientUnix {
+    /// Reconnect and request the rest of the download from `offset`, as long as its `ETag` is
+    /// still `etag`.
+    pub(crate) async fn resume_download(
+        &mut self,
+        endpoint: &PathAndQuery,
+        headers: &[(&str, &str)],
+        etag: &str,
+        offset: u64,
+    ) -> Result<Incoming, Error> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(offset, "resuming download");
+        #[cfg(feature = "log")]
+        log::debug!("resuming download of {} from {}", endpoint, offset);
+
+        self.reconnect_in_place().await?;
+        let range = format!("bytes={}-", offset);
+        let mut headers: Vec<(&str, &str)> = headers
+            .iter()
+            .filter(|(name, _)| {
+                !name.eq_ignore_ascii_case("range") && !name.eq_ignore_ascii_case("if-range")
+            })
+            .copied()
+            .collect();
+        headers.extend([("range", range.as_str()), ("if-range", etag)]);
+        let response = self
+            .send_streaming(endpoint, Method::GET, &headers, None)
+            .await
+            .map_err(|e| match e {
+                ErrorAndResponse::InternalError(e) => e,
+                _ => Error::ResumeRejected("unsuccessful response"),
+            })?;
+
+        // A full response means that the `If-Range` didn't match
+        if response.status() != StatusCode::PARTIAL_CONTENT
+            || response
+                .headers()
+                .get(ETAG)
+                .is_none_or(|value| value != etag)
+        {
+            return Err(Error::ResumeRejected("the resource changed"));
+        }
+        let start = format!("bytes {}-", offset);
+        if !response
+            .headers()
+            .get(CONTENT_RANGE)
+            .is_some_and(|value| value.as_bytes().starts_with(start.as_bytes()))
+        {
+            return Err(Error::ResumeRejected("unexpected content range"));
+        }
+        Ok(response.into_body())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ClientUnix, Error, ErrorAndResponse, Method};
+    use axum::{body::Body, http::HeaderMap, response::IntoResponse};
+    use futures_util::StreamExt;
+    use std::sync::{Arc, Mutex};
+
+    /// Drops the connection after `hello ` until asked for the rest, changed once `version` is
+    /// `v2`.
+    async fn download(version: &'static str, headers: HeaderMap) -> axum::response::Response {
+        let etag = format!("\"{}\"", version);
+        let range = headers.get("range").and_then(|value| value.to_str().ok());
+        let if_range = headers
+            .get("if-range")
+            .and_then(|value| value.to_str().ok());
+        match (range, if_range) {
+            (Some("bytes=6-"), Some(if_range)) if if_range == etag => (
+                axum::http::StatusCode::PARTIAL_CONTENT,
+                [
+                    ("etag", etag),
+                    ("content-range", "bytes 6-10/11".to_string()),
+                ],
+                "world",
+            )
+                .into_response(),
+            _ => (
+                [("etag", etag)],
+                Body::from_stream(futures_util::stream::once(async { Ok("hello ") }).chain(
+                    futures_util::stream::once(async {
+                        // Let the head and the first chunk be flushed
+                        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                        Err(std::io::Error::other("nolanv connection dropped"))
+                    }),
+                )),
+            )
+                .into_response(),
+        }
+    }
+
+    #[tokio::test]
+    async fn resume_interrupted_download() {
+        let server = crate::testing::MockServer::start(
+            axum::Router::new()
+                .route(
+                    "/files/nolanv",
+                    axum::routing::get(|headers| download("v1", headers)),
+                )
+                .route(
+                    "/files/changed",
+                    axum::routing::get(|headers: HeaderMap| async move {
+                        let version = match headers.contains_key("range") {
+                            true => "v2",
+                            false => "v1",
+                        };
+                        download(version, headers).await
+                    }),
+                ),
+        )
+        .await
+        .expect("MockServer::start");
+        let socket_path = server.socket_path().to_string_lossy().to_string();
+
+        let mut client = ClientUnix::builder(&socket_path)
+            .resume_downloads(1)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+        let mut file = Vec::new();
+        let response = client
+            .send_request_to_writer("/files/nolanv", Method::GET, &[], None, &mut file)
+            .await
+            .expect("client.send_request_to_writer");
+        assert_eq!(*response.body(), 11);
+        assert_eq!(file, b"hello world");
+
+        let mut file = Vec::new();
+        let error = client
+            .send_request_to_writer("/files/changed", Method::GET, &[], None, &mut file)
+            .await
+            .expect_err("client.send_request_to_writer");
+        assert!(
+            matches!(&error, ErrorAndResponse::InternalError(e)
+                if matches!(e.inner(), Error::ResumeRejected("the resource changed"))),
+            "{:?}",
+            error
+        );
+        assert_eq!(error.kind(), crate::ErrorKind::Protocol);
+
+        let mut client = ClientUnix::try_new(&socket_path)
+            .await
+            .expect("ClientUnix::try_new");
+        let error = client
+            .send_request_to_writer("/files/nolanv", Method::GET, &[], None, &mut Vec::new())
+            .await
+            .expect_err("client.send_request_to_writer");
+        assert!(
+            matches!(&error, ErrorAndResponse::InternalError(e)
+                if matches!(e.inner(), Error::ResponseCollect(_))),
+            "{:?}",
+            error
+        );
+    }
+
+    #[tokio::test]
+    async fn resume_typed_endpoint() {
+        let paths = Arc::new(Mutex::new(Vec::new()));
+        let received = paths.clone();
+        let server = crate::testing::MockServer::start(axum::Router::new().route(
+            "/files/{*name}",
+            axum::routing::get(move |uri: axum::http::Uri, headers| {
+                received
+                    .lock()
+                    .expect("received.lock")
+                    .push(uri.path().to_string());
+                download("v1", headers)
+            }),
+        ))
+        .await
+        .expect("MockServer::start");
+        let mut client = ClientUnix::builder(&server.socket_path().to_string_lossy())
+            .resume_downloads(1)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        // Sent as is by the first request and the resumed one, not encoded again
+        let endpoint = hyper::http::uri::PathAndQuery::from_static("/files/100%/{id}");
+        let mut file = Vec::new();
+        client
+            .send_request_to_writer(&endpoint, Method::GET, &[], None, &mut file)
+            .await
+            .expect("client.send_request_to_writer");
+        assert_eq!(file, b"hello world");
+        assert_eq!(
+            *paths.lock().expect("paths.lock"),
+            ["/files/100%/{id}", "/files/100%/{id}"]
+        );
+    }
+}
@@ -0,0 +1,468 @@
+use crate::{Body, ErrorKind, Method, client::RequestBody};
+use http_body_util::BodyExt;
+use hyper::{
+    body::{Body as _, Bytes, Frame, SizeHint},
+    header::HeaderName,
+};
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// When and how often a failed request is sent again, see
+/// [`ClientUnixBuilder::retry`](crate::ClientUnixBuilder::retry).
+///
+/// Only requests which failed before a response was received are retried: connection errors and
+/// connections closed by the server before the response head. A connection closed while the
+/// response body is read fails with [`ErrorKind::ResponseInterrupted`] without retry. Non
+/// idempotent methods (`POST`, `PATCH`) are only retried with an
+/// [`idempotency_key`](Self::idempotency_key). The request body is buffered to be sent again, up
+/// to [`max_replay_body`](Self::max_replay_body), and the client reconnects before a retry if the
+/// connection is closed.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub(crate) max_retries: u32,
+    pub(crate) initial_backoff: Duration,
+    pub(crate) max_backoff: Duration,
+    pub(crate) jitter: bool,
+    pub(crate) idempotency_key: Option<HeaderName>,
+    pub(crate) budget: Option<RetryBudget>,
+    pub(crate) max_replay_body: Option<usize>,
+}
+
+/// Limit of the share of the traffic which may be retries, shared by every client whose
+/// [`RetryPolicy`] was given a clone of it.
+///
+/// Every request deposits `ratio` token and every retry withdraws one, so a daemon which is
+/// flapping sees at most `ratio` more traffic than usual instead of `max_retries` times more. The
+/// bucket starts with, and holds at most, `min_retries` tokens so low traffic can still be retried.
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    ratio: f64,
+    max_tokens: f64,
+    tokens: Arc<Mutex<f64>>,
+}
+
+impl RetryBudget {
+    /// Allow e.g. `ratio = 0.1` for retries to be at most 10% of the requests.
+    pub fn new(ratio: f64, min_retries: u32) -> Self {
+        let max_tokens = f64::from(min_retries.max(1));
+        RetryBudget {
+            ratio,
+            max_tokens,
+            tokens: Arc::new(Mutex::new(max_tokens)),
+        }
+    }
+
+    pub(crate) fn deposit(&self) {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        *tokens = (*tokens + self.ratio).min(self.max_tokens);
+    }
+
+    pub(crate) fn try_withdraw(&self) -> bool {
+        let mut tokens = self.tokens.lock().unwrap_or_else(|e| e.into_inner());
+        if *tokens < 1.0 {
+            return false;
+        }
+        *tokens -= 1.0;
+        true
+    }
+}
+
+impl RetryPolicy {
+    /// Retry up to `max_retries` times, waiting 100ms then twice longer after each attempt, up to
+    /// 5s, with full jitter.
+    pub fn new(max_retries: u32) -> Self {
+        RetryPolicy {
+            max_retries,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            jitter: true,
+            idempotency_key: None,
+            budget: None,
+            max_replay_body: None,
+        }
+    }
+
+    /// Wait before the first retry, doubled after each attempt up to `max`.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Wait a random duration between zero and the backoff (full jitter), enabled by default, so
+    /// clients failing at the same time don't retry at the same time.
+    pub fn jitter(mut self, enabled: bool) -> Self {
+        self.jitter = enabled;
+        self
+    }
+
+    /// Share `budget` with the other clients whose policy has a clone of it.
+    pub fn budget(mut self, budget: RetryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Send a generated UUID v4 as `header` (usually `Idempotency-Key`), the same for every attempt
+    /// of a request which doesn't already set it, so the server can deduplicate them. This makes
+    /// non idempotent methods retryable.
+    pub fn idempotency_key(mut self, header: HeaderName) -> Self {
+        self.idempotency_key = Some(header);
+        self
+    }
+
+    /// Buffer at most `bytes` of a streamed request body to send it again, a larger body is sent
+    /// once and not retried, rather than held in memory. Bodies already in memory, as the ones of
+    /// `send_request_bytes`, are always sent again. No limit by default.
+    pub fn max_replay_body(mut self, bytes: usize) -> Self {
+        self.max_replay_body = Some(bytes);
+        self
+    }
+
+    pub(crate) fn should_retry(&self, attempt: u32, method: &Method, kind: ErrorKind) -> bool {
+        attempt < self.max_retries
+            && (self.idempotency_key.is_some() || is_idempotent(method))
+            && matches!(
+                kind,
+                ErrorKind::SocketNotFound
+                    | ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionClosed
+                    | ErrorKind::Connect
+            )
+            && self.budget.as_ref().is_none_or(RetryBudget::try_withdraw)
+    }
+
+    /// Called once per request, before its first attempt.
+    pub(crate) fn record_request(&self) {
+        if let Some(budget) = &self.budget {
+            budget.deposit();
+        }
+    }
+
+    /// Wait before the retry `attempt`, jittered.
+    pub(crate) fn delay_for(&self, attempt: u32) -> Duration {
+        let backoff = self.backoff_for(attempt);
+        match self.jitter {
+            true => backoff.mul_f64(fastrand::f64()),
+            false => backoff,
+        }
+    }
+
+    /// Wait before the retry `attempt`, starting from 0.
+    pub(crate) fn backoff_for(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    !matches!(*method, Method::POST | Method::PATCH | Method::CONNECT)
+}
+
+/// Read a streamed body into memory to send it again, unless it's larger than `limit`: the body
+/// is then returned as a stream, starting with the bytes already read.
+pub(crate) async fn replayable(
+    mut body: Body,
+    limit: Option<usize>,
+) -> Result<RequestBody, axum_core::Error> {
+    let Some(limit) = limit else {
+        return Ok(RequestBody::Bytes(Some(body.collect().await?.to_bytes())));
+    };
+    if body.size_hint().lower() > limit as u64 {
+        return Ok(RequestBody::Stream(body));
+    }
+    let mut buffered = Vec::new();
+    while let Some(frame) = body.frame().await {
+        if let Ok(data) = frame?.into_data() {
+            buffered.extend_from_slice(&data);
+            if buffered.len() > limit {
+                return Ok(RequestBody::Stream(Body::new(Prefixed {
+                    prefix: Some(buffered.into()),
+                    body,
+                })));
+            }
+        }
+    }
+    Ok(RequestBody::Bytes(Some(buffered.into())))
+}
+
+/// Body whose first bytes were already read.
+struct Prefixed {
+    prefix: Option<Bytes>,
+    body: Body,
+}
+
+impl hyper::body::Body for Prefixed {
+    type Data = Bytes;
+    type Error = axum_core::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        match self.prefix.take() {
+            Some(prefix) => Poll::Ready(Some(Ok(Frame::data(prefix)))),
+            None => Pin::new(&mut self.body).poll_frame(cx),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.prefix.is_none() && self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let prefix = self.prefix.as_ref().map_or(0, |prefix| prefix.len() as u64);
+        let hint = self.body.size_hint();
+        let mut size_hint = SizeHint::new();
+        size_hint.set_lower(hint.lower() + prefix);
+        if let Some(upper) = hint.upper() {
+            size_hint.set_upper(upper + prefix);
+        }
+        size_hint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, test_helpers::server::Server, test_helpers::util::*};
+    use hyper::body::Bytes;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn backoff_is_capped() {
+        let policy =
+            RetryPolicy::new(5).backoff(Duration::from_millis(100), Duration::from_millis(300));
+
+        assert_eq!(policy.backoff_for(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff_for(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff_for(2), Duration::from_millis(300));
+        assert_eq!(policy.backoff_for(40), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn post_needs_idempotency_key() {
+        let policy = RetryPolicy::new(1);
+        assert!(policy.should_retry(0, &Method::GET, ErrorKind::ConnectionClosed));
+        assert!(!policy.should_retry(1, &Method::GET, ErrorKind::ConnectionClosed));
+        assert!(!policy.should_retry(0, &Method::GET, ErrorKind::InvalidRequest));
+        assert!(!policy.should_retry(0, &Method::POST, ErrorKind::ConnectionClosed));
+
+        let policy = policy.idempotency_key(HeaderName::from_static("idempotency-key"));
+        assert!(policy.should_retry(0, &Method::POST, ErrorKind::ConnectionClosed));
+    }
+
+    #[test]
+    fn full_jitter() {
+        let policy = RetryPolicy::new(1);
+        for _ in 0..100 {
+            assert!(policy.delay_for(0) <= Duration::from_millis(100));
+        }
+        assert_eq!(
+            policy.jitter(false).delay_for(0),
+            Duration::from_millis(100)
+        );
+    }
+
+    #[test]
+    fn budget_is_shared() {
+        let budget = RetryBudget::new(0.5, 1);
+        let (first, second) = (
+            RetryPolicy::new(5).budget(budget.clone()),
+            RetryPolicy::new(5).budget(budget),
+        );
+
+        assert!(first.should_retry(0, &Method::GET, ErrorKind::ConnectionClosed));
+        assert!(!second.should_retry(0, &Method::GET, ErrorKind::ConnectionClosed));
+        first.record_request();
+        second.record_request();
+        assert!(second.should_retry(0, &Method::GET, ErrorKind::ConnectionClosed));
+        assert!(!first.should_retry(1, &Method::GET, ErrorKind::ConnectionClosed));
+    }
+
+    #[tokio::test]
+    async fn retry_with_same_idempotency_key() {
+        let socket_path = make_socket_path_test("retry", "retry_with_same_idempotency_key");
+        let server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let keys = Arc::new(Mutex::new(Vec::new()));
+        let on_request = keys.clone();
+        let mut client = ClientUnix::builder(&socket_path)
+            .retry(
+                RetryPolicy::new(2)
+                    .backoff(Duration::from_millis(1), Duration::from_millis(1))
+                    .idempotency_key(HeaderName::from_static("idempotency-key")),
+            )
+            .on_request(move |event| {
+                on_request
+                    .lock()
+                    .unwrap()
+                    .push(event.headers.get("idempotency-key").cloned())
+            })
+            .reconnect_when_closed(false)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+        server.abort().await;
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+
+        let (_, response) = client
+            .send_request(
+                "/header/idempotency-key",
+                Method::POST,
+                &[],
+                Some("nolanv".into()),
+            )
+            .await
+            .expect("client.send_request");
+
+        let keys = keys.lock().unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0], keys[1]);
+        assert_eq!(keys[1].as_ref().map(|k| k.as_bytes()), Some(&response[..]));
+    }
+
+    #[tokio::test]
+    async fn retry_with_same_bytes_body() {
+        let socket_path = make_socket_path_test("retry", "retry_with_same_bytes_body");
+        let server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let attempts = Arc::new(Mutex::new(0));
+        let on_request = attempts.clone();
+        let mut client = ClientUnix::builder(&socket_path)
+            .retry(RetryPolicy::new(2).backoff(Duration::from_millis(1), Duration::from_millis(1)))
+            .on_request(move |_| *on_request.lock().unwrap() += 1)
+            .reconnect_when_closed(false)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+        server.abort().await;
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+
+        let body = Bytes::from_static(b"nolanv");
+        let response = client
+            .send_request_bytes(
+                "/header/content-length",
+                Method::PUT,
+                &[],
+                Some(body.clone()),
+            )
+            .await
+            .expect("client.send_request_bytes");
+
+        assert_eq!(*attempts.lock().unwrap(), 2);
+        assert_eq!(response.into_body(), b"6");
+    }
+
+    #[tokio::test]
+    async fn replay_streamed_body_up_to_limit() {
+        for (max_replay_body, attempts) in [(16, 2), (4, 1)] {
+            let socket_path = make_socket_path_test(
+                "retry",
+                &format!("replay_streamed_body_up_to_limit_{}", max_replay_body),
+            );
+            let server = Server::try_new(&socket_path)
+                .await
+                .expect("Server::try_new");
+            let counted = Arc::new(Mutex::new(0));
+            let on_request = counted.clone();
+            let mut client = ClientUnix::builder(&socket_path)
+                .retry(
+                    RetryPolicy::new(2)
+                        .backoff(Duration::from_millis(1), Duration::from_millis(1))
+                        .max_replay_body(max_replay_body),
+                )
+                .on_request(move |_| *on_request.lock().unwrap() += 1)
+                .reconnect_when_closed(false)
+                .build()
+                .await
+                .expect("ClientUnixBuilder::build");
+            server.abort().await;
+            let _server = Server::try_new(&socket_path)
+                .await
+                .expect("Server::try_new");
+
+            let body = Body::from_stream(futures_util::stream::iter([
+                Ok::<_, std::io::Error>("nol"),
+                Ok("anv"),
+            ]));
+            let result = client
+                .send_request("/header/content-length", Method::PUT, &[], Some(body))
+                .await;
+
+            assert_eq!(*counted.lock().unwrap(), attempts);
+            match attempts {
+                2 => assert_eq!(result.expect("client.send_request").1, b"6"),
+                _ => assert_eq!(
+                    result.expect_err("client.send_request").kind(),
+                    ErrorKind::ConnectionClosed
+                ),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn interrupted_response_not_retried() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let socket_path = make_socket_path_test("retry", "interrupted_response_not_retried");
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = tokio::net::UnixListener::bind(&socket_path).expect("UnixListener::bind");
+        let requests = Arc::new(Mutex::new(0));
+        let counted = requests.clone();
+        let server = tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.expect("listener.accept");
+                let _ = stream.read(&mut [0; 1024]).await;
+                *counted.lock().unwrap() += 1;
+                // The head and the start of the body, then the connection is dropped
+                let _ = stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 12\r\n\r\nHello")
+                    .await;
+            }
+        });
+        let mut client = ClientUnix::builder(&socket_path)
+            .retry(RetryPolicy::new(3).backoff(Duration::from_millis(1), Duration::from_millis(1)))
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let error = client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect_err("client.send_request");
+
+        assert_eq!(error.kind(), ErrorKind::ResponseInterrupted);
+        assert_eq!(*requests.lock().unwrap(), 1);
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn send_large_streamed_body_once() {
+        let body = Body::from_stream(futures_util::stream::iter([
+            Ok::<_, std::io::Error>("nol"),
+            Ok("anv"),
+        ]));
+        let RequestBody::Stream(body) = replayable(body, Some(2)).await.expect("replayable") else {
+            panic!("buffered body")
+        };
+        let body = body.collect().await.expect("body.collect").to_bytes();
+        assert_eq!(body, "nolanv");
+
+        let body = Body::from("nolanv");
+        assert!(matches!(
+            replayable(body, Some(2)).await,
+            Ok(RequestBody::Stream(_))
+        ));
+    }
+}
@@ -0,0 +1,221 @@
+use axum::Router;
+use std::{
+    future::Future,
+    io,
+    os::unix::fs::{FileTypeExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+use tokio::net::{UnixListener, UnixStream};
+
+/// HTTP server of an [`axum`] [`Router`] on a unix socket (feature=server).
+///
+/// ```rust,no_run
+/// use axum::{Router, routing::get};
+/// use http_client_unix_domain_socket::ServerUnix;
+///
+/// # async fn run() {
+/// let server = ServerUnix::builder("/run/nolanv/nolanv.sock")
+///     .mode(0o660)
+///     .build()
+///     .await
+///     .expect("ServerUnixBuilder::build");
+/// server
+///     .serve_with_shutdown(
+///         Router::new().route("/_ping", get(|| async { "OK" })),
+///         ServerUnix::shutdown_signal(),
+///     )
+///     .await
+///     .expect("server.serve_with_shutdown");
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ServerUnix {
+    socket_path: PathBuf,
+    listener: UnixListener,
+}
+
+impl ServerUnix {
+    /// Bind `socket_path` with the default options, see [`ServerUnix::builder`] for the others.
+    pub async fn bind(socket_path: impl Into<PathBuf>) -> io::Result<Self> {
+        ServerUnix::builder(socket_path).build().await
+    }
+
+    pub fn builder(socket_path: impl Into<PathBuf>) -> ServerUnixBuilder {
+        ServerUnixBuilder {
+            socket_path: socket_path.into(),
+            mode: None,
+            owner: None,
+            group: None,
+            remove_stale: true,
+        }
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Serve `router` until an error occurs on the listener.
+    pub async fn serve(self, router: Router) -> io::Result<()> {
+        axum::serve(self.listener, router.into_make_service()).await
+    }
+
+    /// Serve `router` until `signal` completes, then wait for the requests in progress and
+    /// remove the socket file.
+    pub async fn serve_with_shutdown(
+        self,
+        router: Router,
+        signal: impl Future<Output = ()> + Send + 'static,
+    ) -> io::Result<()> {
+        let result = axum::serve(self.listener, router.into_make_service())
+            .with_graceful_shutdown(signal)
+            .await;
+        let _ = tokio::fs::remove_file(&self.socket_path).await;
+        result
+    }
+
+    /// Complete on `SIGINT` (Ctrl-C) or `SIGTERM`, for [`serve_with_shutdown`](Self::serve_with_shutdown).
+    pub async fn shutdown_signal() {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let (Ok(mut interrupt), Ok(mut terminate)) = (
+            signal(SignalKind::interrupt()),
+            signal(SignalKind::terminate()),
+        ) else {
+            return std::future::pending().await;
+        };
+        tokio::select! {
+            _ = interrupt.recv() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+}
+
+pub struct ServerUnixBuilder {
+    socket_path: PathBuf,
+    mode: Option<u32>,
+    owner: Option<u32>,
+    group: Option<u32>,
+    remove_stale: bool,
+}
+
+impl ServerUnixBuilder {
+    /// Remove an existing socket file nothing listens on anymore, as left by a crashed server.
+    /// `true` by default. A socket still listened on or any other file is never removed.
+    pub fn remove_stale(mut self, remove_stale: bool) -> Self {
+        self.remove_stale = remove_stale;
+        self
+    }
+
+    /// Permissions of the socket file, e.g. `0o660` for its owner and group only. Connecting
+    /// requires the write permission.
+    pub fn mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Owner of the socket file, which requires the privileges of `chown`.
+    pub fn owner(mut self, uid: u32) -> Self {
+        self.owner = Some(uid);
+        self
+    }
+
+    /// Group of the socket file, e.g. the group allowed to connect with a `0o660` mode.
+    pub fn group(mut self, gid: u32) -> Self {
+        self.group = Some(gid);
+        self
+    }
+
+    /// Create the missing parent directories and bind the socket.
+    pub async fn build(self) -> io::Result<ServerUnix> {
+        if let Some(parent) = self.socket_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        if self.remove_stale && is_stale(&self.socket_path).await {
+            tokio::fs::remove_file(&self.socket_path).await?;
+        }
+        let listener = UnixListener::bind(&self.socket_path)?;
+        if let Some(mode) = self.mode {
+            tokio::fs::set_permissions(&self.socket_path, std::fs::Permissions::from_mode(mode))
+                .await?;
+        }
+        if self.owner.is_some() || self.group.is_some() {
+            std::os::unix::fs::chown(&self.socket_path, self.owner, self.group)?;
+        }
+        Ok(ServerUnix {
+            socket_path: self.socket_path,
+            listener,
+        })
+    }
+}
+
+/// A socket file whose connections are refused.
+async fn is_stale(socket_path: &Path) -> bool {
+    let is_socket = tokio::fs::symlink_metadata(socket_path)
+        .await
+        .is_ok_and(|metadata| metadata.file_type().is_socket());
+    is_socket
+        && UnixStream::connect(socket_path)
+            .await
+            .is_err_and(|e| e.kind() == io::ErrorKind::ConnectionRefused)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, Method, test_helpers::util::make_socket_path_test};
+    use axum::routing::get;
+
+    #[tokio::test]
+    async fn serve_with_mode() {
+        let socket_path = make_socket_path_test("server", "serve_with_mode");
+        let server = ServerUnix::builder(&socket_path)
+            .mode(0o600)
+            .build()
+            .await
+            .expect("ServerUnixBuilder::build");
+        let mode = std::fs::metadata(&socket_path)
+            .expect("metadata")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        tokio::spawn(server.serve(Router::new().route("/_ping", get(|| async { "OK" }))));
+
+        let mut client = ClientUnix::try_new(&socket_path)
+            .await
+            .expect("ClientUnix::try_new");
+        let (_, body) = client
+            .send_request("/_ping", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"OK");
+    }
+
+    #[tokio::test]
+    async fn stale_socket_and_graceful_shutdown() {
+        let socket_path = make_socket_path_test("server", "stale_socket_and_graceful_shutdown");
+        // A socket left behind by a crashed server
+        drop(
+            ServerUnix::bind(&socket_path)
+                .await
+                .expect("ServerUnix::bind"),
+        );
+        assert!(Path::new(&socket_path).exists());
+
+        let server = ServerUnix::bind(&socket_path)
+            .await
+            .expect("ServerUnix::bind");
+        let result = ServerUnix::bind(&socket_path).await;
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(io::ErrorKind::AddrInUse)
+        );
+
+        let (shutdown, signal) = tokio::sync::oneshot::channel::<()>();
+        let serving = tokio::spawn(server.serve_with_shutdown(Router::new(), async {
+            let _ = signal.await;
+        }));
+        let _ = shutdown.send(());
+        serving.await.expect("join").expect("serve_with_shutdown");
+        assert!(!Path::new(&socket_path).exists());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{Body, ClientUnix, Endpoint, Error, ErrorAndResponse, Method};
+use hyper::{Response, StatusCode, body::Bytes};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::PathBuf,
+};
+use tokio::sync::{Mutex, MutexGuard};
+
+#[cfg(feature = "json")]
+use crate::ErrorAndResponseJson;
+#[cfg(feature = "json")]
+use serde::{Serialize, de::DeserializeOwned};
+
+/// Connections with the same settings shared by the tasks of a multi-threaded runtime, each
+/// thread sending on its own one, created with
+/// [`ClientUnixBuilder::build_sharded`](crate::ClientUnixBuilder::build_sharded).
+///
+/// A request is sent on the connection of the worker thread polling it, so the tasks of different
+/// workers don't wait for the same one. When it is taken, by a task which moved to another worker
+/// or by several tasks of the same one, an idle connection is used instead, else the request waits
+/// for the connection of its thread. Every method takes `&self`, share it with an `Arc`.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::{ClientUnix, Method};
+/// use std::sync::Arc;
+///
+/// # async fn run() {
+/// let workers = std::thread::available_parallelism().map_or(4, |workers| workers.get());
+/// let client = Arc::new(
+///     ClientUnix::builder("/var/run/docker.sock")
+///         .build_sharded(workers)
+///         .await
+///         .expect("ClientUnixBuilder::build_sharded"),
+/// );
+/// for _ in 0..1000 {
+///     let client = client.clone();
+///     tokio::spawn(async move {
+///         let (_, body) = client
+///             .send_request("/_ping", Method::GET, &[], None)
+///             .await
+///             .expect("client.send_request");
+///     });
+/// }
+/// # }
+/// ```
+pub struct ShardedClient {
+    shards: Box<[Mutex<ClientUnix>]>,
+}
+
+impl ShardedClient {
+    /// Open `shards` connections, at least one.
+    pub(crate) async fn try_connect(
+        socket_path: PathBuf,
+        options: crate::client::Options,
+        shards: usize,
+    ) -> Result<Self, Error> {
+        let mut clients = Vec::with_capacity(shards.max(1));
+        for _ in 0..shards.max(1) {
+            clients.push(Mutex::new(
+                ClientUnix::try_connect(socket_path.clone(), options.clone()).await?,
+            ));
+        }
+        Ok(ShardedClient {
+            shards: clients.into_boxed_slice(),
+        })
+    }
+
+    /// Number of connections.
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// The connection of the current thread, or an idle one when it is taken, held until the
+    /// guard is dropped. Gives access to every method of [`ClientUnix`].
+    pub async fn client(&self) -> MutexGuard<'_, ClientUnix> {
+        let mut hasher = DefaultHasher::new();
+        std::thread::current().id().hash(&mut hasher);
+        let len = self.shards.len();
+        let home = hasher.finish() as usize % len;
+
+        for shard in (0..len).map(|offset| (home + offset) % len) {
+            if let Ok(client) = self.shards[shard].try_lock() {
+                return client;
+            }
+        }
+        self.shards[home].lock().await
+    }
+
+    /// [`ClientUnix::send_request`] on the connection of the current thread.
+    pub async fn send_request(
+        &self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Body>,
+    ) -> Result<(StatusCode, Vec<u8>), ErrorAndResponse> {
+        self.client()
+            .await
+            .send_request(endpoint, method, headers, body_request)
+            .await
+    }
+
+    /// [`ClientUnix::send_request_full`] on the connection of the current thread.
+    pub async fn send_request_full(
+        &self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Body>,
+    ) -> Result<Response<Vec<u8>>, ErrorAndResponse> {
+        self.client()
+            .await
+            .send_request_full(endpoint, method, headers, body_request)
+            .await
+    }
+
+    /// [`ClientUnix::send_request_bytes`] on the connection of the current thread.
+    pub async fn send_request_bytes(
+        &self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Bytes>,
+    ) -> Result<Response<Vec<u8>>, ErrorAndResponse> {
+        self.client()
+            .await
+            .send_request_bytes(endpoint, method, headers, body_request)
+            .await
+    }
+
+    /// [`ClientUnix::send_request_json`] on the connection of the current thread.
+    #[cfg(feature = "json")]
+    pub async fn send_request_json<IN: Serialize, OUT: DeserializeOwned, ERR: DeserializeOwned>(
+        &self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<&IN>,
+    ) -> Result<(StatusCode, OUT), ErrorAndResponseJson<ERR>> {
+        self.client()
+            .await
+            .send_request_json(endpoint, method, headers, body_request)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_helpers::server::Server, test_helpers::util::*};
+    use std::sync::Arc;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn share_across_workers() {
+        let socket_path = make_socket_path_test("sharded", "share_across_workers");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let client = Arc::new(
+            ClientUnix::builder(&socket_path)
+                .build_sharded(4)
+                .await
+                .expect("ClientUnixBuilder::build_sharded"),
+        );
+        assert_eq!(client.shards(), 4);
+
+        let tasks: Vec<_> = (0..32)
+            .map(|i| {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    client
+                        .send_request(&format!("/nolanv{}", i), Method::GET, &[], None)
+                        .await
+                })
+            })
+            .collect();
+        for (i, task) in tasks.into_iter().enumerate() {
+            let (status, body) = task.await.expect("task").expect("client.send_request");
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body, format!("Hello nolanv{}", i).into_bytes());
+        }
+
+        let mut sent = 0;
+        for shard in &client.shards {
+            sent += shard.lock().await.stats().requests;
+        }
+        assert_eq!(sent, 32);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{Error, Interceptor};
+use axum_core::body::Body;
+use hmac::{Hmac, Mac};
+use http_body_util::BodyExt;
+use hyper::{Request, header::HeaderName};
+use sha2::{Sha256, Sha512};
+use std::fmt::Write;
+
+/// Hash function of the HMAC computed by [`HmacSigner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HmacAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+/// [`Interceptor`] signing every request with an HMAC sent as a hex encoded header (feature=signing).
+///
+/// The signed message is the method, the path and query and the body, separated by `\n`:
+/// `POST\n/containers/create?name=nolanv\n{"image":"alpine"}`. The body is buffered to be signed.
+///
+/// ```rust
+/// use http_client_unix_domain_socket::{ClientUnix, HmacAlgorithm, HmacSigner};
+///
+/// # async fn build() {
+/// let client = ClientUnix::builder("/tmp/control.sock")
+///     .interceptor(
+///         HmacSigner::new(b"secret")
+///             .algorithm(HmacAlgorithm::Sha512)
+///             .header("x-control-signature".parse().unwrap()),
+///     )
+///     .build()
+///     .await;
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct HmacSigner {
+    key: Vec<u8>,
+    algorithm: HmacAlgorithm,
+    header: HeaderName,
+}
+
+impl HmacSigner {
+    /// Sign with HMAC-SHA256 in the `X-Signature` header.
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        HmacSigner {
+            key: key.into(),
+            algorithm: HmacAlgorithm::default(),
+            header: HeaderName::from_static("x-signature"),
+        }
+    }
+
+    pub fn algorithm(mut self, algorithm: HmacAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    fn sign(&self, message: &[&[u8]]) -> String {
+        let signature = match self.algorithm {
+            HmacAlgorithm::Sha256 => mac::<Hmac<Sha256>>(&self.key, message),
+            HmacAlgorithm::Sha512 => mac::<Hmac<Sha512>>(&self.key, message),
+        };
+        signature
+            .iter()
+            .fold(String::with_capacity(signature.len() * 2), |mut hex, b| {
+                let _ = write!(hex, "{:02x}", b);
+                hex
+            })
+    }
+}
+
+fn mac<M: Mac + hmac::digest::KeyInit>(key: &[u8], message: &[&[u8]]) -> Vec<u8> {
+    let mut mac = <M as Mac>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    for (i, part) in message.iter().enumerate() {
+        if i > 0 {
+            mac.update(b"\n");
+        }
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().to_vec()
+}
+
+impl Interceptor for HmacSigner {
+    async fn before(&self, request: &mut Request<Body>) -> Result<(), Error> {
+        let body = std::mem::take(request.body_mut())
+            .collect()
+            .await
+            .map_err(|e| Error::Interceptor(e.into()))?
+            .to_bytes();
+        let path_and_query = request
+            .uri()
+            .path_and_query()
+            .map(|p| p.as_str())
+            .unwrap_or("/");
+        let signature = self.sign(&[
+            request.method().as_str().as_bytes(),
+            path_and_query.as_bytes(),
+            &body,
+        ]);
+
+        request.headers_mut().insert(
+            self.header.clone(),
+            signature.parse().expect("hex is a valid header value"),
+        );
+        *request.body_mut() = Body::from(body);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, test_helpers::server::Server, test_helpers::util::*};
+    use hyper::Method;
+
+    #[test]
+    fn known_signature() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            HmacSigner::new("Jefe").sign(&[b"what do ya want for nothing?"]),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn signature_header() {
+        let socket_path = make_socket_path_test("signing", "signature_header");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let signer = HmacSigner::new("nolanv").algorithm(HmacAlgorithm::Sha512);
+        let expected = signer.sign(&[b"POST\n/header/x-signature\nbody"]);
+        let mut client = ClientUnix::builder(&socket_path)
+            .interceptor(signer)
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let (_, response) = client
+            .send_request(
+                "/header/x-signature",
+                Method::POST,
+                &[],
+                Some(Body::from("body")),
+            )
+            .await
+            .expect("client.send_request");
+        assert_eq!(response, expected.as_bytes());
+    }
+}
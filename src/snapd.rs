@@ -0,0 +1,260 @@
+//! Typed wrappers of the [snapd REST API](https://snapcraft.io/docs/snapd-api) (feature=snapd).
+//!
+//! Every response is an envelope whose `result` is the actual body, and the actions on snaps are
+//! `async`: they return the id of a change to poll until it is ready.
+//!
+//! ```rust,no_run
+//! use http_client_unix_domain_socket::snapd::{Snapd, SnapAction};
+//! use std::time::Duration;
+//!
+//! # async fn run() {
+//! let mut snapd = Snapd::connect_default().await.expect("Snapd::connect_default");
+//! let change = snapd
+//!     .snap_action("hello", &SnapAction::install())
+//!     .await
+//!     .expect("snapd.snap_action");
+//! let change = snapd
+//!     .wait_change(&change, Duration::from_millis(100))
+//!     .await
+//!     .expect("snapd.wait_change");
+//! assert_eq!(change.status, "Done");
+//! # }
+//! ```
+
+use crate::{ClientUnix, Error, ErrorAndResponseJson, Method};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::time::Duration;
+
+pub const DEFAULT_SOCKET_PATH: &str = "/run/snapd.socket";
+
+/// Envelope of the snapd error responses.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SnapdError {
+    #[serde(rename = "status-code")]
+    pub status_code: u16,
+    pub status: String,
+    pub result: SnapdErrorResult,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SnapdErrorResult {
+    pub message: String,
+    /// Machine readable error, such as `snap-not-found`, empty if none.
+    #[serde(default)]
+    pub kind: String,
+}
+
+pub type SnapdResult<T> = Result<T, ErrorAndResponseJson<SnapdError>>;
+
+/// Envelope of every successful response.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Envelope<T> {
+    /// `sync` or `async`.
+    #[serde(rename = "type")]
+    pub response_type: String,
+    #[serde(rename = "status-code")]
+    pub status_code: u16,
+    pub status: String,
+    /// Id of the change of an `async` response.
+    #[serde(default)]
+    pub change: Option<String>,
+    pub result: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Snap {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub revision: String,
+    #[serde(default)]
+    pub channel: String,
+    #[serde(default)]
+    pub tracking_channel: String,
+    pub status: String,
+    #[serde(default)]
+    pub summary: String,
+}
+
+/// Body of [`Snapd::snap_action`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SnapAction {
+    /// `install`, `refresh`, `remove`, `revert`, `enable` or `disable`.
+    pub action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub classic: bool,
+    /// Remove without saving a snapshot of the snap data.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub purge: bool,
+}
+
+impl SnapAction {
+    pub fn new(action: &str) -> Self {
+        SnapAction {
+            action: action.to_string(),
+            channel: None,
+            classic: false,
+            purge: false,
+        }
+    }
+
+    pub fn install() -> Self {
+        SnapAction::new("install")
+    }
+
+    pub fn refresh() -> Self {
+        SnapAction::new("refresh")
+    }
+
+    pub fn remove() -> Self {
+        SnapAction::new("remove")
+    }
+
+    pub fn channel(mut self, channel: &str) -> Self {
+        self.channel = Some(channel.to_string());
+        self
+    }
+}
+
+/// Background change of an action, see [`Snapd::wait_change`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Change {
+    pub id: String,
+    pub kind: String,
+    #[serde(default)]
+    pub summary: String,
+    /// `Do`, `Doing`, `Done`, `Error`, ...
+    pub status: String,
+    pub ready: bool,
+    /// Error of a failed change.
+    #[serde(default)]
+    pub err: Option<String>,
+}
+
+/// snapd REST API client.
+pub struct Snapd {
+    client: ClientUnix,
+}
+
+impl Snapd {
+    pub fn new(client: ClientUnix) -> Self {
+        Snapd { client }
+    }
+
+    /// Connect to [`DEFAULT_SOCKET_PATH`].
+    pub async fn connect_default() -> Result<Self, Error> {
+        Ok(Snapd::new(ClientUnix::try_new(DEFAULT_SOCKET_PATH).await?))
+    }
+
+    /// The underlying client, for the endpoints without a wrapper.
+    pub fn client(&mut self) -> &mut ClientUnix {
+        &mut self.client
+    }
+
+    /// Installed snaps.
+    pub async fn snaps(&mut self) -> SnapdResult<Vec<Snap>> {
+        self.get("/v2/snaps").await
+    }
+
+    pub async fn snap(&mut self, name: &str) -> SnapdResult<Snap> {
+        self.get(&format!("/v2/snaps/{}", name)).await
+    }
+
+    /// Start the action on the snap and return the id of its change.
+    pub async fn snap_action(&mut self, name: &str, action: &SnapAction) -> SnapdResult<String> {
+        let (_, envelope) = self
+            .client
+            .send_request_json::<_, Envelope<serde_json::Value>, _>(
+                &format!("/v2/snaps/{}", name),
+                Method::POST,
+                &[],
+                Some(action),
+            )
+            .await?;
+        envelope
+            .change
+            .ok_or(ErrorAndResponseJson::InternalError(Error::ResponseFraming(
+                "missing change id in async response",
+            )))
+    }
+
+    pub async fn change(&mut self, id: &str) -> SnapdResult<Change> {
+        self.get(&format!("/v2/changes/{}", id)).await
+    }
+
+    /// Poll the change every `interval` until it is ready, whether it succeeded or not.
+    pub async fn wait_change(&mut self, id: &str, interval: Duration) -> SnapdResult<Change> {
+        loop {
+            let change = self.change(id).await?;
+            if change.ready {
+                return Ok(change);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    async fn get<OUT: DeserializeOwned>(&mut self, endpoint: &str) -> SnapdResult<OUT> {
+        self.client
+            .send_request_json::<(), Envelope<OUT>, _>(endpoint, Method::GET, &[], None)
+            .await
+            .map(|(_, envelope)| envelope.result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StatusCode, test_helpers::server::Server, test_helpers::util::*};
+
+    async fn make_snapd(test_function: &str) -> (Server, Snapd) {
+        let socket_path = make_socket_path_test("snapd", test_function);
+        let server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let client = ClientUnix::try_new(&socket_path)
+            .await
+            .expect("ClientUnix::try_new");
+        (server, Snapd::new(client))
+    }
+
+    #[tokio::test]
+    async fn install_and_wait_change() {
+        let (_server, mut snapd) = make_snapd("install_and_wait_change").await;
+
+        let change = snapd
+            .snap_action("nolanv", &SnapAction::install().channel("edge"))
+            .await
+            .expect("snapd.snap_action");
+        let change = snapd
+            .wait_change(&change, Duration::from_millis(1))
+            .await
+            .expect("snapd.wait_change");
+
+        assert_eq!(change.id, "42");
+        assert_eq!(change.status, "Done");
+    }
+
+    #[tokio::test]
+    async fn error_envelope() {
+        let (_server, mut snapd) = make_snapd("error_envelope").await;
+
+        let result = snapd.snap("missing").await;
+
+        assert!(matches!(
+            result.err(),
+            Some(ErrorAndResponseJson::ResponseUnsuccessful(StatusCode::NOT_FOUND, e))
+                if e.result.kind == "snap-not-found"
+        ));
+    }
+
+    #[test]
+    fn snap_action_body() {
+        assert_eq!(
+            serde_json::to_string(&SnapAction::remove()).unwrap(),
+            r#"{"action":"remove"}"#
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Buffer the response bodies in memory up to a threshold and spool the remainder to a temporary
+//! file (feature=spool), to read large bodies without holding them in memory.
+//!
+//! ```rust,no_run
+//! use http_client_unix_domain_socket::{ClientUnix, Method};
+//!
+//! # async fn run() {
+//! let mut client = ClientUnix::builder("/var/run/docker.sock")
+//!     .spool_threshold(8 * 1024 * 1024)
+//!     .build()
+//!     .await
+//!     .expect("ClientUnixBuilder::build");
+//! let response = client
+//!     .send_request_spooled("/images/nolanv/get", Method::GET, &[], None)
+//!     .await
+//!     .expect("client.send_request_spooled");
+//! let mut archive = tokio::fs::File::create("nolanv.tar")
+//!     .await
+//!     .expect("File::create");
+//! tokio::io::copy(&mut response.into_body().into_reader(), &mut archive)
+//!     .await
+//!     .expect("tokio::io::copy");
+//! # }
+//! ```
+
+use crate::{Body, ClientUnix, Endpoint, Error, ErrorAndResponse, Method};
+use http_body_util::BodyExt;
+use hyper::{Response, body::Body as _};
+use std::{
+    io::{self, Cursor, SeekFrom},
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncRead, AsyncSeekExt, AsyncWriteExt, ReadBuf},
+};
+
+/// Part of the body kept in memory by default.
+pub(crate) const DEFAULT_SPOOL_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+/// Body of [`ClientUnix::send_request_spooled`], in memory up to the threshold of the client and
+/// in an unlinked temporary file past it, removed once closed.
+#[derive(Debug)]
+pub struct SpooledBody {
+    memory: Vec<u8>,
+    file: Option<File>,
+    len: u64,
+}
+
+impl SpooledBody {
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the body exceeded the threshold and was partly written to a file.
+    pub fn is_spooled(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// The part of the body in memory, the whole body when not spooled.
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    /// Read the body from the start.
+    pub fn into_reader(self) -> SpooledReader {
+        SpooledReader {
+            memory: Cursor::new(self.memory),
+            file: self.file,
+        }
+    }
+}
+
+/// [`AsyncRead`] of a [`SpooledBody`].
+#[derive(Debug)]
+pub struct SpooledReader {
+    memory: Cursor<Vec<u8>>,
+    file: Option<File>,
+}
+
+impl AsyncRead for SpooledReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if (self.memory.position() as usize) < self.memory.get_ref().len() {
+            return Pin::new(&mut self.memory).poll_read(cx, buf);
+        }
+        match &mut self.file {
+            Some(file) => Pin::new(file).poll_read(cx, buf),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+impl ClientUnix {
+    /// Like [`send_request_full`](Self::send_request_full) but keep at most
+    /// [`spool_threshold`](crate::ClientUnixBuilder::spool_threshold) bytes of the body in memory,
+    /// 16 MiB by default, and write the remainder to a temporary file.
+    ///
+    /// Retries, Digest authentication and the request timeout don't apply, as for
+    /// [`send_request_streaming`](Self::send_request_streaming).
+    pub async fn send_request_spooled(
+        &mut self,
+        endpoint: impl Endpoint,
+        method: Method,
+        headers: &[(&str, &str)],
+        body_request: Option<Body>,
+    ) -> Result<Response<SpooledBody>, ErrorAndResponse> {
+        let threshold = self
+            .options()
+            .spool_threshold
+            .unwrap_or(DEFAULT_SPOOL_THRESHOLD);
+        let response = self
+            .send_request_streaming(endpoint, method, headers, body_request)
+            .await?;
+        let (parts, mut body) = response.into_parts();
+
+        let capacity = body.size_hint().lower().min(threshold) as usize;
+        let mut spooled = SpooledBody {
+            memory: Vec::with_capacity(capacity),
+            file: None,
+            len: 0,
+        };
+        while let Some(frame) = body.frame().await {
+            let frame =
+                frame.map_err(|e| ErrorAndResponse::InternalError(Error::ResponseCollect(e)))?;
+            let Ok(mut data) = frame.into_data() else {
+                continue;
+            };
+            spooled.len += data.len() as u64;
+            let in_memory =
+                (threshold.saturating_sub(spooled.memory.len() as u64) as usize).min(data.len());
+            spooled.memory.extend_from_slice(&data.split_to(in_memory));
+            if data.is_empty() {
+                continue;
+            }
+            let file = match &mut spooled.file {
+                Some(file) => file,
+                None => spooled.file.insert(
+                    temporary_file()
+                        .await
+                        .map_err(|e| ErrorAndResponse::InternalError(Error::Spool(e)))?,
+                ),
+            };
+            file.write_all(&data)
+                .await
+                .map_err(|e| ErrorAndResponse::InternalError(Error::Spool(e)))?;
+        }
+        if let Some(file) = &mut spooled.file {
+            file.seek(SeekFrom::Start(0))
+                .await
+                .map_err(|e| ErrorAndResponse::InternalError(Error::Spool(e)))?;
+        }
+        Ok(Response::from_parts(parts, spooled))
+    }
+}
+
+/// A file open for reading and writing, already removed from the temporary directory.
+async fn temporary_file() -> io::Result<File> {
+    let path = std::env::temp_dir().join(format!(
+        "http-client-unix-domain-socket-spool-{}",
+        uuid::Uuid::new_v4()
+    ));
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .await?;
+    tokio::fs::remove_file(&path).await?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test]
+    async fn spool_past_threshold() {
+        const LEN: usize = 256 * 1024;
+        let server = crate::testing::MockServer::start(axum::Router::new().route(
+            "/layer",
+            axum::routing::get(|| async { (0..LEN).map(|i| i as u8).collect::<Vec<u8>>() }),
+        ))
+        .await
+        .expect("MockServer::start");
+        let socket_path = server.socket_path().to_string_lossy().into_owned();
+
+        for (threshold, is_spooled) in [(1024, true), (LEN as u64, false)] {
+            let mut client = ClientUnix::builder(&socket_path)
+                .spool_threshold(threshold)
+                .build()
+                .await
+                .expect("ClientUnixBuilder::build");
+            let body = client
+                .send_request_spooled("/layer", Method::GET, &[], None)
+                .await
+                .expect("client.send_request_spooled")
+                .into_body();
+            assert_eq!(body.is_spooled(), is_spooled);
+            assert_eq!(body.len(), LEN as u64);
+            assert_eq!(body.memory().len(), LEN.min(threshold as usize));
+
+            let mut read = Vec::new();
+            body.into_reader()
+                .read_to_end(&mut read)
+                .await
+                .expect("read_to_end");
+            assert_eq!(read, (0..LEN).map(|i| i as u8).collect::<Vec<u8>>());
+        }
+    }
+}
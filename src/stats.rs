@@ -0,0 +1,101 @@
+use std::{
+    pin::pin,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+use tokio::sync::Notify;
+
+/// Counters of the current connection of a [`ClientUnix`](crate::ClientUnix), reset on reconnection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionStats {
+    /// Requests sent on the connection.
+    pub requests: u64,
+    /// Requests sent on the connection after the first one.
+    pub reuses: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Time since the connection was established.
+    pub age: Duration,
+}
+
+pub(crate) struct ConnectionCounters {
+    requests: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    first_byte: Notify,
+    established: Instant,
+}
+
+impl ConnectionCounters {
+    pub(crate) fn new() -> Self {
+        ConnectionCounters {
+            requests: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            first_byte: Notify::new(),
+            established: Instant::now(),
+        }
+    }
+
+    pub(crate) fn add_request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_sent(&self, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_received(&self, bytes: usize) {
+        let received = self
+            .bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        if received == 0 && bytes > 0 {
+            self.first_byte.notify_waiters();
+        }
+    }
+
+    /// Resolves once the connection received its first byte.
+    pub(crate) async fn first_byte(&self) {
+        let mut received = pin!(self.first_byte.notified());
+        received.as_mut().enable();
+        if self.bytes_received.load(Ordering::Relaxed) == 0 {
+            received.await;
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> ConnectionStats {
+        let requests = self.requests.load(Ordering::Relaxed);
+        ConnectionStats {
+            requests,
+            reuses: requests.saturating_sub(1),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            age: self.established.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::util::make_client_server;
+    use hyper::Method;
+
+    #[tokio::test]
+    async fn stats_are_counted() {
+        let (_, mut client) = make_client_server("stats_are_counted").await;
+        assert_eq!(client.stats().requests, 0);
+
+        for _ in 0..2 {
+            client
+                .send_request("/nolanv", Method::GET, &[], None)
+                .await
+                .expect("client.send_request");
+        }
+
+        let stats = client.stats();
+        assert_eq!(stats.requests, 2);
+        assert_eq!(stats.reuses, 1);
+        assert!(stats.bytes_sent > 0);
+        assert!(stats.bytes_received > 2 * "Hello nolanv".len() as u64);
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::watch;
+
+/// State of the connection of a [`ClientUnix`](crate::ClientUnix), see
+/// [`ClientUnix::status`](crate::ClientUnix::status).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    /// Connecting or reconnecting to the socket.
+    Connecting,
+    Connected,
+    /// The connection was closed, aborted or couldn't be established.
+    Disconnected {
+        reason: String,
+    },
+}
+
+/// Sender of the statuses of a client, kept across its reconnections.
+///
+/// Every connection attempt gets a new generation, the late updates of the previous connections
+/// are ignored.
+pub(crate) struct StatusSender {
+    sender: watch::Sender<ConnectionStatus>,
+    generation: AtomicU64,
+}
+
+impl StatusSender {
+    pub(crate) fn new() -> Self {
+        StatusSender {
+            sender: watch::Sender::new(ConnectionStatus::Connecting),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<ConnectionStatus> {
+        self.sender.subscribe()
+    }
+
+    /// Start a connection attempt and return its generation.
+    pub(crate) fn connecting(&self) -> u64 {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.sender.send_replace(ConnectionStatus::Connecting);
+        generation
+    }
+
+    pub(crate) fn connected(&self, generation: u64) {
+        self.update(generation, ConnectionStatus::Connected);
+    }
+
+    pub(crate) fn disconnected(&self, generation: u64, reason: String) {
+        self.update(generation, ConnectionStatus::Disconnected { reason });
+    }
+
+    fn update(&self, generation: u64, status: ConnectionStatus) {
+        self.sender.send_if_modified(|current| {
+            // Checked under the lock of the channel, so a newer attempt can't start in between
+            if self.generation.load(Ordering::SeqCst) != generation || *current == status {
+                return false;
+            }
+            *current = status;
+            true
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, Method, test_helpers::server::Server, test_helpers::util::*};
+
+    #[test]
+    fn ignore_previous_connections() {
+        let status = StatusSender::new();
+        let first = status.connecting();
+        status.connected(first);
+        let second = status.connecting();
+        status.disconnected(first, "closed".to_string());
+        assert_eq!(*status.subscribe().borrow(), ConnectionStatus::Connecting);
+        status.connected(second);
+        assert_eq!(*status.subscribe().borrow(), ConnectionStatus::Connected);
+    }
+
+    #[tokio::test]
+    async fn follow_reconnections() {
+        let socket_path = make_socket_path_test("status", "follow_reconnections");
+        std::fs::create_dir_all("/tmp/http_client_unix_domain_socket").expect("create_dir_all");
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = tokio::net::UnixListener::bind(&socket_path).expect("UnixListener::bind");
+        let mut client = ClientUnix::try_new(&socket_path)
+            .await
+            .expect("ClientUnix::try_new");
+        let mut status = client.status();
+        assert_eq!(*status.borrow_and_update(), ConnectionStatus::Connected);
+
+        // Closed by the server
+        let (stream, _) = listener.accept().await.expect("listener.accept");
+        drop((stream, listener));
+        status
+            .wait_for(|status| matches!(status, ConnectionStatus::Disconnected { .. }))
+            .await
+            .expect("status.wait_for");
+
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(*status.borrow_and_update(), ConnectionStatus::Connected);
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{ClientUnix, Error, ErrorAndResponse, Method, query::percent_encode};
+use hyper::{
+    Response,
+    body::Bytes,
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+
+/// Method, path and headers of a request prepared once, for [`ClientUnix::send_template`] to only
+/// fill in the parameters of the path on every call.
+///
+/// The parameters are the `{name}` segments of the path, filled in order or by name and
+/// percent-encoded, so a value such as `../images` can't change the path. The headers are parsed
+/// once, when added to the template.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::{ClientUnix, Method, RequestTemplate, header};
+///
+/// # async fn run(mut client: ClientUnix, ids: Vec<String>) {
+/// let inspect = RequestTemplate::new(Method::GET, "/containers/{id}/json").header(
+///     header::ACCEPT,
+///     header::HeaderValue::from_static("application/json"),
+/// );
+/// for id in &ids {
+///     let response = client
+///         .send_template(&inspect, &[id], None)
+///         .await
+///         .expect("client.send_template");
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestTemplate {
+    method: Method,
+    segments: Vec<Segment>,
+    headers: HeaderMap,
+    /// Length of the path without its parameters.
+    literal_len: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    /// The name of the parameter.
+    Parameter(String),
+}
+
+impl RequestTemplate {
+    pub fn new(method: Method, path: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut rest = path;
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+            if start > 0 {
+                segments.push(Segment::Literal(rest[..start].to_string()));
+            }
+            segments.push(Segment::Parameter(rest[start + 1..start + end].to_string()));
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_string()));
+        }
+        let literal_len = segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(literal) => literal.len(),
+                Segment::Parameter(_) => 0,
+            })
+            .sum();
+        RequestTemplate {
+            method,
+            segments,
+            headers: HeaderMap::new(),
+            literal_len,
+        }
+    }
+
+    /// Send `header` with every request of the template.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The path with its parameters filled in by `params`, in order.
+    ///
+    /// Fails with [`Error::InvalidEndpoint`] when the number of `params` differs from the number
+    /// of parameters of the path.
+    pub fn endpoint(&self, params: &[&str]) -> Result<String, Error> {
+        let mut params = params.iter();
+        let mut endpoint = String::with_capacity(self.literal_len + params.len() * 16);
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => endpoint.push_str(literal),
+                Segment::Parameter(_) => {
+                    let param = params.next().ok_or(Error::InvalidEndpoint(
+                        "no value for a parameter of the path",
+                    ))?;
+                    endpoint.push_str(&percent_encode(param));
+                }
+            }
+        }
+        if params.next().is_some() {
+            return Err(Error::InvalidEndpoint(
+                "a value has no parameter in the path",
+            ));
+        }
+        Ok(endpoint)
+    }
+
+    /// The path with its parameters filled in by the values of `params` with their name. A name
+    /// used by several parameters fills all of them.
+    ///
+    /// Fails with [`Error::InvalidEndpoint`] when a parameter has no value or a value has no
+    /// parameter.
+    pub fn endpoint_named(&self, params: &[(&str, &str)]) -> Result<String, Error> {
+        let mut used = vec![false; params.len()];
+        let mut endpoint = String::with_capacity(self.literal_len + params.len() * 16);
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => endpoint.push_str(literal),
+                Segment::Parameter(name) => {
+                    let index = params.iter().position(|(param, _)| param == name).ok_or(
+                        Error::InvalidEndpoint("no value for a parameter of the path"),
+                    )?;
+                    used[index] = true;
+                    endpoint.push_str(&percent_encode(params[index].1));
+                }
+            }
+        }
+        if used.contains(&false) {
+            return Err(Error::InvalidEndpoint(
+                "a value has no parameter in the path",
+            ));
+        }
+        Ok(endpoint)
+    }
+}
+
+impl ClientUnix {
+    /// `GET` the path `template` with its `{name}` parameters filled in by `params`, see
+    /// [`RequestTemplate::endpoint_named`], and return the result as of
+    /// [`send_request_full`](Self::send_request_full).
+    ///
+    /// ```rust,no_run
+    /// use http_client_unix_domain_socket::ClientUnix;
+    ///
+    /// # async fn run(mut client: ClientUnix, id: &str) {
+    /// let logs = client
+    ///     .get_tpl("/containers/{id}/logs?tail={tail}", &[("id", id), ("tail", "10")])
+    ///     .await
+    ///     .expect("client.get_tpl");
+    /// # }
+    /// ```
+    pub async fn get_tpl(
+        &mut self,
+        template: &str,
+        params: &[(&str, &str)],
+    ) -> Result<Response<Vec<u8>>, ErrorAndResponse> {
+        let endpoint = RequestTemplate::new(Method::GET, template)
+            .endpoint_named(params)
+            .map_err(|e| {
+                ErrorAndResponse::InternalError(e.with_context(self.error_context(
+                    &Method::GET,
+                    Some(template),
+                    None,
+                )))
+            })?;
+        self.send_request_full(&endpoint, Method::GET, &[], None)
+            .await
+    }
+
+    /// Send the request of `template` with its path filled in by `params`, see
+    /// [`RequestTemplate::endpoint`], and return the result as of
+    /// [`send_request_full`](Self::send_request_full).
+    pub async fn send_template(
+        &mut self,
+        template: &RequestTemplate,
+        params: &[&str],
+        body_request: Option<Bytes>,
+    ) -> Result<Response<Vec<u8>>, ErrorAndResponse> {
+        let endpoint = template
+            .endpoint(params)
+            .map_err(|e| e.with_context(self.error_context(&template.method, None, None)))
+            .and_then(|endpoint| self.request_target(&endpoint, &template.method))
+            .map_err(ErrorAndResponse::InternalError)?;
+        let response = self
+            .send_buffered_headers(
+                &endpoint,
+                template.method.clone(),
+                &template.headers,
+                body_request.into(),
+            )
+            .await
+            .map_err(ErrorAndResponse::InternalError)?;
+        self.options().successful(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::util::*;
+
+    #[test]
+    fn fill_in_parameters() {
+        let template = RequestTemplate::new(Method::GET, "/containers/{id}/logs?tail={tail}");
+        assert_eq!(
+            template
+                .endpoint(&["nolanv/web", "10"])
+                .expect("template.endpoint"),
+            "/containers/nolanv%2Fweb/logs?tail=10"
+        );
+        assert_eq!(
+            RequestTemplate::new(Method::GET, "/_ping")
+                .endpoint(&[])
+                .expect("template.endpoint"),
+            "/_ping"
+        );
+    }
+
+    #[test]
+    fn wrong_number_of_parameters() {
+        let template = RequestTemplate::new(Method::GET, "/containers/{id}/json");
+        assert!(matches!(
+            template.endpoint(&[]),
+            Err(Error::InvalidEndpoint(_))
+        ));
+        assert!(matches!(
+            template.endpoint(&["nolanv", "web"]),
+            Err(Error::InvalidEndpoint(_))
+        ));
+    }
+
+    #[test]
+    fn fill_in_named_parameters() {
+        let template = RequestTemplate::new(Method::GET, "/containers/{id}/logs?tail={tail}");
+        assert_eq!(
+            template
+                .endpoint_named(&[("tail", "10"), ("id", "../images")])
+                .expect("template.endpoint_named"),
+            "/containers/..%2Fimages/logs?tail=10"
+        );
+        assert!(matches!(
+            template.endpoint_named(&[("id", "nolanv")]),
+            Err(Error::InvalidEndpoint(_))
+        ));
+        assert!(matches!(
+            template.endpoint_named(&[("id", "nolanv"), ("tail", "10"), ("since", "0")]),
+            Err(Error::InvalidEndpoint(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_tpl() {
+        let (_, mut client) = make_client_server("get_tpl").await;
+        let response = client
+            .get_tpl("/{name}", &[("name", "nolanv")])
+            .await
+            .expect("client.get_tpl");
+        assert_eq!(response.into_body(), b"Hello nolanv");
+    }
+
+    #[tokio::test]
+    async fn send_template() {
+        let (_, mut client) = make_client_server("send_template").await;
+        let template = RequestTemplate::new(Method::GET, "/header/{name}").header(
+            HeaderName::from_static("x-tenant"),
+            HeaderValue::from_static("nolanv"),
+        );
+
+        let response = client
+            .send_template(&template, &["x-tenant"], None)
+            .await
+            .expect("client.send_template");
+        assert_eq!(response.into_body(), b"nolanv");
+
+        let error = client
+            .send_template(&template, &[], None)
+            .await
+            .expect_err("client.send_template");
+        assert!(
+            matches!(&error, ErrorAndResponse::InternalError(e)
+                if matches!(e.inner(), Error::InvalidEndpoint(_))),
+            "{:?}",
+            error
+        );
+    }
+}
@@ -1,12 +1,30 @@
-#[cfg(feature = "json")]
 use hyper::StatusCode;
 use std::path::PathBuf;
 
 #[cfg(feature = "json")]
-use axum::{Json, response::IntoResponse, routing::post};
-use axum::{Router, extract::Path, routing::get};
+use axum::{
+    Json,
+    extract::{
+        Query, RawQuery,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::IntoResponse,
+    routing::{post, put},
+};
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::HeaderMap,
+    routing::{any, get},
+};
 #[cfg(feature = "json")]
 use serde_json::Value;
+#[cfg(feature = "json")]
+use std::collections::HashMap;
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
 use tokio::{
     fs::{create_dir_all, remove_file, try_exists},
     net::UnixListener,
@@ -30,12 +48,21 @@ pub struct Server {
     server_handle: JoinHandle<ErrorServer>,
 }
 
+/// Polls of the routes answering differently as they are polled, counted per server for the
+/// tests not to depend on each other.
+#[derive(Clone, Default)]
+struct Polls {
+    health: Arc<AtomicUsize>,
+    #[cfg(feature = "json")]
+    snapd_change: Arc<AtomicUsize>,
+}
+
 impl Server {
     pub async fn try_new(socket_path: &str) -> Result<Self, ErrorServer> {
-        Server::try_listen(socket_path.into()).await
+        Server::try_listen(socket_path.into(), Polls::default()).await
     }
 
-    async fn try_listen(socket_path: PathBuf) -> Result<Self, ErrorServer> {
+    async fn try_listen(socket_path: PathBuf, polls: Polls) -> Result<Self, ErrorServer> {
         let is_socket_exist = try_exists(socket_path.clone())
             .await
             .map_err(ErrorServer::CheckOldSocketExist)?;
@@ -63,13 +90,77 @@ impl Server {
             #[cfg(not(feature = "json"))]
             let app = Router::new()
                 .route("/{name}", get(Server::respond))
+                .route("/header/{name}", any(Server::respond_header))
+                .route("/sleep/{millis}", get(Server::respond_sleep))
+                .route("/metrics", get(Server::respond_metrics))
+                .route("/health", get(Server::respond_health))
+                .route("/digest/{name}", get(Server::respond_digest))
+                .with_state(polls)
                 .into_make_service();
             #[cfg(feature = "json")]
             let app = Router::new()
                 .route("/{name}", get(Server::respond))
+                .route("/header/{name}", any(Server::respond_header))
+                .route("/sleep/{millis}", get(Server::respond_sleep))
+                .route("/metrics", get(Server::respond_metrics))
+                .route("/health", get(Server::respond_health))
+                .route("/digest/{name}", get(Server::respond_digest))
+                .route("/json/problem", get(Server::respond_problem_json))
+                .route("/v1.43/version", get(Server::respond_docker_version))
+                .route(
+                    "/v1.43/containers/{id}/start",
+                    post(Server::respond_docker_start),
+                )
+                .route(
+                    "/v1.43/containers/{id}/logs",
+                    get(Server::respond_docker_logs),
+                )
+                .route("/v1.43/events", get(Server::respond_docker_events))
+                .route("/v4.0.0/libpod/pods/json", get(Server::respond_podman_pods))
+                .route(
+                    "/v4.0.0/libpod/pods/{name}/start",
+                    post(Server::respond_podman_start_pod),
+                )
+                .route("/1.0/instances/{name}/exec", post(Server::respond_lxd_exec))
+                .route("/1.0/operations/{id}", get(Server::respond_lxd_operation))
+                .route("/1.0/operations/{id}/wait", get(Server::respond_lxd_wait))
+                .route(
+                    "/1.0/operations/{id}/websocket",
+                    get(Server::respond_lxd_websocket),
+                )
+                .route(
+                    "/v2/snaps/{name}",
+                    get(Server::respond_snapd_snap).post(Server::respond_snapd_action),
+                )
+                .route("/v2/changes/{id}", get(Server::respond_snapd_change))
+                .route(
+                    "/machine-config",
+                    put(Server::respond_firecracker_machine_config),
+                )
+                .route("/actions", put(Server::respond_firecracker_action))
+                .route(
+                    "/v1/agent/service/register",
+                    put(Server::respond_consul_register),
+                )
+                .route("/v1/agent/services", get(Server::respond_consul_services))
+                .route(
+                    "/v1/kv/{*key}",
+                    get(Server::respond_consul_kv_get).put(Server::respond_consul_kv_put),
+                )
+                .route("/load", post(Server::respond_caddy_load))
+                .route(
+                    "/config/{*path}",
+                    get(Server::respond_caddy_config).patch(Server::respond_caddy_patch),
+                )
+                .route("/api/v1/vmm.ping", get(Server::respond_ch_ping))
+                .route("/api/v1/vm.resize", put(Server::respond_ch_resize))
+                .route("/pages/link", get(Server::respond_pages_link))
+                .route("/pages/cursor", get(Server::respond_pages_cursor))
+                .route("/graphql", post(Server::respond_graphql))
                 .route("/json/{name}", get(Server::respond_get_json))
                 .route("/json", post(Server::respond_post_json))
                 .fallback(Server::respond_404_json)
+                .with_state(polls)
                 .into_make_service();
 
             if axum::serve(socket, app).await.is_err() {
@@ -86,6 +177,62 @@ impl Server {
         format!("Hello {}", name)
     }
 
+    async fn respond_metrics() -> &'static str {
+        "# TYPE nolanv_requests_total counter\nnolanv_requests_total{code=\"200\"} 42\n"
+    }
+
+    /// Unavailable for the first two polls.
+    async fn respond_health(State(polls): State<Polls>) -> (StatusCode, String) {
+        match polls.health.fetch_add(1, Ordering::SeqCst) + 1 {
+            polls @ ..3 => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("starting, poll {}", polls),
+            ),
+            polls => (StatusCode::OK, format!("ready after {} polls", polls)),
+        }
+    }
+
+    async fn respond_sleep(Path(millis): Path<u64>) -> String {
+        tokio::time::sleep(std::time::Duration::from_millis(millis)).await;
+        format!("Slept {}ms", millis)
+    }
+
+    /// Accept any Digest answer to its challenge, the computation is tested by the client.
+    async fn respond_digest(
+        Path(name): Path<String>,
+        headers: HeaderMap,
+    ) -> Result<String, (StatusCode, HeaderMap)> {
+        let authorization = headers
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if authorization.starts_with("Digest username=\"nolanv\"")
+            && authorization.contains("nonce=\"nolanv-nonce\"")
+        {
+            return Ok(format!("Hello {}", name));
+        }
+
+        let mut challenge = HeaderMap::new();
+        challenge.insert(
+            "www-authenticate",
+            "Digest realm=\"nolanv\", qop=\"auth\", nonce=\"nolanv-nonce\", algorithm=SHA-256"
+                .parse()
+                .unwrap(),
+        );
+        Err((StatusCode::UNAUTHORIZED, challenge))
+    }
+
+    async fn respond_header(
+        Path(name): Path<String>,
+        headers: HeaderMap,
+    ) -> Result<String, StatusCode> {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+
     #[cfg(feature = "json")]
     async fn respond_get_json(Path(name): Path<String>) -> String {
         format!("{{\"hello\": \"{}\"}}", name)
@@ -103,6 +250,277 @@ impl Server {
         ))
     }
 
+    #[cfg(feature = "json")]
+    async fn respond_problem_json() -> impl IntoResponse {
+        (
+            StatusCode::CONFLICT,
+            [("Content-Type", "application/problem+json")],
+            "{\"type\": \"about:blank\", \"title\": \"Conflict\", \"status\": 409, \"container\": \"nolanv\"}".to_string(),
+        )
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_docker_version() -> &'static str {
+        "{\"Version\": \"27.0.0-nolanv\", \"ApiVersion\": \"1.43\", \"Os\": \"linux\", \"Arch\": \"amd64\"}"
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_docker_start(Path(id): Path<String>) -> impl IntoResponse {
+        match id.as_str() {
+            "nolanv" => (StatusCode::NO_CONTENT, String::new()),
+            _ => (
+                StatusCode::NOT_FOUND,
+                format!("{{\"message\": \"No such container: {}\"}}", id),
+            ),
+        }
+    }
+
+    /// Multiplexed stdout and stderr frames.
+    #[cfg(feature = "json")]
+    async fn respond_docker_logs() -> Vec<u8> {
+        let mut logs = Vec::new();
+        for (stream, payload) in [(1, "hello "), (2, "warning\n"), (1, "nolanv\n")] {
+            logs.extend_from_slice(&[stream, 0, 0, 0]);
+            logs.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+            logs.extend_from_slice(payload.as_bytes());
+        }
+        logs
+    }
+
+    /// Two events, then the second and a third one when asked for the events since the second.
+    #[cfg(feature = "json")]
+    async fn respond_docker_events(RawQuery(query): RawQuery) -> String {
+        let event = |action, time| {
+            format!(
+                "{{\"Type\": \"container\", \"Action\": \"{}\", \"Actor\": {{\"ID\": \"nolanv\"}}, \"time\": {}, \"timeNano\": {}000000000}}\n",
+                action, time, time
+            )
+        };
+        match query
+            .unwrap_or_default()
+            .contains("since=1700000001.000000000")
+        {
+            true => event("start", 1700000001) + &event("die", 1700000002),
+            false => event("create", 1700000000) + &event("start", 1700000001),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_podman_pods() -> &'static str {
+        "[{\"Id\": \"1\", \"Name\": \"nolanv\", \"Status\": \"Running\", \"Containers\": [{\"Id\": \"2\", \"Names\": \"nolanv-infra\", \"Status\": \"running\"}]}]"
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_podman_start_pod(Path(name): Path<String>) -> impl IntoResponse {
+        (
+            StatusCode::NOT_FOUND,
+            format!(
+                "{{\"cause\": \"no such pod\", \"message\": \"no pod with name or ID {} found: no such pod\", \"response\": 404}}",
+                name
+            ),
+        )
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_lxd_exec() -> impl IntoResponse {
+        (
+            StatusCode::ACCEPTED,
+            "{\"type\": \"async\", \"status\": \"Operation created\", \"status_code\": 100, \"operation\": \"/1.0/operations/nolanv\", \"metadata\": {\"id\": \"nolanv\", \"class\": \"websocket\", \"status\": \"Running\", \"status_code\": 103, \"metadata\": {\"fds\": {\"0\": \"s0\", \"1\": \"s1\", \"2\": \"s2\", \"control\": \"sc\"}}}}",
+        )
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_lxd_operation() -> impl IntoResponse {
+        (
+            StatusCode::NOT_FOUND,
+            "{\"type\": \"error\", \"error\": \"Operation not found\", \"error_code\": 404}",
+        )
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_lxd_wait() -> &'static str {
+        "{\"type\": \"sync\", \"status\": \"Success\", \"status_code\": 200, \"metadata\": {\"id\": \"nolanv\", \"class\": \"websocket\", \"status\": \"Success\", \"status_code\": 200, \"metadata\": {\"return\": 0}}}"
+    }
+
+    /// The stdout websocket sends a line then closes, the others close right away.
+    #[cfg(feature = "json")]
+    async fn respond_lxd_websocket(
+        upgrade: WebSocketUpgrade,
+        Query(query): Query<HashMap<String, String>>,
+    ) -> impl IntoResponse {
+        let stdout = query.get("secret").map(String::as_str) == Some("s1");
+        upgrade.on_upgrade(move |mut socket: WebSocket| async move {
+            if stdout {
+                let _ = socket.send(Message::Binary("hello nolanv\n".into())).await;
+            }
+            let _ = socket.send(Message::Close(None)).await;
+        })
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_snapd_snap(Path(name): Path<String>) -> impl IntoResponse {
+        (
+            StatusCode::NOT_FOUND,
+            format!(
+                "{{\"type\": \"error\", \"status-code\": 404, \"status\": \"Not Found\", \"result\": {{\"message\": \"snap not installed: {}\", \"kind\": \"snap-not-found\"}}}}",
+                name
+            ),
+        )
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_snapd_action() -> impl IntoResponse {
+        (
+            StatusCode::ACCEPTED,
+            "{\"type\": \"async\", \"status-code\": 202, \"status\": \"Accepted\", \"change\": \"42\", \"result\": null}",
+        )
+    }
+
+    /// Doing on the first poll, then Done.
+    #[cfg(feature = "json")]
+    async fn respond_snapd_change(State(polls): State<Polls>, Path(id): Path<String>) -> String {
+        let (status, ready) = match polls.snapd_change.fetch_add(1, Ordering::SeqCst) {
+            0 => ("Doing", false),
+            _ => ("Done", true),
+        };
+        format!(
+            "{{\"type\": \"sync\", \"status-code\": 200, \"status\": \"OK\", \"result\": {{\"id\": \"{}\", \"kind\": \"install-snap\", \"status\": \"{}\", \"ready\": {}}}}}",
+            id, status, ready
+        )
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_firecracker_machine_config(Json(config): Json<Value>) -> impl IntoResponse {
+        match config["vcpu_count"].as_u64() {
+            Some(1..) => (StatusCode::NO_CONTENT, String::new()),
+            _ => (
+                StatusCode::BAD_REQUEST,
+                "{\"fault_message\": \"The vCPU number is invalid!\"}".to_string(),
+            ),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_firecracker_action() -> StatusCode {
+        StatusCode::NO_CONTENT
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_consul_register(Json(registration): Json<Value>) -> impl IntoResponse {
+        match registration["Name"].as_str() {
+            Some("") | None => (StatusCode::BAD_REQUEST, "Missing service name"),
+            Some(_) => (StatusCode::OK, ""),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_consul_services() -> &'static str {
+        "{\"nolanv\": {\"ID\": \"nolanv\", \"Service\": \"nolanv\", \"Tags\": [], \"Address\": \"\", \"Port\": 8080, \"Meta\": {}}}"
+    }
+
+    /// Only `nolanv/config` exists, with the value `nolanv`.
+    #[cfg(feature = "json")]
+    async fn respond_consul_kv_get(Path(key): Path<String>) -> impl IntoResponse {
+        match key.as_str() {
+            "nolanv/config" => (
+                StatusCode::OK,
+                "[{\"Key\": \"nolanv/config\", \"Value\": \"bm9sYW52\", \"Flags\": 0, \"CreateIndex\": 42, \"ModifyIndex\": 42, \"LockIndex\": 0}]",
+            ),
+            _ => (StatusCode::NOT_FOUND, ""),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_consul_kv_put() -> &'static str {
+        "true"
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_caddy_load(Json(config): Json<Value>) -> impl IntoResponse {
+        match config.is_object() {
+            true => (StatusCode::OK, String::new()),
+            false => (
+                StatusCode::BAD_REQUEST,
+                "{\"error\": \"loading config: top-level config must be an object\"}".to_string(),
+            ),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_caddy_config(Path(path): Path<String>) -> impl IntoResponse {
+        match path.as_str() {
+            "apps/http/servers/srv0/listen" => (StatusCode::OK, "[\":443\"]".to_string()),
+            _ => (StatusCode::OK, "null".to_string()),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_caddy_patch(Path(path): Path<String>) -> impl IntoResponse {
+        match path.starts_with("apps/http/") {
+            true => (StatusCode::OK, String::new()),
+            false => (
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "{{\"error\": \"invalid traversal path at: config/{}\"}}",
+                    path
+                ),
+            ),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_ch_ping() -> &'static str {
+        "{\"build_version\": \"v44.0-nolanv\", \"version\": \"v44.0\", \"pid\": 42}"
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_ch_resize(Json(resize): Json<Value>) -> impl IntoResponse {
+        match resize["desired_vcpus"].as_u64() {
+            Some(..=8) => (StatusCode::NO_CONTENT, ""),
+            _ => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error from API: The VM could not be resized: Error resizing the vCPUs",
+            ),
+        }
+    }
+
+    /// Pages of two items out of five, linked with absolute urls.
+    #[cfg(feature = "json")]
+    async fn respond_pages_link(Query(query): Query<HashMap<String, u32>>) -> impl IntoResponse {
+        let page = query.get("page").copied().unwrap_or(1);
+        let items: Vec<u32> = ((page - 1) * 2 + 1..=(page * 2).min(5)).collect();
+        let mut headers = HeaderMap::new();
+        if page < 3 {
+            let link = format!(
+                "<http://localhost/pages/link?page={}>; rel=\"next\"",
+                page + 1
+            );
+            headers.insert("link", link.parse().unwrap());
+        }
+        (headers, Json(items))
+    }
+
+    #[cfg(feature = "json")]
+    async fn respond_pages_cursor(Query(query): Query<HashMap<String, String>>) -> Json<Value> {
+        Json(match query.get("cursor").map(String::as_str) {
+            None => serde_json::json!({"items": ["a"], "next": "b"}),
+            _ => serde_json::json!({"items": ["b"], "next": null}),
+        })
+    }
+
+    /// Only the `hello` field, as GraphQL servers answer errors with `200 OK`.
+    #[cfg(feature = "json")]
+    async fn respond_graphql(Json(request): Json<Value>) -> Json<Value> {
+        Json(match request["query"].as_str() {
+            Some(query) if query.contains("hello") => {
+                serde_json::json!({"data": {"hello": format!("Hello {}", request["variables"]["name"].as_str().unwrap_or_default())}})
+            }
+            _ => serde_json::json!({
+                "errors": [{"message": "Cannot query field", "locations": [{"line": 1, "column": 3}]}]
+            }),
+        })
+    }
+
     #[cfg(feature = "json")]
     async fn respond_404_json() -> impl IntoResponse {
         (
@@ -0,0 +1,168 @@
+use crate::{ErrorAndResponse, Response, StatusCode};
+
+/// Status code and body of a response, for [`assert_status!`](crate::assert_status) and
+/// [`assert_json_body!`](crate::assert_json_body).
+///
+/// Implemented for the results of `send_request` and `send_request_full`, where an unsuccessful
+/// response is an `Err`. Panics on an [`ErrorAndResponse::InternalError`].
+pub trait TestResponse {
+    fn status_code(&self) -> StatusCode;
+    fn body_bytes(&self) -> &[u8];
+}
+
+impl TestResponse for (StatusCode, Vec<u8>) {
+    fn status_code(&self) -> StatusCode {
+        self.0
+    }
+
+    fn body_bytes(&self) -> &[u8] {
+        &self.1
+    }
+}
+
+impl TestResponse for Response<Vec<u8>> {
+    fn status_code(&self) -> StatusCode {
+        self.status()
+    }
+
+    fn body_bytes(&self) -> &[u8] {
+        self.body()
+    }
+}
+
+impl TestResponse for ErrorAndResponse {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ErrorAndResponse::InternalError(e) => panic!("no response: {}", e),
+            error => error.status_code().expect("an unsuccessful response"),
+        }
+    }
+
+    fn body_bytes(&self) -> &[u8] {
+        match self {
+            ErrorAndResponse::InternalError(e) => panic!("no response: {}", e),
+            error => error.body().expect("an unsuccessful response"),
+        }
+    }
+}
+
+impl<T: TestResponse> TestResponse for Result<T, ErrorAndResponse> {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Ok(response) => response.status_code(),
+            Err(e) => TestResponse::status_code(e),
+        }
+    }
+
+    fn body_bytes(&self) -> &[u8] {
+        match self {
+            Ok(response) => response.body_bytes(),
+            Err(e) => e.body_bytes(),
+        }
+    }
+}
+
+/// Parse the body of a response for [`assert_json_body!`](crate::assert_json_body).
+#[cfg(feature = "json")]
+#[doc(hidden)]
+pub fn json_body(response: &impl TestResponse) -> serde_json::Value {
+    let body = response.body_bytes();
+    serde_json::from_slice(body).unwrap_or_else(|e| {
+        panic!(
+            "response body is not JSON ({}): {}",
+            e,
+            String::from_utf8_lossy(body)
+        )
+    })
+}
+
+/// Assert the status code of a [`TestResponse`](crate::testing::TestResponse), given as a
+/// [`StatusCode`](crate::StatusCode) or a `u16`.
+///
+/// ```rust,ignore
+/// assert_status!(client.send_request("/nolanv", Method::GET, &[], None).await, 404);
+/// ```
+#[macro_export]
+macro_rules! assert_status {
+    ($response:expr, $status:expr $(,)?) => {{
+        let response = &$response;
+        let expected =
+            $crate::StatusCode::try_from($status).expect("assert_status!: invalid status code");
+        let actual = $crate::testing::TestResponse::status_code(response);
+        assert!(
+            actual == expected,
+            "assert_status!: expected {}, got {} with body {:?}",
+            expected,
+            actual,
+            String::from_utf8_lossy($crate::testing::TestResponse::body_bytes(response))
+        );
+    }};
+}
+
+/// Assert that the body of a [`TestResponse`](crate::testing::TestResponse) is the JSON value,
+/// whatever its formatting (feature=json).
+///
+/// ```rust,ignore
+/// assert_json_body!(response, json!({"name": "nolanv"}));
+/// ```
+#[cfg(feature = "json")]
+#[macro_export]
+macro_rules! assert_json_body {
+    ($response:expr, $expected:expr $(,)?) => {{
+        let actual = $crate::testing::json_body(&$response);
+        let expected = $expected;
+        assert!(
+            actual == expected,
+            "assert_json_body!: expected\n{:#}\ngot\n{:#}",
+            expected,
+            actual
+        );
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Method,
+        testing::{Mock, MockServer, ResponseTemplate, Router},
+    };
+
+    #[tokio::test]
+    async fn assert_responses_and_requests() {
+        let server = MockServer::start(Router::new())
+            .await
+            .expect("MockServer::start");
+        server.register(
+            Mock::given(Method::POST)
+                .path("/nolanv")
+                .respond_with(ResponseTemplate::new(201).body(r#"{"name": "nolanv"}"#)),
+        );
+        let mut client = server.client().await.expect("server.client");
+
+        let created = client
+            .send_request("/nolanv", Method::POST, &[("x-name", "nolanv")], None)
+            .await;
+        assert_status!(created, StatusCode::CREATED);
+        #[cfg(feature = "json")]
+        assert_json_body!(created, serde_json::json!({"name": "nolanv"}));
+        assert_status!(
+            client
+                .send_request("/missing", Method::GET, &[], None)
+                .await,
+            404
+        );
+
+        let requests = server.received_requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method(), Method::POST);
+        assert_eq!(requests[0].headers()["x-name"], "nolanv");
+        assert_eq!(requests[1].uri().path(), "/missing");
+    }
+
+    #[test]
+    #[should_panic(expected = "assert_status!: expected 200 OK, got 404 Not Found")]
+    fn assert_status_fails() {
+        assert_status!((StatusCode::NOT_FOUND, b"missing".to_vec()), 200);
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    collections::VecDeque,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{UnixListener, UnixStream},
+    task::JoinHandle,
+};
+
+/// Failure injected by a [`FaultProxy`] into one connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// Close the connection as soon as it is accepted.
+    Close,
+    /// Forward the first bytes of the response then close both directions, as a crashed server.
+    ResetAfter(usize),
+    /// Forward the first bytes of the response then only close the writing direction, as a body
+    /// shorter than announced.
+    Truncate(usize),
+    /// Wait before forwarding the response.
+    Delay(Duration),
+    /// Answer with these bytes instead of the response.
+    Garbage(Vec<u8>),
+}
+
+#[derive(Default)]
+struct FaultState {
+    faults: VecDeque<Fault>,
+    listener: Option<JoinHandle<()>>,
+}
+
+/// Proxy forwarding the bytes of each connection to a real server, with [`Fault`]s injected into
+/// the next connections to test the retry and reconnection handling of a client.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::{ClientUnix, testing::{Fault, FaultProxy}};
+///
+/// # async fn run() {
+/// let proxy = FaultProxy::start("/var/run/docker.sock")
+///     .await
+///     .expect("FaultProxy::start");
+/// proxy.inject(Fault::ResetAfter(10));
+/// let client = ClientUnix::try_new(&proxy.socket_path().to_string_lossy()).await;
+/// # }
+/// ```
+pub struct FaultProxy {
+    socket_path: PathBuf,
+    target: Arc<PathBuf>,
+    state: Arc<Mutex<FaultState>>,
+}
+
+impl FaultProxy {
+    /// Forward to the server on `target`, from a new socket in the temporary directory.
+    pub async fn start(target: impl Into<PathBuf>) -> io::Result<Self> {
+        let proxy = FaultProxy {
+            socket_path: super::unique_socket_path("fault"),
+            target: Arc::new(target.into()),
+            state: Arc::new(Mutex::new(FaultState::default())),
+        };
+        proxy.listen()?;
+        Ok(proxy)
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Inject `fault` into the next connection without a fault, the others are forwarded as is.
+    pub fn inject(&self, fault: Fault) {
+        self.state
+            .lock()
+            .expect("fault state lock")
+            .faults
+            .push_back(fault);
+    }
+
+    /// Stop or resume accepting connections. While refused, connecting fails with
+    /// `ConnectionRefused`, as the socket exists without a listener. The open connections are
+    /// kept.
+    pub async fn refuse_connections(&self, refuse: bool) -> io::Result<()> {
+        match refuse {
+            true => {
+                let listener = self.state.lock().expect("fault state lock").listener.take();
+                if let Some(listener) = listener {
+                    listener.abort();
+                    // The listener is closed once its task is dropped
+                    let _ = listener.await;
+                }
+                Ok(())
+            }
+            false
+                if self
+                    .state
+                    .lock()
+                    .expect("fault state lock")
+                    .listener
+                    .is_none() =>
+            {
+                self.listen()
+            }
+            false => Ok(()),
+        }
+    }
+
+    fn listen(&self) -> io::Result<()> {
+        if self.socket_path.exists() {
+            std::fs::remove_file(&self.socket_path)?;
+        }
+        let listener = UnixListener::bind(&self.socket_path)?;
+        let (target, state) = (self.target.clone(), self.state.clone());
+        let handle = tokio::spawn(async move {
+            while let Ok((client, _)) = listener.accept().await {
+                let fault = state.lock().expect("fault state lock").faults.pop_front();
+                tokio::spawn(forward(client, target.clone(), fault));
+            }
+        });
+        self.state.lock().expect("fault state lock").listener = Some(handle);
+        Ok(())
+    }
+}
+
+impl Drop for FaultProxy {
+    fn drop(&mut self) {
+        if let Some(listener) = self
+            .state
+            .lock()
+            .ok()
+            .and_then(|mut state| state.listener.take())
+        {
+            listener.abort();
+        }
+        let _ = std::fs::remove_file(&self.socket_path);
+    }
+}
+
+async fn forward(mut client: UnixStream, target: Arc<PathBuf>, fault: Option<Fault>) {
+    let limit = match fault {
+        Some(Fault::Close) => return,
+        Some(Fault::Garbage(bytes)) => {
+            // Wait for the request, then answer it
+            let _ = client.read(&mut [0; 4096]).await;
+            let _ = client.write_all(&bytes).await;
+            let _ = client.shutdown().await;
+            return;
+        }
+        Some(Fault::ResetAfter(limit)) | Some(Fault::Truncate(limit)) => Some(limit),
+        Some(Fault::Delay(_)) | None => None,
+    };
+    let Ok(server) = UnixStream::connect(target.as_path()).await else {
+        return;
+    };
+    let (mut client_read, mut client_write) = client.into_split();
+    let (mut server_read, mut server_write) = server.into_split();
+    let requests = tokio::spawn(async move {
+        let _ = tokio::io::copy(&mut client_read, &mut server_write).await;
+    });
+
+    if let Some(Fault::Delay(delay)) = &fault {
+        // Delay from the start of the response
+        if server_read.readable().await.is_ok() {
+            tokio::time::sleep(*delay).await;
+        }
+    }
+    let mut forwarded = 0;
+    let mut buffer = [0; 8192];
+    loop {
+        let read = match server_read.read(&mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        let allowed = limit.map_or(read, |limit| read.min(limit - forwarded));
+        if client_write.write_all(&buffer[..allowed]).await.is_err() {
+            break;
+        }
+        forwarded += allowed;
+        if limit == Some(forwarded) {
+            break;
+        }
+    }
+    let _ = client_write.shutdown().await;
+    if matches!(fault, Some(Fault::ResetAfter(_))) {
+        requests.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ClientUnix, ErrorAndResponse, ErrorKind, Method,
+        test_helpers::{server::Server, util::make_socket_path_test},
+    };
+
+    async fn make_proxy(test_function: &str) -> (Server, FaultProxy) {
+        let socket_path = make_socket_path_test("fault", test_function);
+        let server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let proxy = FaultProxy::start(&socket_path)
+            .await
+            .expect("FaultProxy::start");
+        (server, proxy)
+    }
+
+    async fn send(proxy: &FaultProxy) -> Result<(crate::StatusCode, Vec<u8>), ErrorAndResponse> {
+        let mut client = ClientUnix::try_new(&proxy.socket_path().to_string_lossy())
+            .await
+            .map_err(ErrorAndResponse::InternalError)?;
+        client.send_request("/nolanv", Method::GET, &[], None).await
+    }
+
+    #[tokio::test]
+    async fn faults_then_forwarded() {
+        let (_server, proxy) = make_proxy("faults_then_forwarded").await;
+        proxy.inject(Fault::Close);
+        proxy.inject(Fault::Garbage(b"nolanv\r\n\r\n".to_vec()));
+        proxy.inject(Fault::Truncate(60));
+
+        for expected in [
+            ErrorKind::ConnectionClosed,
+            ErrorKind::Protocol,
+            ErrorKind::ConnectionClosed,
+        ] {
+            let result = send(&proxy).await;
+            assert_eq!(result.err().map(|e| e.kind()), Some(expected));
+        }
+        let (_, body) = send(&proxy).await.expect("forwarded");
+        assert_eq!(body, b"Hello nolanv");
+    }
+
+    #[tokio::test]
+    async fn delayed_response_times_out() {
+        let (_server, proxy) = make_proxy("delayed_response_times_out").await;
+        proxy.inject(Fault::Delay(Duration::from_secs(5)));
+        let mut client = ClientUnix::builder(&proxy.socket_path().to_string_lossy())
+            .request_timeout(Duration::from_millis(50))
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let result = client.send_request("/nolanv", Method::GET, &[], None).await;
+
+        assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::Timeout));
+    }
+
+    #[tokio::test]
+    async fn refused_connections() {
+        let (_server, proxy) = make_proxy("refused_connections").await;
+
+        proxy.refuse_connections(true).await.expect("refuse");
+        let result = send(&proxy).await;
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(ErrorKind::ConnectionRefused)
+        );
+
+        proxy.refuse_connections(false).await.expect("accept");
+        assert!(send(&proxy).await.is_ok());
+    }
+}
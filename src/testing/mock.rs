@@ -0,0 +1,274 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::Request,
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    response::Response,
+};
+use std::{
+    fmt,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+/// Request received by a [`MockServer`](super::MockServer), with its collected body.
+pub type ReceivedRequest = axum::http::Request<Bytes>;
+
+type Matcher = Box<dyn Fn(&ReceivedRequest) -> bool + Send + Sync>;
+type Responder = Box<dyn Fn(&ReceivedRequest) -> ResponseTemplate + Send + Sync>;
+
+/// Response of a [`Mock`].
+#[derive(Debug, Clone)]
+pub struct ResponseTemplate {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    delay: Option<Duration>,
+}
+
+impl ResponseTemplate {
+    pub fn new(status: u16) -> Self {
+        ResponseTemplate {
+            status: StatusCode::from_u16(status).expect("valid status code"),
+            headers: HeaderMap::new(),
+            body: Bytes::new(),
+            delay: None,
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.append(
+            HeaderName::try_from(name).expect("valid header name"),
+            HeaderValue::try_from(value).expect("valid header value"),
+        );
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// JSON body, with its `Content-Type`.
+    #[cfg(feature = "json")]
+    pub fn body_json(self, body: &impl serde::Serialize) -> Self {
+        self.header("content-type", "application/json")
+            .body(serde_json::to_vec(body).expect("serializable body"))
+    }
+
+    /// Wait before responding, e.g. to test the timeouts.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    async fn into_response(self) -> Response {
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
+        let mut response = Response::new(Body::from(self.body));
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers;
+        response
+    }
+}
+
+/// Matchers of a request and the response to it, registered with
+/// [`MockServer::register`](super::MockServer::register).
+///
+/// Every matcher must match, and a request is answered by the first registered mock matching it.
+pub struct Mock {
+    description: Vec<String>,
+    matchers: Vec<Matcher>,
+    responder: Responder,
+    expected_calls: Option<u64>,
+}
+
+impl Mock {
+    pub fn given(method: Method) -> Self {
+        Mock::new(method.to_string()).matching(move |request| request.method() == method)
+    }
+
+    /// Match any method.
+    pub fn any() -> Self {
+        Mock::new("*".to_string())
+    }
+
+    fn new(method: String) -> Self {
+        Mock {
+            description: vec![method],
+            matchers: Vec::new(),
+            responder: Box::new(|_| ResponseTemplate::new(200)),
+            expected_calls: None,
+        }
+    }
+
+    /// Match the path, without the query.
+    pub fn path(mut self, path: &str) -> Self {
+        self.description.push(path.to_string());
+        let path = path.to_string();
+        self.matching(move |request| request.uri().path() == path)
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.description.push(format!("{}: {}", name, value));
+        let (name, value) = (name.to_string(), value.to_string());
+        self.matching(move |request| {
+            request
+                .headers()
+                .get_all(name.as_str())
+                .iter()
+                .any(|v| v.as_bytes() == value.as_bytes())
+        })
+    }
+
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.description.push("body".to_string());
+        let body = body.into();
+        self.matching(move |request| request.body() == &body)
+    }
+
+    /// Match a JSON body equal to `body`, whatever its formatting.
+    #[cfg(feature = "json")]
+    pub fn body_json(mut self, body: &impl serde::Serialize) -> Self {
+        self.description.push("json body".to_string());
+        let body = serde_json::to_value(body).expect("serializable body");
+        self.matching(move |request| {
+            serde_json::from_slice::<serde_json::Value>(request.body()).ok() == Some(body.clone())
+        })
+    }
+
+    /// Match with a custom function.
+    pub fn matching(
+        mut self,
+        matcher: impl Fn(&ReceivedRequest) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.matchers.push(Box::new(matcher));
+        self
+    }
+
+    pub fn respond_with(self, template: ResponseTemplate) -> Self {
+        self.respond_with_fn(move |_| template.clone())
+    }
+
+    /// Build the response from the request.
+    pub fn respond_with_fn(
+        mut self,
+        responder: impl Fn(&ReceivedRequest) -> ResponseTemplate + Send + Sync + 'static,
+    ) -> Self {
+        self.responder = Box::new(responder);
+        self
+    }
+
+    /// Expect exactly `calls` matching requests, verified when the server is dropped or by
+    /// [`MockServer::verify`](super::MockServer::verify).
+    pub fn expect(mut self, calls: u64) -> Self {
+        self.expected_calls = Some(calls);
+        self
+    }
+}
+
+impl fmt::Debug for Mock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mock")
+            .field("matchers", &self.description.join(" "))
+            .field("expected_calls", &self.expected_calls)
+            .finish()
+    }
+}
+
+pub(super) struct RegisteredMock {
+    mock: Mock,
+    calls: AtomicU64,
+}
+
+/// Mocks of a server and the requests it received.
+#[derive(Default)]
+pub(super) struct MockState {
+    mocks: Mutex<Vec<Arc<RegisteredMock>>>,
+    received: Mutex<Vec<ReceivedRequest>>,
+}
+
+impl MockState {
+    pub(super) fn register(&self, mock: Mock) {
+        self.mocks
+            .lock()
+            .expect("mocks lock")
+            .push(Arc::new(RegisteredMock {
+                mock,
+                calls: AtomicU64::new(0),
+            }));
+    }
+
+    /// Descriptions of the mocks whose expected calls differ from the received ones.
+    pub(super) fn unmet_expectations(&self) -> Vec<String> {
+        self.mocks
+            .lock()
+            .expect("mocks lock")
+            .iter()
+            .filter_map(|registered| {
+                let calls = registered.calls.load(Ordering::SeqCst);
+                match registered.mock.expected_calls {
+                    Some(expected) if expected != calls => Some(format!(
+                        "{} expected {} calls, received {}",
+                        registered.mock.description.join(" "),
+                        expected,
+                        calls
+                    )),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    pub(super) fn received_requests(&self) -> Vec<ReceivedRequest> {
+        self.received
+            .lock()
+            .expect("received lock")
+            .iter()
+            .map(|request| {
+                let mut copy = ReceivedRequest::new(request.body().clone());
+                *copy.method_mut() = request.method().clone();
+                *copy.uri_mut() = request.uri().clone();
+                *copy.version_mut() = request.version();
+                *copy.headers_mut() = request.headers().clone();
+                copy
+            })
+            .collect()
+    }
+
+    /// Answer with the first matching mock, else `404 Not Found`.
+    pub(super) async fn respond(&self, request: Request) -> Response {
+        let (parts, body) = request.into_parts();
+        let body = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(_) => return ResponseTemplate::new(400).into_response().await,
+        };
+        let request = ReceivedRequest::from_parts(parts, body);
+
+        let matched = self
+            .mocks
+            .lock()
+            .expect("mocks lock")
+            .iter()
+            .find(|registered| {
+                registered
+                    .mock
+                    .matchers
+                    .iter()
+                    .all(|matcher| matcher(&request))
+            })
+            .cloned();
+        let template = match matched {
+            Some(registered) => {
+                registered.calls.fetch_add(1, Ordering::SeqCst);
+                (registered.mock.responder)(&request)
+            }
+            None => ResponseTemplate::new(404).body("no mock matched the request"),
+        };
+        self.received.lock().expect("received lock").push(request);
+        template.into_response().await
+    }
+}
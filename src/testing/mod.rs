@@ -0,0 +1,219 @@
+//! Mock HTTP server on a unix socket, to test the code using a [`ClientUnix`] (feature=test-util).
+//!
+//! The routes and handlers are those of an [`axum`] [`Router`], and the requests it doesn't route
+//! are answered by the registered [`Mock`]s. With the `json` feature, a [`Recording`] proxy
+//! records the traffic of a real server into a [`Cassette`] fixture which is then replayed. A
+//! [`FaultProxy`] injects connection and response failures in front of a server.
+//!
+//! [`assert_status!`](crate::assert_status) and [`assert_json_body!`](crate::assert_json_body)
+//! check the responses and [`MockServer::received_requests`] the requests. [`TempSocketPath`]
+//! gives the other servers of a test a unique socket path.
+//!
+//! ```rust
+//! use http_client_unix_domain_socket::{
+//!     Method,
+//!     testing::{MockServer, Router, routing::get},
+//! };
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let server = MockServer::start(Router::new().route("/_ping", get(|| async { "OK" })))
+//!     .await
+//!     .expect("MockServer::start");
+//! let mut client = server.client().await.expect("server.client");
+//!
+//! let (_, body) = client
+//!     .send_request("/_ping", Method::GET, &[], None)
+//!     .await
+//!     .expect("client.send_request");
+//! assert_eq!(body, b"OK");
+//! # }
+//! ```
+
+mod assert;
+mod fault;
+mod mock;
+mod socket_path;
+#[cfg(feature = "json")]
+mod vcr;
+
+use crate::{ClientUnix, Error, ServerUnix};
+pub use assert::TestResponse;
+#[cfg(feature = "json")]
+#[doc(hidden)]
+pub use assert::json_body;
+use axum::extract::Request;
+pub use axum::{self, Router, routing};
+pub use fault::{Fault, FaultProxy};
+use mock::MockState;
+pub use mock::{Mock, ReceivedRequest, ResponseTemplate};
+pub use socket_path::{TempSocketPath, unique_socket_path};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::task::JoinHandle;
+#[cfg(feature = "json")]
+pub use vcr::{Cassette, Interaction, RecordedBody, RecordedRequest, RecordedResponse, Recording};
+
+/// Server running until dropped, which also removes its socket and verifies the expectations of
+/// its mocks.
+pub struct MockServer {
+    socket_path: PathBuf,
+    handle: JoinHandle<()>,
+    state: Arc<MockState>,
+}
+
+impl MockServer {
+    /// Serve `router` on a new socket in the temporary directory, unique to this server.
+    pub async fn start(router: Router) -> io::Result<Self> {
+        MockServer::start_at(unique_socket_path("mock"), router).await
+    }
+
+    /// Serve `router` on `socket_path`, replacing a stale socket and creating its parent
+    /// directories, see [`ServerUnix`].
+    pub async fn start_at(socket_path: impl Into<PathBuf>, router: Router) -> io::Result<Self> {
+        let socket_path = socket_path.into();
+        let server = ServerUnix::bind(&socket_path).await?;
+
+        let state = Arc::new(MockState::default());
+        let fallback_state = state.clone();
+        let router = router.fallback(move |request: Request| {
+            let state = fallback_state.clone();
+            async move { state.respond(request).await }
+        });
+        let handle = tokio::spawn(async move {
+            let _ = server.serve(router).await;
+        });
+        Ok(MockServer {
+            socket_path,
+            handle,
+            state,
+        })
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Answer the matching requests, after the mocks registered before.
+    pub fn register(&self, mock: Mock) {
+        self.state.register(mock);
+    }
+
+    /// The requests answered by the mocks so far, matched or not, in their received order.
+    pub fn received_requests(&self) -> Vec<ReceivedRequest> {
+        self.state.received_requests()
+    }
+
+    /// Panic if a mock didn't receive its [`Mock::expect`]ed calls.
+    pub fn verify(&self) {
+        let unmet = self.state.unmet_expectations();
+        if !unmet.is_empty() {
+            panic!("unmet mock expectations:\n{}", unmet.join("\n"));
+        }
+    }
+
+    /// A client connected to this server with the default options, see
+    /// [`ClientUnix::builder`] for the others.
+    pub async fn client(&self) -> Result<ClientUnix, Error> {
+        ClientUnix::try_new(&self.socket_path.to_string_lossy()).await
+    }
+}
+
+impl std::fmt::Debug for MockServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockServer")
+            .field("socket_path", &self.socket_path)
+            .finish()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+        let _ = std::fs::remove_file(&self.socket_path);
+        if !std::thread::panicking() {
+            self.verify();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorKind, Method};
+    use axum::extract::Path;
+
+    #[tokio::test]
+    async fn serve_router_until_dropped() {
+        let router = Router::new().route(
+            "/hello/{name}",
+            routing::get(|Path(name): Path<String>| async move { format!("Hello {}", name) }),
+        );
+        let server = MockServer::start(router).await.expect("MockServer::start");
+        let mut client = server.client().await.expect("server.client");
+
+        let (_, body) = client
+            .send_request("/hello/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"Hello nolanv");
+
+        let socket_path = server.socket_path().to_path_buf();
+        drop(server);
+        assert!(!socket_path.exists());
+        let result = ClientUnix::try_new(&socket_path.to_string_lossy()).await;
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(ErrorKind::SocketNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn mocks_match_in_order() {
+        let server = MockServer::start(Router::new())
+            .await
+            .expect("MockServer::start");
+        server.register(
+            Mock::given(Method::POST)
+                .path("/containers/create")
+                .header("x-name", "nolanv")
+                .respond_with(ResponseTemplate::new(201).body("created"))
+                .expect(1),
+        );
+        server.register(Mock::any().respond_with_fn(|request| {
+            ResponseTemplate::new(200).body(format!("fallback {}", request.uri().path()))
+        }));
+        let mut client = server.client().await.expect("server.client");
+
+        let (status_code, body) = client
+            .send_request(
+                "/containers/create",
+                Method::POST,
+                &[("x-name", "nolanv")],
+                None,
+            )
+            .await
+            .expect("client.send_request");
+        assert_eq!(status_code.as_u16(), 201);
+        assert_eq!(body, b"created");
+
+        let (_, body) = client
+            .send_request("/containers/create", Method::POST, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"fallback /containers/create");
+        server.verify();
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "GET /nolanv expected 1 calls, received 0")]
+    async fn unmet_expectation_panics_on_drop() {
+        let server = MockServer::start(Router::new())
+            .await
+            .expect("MockServer::start");
+        server.register(Mock::given(Method::GET).path("/nolanv").expect(1));
+    }
+}
@@ -0,0 +1,103 @@
+use std::{
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+/// A new socket path in the temporary directory, unique to this call, named after `name` (e.g.
+/// the test function).
+pub fn unique_socket_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "http-client-unix-domain-socket-{}-{}.sock",
+        name,
+        uuid::Uuid::new_v4()
+    ))
+}
+
+/// Unique socket path, see [`unique_socket_path`], whose socket file is removed when dropped.
+///
+/// ```rust
+/// use http_client_unix_domain_socket::{ServerUnix, testing::TempSocketPath};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let socket_path = TempSocketPath::new("my_test");
+/// let server = ServerUnix::bind(socket_path.path())
+///     .await
+///     .expect("ServerUnix::bind");
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TempSocketPath {
+    path: PathBuf,
+}
+
+impl TempSocketPath {
+    pub fn new(name: &str) -> Self {
+        TempSocketPath {
+            path: unique_socket_path(name),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The path as the `&str` taken by [`ClientUnix::try_new`](crate::ClientUnix::try_new).
+    pub fn to_str(&self) -> &str {
+        self.path.to_str().expect("temporary directory is UTF-8")
+    }
+}
+
+impl Deref for TempSocketPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for TempSocketPath {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempSocketPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, Method, ServerUnix};
+    use axum::{Router, routing::get};
+
+    #[tokio::test]
+    async fn removed_on_drop() {
+        let socket_path = TempSocketPath::new("removed_on_drop");
+        assert_ne!(
+            socket_path.path(),
+            TempSocketPath::new("removed_on_drop").path()
+        );
+        let server = ServerUnix::bind(socket_path.path())
+            .await
+            .expect("ServerUnix::bind");
+        tokio::spawn(server.serve(Router::new().route("/_ping", get(|| async { "OK" }))));
+
+        let mut client = ClientUnix::try_new(socket_path.to_str())
+            .await
+            .expect("ClientUnix::try_new");
+        let (_, body) = client
+            .send_request("/_ping", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"OK");
+
+        let path = socket_path.to_path_buf();
+        assert!(path.exists());
+        drop(socket_path);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,351 @@
+use super::MockServer;
+use crate::{ClientUnix, Error};
+use axum::{
+    Router,
+    body::Body,
+    extract::Request,
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::Response,
+    routing::any,
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+/// Headers not recorded: they describe the connection or the framing, not the exchange.
+const SKIPPED_HEADERS: [&str; 4] = ["connection", "content-length", "transfer-encoding", "host"];
+/// Headers whose value is masked in the recording.
+const MASKED_HEADERS: [&str; 3] = ["authorization", "proxy-authorization", "cookie"];
+
+/// Request and response pairs recorded by a [`Recording`], saved to and loaded from a JSON
+/// fixture file (feature=test-util and json).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Path and query.
+    pub uri: String,
+    pub headers: Vec<(String, String)>,
+    pub body: RecordedBody,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: RecordedBody,
+}
+
+/// UTF-8 bodies are kept as is for readable fixtures, the others are base64 encoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordedBody {
+    Text(String),
+    Base64(String),
+}
+
+impl RecordedBody {
+    fn new(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => RecordedBody::Text(text.to_string()),
+            Err(_) => RecordedBody::Base64(STANDARD.encode(bytes)),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            RecordedBody::Text(text) => text.as_bytes().to_vec(),
+            RecordedBody::Base64(encoded) => STANDARD.decode(encoded).unwrap_or_default(),
+        }
+    }
+}
+
+impl Cassette {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        serde_json::from_reader(io::BufReader::new(file)).map_err(io::Error::other)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, self).map_err(io::Error::other)
+    }
+
+    /// Serve the recorded responses: each request is answered by the first unused interaction
+    /// with the same method, uri and body, so repeated requests are replayed in their recorded
+    /// order. Unmatched requests are answered with `404 Not Found`.
+    pub async fn replay(self) -> io::Result<MockServer> {
+        let interactions = Arc::new(Mutex::new(
+            self.interactions
+                .into_iter()
+                .map(|interaction| (interaction, false))
+                .collect::<Vec<_>>(),
+        ));
+        let handler = move |request: Request| {
+            let interactions = interactions.clone();
+            async move {
+                let (parts, body) = request.into_parts();
+                let body = axum::body::to_bytes(body, usize::MAX)
+                    .await
+                    .unwrap_or_default();
+                let uri = parts.uri.path_and_query().map_or("/", |uri| uri.as_str());
+                let mut interactions = interactions.lock().expect("interactions lock");
+                let Some((interaction, used)) =
+                    interactions.iter_mut().find(|(interaction, used)| {
+                        !used
+                            && interaction.request.method == parts.method.as_str()
+                            && interaction.request.uri == uri
+                            && interaction.request.body.to_bytes() == body
+                    })
+                else {
+                    let mut response = Response::new(Body::from("no recorded interaction"));
+                    *response.status_mut() = StatusCode::NOT_FOUND;
+                    return response;
+                };
+                *used = true;
+                build_response(&interaction.response)
+            }
+        };
+        let router = Router::new()
+            .route("/", any(handler.clone()))
+            .route("/{*path}", any(handler));
+        MockServer::start(router).await
+    }
+}
+
+fn build_response(recorded: &RecordedResponse) -> Response {
+    let mut response = Response::new(Body::from(recorded.body.to_bytes()));
+    *response.status_mut() = StatusCode::from_u16(recorded.status).unwrap_or(StatusCode::OK);
+    for (name, value) in &recorded.headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::try_from(name.as_str()),
+            HeaderValue::try_from(value.as_str()),
+        ) {
+            response.headers_mut().append(name, value);
+        }
+    }
+    response
+}
+
+fn record_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+        .map(|(name, value)| {
+            let value = match MASKED_HEADERS.contains(&name.as_str()) {
+                true => "[REDACTED]".to_string(),
+                false => String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// Proxy recording the requests sent to its socket and the responses of the real server, to be
+/// saved as a [`Cassette`] and replayed without the server.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::{Method, testing::{Cassette, Recording}};
+///
+/// # async fn run() {
+/// let recording = Recording::start("/var/run/docker.sock")
+///     .await
+///     .expect("Recording::start");
+/// let mut client = recording.server().client().await.expect("client");
+/// client
+///     .send_request("/version", Method::GET, &[], None)
+///     .await
+///     .expect("client.send_request");
+/// recording.cassette().save("tests/fixtures/docker_version.json").expect("save");
+///
+/// // In CI, without the daemon
+/// let server = Cassette::load("tests/fixtures/docker_version.json")
+///     .expect("Cassette::load")
+///     .replay()
+///     .await
+///     .expect("cassette.replay");
+/// # }
+/// ```
+pub struct Recording {
+    server: MockServer,
+    cassette: Arc<Mutex<Cassette>>,
+}
+
+impl Recording {
+    /// Forward the requests to the server listening on `socket_path`.
+    pub async fn start(socket_path: &str) -> Result<Self, Error> {
+        let client = Arc::new(tokio::sync::Mutex::new(
+            ClientUnix::try_new(socket_path).await?,
+        ));
+        let cassette = Arc::new(Mutex::new(Cassette::default()));
+        let recorded = cassette.clone();
+        let handler = move |request: Request| {
+            let (client, cassette) = (client.clone(), recorded.clone());
+            async move {
+                let (parts, body) = request.into_parts();
+                let body = axum::body::to_bytes(body, usize::MAX)
+                    .await
+                    .unwrap_or_default();
+                let uri = parts
+                    .uri
+                    .path_and_query()
+                    .map_or("/", |uri| uri.as_str())
+                    .to_string();
+                let headers: Vec<(String, String)> = parts
+                    .headers
+                    .iter()
+                    .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+                    .filter_map(|(name, value)| {
+                        Some((name.to_string(), value.to_str().ok()?.to_string()))
+                    })
+                    .collect();
+                let forwarded: Vec<(&str, &str)> = headers
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str()))
+                    .collect();
+                let response = {
+                    let mut client = client.lock().await;
+                    match client.request_target(&parts.uri, &parts.method) {
+                        Ok(endpoint) => {
+                            client
+                                .send_buffered(
+                                    &endpoint,
+                                    parts.method.clone(),
+                                    &forwarded,
+                                    Some(body.clone()).into(),
+                                )
+                                .await
+                        }
+                        Err(e) => Err(e),
+                    }
+                };
+                let response = match response {
+                    Ok(response) => response,
+                    Err(e) => {
+                        let mut response = Response::new(Body::from(e.to_string()));
+                        *response.status_mut() = StatusCode::BAD_GATEWAY;
+                        return response;
+                    }
+                };
+
+                let interaction = Interaction {
+                    request: RecordedRequest {
+                        method: parts.method.to_string(),
+                        uri,
+                        headers: record_headers(&parts.headers),
+                        body: RecordedBody::new(&body),
+                    },
+                    response: RecordedResponse {
+                        status: response.status().as_u16(),
+                        headers: record_headers(response.headers()),
+                        body: RecordedBody::new(response.body()),
+                    },
+                };
+                let response = build_response(&interaction.response);
+                cassette
+                    .lock()
+                    .expect("cassette lock")
+                    .interactions
+                    .push(interaction);
+                response
+            }
+        };
+        let router = Router::new()
+            .route("/", any(handler.clone()))
+            .route("/{*path}", any(handler));
+        let server = MockServer::start(router)
+            .await
+            .map_err(Error::SocketConnectionInitiation)?;
+        Ok(Recording { server, cassette })
+    }
+
+    /// The proxy, to connect the recorded clients to.
+    pub fn server(&self) -> &MockServer {
+        &self.server
+    }
+
+    /// The interactions recorded so far.
+    pub fn cassette(&self) -> Cassette {
+        self.cassette.lock().expect("cassette lock").clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Method,
+        test_helpers::{server::Server, util::make_socket_path_test},
+    };
+
+    #[tokio::test]
+    async fn record_then_replay() {
+        let socket_path = make_socket_path_test("vcr", "record_then_replay");
+        let real_server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+        let recording = Recording::start(&socket_path)
+            .await
+            .expect("Recording::start");
+        let mut client = recording.server().client().await.expect("client");
+        client
+            .send_request("/nolanv", Method::GET, &[("authorization", "secret")], None)
+            .await
+            .expect("client.send_request");
+        let cassette = recording.cassette();
+        drop(recording);
+        real_server.abort().await;
+
+        let fixture = std::env::temp_dir().join("http_client_unix_domain_socket_vcr.json");
+        cassette.save(&fixture).expect("cassette.save");
+        let cassette = Cassette::load(&fixture).expect("Cassette::load");
+        assert_eq!(cassette.interactions.len(), 1);
+        assert!(
+            cassette.interactions[0]
+                .request
+                .headers
+                .contains(&("authorization".to_string(), "[REDACTED]".to_string()))
+        );
+
+        let replay = cassette.replay().await.expect("cassette.replay");
+        let mut client = replay.client().await.expect("client");
+        let (_, body) = client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+        assert_eq!(body, b"Hello nolanv");
+
+        // Each interaction is replayed once
+        let result = client.send_request("/nolanv", Method::GET, &[], None).await;
+        assert!(matches!(
+            result.err(),
+            Some(crate::ErrorAndResponse::ResponseUnsuccessful(
+                StatusCode::NOT_FOUND,
+                _
+            ))
+        ));
+    }
+
+    #[test]
+    fn binary_bodies_are_base64() {
+        assert_eq!(
+            RecordedBody::new(&[0xff, 0x00]),
+            RecordedBody::Base64("/wA=".into())
+        );
+        assert_eq!(RecordedBody::Base64("/wA=".into()).to_bytes(), [0xff, 0x00]);
+    }
+}
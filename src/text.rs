@@ -0,0 +1,123 @@
+use hyper::{HeaderMap, Response, header::CONTENT_TYPE};
+use std::borrow::Cow;
+
+/// Decode the body of a response as text, with the charset of its `Content-Type`.
+///
+/// ```rust,no_run
+/// use http_client_unix_domain_socket::{ClientUnix, Method, ResponseText};
+///
+/// # async fn run(mut client: ClientUnix) {
+/// let response = client
+///     .send_request_full("/containers/nolanv/logs?stdout=true", Method::GET, &[], None)
+///     .await
+///     .expect("client.send_request_full");
+/// println!("{}", response.text());
+/// # }
+/// ```
+pub trait ResponseText {
+    /// The body decoded as UTF-8, US-ASCII, ISO-8859-1 or UTF-16 as its charset says, and as
+    /// UTF-8 without charset or with another one. Invalid sequences are replaced by `U+FFFD`.
+    fn text(&self) -> Cow<'_, str>;
+}
+
+impl ResponseText for Response<Vec<u8>> {
+    fn text(&self) -> Cow<'_, str> {
+        decode(self.body(), charset(self.headers()).as_deref())
+    }
+}
+
+/// Charset parameter of the `Content-Type`, lowercase and unquoted.
+fn charset(headers: &HeaderMap) -> Option<String> {
+    let content_type = headers.get(CONTENT_TYPE)?.to_str().ok()?;
+    content_type.split(';').skip(1).find_map(|parameter| {
+        let (name, value) = parameter.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"').to_ascii_lowercase())
+    })
+}
+
+fn decode<'a>(body: &'a [u8], charset: Option<&str>) -> Cow<'a, str> {
+    match charset {
+        Some("iso-8859-1" | "latin1" | "l1") => body.iter().map(|&b| b as char).collect(),
+        Some("utf-16le") => decode_utf16(body, u16::from_le_bytes),
+        Some("utf-16be") => decode_utf16(body, u16::from_be_bytes),
+        // Big endian without byte order mark
+        Some("utf-16") => match body {
+            [0xFF, 0xFE, rest @ ..] => decode_utf16(rest, u16::from_le_bytes),
+            [0xFE, 0xFF, rest @ ..] => decode_utf16(rest, u16::from_be_bytes),
+            _ => decode_utf16(body, u16::from_be_bytes),
+        },
+        // US-ASCII is a subset of UTF-8
+        _ => String::from_utf8_lossy(body),
+    }
+}
+
+fn decode_utf16(body: &[u8], unit: fn([u8; 2]) -> u16) -> Cow<'_, str> {
+    let units = body.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    let mut text: String = char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+    if body.len() % 2 == 1 {
+        text.push(char::REPLACEMENT_CHARACTER);
+    }
+    Cow::Owned(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(content_type: Option<&str>, body: &[u8]) -> Response<Vec<u8>> {
+        let mut response = Response::new(body.to_vec());
+        if let Some(content_type) = content_type {
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, content_type.parse().unwrap());
+        }
+        response
+    }
+
+    #[test]
+    fn decode_charsets() {
+        for (content_type, body, text) in [
+            (None, &b"caf\xC3\xA9"[..], "caf\u{e9}"),
+            (
+                Some("text/plain; charset=utf-8"),
+                b"caf\xC3\xA9",
+                "caf\u{e9}",
+            ),
+            (
+                Some("text/plain; charset=\"ISO-8859-1\""),
+                b"caf\xE9",
+                "caf\u{e9}",
+            ),
+            (Some("text/plain;charset=us-ascii"), b"nolanv", "nolanv"),
+            (
+                Some("text/plain; charset=utf-16le"),
+                b"c\0a\0f\0\xE9\0",
+                "caf\u{e9}",
+            ),
+            (
+                Some("text/plain; charset=utf-16"),
+                b"\xFF\xFEc\0a\0f\0\xE9\0",
+                "caf\u{e9}",
+            ),
+            (
+                Some("text/plain; charset=utf-16"),
+                b"\0c\0a\0f\0\xE9",
+                "caf\u{e9}",
+            ),
+            (Some("text/plain; charset=koi8-r"), b"nolanv", "nolanv"),
+            (Some("text/plain"), b"caf\xE9", "caf\u{fffd}"),
+            (Some("text/plain; charset=utf-16be"), b"\0c\0", "c\u{fffd}"),
+        ] {
+            assert_eq!(
+                response(content_type, body).text(),
+                text,
+                "{:?}",
+                content_type
+            );
+        }
+    }
+}
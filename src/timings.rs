@@ -0,0 +1,14 @@
+use std::time::Duration;
+
+/// Time spent in each phase of a request, inserted in the extensions of the responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestTimings {
+    /// Waiting for the connection to be ready to send the request.
+    pub queue: Duration,
+    /// Establishing a new connection for the request.
+    pub connect: Duration,
+    /// From sending the request to receiving the response head.
+    pub time_to_first_byte: Duration,
+    /// Collecting the response body, `None` when the body is not collected by the client.
+    pub body_read: Option<Duration>,
+}
@@ -0,0 +1,430 @@
+use crate::{
+    rate_limit::RateLimiter,
+    redact::{HeadBuffer, PendingRequests, Redactor},
+    stats::ConnectionCounters,
+};
+use std::{
+    future::Future,
+    io::{IoSlice, Write},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, ready},
+    time::Instant,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    time::Sleep,
+};
+
+/// Direction of the bytes given to a [`WireSink`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Written by the client to the socket.
+    Sent,
+    /// Read by the client from the socket.
+    Received,
+}
+
+/// Receive a copy of every raw byte written to and read from the socket.
+///
+/// Implemented for closures `Fn(Direction, &[u8])` and for `Mutex<W: Write>` (e.g. a file).
+pub trait WireSink: Send + Sync {
+    fn dump(&self, direction: Direction, bytes: &[u8]);
+}
+
+impl<F: Fn(Direction, &[u8]) + Send + Sync> WireSink for F {
+    fn dump(&self, direction: Direction, bytes: &[u8]) {
+        self(direction, bytes)
+    }
+}
+
+/// Each chunk is written after a `> N bytes sent` or `< N bytes received` line.
+impl<W: Write + Send> WireSink for Mutex<W> {
+    fn dump(&self, direction: Direction, bytes: &[u8]) {
+        let Ok(mut writer) = self.lock() else {
+            return;
+        };
+        let marker = match direction {
+            Direction::Sent => "> sent",
+            Direction::Received => "< received",
+        };
+        let _ = writeln!(writer, "{} {} bytes", marker, bytes.len());
+        let _ = writer.write_all(bytes);
+        let _ = writeln!(writer);
+        let _ = writer.flush();
+    }
+}
+
+/// Bandwidth limits of a connection, in bytes per second.
+#[derive(Clone, Default)]
+pub(crate) struct Bandwidth {
+    pub(crate) upload: Option<Arc<RateLimiter>>,
+    pub(crate) download: Option<Arc<RateLimiter>>,
+}
+
+/// Delays the reads or writes of a connection until the bytes of the previous ones are paid for,
+/// keeping the average rate under the limit.
+struct Throttle {
+    limiter: Arc<RateLimiter>,
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl Throttle {
+    fn new(limiter: Option<Arc<RateLimiter>>) -> Option<Self> {
+        limiter.map(|limiter| Throttle {
+            limiter,
+            delay: None,
+        })
+    }
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(delay) = &mut self.delay {
+            ready!(delay.as_mut().poll(cx));
+            self.delay = None;
+        }
+        Poll::Ready(())
+    }
+
+    fn consume(&mut self, bytes: usize) {
+        let wait = self.limiter.reserve(Instant::now(), bytes as f64);
+        if !wait.is_zero() {
+            self.delay = Some(Box::pin(tokio::time::sleep(wait)));
+        }
+    }
+}
+
+/// Socket wrapper counting the bytes and teeing them, with redacted headers masked, to an
+/// optional [`WireSink`], and throttling them to the bandwidth limits of the client. With
+/// redacted headers, a head split across reads or writes reaches the sink once complete.
+pub(crate) struct WireStream<S> {
+    inner: S,
+    sink: Option<Arc<dyn WireSink>>,
+    redactor: Redactor,
+    sent_head: HeadBuffer,
+    received_head: HeadBuffer,
+    requests: PendingRequests,
+    counters: Arc<ConnectionCounters>,
+    upload: Option<Throttle>,
+    download: Option<Throttle>,
+}
+
+impl<S> WireStream<S> {
+    pub(crate) fn new(
+        inner: S,
+        sink: Option<Arc<dyn WireSink>>,
+        redactor: Redactor,
+        counters: Arc<ConnectionCounters>,
+        bandwidth: Bandwidth,
+    ) -> Self {
+        WireStream {
+            inner,
+            sink,
+            redactor,
+            sent_head: HeadBuffer::new(Direction::Sent),
+            received_head: HeadBuffer::new(Direction::Received),
+            requests: PendingRequests::new(),
+            counters,
+            upload: Throttle::new(bandwidth.upload),
+            download: Throttle::new(bandwidth.download),
+        }
+    }
+}
+
+/// Dumps the heads the connection ended in the middle of.
+impl<S> Drop for WireStream<S> {
+    fn drop(&mut self) {
+        // A sink which panicked isn't called again while unwinding
+        let Some(sink) = self.sink.as_ref().filter(|_| !std::thread::panicking()) else {
+            return;
+        };
+        if let Some(bytes) = self.sent_head.finish(&self.redactor) {
+            sink.dump(Direction::Sent, &bytes);
+        }
+        if let Some(bytes) = self.received_head.finish(&self.redactor) {
+            sink.dump(Direction::Received, &bytes);
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for WireStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        if let Some(download) = &mut self.download {
+            ready!(download.poll_ready(cx));
+        }
+        let filled_before = buf.filled().len();
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = &result {
+            let read = &buf.filled()[filled_before..];
+            self.counters.add_received(read.len());
+            if let Some(download) = &mut self.download {
+                download.consume(read.len());
+            }
+            let this = &mut *self;
+            if let Some(sink) = &this.sink
+                && !read.is_empty()
+                && let Some(bytes) =
+                    this.received_head
+                        .redact(&this.redactor, read, &mut this.requests)
+            {
+                sink.dump(Direction::Received, &bytes);
+            }
+        }
+        result
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for WireStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        if let Some(upload) = &mut self.upload {
+            ready!(upload.poll_ready(cx));
+        }
+        let result = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = &result {
+            self.counters.add_sent(*written);
+            if let Some(upload) = &mut self.upload {
+                upload.consume(*written);
+            }
+            let this = &mut *self;
+            if let Some(sink) = &this.sink
+                && let Some(bytes) =
+                    this.sent_head
+                        .redact(&this.redactor, &buf[..*written], &mut this.requests)
+            {
+                sink.dump(Direction::Sent, &bytes);
+            }
+        }
+        result
+    }
+
+    /// Write the buffers in one call when the socket supports it, the sink gets them
+    /// concatenated.
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        if let Some(upload) = &mut self.upload {
+            ready!(upload.poll_ready(cx));
+        }
+        let result = Pin::new(&mut self.inner).poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(written)) = &result {
+            self.counters.add_sent(*written);
+            if let Some(upload) = &mut self.upload {
+                upload.consume(*written);
+            }
+            let this = &mut *self;
+            if let Some(sink) = &this.sink {
+                let mut bytes = Vec::with_capacity(*written);
+                for buf in bufs {
+                    let remaining = *written - bytes.len();
+                    if remaining == 0 {
+                        break;
+                    }
+                    bytes.extend_from_slice(&buf[..buf.len().min(remaining)]);
+                }
+                if let Some(bytes) =
+                    this.sent_head
+                        .redact(&this.redactor, &bytes, &mut this.requests)
+                {
+                    sink.dump(Direction::Sent, &bytes);
+                }
+            }
+        }
+        result
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClientUnix, test_helpers::server::Server, test_helpers::util::*};
+    use hyper::{Method, header::AUTHORIZATION};
+
+    #[tokio::test]
+    async fn wire_dump_callback() {
+        let socket_path = make_socket_path_test("wire", "wire_dump_callback");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+
+        let dumped = Arc::new(Mutex::new((Vec::new(), Vec::new())));
+        let dumped_sink = dumped.clone();
+        let mut client = ClientUnix::builder(&socket_path)
+            .wire_dump(move |direction, bytes: &[u8]| {
+                let mut dumped = dumped_sink.lock().unwrap();
+                match direction {
+                    Direction::Sent => dumped.0.extend_from_slice(bytes),
+                    Direction::Received => dumped.1.extend_from_slice(bytes),
+                }
+            })
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        client
+            .send_request("/nolanv", Method::GET, &[], None)
+            .await
+            .expect("client.send_request");
+
+        let dumped = dumped.lock().unwrap();
+        let sent = String::from_utf8_lossy(&dumped.0);
+        let received = String::from_utf8_lossy(&dumped.1);
+        assert!(sent.starts_with("GET http://unix.socket/nolanv HTTP/1.1\r\n"));
+        assert!(received.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(received.ends_with("Hello nolanv"));
+    }
+
+    #[tokio::test]
+    async fn wire_dump_redacted() {
+        let socket_path = make_socket_path_test("wire", "wire_dump_redacted");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sent_sink = sent.clone();
+        let mut client = ClientUnix::builder(&socket_path)
+            .redact_header(AUTHORIZATION)
+            .wire_dump(move |direction, bytes: &[u8]| {
+                if direction == Direction::Sent {
+                    sent_sink.lock().unwrap().extend_from_slice(bytes)
+                }
+            })
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let (_, response) = client
+            .send_request(
+                "/header/authorization",
+                Method::GET,
+                &[("Authorization", "Bearer secret")],
+                None,
+            )
+            .await
+            .expect("client.send_request");
+
+        assert_eq!(response, "Bearer secret".as_bytes());
+        let sent = String::from_utf8_lossy(&sent.lock().unwrap()).to_string();
+        assert!(sent.contains("authorization: ***\r\n"));
+        assert!(!sent.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn vectored_streamed_upload() {
+        let socket_path = make_socket_path_test("wire", "vectored_streamed_upload");
+        let _server = Server::try_new(&socket_path)
+            .await
+            .expect("Server::try_new");
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sent_sink = sent.clone();
+        let mut client = ClientUnix::builder(&socket_path)
+            .vectored_writes(true)
+            .wire_dump(move |direction, bytes: &[u8]| {
+                if direction == Direction::Sent {
+                    sent_sink.lock().unwrap().extend_from_slice(bytes)
+                }
+            })
+            .build()
+            .await
+            .expect("ClientUnixBuilder::build");
+
+        let chunks = ["nolanv-", "layer-", "data"].map(|chunk| {
+            Ok::<_, std::io::Error>(hyper::body::Frame::data(hyper::body::Bytes::from(chunk)))
+        });
+        let body = crate::Body::new(http_body_util::StreamBody::new(futures_util::stream::iter(
+            chunks,
+        )));
+        client
+            .send_request("/header/transfer-encoding", Method::POST, &[], Some(body))
+            .await
+            .expect("client.send_request");
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len() as u64, client.stats().bytes_sent);
+        let sent = String::from_utf8_lossy(&sent).to_string();
+        assert!(sent.contains("7\r\nnolanv-\r\n6\r\nlayer-\r\n4\r\ndata\r\n0\r\n\r\n"));
+    }
+
+    #[test]
+    fn wire_dump_writer() {
+        let sink = Mutex::new(Vec::new());
+        sink.dump(Direction::Sent, b"GET / HTTP/1.1\r\n\r\n");
+        sink.dump(Direction::Received, b"HTTP/1.1 200 OK\r\n\r\n");
+
+        assert_eq!(
+            sink.into_inner().unwrap(),
+            b"> sent 18 bytes\nGET / HTTP/1.1\r\n\r\n\n< received 19 bytes\nHTTP/1.1 200 OK\r\n\r\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn bandwidth_limits() {
+        const LEN: usize = 128 * 1024;
+        let server = crate::testing::MockServer::start(
+            axum::Router::new()
+                .route("/layer", axum::routing::get(|| async { vec![0u8; LEN] }))
+                .route(
+                    "/layer",
+                    axum::routing::post(|body: hyper::body::Bytes| async move {
+                        body.len().to_string()
+                    }),
+                ),
+        )
+        .await
+        .expect("MockServer::start");
+        let socket_path = server.socket_path().to_string_lossy().into_owned();
+
+        for (upload, download) in [(Some(256 * 1024), None), (None, Some(256 * 1024))] {
+            let mut builder = ClientUnix::builder(&socket_path);
+            if let Some(upload) = upload {
+                builder = builder.upload_limit(upload);
+            }
+            if let Some(download) = download {
+                builder = builder.download_limit(download);
+            }
+            let mut client = builder.build().await.expect("ClientUnixBuilder::build");
+
+            let started = Instant::now();
+            let (_, uploaded) = client
+                .send_request(
+                    "/layer",
+                    Method::POST,
+                    &[],
+                    Some(crate::Body::from(vec![0u8; LEN])),
+                )
+                .await
+                .expect("client.send_request");
+            let (_, downloaded) = client
+                .send_request("/layer", Method::GET, &[], None)
+                .await
+                .expect("client.send_request");
+
+            assert_eq!(uploaded, LEN.to_string().as_bytes());
+            assert_eq!(downloaded.len(), LEN);
+            // A tenth of a second of burst, then the remaining bytes at the limit
+            assert!(started.elapsed() >= std::time::Duration::from_millis(250));
+        }
+    }
+}